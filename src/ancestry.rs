@@ -2,8 +2,10 @@
 //!
 //! プロセスが現在セッションの子孫かどうかを判定する。
 
-use crate::process_info::ProcessInfoProvider;
+use crate::config::{RootConfig, RootStrategy};
+use crate::process_info::{ProcessInfo, ProcessInfoProvider};
 use std::env;
+use std::fmt;
 
 /// 無限ループを防ぐための ancestry 走査最大深度
 const MAX_ANCESTRY_DEPTH: u32 = 100;
@@ -11,22 +13,101 @@ const MAX_ANCESTRY_DEPTH: u32 = 100;
 /// ルート PID を上書きする環境変数名
 const ROOT_PID_ENV_VAR: &str = "SAFE_KILL_ROOT_PID";
 
+/// 信頼ルートが選ばれた理由
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RootSource {
+    /// `SAFE_KILL_ROOT_PID` 環境変数で指定された
+    EnvVar,
+    /// 呼び出し元が明示指定した（`AncestryChecker::with_root_pid`）
+    Explicit,
+    /// 呼び出しシェルの親（現在プロセスの祖父）
+    Grandparent,
+    /// 呼び出しシェル（現在プロセスの親）。祖父が信頼ルートに不適格だった
+    Parent,
+    /// `levels` 戦略で選ばれた祖先。`reached` は実際にさかのぼれた階層数
+    Levels { requested: u32, reached: u32 },
+    /// `name` 戦略で名前が一致した祖先
+    NameMatch(String),
+    /// `session_leader` 戦略で見つかったセッションリーダー
+    SessionLeader,
+    /// 適格な祖先が見つからず、現在プロセスへフォールバックした（fail-closed）
+    CurrentProcess,
+}
+
+impl fmt::Display for RootSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RootSource::EnvVar => write!(f, "set by {}", ROOT_PID_ENV_VAR),
+            RootSource::Explicit => write!(f, "explicitly specified"),
+            RootSource::Grandparent => {
+                write!(f, "grandparent of safe-kill (parent of the calling shell)")
+            }
+            RootSource::Parent => {
+                write!(
+                    f,
+                    "parent of safe-kill (grandparent is not a valid trust root)"
+                )
+            }
+            RootSource::Levels { requested, reached } if requested == reached => {
+                write!(f, "{} level(s) above safe-kill", reached)
+            }
+            RootSource::Levels { requested, reached } => write!(
+                f,
+                "{} level(s) above safe-kill ({} requested, but higher ancestors are not valid trust roots)",
+                reached, requested
+            ),
+            RootSource::NameMatch(name) => write!(f, "nearest ancestor named '{}'", name),
+            RootSource::SessionLeader => write!(f, "nearest ancestor that is a session leader"),
+            RootSource::CurrentProcess => write!(
+                f,
+                "no suitable ancestor found; falling back to safe-kill itself (only its own descendants are killable)"
+            ),
+        }
+    }
+}
+
+/// 信頼ルートの解決結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootResolution {
+    /// 信頼ルート PID
+    pub pid: u32,
+    /// 使用した探索戦略
+    pub strategy: RootStrategy,
+    /// このルートが選ばれた理由
+    pub source: RootSource,
+}
+
 /// プロセスツリー検証用 ancestry チェッカー
 pub struct AncestryChecker {
     provider: ProcessInfoProvider,
-    root_pid: u32,
+    root: RootResolution,
 }
 
 impl AncestryChecker {
     /// ルート PID を自動検出して `AncestryChecker` を生成する
     pub fn new(provider: ProcessInfoProvider) -> Self {
-        let root_pid = Self::get_root_pid(&provider);
-        Self { provider, root_pid }
+        Self::with_root_config(provider, None)
+    }
+
+    /// `[root]` 設定に従ってルート PID を検出し `AncestryChecker` を生成する
+    pub fn with_root_config(
+        provider: ProcessInfoProvider,
+        root_config: Option<&RootConfig>,
+    ) -> Self {
+        let root = Self::resolve_root(&provider, root_config);
+        Self { provider, root }
     }
 
     /// ルート PID を明示指定して `AncestryChecker` を生成する
     pub fn with_root_pid(provider: ProcessInfoProvider, root_pid: u32) -> Self {
-        Self { provider, root_pid }
+        Self {
+            provider,
+            root: RootResolution {
+                pid: root_pid,
+                strategy: RootStrategy::Default,
+                source: RootSource::Explicit,
+            },
+        }
     }
 
     /// 信頼ルートとして妥当な PID か判定する
@@ -63,46 +144,162 @@ impl AncestryChecker {
     /// これにより、コンテナや systemd サービス配下で親が PID 1 になる場合でも
     /// 「全プロセスが子孫」と誤判定せず、自プロセスの子孫のみを kill 対象とする。
     pub fn get_root_pid(provider: &ProcessInfoProvider) -> u32 {
-        // まず環境変数を確認する
+        Self::resolve_root(provider, None).pid
+    }
+
+    /// `[root]` 設定に従って信頼ルートを解決し、選ばれた理由も返す
+    ///
+    /// `SAFE_KILL_ROOT_PID` 環境変数はどの戦略よりも優先する。
+    /// 戦略に合う祖先が見つからない場合は現在プロセスへフォールバックする（fail-closed）。
+    pub fn resolve_root(
+        provider: &ProcessInfoProvider,
+        root_config: Option<&RootConfig>,
+    ) -> RootResolution {
+        let strategy = root_config.map(|c| c.strategy).unwrap_or_default();
+
         if let Ok(env_pid) = env::var(ROOT_PID_ENV_VAR) {
             if let Some(pid) = Self::parse_root_pid(&env_pid) {
-                return pid;
+                return RootResolution {
+                    pid,
+                    strategy,
+                    source: RootSource::EnvVar,
+                };
             }
         }
 
-        // 祖父プロセス（シェルの親）を信頼ルートとして採用する
-        // 現在プロセス -> シェル -> 信頼ルート
-        let current_pid = ProcessInfoProvider::current_pid();
+        Self::resolve_root_with(
+            ProcessInfoProvider::current_pid(),
+            root_config,
+            |pid| provider.get(pid),
+            |pid| provider.session_id(pid),
+        )
+    }
+
+    /// プロセス情報の取得方法を差し替え可能にした信頼ルート解決の本体
+    fn resolve_root_with(
+        current_pid: u32,
+        root_config: Option<&RootConfig>,
+        get: impl Fn(u32) -> Option<ProcessInfo>,
+        session_id: impl Fn(u32) -> Option<u32>,
+    ) -> RootResolution {
+        let strategy = root_config.map(|c| c.strategy).unwrap_or_default();
+        // 信頼ルートになり得る祖先（親から上へ、PID 1 に達する手前まで）
+        let ancestors = Self::valid_ancestors(current_pid, &get);
+
+        let found = match strategy {
+            RootStrategy::Default => match ancestors.as_slice() {
+                [_, grandparent, ..] => Some((grandparent.pid, RootSource::Grandparent)),
+                [parent] => Some((parent.pid, RootSource::Parent)),
+                [] => None,
+            },
+            RootStrategy::Levels => {
+                let requested = root_config.and_then(|c| c.levels).unwrap_or(2).max(1);
+                let reached = (requested as usize).min(ancestors.len());
+                reached.checked_sub(1).map(|index| {
+                    (
+                        ancestors[index].pid,
+                        RootSource::Levels {
+                            requested,
+                            reached: reached as u32,
+                        },
+                    )
+                })
+            }
+            RootStrategy::Name => {
+                let names = root_config
+                    .and_then(|c| c.names.as_deref())
+                    .unwrap_or_default();
+                ancestors
+                    .iter()
+                    .find(|info| names.iter().any(|name| name == &info.name))
+                    .map(|info| (info.pid, RootSource::NameMatch(info.name.clone())))
+            }
+            RootStrategy::SessionLeader => ancestors
+                .iter()
+                .find(|info| session_id(info.pid) == Some(info.pid))
+                .map(|info| (info.pid, RootSource::SessionLeader)),
+        };
+
+        let (pid, source) = found.unwrap_or((current_pid, RootSource::CurrentProcess));
+        RootResolution {
+            pid,
+            strategy,
+            source,
+        }
+    }
 
-        if let Some(current_info) = provider.get(current_pid) {
-            if let Some(parent_pid) = current_info.parent_pid {
-                // 祖父が妥当な信頼ルートであれば採用する
-                if let Some(parent_info) = provider.get(parent_pid) {
-                    if let Some(grandparent_pid) = parent_info.parent_pid {
-                        if Self::is_valid_root_pid(grandparent_pid) {
-                            return grandparent_pid;
-                        }
-                    }
-                }
-                // 祖父が不適格（PID 1 等）な場合は親へフォールバックする
-                if Self::is_valid_root_pid(parent_pid) {
-                    return parent_pid;
-                }
+    /// 現在プロセスの祖先のうち、信頼ルートとして妥当なものを近い順に返す
+    ///
+    /// 親から上へたどり、PID 1 以下・情報取得失敗・最大深度のいずれかで停止する。
+    fn valid_ancestors(
+        current_pid: u32,
+        get: &impl Fn(u32) -> Option<ProcessInfo>,
+    ) -> Vec<ProcessInfo> {
+        let mut ancestors = Vec::new();
+        let mut pid = current_pid;
+
+        for _ in 0..MAX_ANCESTRY_DEPTH {
+            let Some(parent_pid) = get(pid).and_then(|info| info.parent_pid) else {
+                break;
+            };
+            if !Self::is_valid_root_pid(parent_pid) || parent_pid == pid {
+                break;
+            }
+            // 親のプロセス情報が取れない場合でも PID だけは祖先として扱う
+            let parent = get(parent_pid).unwrap_or_else(|| ProcessInfo {
+                pid: parent_pid,
+                parent_pid: None,
+                name: String::new(),
+                cmd: Vec::new(),
+                start_time: 0,
+                uid: None,
+                euid: None,
+                cwd: None,
+                start_ticks: None,
+            });
+            ancestors.push(parent);
+            pid = parent_pid;
+        }
+
+        ancestors
+    }
+
+    /// 信頼ルートの解決結果を返す
+    pub fn root_resolution(&self) -> &RootResolution {
+        &self.root
+    }
+
+    /// `pid` から親チェーンをたどったプロセス一覧を返す（`pid` 自身を含む）
+    ///
+    /// PID 1 に到達するか、情報取得に失敗するか、最大深度に達した時点で停止する。
+    pub fn ancestor_chain(&self, pid: u32) -> Vec<ProcessInfo> {
+        let mut chain = Vec::new();
+        let mut current = self.provider.get(pid);
+
+        while let Some(info) = current {
+            if chain.len() as u32 > MAX_ANCESTRY_DEPTH {
+                break;
+            }
+            let parent_pid = info.parent_pid;
+            let is_top = info.pid <= 1;
+            chain.push(info);
+            if is_top {
+                break;
             }
+            current = parent_pid.and_then(|ppid| self.provider.get(ppid));
         }
 
-        // 最終フォールバックは現在 PID（自プロセスの子孫のみ kill 可能=fail-closed）
-        current_pid
+        chain
     }
 
     /// 設定済みルート PID を返す
     pub fn root_pid(&self) -> u32 {
-        self.root_pid
+        self.root.pid
     }
 
     /// `target_pid` が `root_pid` の子孫か判定する
     pub fn is_descendant(&self, target_pid: u32) -> bool {
-        self.is_descendant_of(target_pid, self.root_pid)
+        self.is_descendant_of(target_pid, self.root.pid)
     }
 
     /// `target_pid` が特定の `ancestor_pid` の子孫か判定する
@@ -186,6 +383,45 @@ impl AncestryChecker {
         false
     }
 
+    /// `target_pid` が現在プロセスの祖先（親から PID 1 まで）か判定する
+    ///
+    /// エージェント本体の node ホストやシェルなど、safe-kill を起動した側のプロセスは
+    /// allowlist に一致しても終了してはならない。親は `is_suicide` でも保護されるが、
+    /// ここでは祖父より上を含むチェーン全体を対象にする。
+    pub fn is_ancestor_of_current(&self, target_pid: u32) -> bool {
+        let current_pid = ProcessInfoProvider::current_pid();
+        Self::is_ancestor_with(current_pid, target_pid, |pid| {
+            self.provider.get(pid).map(|info| info.parent_pid)
+        })
+    }
+
+    /// 親 PID の取得方法を差し替え可能にした祖先判定の本体
+    ///
+    /// `parent_of` は PID に対して「プロセスが存在すればその親 PID」を返す。
+    /// `start_pid` 自身は祖先に含めない。PID 1 に到達した場合は PID 1 までを祖先とみなす。
+    pub(crate) fn is_ancestor_with(
+        start_pid: u32,
+        target_pid: u32,
+        parent_of: impl Fn(u32) -> Option<Option<u32>>,
+    ) -> bool {
+        let mut pid = start_pid;
+
+        for _ in 0..MAX_ANCESTRY_DEPTH {
+            let Some(Some(parent_pid)) = parent_of(pid) else {
+                return false;
+            };
+            if parent_pid == target_pid {
+                return true;
+            }
+            if parent_pid <= 1 || parent_pid == pid {
+                return false;
+            }
+            pid = parent_pid;
+        }
+
+        false
+    }
+
     /// プロセス情報を再取得する
     pub fn refresh(&mut self) {
         self.provider.refresh();
@@ -458,4 +694,274 @@ mod tests {
     fn test_max_depth_constant() {
         assert_eq!(MAX_ANCESTRY_DEPTH, 100);
     }
+
+    // =============================================================================
+    // [root] 戦略による信頼ルート解決テスト
+    // =============================================================================
+
+    /// テスト用のプロセスツリー: 1 (init) → 100 (tmux, session leader) → 200 (claude)
+    /// → 300 (sh) → 400 (npx) → 500 (safe-kill)
+    fn fake_tree() -> std::collections::HashMap<u32, ProcessInfo> {
+        [
+            (1, None, "init"),
+            (100, Some(1), "tmux"),
+            (200, Some(100), "claude"),
+            (300, Some(200), "sh"),
+            (400, Some(300), "npx"),
+            (500, Some(400), "safe-kill"),
+        ]
+        .into_iter()
+        .map(|(pid, parent_pid, name)| {
+            (
+                pid,
+                ProcessInfo {
+                    pid,
+                    parent_pid,
+                    name: name.to_string(),
+                    cmd: vec![],
+                    start_time: 0,
+                    uid: None,
+                    euid: None,
+                    cwd: None,
+                    start_ticks: None,
+                },
+            )
+        })
+        .collect()
+    }
+
+    fn resolve_in_fake_tree(current_pid: u32, root_config: Option<&RootConfig>) -> RootResolution {
+        let tree = fake_tree();
+        AncestryChecker::resolve_root_with(
+            current_pid,
+            root_config,
+            |pid| tree.get(&pid).cloned(),
+            |_| Some(100),
+        )
+    }
+
+    fn root_config(strategy: RootStrategy) -> RootConfig {
+        RootConfig {
+            strategy,
+            levels: None,
+            names: None,
+        }
+    }
+
+    #[test]
+    fn test_resolve_root_default_uses_grandparent() {
+        let resolution = resolve_in_fake_tree(500, None);
+        assert_eq!(resolution.pid, 300);
+        assert_eq!(resolution.source, RootSource::Grandparent);
+        assert_eq!(resolution.strategy, RootStrategy::Default);
+    }
+
+    #[test]
+    fn test_resolve_root_default_falls_back_to_parent_when_grandparent_is_init() {
+        let resolution = resolve_in_fake_tree(200, None);
+        assert_eq!(resolution.pid, 100);
+        assert_eq!(resolution.source, RootSource::Parent);
+    }
+
+    #[test]
+    fn test_resolve_root_default_falls_back_to_current_process() {
+        let resolution = resolve_in_fake_tree(100, None);
+        assert_eq!(resolution.pid, 100);
+        assert_eq!(resolution.source, RootSource::CurrentProcess);
+    }
+
+    #[test]
+    fn test_resolve_root_levels() {
+        let mut config = root_config(RootStrategy::Levels);
+        config.levels = Some(3);
+        let resolution = resolve_in_fake_tree(500, Some(&config));
+        assert_eq!(resolution.pid, 200);
+        assert_eq!(
+            resolution.source,
+            RootSource::Levels {
+                requested: 3,
+                reached: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_resolve_root_levels_stops_below_init() {
+        // PID 1 は信頼ルートにできないため、届く範囲で最も上の祖先に留まる
+        let mut config = root_config(RootStrategy::Levels);
+        config.levels = Some(10);
+        let resolution = resolve_in_fake_tree(500, Some(&config));
+        assert_eq!(resolution.pid, 100);
+        assert_eq!(
+            resolution.source,
+            RootSource::Levels {
+                requested: 10,
+                reached: 4
+            }
+        );
+    }
+
+    #[test]
+    fn test_resolve_root_name_picks_nearest_match() {
+        let mut config = root_config(RootStrategy::Name);
+        config.names = Some(vec!["tmux".to_string(), "claude".to_string()]);
+        let resolution = resolve_in_fake_tree(500, Some(&config));
+        assert_eq!(resolution.pid, 200);
+        assert_eq!(
+            resolution.source,
+            RootSource::NameMatch("claude".to_string())
+        );
+    }
+
+    #[test]
+    fn test_resolve_root_name_without_match_fails_closed() {
+        let mut config = root_config(RootStrategy::Name);
+        config.names = Some(vec!["codex".to_string()]);
+        let resolution = resolve_in_fake_tree(500, Some(&config));
+        assert_eq!(resolution.pid, 500);
+        assert_eq!(resolution.source, RootSource::CurrentProcess);
+    }
+
+    #[test]
+    fn test_resolve_root_name_never_matches_init() {
+        let mut config = root_config(RootStrategy::Name);
+        config.names = Some(vec!["init".to_string()]);
+        let resolution = resolve_in_fake_tree(500, Some(&config));
+        assert_eq!(resolution.source, RootSource::CurrentProcess);
+    }
+
+    #[test]
+    fn test_resolve_root_session_leader() {
+        let config = root_config(RootStrategy::SessionLeader);
+        let resolution = resolve_in_fake_tree(500, Some(&config));
+        assert_eq!(resolution.pid, 100);
+        assert_eq!(resolution.source, RootSource::SessionLeader);
+    }
+
+    #[test]
+    fn test_resolve_root_session_leader_not_found_fails_closed() {
+        let tree = fake_tree();
+        let config = root_config(RootStrategy::SessionLeader);
+        let resolution = AncestryChecker::resolve_root_with(
+            500,
+            Some(&config),
+            |pid| tree.get(&pid).cloned(),
+            |_| None,
+        );
+        assert_eq!(resolution.pid, 500);
+        assert_eq!(resolution.source, RootSource::CurrentProcess);
+    }
+
+    #[test]
+    fn test_root_source_display_mentions_reason() {
+        assert!(RootSource::EnvVar.to_string().contains(ROOT_PID_ENV_VAR));
+        assert!(
+            RootSource::NameMatch("claude".to_string())
+                .to_string()
+                .contains("claude")
+        );
+        assert!(
+            RootSource::Levels {
+                requested: 5,
+                reached: 2
+            }
+            .to_string()
+            .contains("5 requested")
+        );
+        assert!(
+            RootSource::CurrentProcess
+                .to_string()
+                .contains("falling back")
+        );
+    }
+
+    #[test]
+    fn test_with_root_pid_reports_explicit_source() {
+        let checker = AncestryChecker::with_root_pid(ProcessInfoProvider::new(), 12345);
+        assert_eq!(checker.root_resolution().source, RootSource::Explicit);
+    }
+
+    #[test]
+    fn test_ancestor_chain_starts_with_target() {
+        let checker = AncestryChecker::new(ProcessInfoProvider::new());
+        let current_pid = ProcessInfoProvider::current_pid();
+        let chain = checker.ancestor_chain(current_pid);
+        assert_eq!(chain.first().map(|p| p.pid), Some(current_pid));
+        assert!(chain.len() >= 2, "現在プロセスには少なくとも親がいるはず");
+    }
+
+    // =============================================================================
+    // 祖先チェーン保護のテスト
+    // =============================================================================
+
+    fn parent_in_fake_tree(pid: u32) -> Option<Option<u32>> {
+        fake_tree().get(&pid).map(|info| info.parent_pid)
+    }
+
+    #[test]
+    fn test_is_ancestor_with_detects_every_level() {
+        for ancestor in [400, 300, 200, 100, 1] {
+            assert!(
+                AncestryChecker::is_ancestor_with(500, ancestor, parent_in_fake_tree),
+                "{ancestor} は 500 の祖先として検出されるべき"
+            );
+        }
+    }
+
+    #[test]
+    fn test_is_ancestor_with_excludes_self_and_descendants() {
+        assert!(!AncestryChecker::is_ancestor_with(
+            300,
+            300,
+            parent_in_fake_tree
+        ));
+        assert!(!AncestryChecker::is_ancestor_with(
+            300,
+            500,
+            parent_in_fake_tree
+        ));
+    }
+
+    #[test]
+    fn test_is_ancestor_with_stops_when_chain_breaks() {
+        // 情報が取れない PID で停止し、それより上は祖先と判定しない
+        assert!(!AncestryChecker::is_ancestor_with(
+            9999,
+            100,
+            parent_in_fake_tree
+        ));
+    }
+
+    #[test]
+    fn test_is_ancestor_of_current_detects_parent_and_grandparent() {
+        let provider = ProcessInfoProvider::new();
+        let current_pid = ProcessInfoProvider::current_pid();
+        let parent_pid = provider.get(current_pid).and_then(|p| p.parent_pid);
+        let grandparent_pid = parent_pid
+            .and_then(|pid| provider.get(pid))
+            .and_then(|p| p.parent_pid);
+        let checker = AncestryChecker::new(provider);
+
+        if let Some(parent_pid) = parent_pid {
+            assert!(checker.is_ancestor_of_current(parent_pid));
+        }
+        if let Some(grandparent_pid) = grandparent_pid {
+            assert!(checker.is_ancestor_of_current(grandparent_pid));
+        }
+        assert!(!checker.is_ancestor_of_current(current_pid));
+    }
+
+    #[test]
+    fn test_is_ancestor_of_current_rejects_child_process() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("5")
+            .spawn()
+            .expect("sleep プロセスの起動に失敗");
+        let checker = AncestryChecker::new(ProcessInfoProvider::new());
+
+        assert!(!checker.is_ancestor_of_current(child.id()));
+
+        let _ = child.kill();
+        let _ = child.wait();
+    }
 }
@@ -2,9 +2,17 @@
 //!
 //! clap derive を使用した型安全な引数解析を提供する。
 
-use clap::{Parser, Subcommand};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::time::Duration;
 
+use clap::{Args, Parser, Subcommand};
+
+use crate::config::{LoadOptions, PortSpec};
 use crate::error::SafeKillError;
+use crate::grants::{self, Grant};
+use crate::init::InitTemplate;
+use crate::port::{AddressFamily, PortFilter, PortProtocol, PortSelection};
 use crate::signal::{Signal, SignalSender};
 
 /// CLI 引数から決定される実行モード
@@ -14,12 +22,32 @@ pub enum ExecutionMode {
     KillByPid(u32),
     /// プロセス名の完全一致で終了
     KillByName(String),
-    /// ポート指定でプロセスを kill
-    KillByPort(u16),
+    /// ポート（範囲・リスト可）指定でプロセスを kill
+    KillByPort(PortSelection),
+    /// Unix ドメインソケットのパス指定でプロセスを kill
+    KillBySocket(PathBuf),
+    /// 指定パスを開いているプロセスを kill
+    KillByHolding(PathBuf),
+    /// カレントディレクトリが指定ディレクトリ配下のプロセスを kill
+    KillByCwd(PathBuf),
     /// kill 可能なプロセスを一覧表示
     ListKillable,
     /// 設定ファイルを初期化
-    InitConfig { force: bool },
+    InitConfig {
+        force: bool,
+        interactive: bool,
+        template: Option<InitTemplate>,
+    },
+    /// 信頼ルートの解決結果を表示
+    ShowRoot,
+    /// 対応シグナルとポリシー上の可否を表示
+    ListSignals,
+    /// 待ち受け中のポートと kill 可否を表示
+    ListPorts { json: bool, all: bool },
+    /// 期限付きの許可を追加
+    AllowTemporarily { grant: Grant, duration: Duration },
+    /// 設定ファイルの検証・表示・編集
+    ManageConfig(ConfigAction),
 }
 
 /// safe-kill のサブコマンド
@@ -30,7 +58,185 @@ pub enum Command {
         /// 既存の設定ファイルを確認なしで上書き
         #[arg(long)]
         force: bool,
+        /// セッション内で待ち受け中のポートを検出し、許可するポートと allowlist を対話的に選ぶ
+        #[arg(short, long)]
+        interactive: bool,
+        /// 開発スタックのプリセット（node、python、rust、tauri）
+        #[arg(long, value_name = "NAME", value_parser = InitTemplate::parse)]
+        template: Option<InitTemplate>,
+    },
+    /// ancestry 判定の信頼ルートとして選ばれる PID とその理由を表示
+    Root,
+    /// 送信できるシグナルの番号・名前と [signals] 設定による可否を表示
+    Signals,
+    /// 許可ポートで待ち受け中のソケットと、--port で kill できるかを表示
+    Ports {
+        /// JSON 形式で出力
+        #[arg(long)]
+        json: bool,
+        /// [allowed_ports] に含まれないポートも表示（kill 不可として）
+        #[arg(long)]
+        all: bool,
+    },
+    /// プロセス名またはポートを一定時間だけ許可（~/.config/safe-kill/grants.toml に保存）
+    Allow {
+        /// [allowlist] に追加するプロセス名
+        #[arg(
+            short = 'N',
+            long,
+            value_name = "NAME",
+            required_unless_present = "port"
+        )]
+        name: Option<String>,
+        /// [allowed_ports] に追加するポート指定（"9229"、"tcp:127.0.0.1:9229"）
+        #[arg(short = 'p', long, value_name = "PORT", conflicts_with = "name")]
+        port: Option<String>,
+        /// 許可する期間（"30m"、"2h"、"1d"）
+        #[arg(long = "for", value_name = "DURATION", value_parser = grants::parse_duration)]
+        duration: Duration,
+    },
+    /// 設定ファイルの検証・表示・編集
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+/// `safe-kill config` の操作
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum ConfigAction {
+    /// 設定ファイルを検証し、問題を行・列付きですべて表示
+    Validate {
+        /// 検証するファイル（省略時は ~/.config/safe-kill/config.toml）
+        path: Option<PathBuf>,
+        /// 問題を警告として表示するだけにし、常に成功で終了する
+        #[arg(long)]
+        lint: bool,
+    },
+    /// デフォルトと期限付きの許可を合流した、実際に使われる設定を表示
+    Show,
+    /// $EDITOR で設定ファイルを開き、閉じた後に検証
+    Edit,
+    /// 設定ファイルの JSON Schema を表示（エディタでの補完・検証用）
+    Schema,
+    /// [allowed_ports] にポート指定を追加（"4000-4010"、"tcp:127.0.0.1:9229"）
+    AddPort {
+        /// 追加するポート指定
+        spec: String,
     },
+    /// [denylist] にプロセス名を追加
+    AddDeny {
+        /// 追加するプロセス名
+        name: String,
+    },
+}
+
+impl Command {
+    /// サブコマンド名を返す
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Init { .. } => "init",
+            Command::Root => "root",
+            Command::Signals => "signals",
+            Command::Ports { .. } => "ports",
+            Command::Allow { .. } => "allow",
+            Command::Config { .. } => "config",
+        }
+    }
+}
+
+/// `--port` の対象ソケットを絞り込むオプション
+#[derive(Args, Debug, Clone, Default, PartialEq, Eq)]
+pub struct PortFilterArgs {
+    /// TCP リスナーのみを対象にする
+    #[arg(long, conflicts_with = "udp")]
+    pub tcp: bool,
+
+    /// UDP ソケットのみを対象にする
+    #[arg(long)]
+    pub udp: bool,
+
+    /// IPv4 ソケットのみを対象にする
+    #[arg(short = '4', long = "ipv4", conflicts_with = "ipv6")]
+    pub ipv4: bool,
+
+    /// IPv6 ソケットのみを対象にする
+    #[arg(short = '6', long = "ipv6")]
+    pub ipv6: bool,
+
+    /// 指定アドレスにバインドされたソケットのみを対象にする
+    #[arg(long, value_name = "ADDR")]
+    pub bind: Option<IpAddr>,
+}
+
+impl PortFilterArgs {
+    /// いずれかの絞り込みオプションが指定されているか
+    pub fn is_set(&self) -> bool {
+        self.tcp || self.udp || self.ipv4 || self.ipv6 || self.bind.is_some()
+    }
+
+    /// 絞り込み条件に変換する
+    ///
+    /// `-4` / `-6` と `--bind` のアドレスファミリーが食い違う場合はエラーを返す。
+    pub fn to_filter(&self) -> Result<PortFilter, SafeKillError> {
+        let protocol = if self.tcp {
+            Some(PortProtocol::Tcp)
+        } else if self.udp {
+            Some(PortProtocol::Udp)
+        } else {
+            None
+        };
+        let family = if self.ipv4 {
+            Some(AddressFamily::Ipv4)
+        } else if self.ipv6 {
+            Some(AddressFamily::Ipv6)
+        } else {
+            None
+        };
+
+        if let (Some(family), Some(bind)) = (family, self.bind)
+            && AddressFamily::of(&bind) != family
+        {
+            return Err(SafeKillError::InvalidUsage(format!(
+                "--bind {} does not match {}",
+                bind,
+                if family == AddressFamily::Ipv4 {
+                    "-4"
+                } else {
+                    "-6"
+                }
+            )));
+        }
+
+        Ok(PortFilter {
+            protocol,
+            family,
+            bind: self.bind,
+        })
+    }
+}
+
+/// PID・--name 以外のターゲット指定オプション
+///
+/// 複数のターゲットが指定された場合は、この順で最初に指定されたものを主語にエラーを出す。
+const EXCLUSIVE_TARGETS: [&str; 5] = ["--list", "--port", "--socket", "--holding", "--cwd"];
+
+/// `EXCLUSIVE_TARGETS[primary]` と後続のターゲットの組み合わせエラーのメッセージを組み立てる
+///
+/// 例: "--port cannot be combined with PID, --name, --socket, --holding, or --cwd"
+fn target_conflict_message(primary: usize) -> String {
+    let mut others = vec!["PID", "--name"];
+    others.extend(&EXCLUSIVE_TARGETS[primary + 1..]);
+    let last = others.pop().unwrap_or_default();
+    let joined = if others.len() >= 2 {
+        format!("{}, or {}", others.join(", "), last)
+    } else {
+        format!("{} or {}", others.join(", "), last)
+    };
+    format!(
+        "{} cannot be combined with {}",
+        EXCLUSIVE_TARGETS[primary], joined
+    )
 }
 
 /// safe-kill の CLI 引数
@@ -45,7 +251,7 @@ pub enum Command {
                   preventing accidental termination of system or unrelated processes."
 )]
 pub struct CliArgs {
-    /// サブコマンド（例: init）
+    /// サブコマンド（例: init, root）
     #[command(subcommand)]
     pub command: Option<Command>,
 
@@ -57,9 +263,29 @@ pub struct CliArgs {
     #[arg(short = 'N', long, value_name = "NAME")]
     pub name: Option<String>,
 
-    /// 指定ポートを使用するプロセスを kill
+    /// 指定ポートを使用するプロセスを kill（"3000"、"3000-3010"、"3000-3010,5173"）
     #[arg(short = 'p', long, value_name = "PORT")]
-    pub port: Option<u16>,
+    pub port: Option<PortSelection>,
+
+    /// 指定パスの Unix ドメインソケットを保持するプロセスを kill（抽象名前空間は "@name"）
+    #[arg(long, value_name = "PATH")]
+    pub socket: Option<PathBuf>,
+
+    /// 指定パス（ディレクトリなら配下）を開いている・マップしているプロセスを kill
+    #[arg(long, value_name = "PATH")]
+    pub holding: Option<PathBuf>,
+
+    /// カレントディレクトリが指定ディレクトリ配下にあるセッション内のプロセスを kill
+    #[arg(long, value_name = "DIR")]
+    pub cwd: Option<PathBuf>,
+
+    /// 一括 kill（--name / --cwd / --port / --socket / --holding）で kill 前の確認を省略
+    #[arg(short = 'y', long)]
+    pub yes: bool,
+
+    /// 一括 kill（--name / --cwd / --port / --socket / --holding）で候補ごとに確認してから kill（端末が必要）
+    #[arg(short = 'i', long)]
+    pub interactive: bool,
 
     /// 送信するシグナル（名前または番号）
     #[arg(short, long, default_value = "SIGTERM", value_name = "SIGNAL")]
@@ -72,6 +298,22 @@ pub struct CliArgs {
     /// dry-run モード（実際にはシグナルを送信しない）
     #[arg(short = 'n', long)]
     pub dry_run: bool,
+
+    /// `--port` の絞り込み条件
+    #[command(flatten)]
+    pub port_filter: PortFilterArgs,
+
+    /// 使用する設定ファイル（SAFE_KILL_CONFIG より優先）
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// 設定値を厳しくする方向に上書きする（例: allowed_ports.ports=3000,5173。繰り返し指定可）
+    #[arg(long = "set", global = true, value_name = "KEY=VALUE")]
+    pub set: Vec<String>,
+
+    /// 設定の [profiles.<NAME>] を使う（SAFE_KILL_PROFILE より優先）
+    #[arg(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
 }
 
 impl CliArgs {
@@ -80,58 +322,151 @@ impl CliArgs {
         Self::parse()
     }
 
+    /// `--config` / `--set` / `--profile` から設定の読み込み方法を組み立てる
+    pub fn load_options(&self) -> LoadOptions {
+        LoadOptions {
+            path: self.config.clone(),
+            sets: self.set.clone(),
+            profile: self.profile.clone(),
+        }
+    }
+
     /// 引数を検証し、実行モードを決定する
     ///
     /// 以下の場合にエラーを返す:
-    /// - ターゲットが指定されていない（PID、--name、--port、--list のいずれもなし）
-    /// - 複数のターゲットが指定されている（PID と --name/--port、または --list と他の組み合わせ）
+    /// - ターゲットが指定されていない（PID、--name、--port、--socket、--holding、--cwd、--list のいずれもなし）
+    /// - 複数のターゲットが指定されている（PID と --name/--port/--socket/--holding/--cwd、または --list と他の組み合わせ）
     pub fn validate(&self) -> Result<ExecutionMode, SafeKillError> {
         // サブコマンドを先に処理
         if let Some(ref cmd) = self.command {
             let has_runtime_options = self.pid.is_some()
                 || self.name.is_some()
                 || self.port.is_some()
+                || self.socket.is_some()
+                || self.holding.is_some()
+                || self.cwd.is_some()
+                || self.yes
+                || self.interactive
                 || self.list
                 || self.dry_run
+                || self.port_filter.is_set()
                 || !self.signal.eq_ignore_ascii_case("SIGTERM");
 
             if has_runtime_options {
-                return Err(SafeKillError::InvalidUsage(
-                    "init cannot be combined with PID, --name, --port, --list, --signal, or --dry-run"
-                        .to_string(),
-                ));
+                return Err(SafeKillError::InvalidUsage(format!(
+                    "{} cannot be combined with PID, --name, --port, --socket, --holding, --cwd, --list, --signal, --yes, --interactive, or --dry-run",
+                    cmd.name()
+                )));
             }
 
-            match cmd {
-                Command::Init { force } => {
-                    return Ok(ExecutionMode::InitConfig { force: *force });
+            return Ok(match cmd {
+                Command::Init {
+                    force,
+                    interactive,
+                    template,
+                } => ExecutionMode::InitConfig {
+                    force: *force,
+                    interactive: *interactive,
+                    template: *template,
+                },
+                Command::Root => ExecutionMode::ShowRoot,
+                Command::Signals => ExecutionMode::ListSignals,
+                Command::Ports { json, all } => ExecutionMode::ListPorts {
+                    json: *json,
+                    all: *all,
+                },
+                Command::Allow {
+                    name,
+                    port,
+                    duration,
+                } => ExecutionMode::AllowTemporarily {
+                    grant: match (name, port) {
+                        (Some(name), _) => Grant::Process(name.clone()),
+                        (None, Some(port)) => {
+                            PortSpec::parse(port)?;
+                            Grant::Port(port.clone())
+                        }
+                        (None, None) => return Err(SafeKillError::NoTarget),
+                    },
+                    duration: *duration,
+                },
+                Command::Config { action } => {
+                    if let ConfigAction::AddPort { spec } = action {
+                        PortSpec::parse(spec)?;
+                    }
+                    ExecutionMode::ManageConfig(action.clone())
                 }
-            }
+            });
+        }
+
+        // 絞り込みオプションは --port 専用
+        if self.port_filter.is_set() && self.port.is_none() {
+            return Err(SafeKillError::InvalidUsage(
+                "--tcp, --udp, -4, -6 and --bind can only be used with --port".to_string(),
+            ));
+        }
+        self.port_filter.to_filter()?;
+
+        // 確認の省略と対話的確認は、複数のプロセスに送りうる一括 kill 専用
+        let confirmable = self.name.is_some()
+            || self.cwd.is_some()
+            || self.port.is_some()
+            || self.socket.is_some()
+            || self.holding.is_some();
+        if self.yes && !confirmable {
+            return Err(SafeKillError::InvalidUsage(
+                "--yes can only be used with --name, --cwd, --port, --socket or --holding"
+                    .to_string(),
+            ));
+        }
+        if self.interactive && !confirmable {
+            return Err(SafeKillError::InvalidUsage(
+                "--interactive can only be used with --name, --cwd, --port, --socket or --holding"
+                    .to_string(),
+            ));
+        }
+        if self.interactive && self.yes {
+            return Err(SafeKillError::InvalidUsage(
+                "--interactive cannot be combined with --yes".to_string(),
+            ));
         }
 
         // ターゲットオプションの指定数をカウント
         let has_pid = self.pid.is_some();
         let has_name = self.name.is_some();
-        let has_port = self.port.is_some();
-        let has_list = self.list;
+        // EXCLUSIVE_TARGETS と同じ順序
+        let exclusive = [
+            self.list,
+            self.port.is_some(),
+            self.socket.is_some(),
+            self.holding.is_some(),
+            self.cwd.is_some(),
+        ];
 
         // 排他性チェック
-        let target_count = [has_pid, has_name, has_port, has_list]
+        let target_count = [has_pid, has_name]
             .iter()
+            .chain(exclusive.iter())
             .filter(|&&b| b)
             .count();
 
         match target_count {
             0 => Err(SafeKillError::NoTarget),
             1 => {
-                if has_list {
+                if self.list {
                     Ok(ExecutionMode::ListKillable)
                 } else if let Some(pid) = self.pid {
                     Ok(ExecutionMode::KillByPid(pid))
                 } else if let Some(ref name) = self.name {
                     Ok(ExecutionMode::KillByName(name.clone()))
-                } else if let Some(port) = self.port {
-                    Ok(ExecutionMode::KillByPort(port))
+                } else if let Some(ref ports) = self.port {
+                    Ok(ExecutionMode::KillByPort(ports.clone()))
+                } else if let Some(ref path) = self.socket {
+                    Ok(ExecutionMode::KillBySocket(path.clone()))
+                } else if let Some(ref path) = self.holding {
+                    Ok(ExecutionMode::KillByHolding(path.clone()))
+                } else if let Some(ref dir) = self.cwd {
+                    Ok(ExecutionMode::KillByCwd(dir.clone()))
                 } else {
                     // 上記のロジックからここには到達しないはず
                     Err(SafeKillError::NoTarget)
@@ -139,18 +474,13 @@ impl CliArgs {
             }
             _ => {
                 // 複数ターゲット指定はエラー
-                if has_list {
-                    Err(SafeKillError::InvalidUsage(
-                        "--list cannot be combined with PID, --name, or --port".to_string(),
-                    ))
-                } else if has_port {
-                    Err(SafeKillError::InvalidUsage(
-                        "--port cannot be combined with PID or --name".to_string(),
-                    ))
-                } else {
-                    Err(SafeKillError::InvalidUsage(
+                match exclusive.iter().position(|&b| b) {
+                    Some(primary) => Err(SafeKillError::InvalidUsage(target_conflict_message(
+                        primary,
+                    ))),
+                    None => Err(SafeKillError::InvalidUsage(
                         "Cannot specify both PID and --name".to_string(),
-                    ))
+                    )),
                 }
             }
         }
@@ -160,6 +490,11 @@ impl CliArgs {
     pub fn parse_signal(&self) -> Result<Signal, SafeKillError> {
         SignalSender::parse_signal(&self.signal)
     }
+
+    /// `--port` の絞り込み条件を返す
+    pub fn port_filter(&self) -> Result<PortFilter, SafeKillError> {
+        self.port_filter.to_filter()
+    }
 }
 
 #[cfg(test)]
@@ -179,10 +514,19 @@ mod tests {
             command: None,
             pid,
             name,
-            port,
+            port: port.map(PortSelection::single),
+            socket: None,
+            holding: None,
+            cwd: None,
+            yes: false,
+            interactive: false,
             signal: signal.to_string(),
             list,
             dry_run,
+            port_filter: PortFilterArgs::default(),
+            config: None,
+            set: Vec::new(),
+            profile: None,
         }
     }
 
@@ -193,9 +537,18 @@ mod tests {
             pid: None,
             name: None,
             port: None,
+            socket: None,
+            holding: None,
+            cwd: None,
+            yes: false,
+            interactive: false,
             signal: "SIGTERM".to_string(),
             list: false,
             dry_run: false,
+            port_filter: PortFilterArgs::default(),
+            config: None,
+            set: Vec::new(),
+            profile: None,
         }
     }
 
@@ -265,7 +618,10 @@ mod tests {
     fn test_validate_port_only() {
         let args = make_args(None, None, Some(8080), "SIGTERM", false, false);
         let result = args.validate();
-        assert!(matches!(result, Ok(ExecutionMode::KillByPort(8080))));
+        assert_eq!(
+            result,
+            Ok(ExecutionMode::KillByPort(PortSelection::single(8080)))
+        );
     }
 
     #[test]
@@ -433,7 +789,7 @@ mod tests {
         );
         assert_eq!(args.pid, Some(100));
         assert_eq!(args.name, Some("test".to_string()));
-        assert_eq!(args.port, Some(3000));
+        assert_eq!(args.port, Some(PortSelection::single(3000)));
         assert_eq!(args.signal, "SIGKILL");
         assert!(args.list);
         assert!(args.dry_run);
@@ -481,7 +837,7 @@ mod tests {
 
         // バリデーション
         let mode = args.validate().unwrap();
-        assert!(matches!(mode, ExecutionMode::KillByPort(8080)));
+        assert_eq!(mode, ExecutionMode::KillByPort(PortSelection::single(8080)));
 
         // シグナル解析
         let signal = args.parse_signal().unwrap();
@@ -503,34 +859,57 @@ mod tests {
     // Init サブコマンドテスト
     #[test]
     fn test_init_command() {
-        let args = make_args_with_command(Command::Init { force: false });
+        let args = make_args_with_command(Command::Init {
+            force: false,
+            interactive: false,
+            template: None,
+        });
         let result = args.validate();
         assert!(matches!(
             result,
-            Ok(ExecutionMode::InitConfig { force: false })
+            Ok(ExecutionMode::InitConfig { force: false, .. })
         ));
     }
 
     #[test]
     fn test_init_command_with_force() {
-        let args = make_args_with_command(Command::Init { force: true });
+        let args = make_args_with_command(Command::Init {
+            force: true,
+            interactive: false,
+            template: None,
+        });
         let result = args.validate();
         assert!(matches!(
             result,
-            Ok(ExecutionMode::InitConfig { force: true })
+            Ok(ExecutionMode::InitConfig { force: true, .. })
         ));
     }
 
+    #[test]
+    fn test_init_command_with_interactive_and_template() {
+        let args =
+            CliArgs::try_parse_from(["safe-kill", "init", "-i", "--template", "python"]).unwrap();
+        assert_eq!(
+            args.validate(),
+            Ok(ExecutionMode::InitConfig {
+                force: false,
+                interactive: true,
+                template: Some(InitTemplate::Python),
+            })
+        );
+        assert!(CliArgs::try_parse_from(["safe-kill", "init", "--template", "perl"]).is_err());
+    }
+
     #[test]
     fn test_init_command_rejects_runtime_options() {
         let args = CliArgs {
-            command: Some(Command::Init { force: false }),
             pid: Some(1234),
-            name: None,
-            port: None,
-            signal: "SIGTERM".to_string(),
-            list: false,
             dry_run: true,
+            ..make_args_with_command(Command::Init {
+                force: false,
+                interactive: false,
+                template: None,
+            })
         };
         let result = args.validate();
         assert!(matches!(result, Err(SafeKillError::InvalidUsage(_))));
@@ -540,40 +919,36 @@ mod tests {
     fn test_init_command_rejects_each_runtime_option() {
         let cases = [
             CliArgs {
-                command: Some(Command::Init { force: false }),
-                pid: None,
                 name: Some("node".to_string()),
-                port: None,
-                signal: "SIGTERM".to_string(),
-                list: false,
-                dry_run: false,
+                ..make_args_with_command(Command::Init {
+                    force: false,
+                    interactive: false,
+                    template: None,
+                })
             },
             CliArgs {
-                command: Some(Command::Init { force: false }),
-                pid: None,
-                name: None,
-                port: Some(3000),
-                signal: "SIGTERM".to_string(),
-                list: false,
-                dry_run: false,
+                port: Some(PortSelection::single(3000)),
+                ..make_args_with_command(Command::Init {
+                    force: false,
+                    interactive: false,
+                    template: None,
+                })
             },
             CliArgs {
-                command: Some(Command::Init { force: false }),
-                pid: None,
-                name: None,
-                port: None,
-                signal: "SIGTERM".to_string(),
                 list: true,
-                dry_run: false,
+                ..make_args_with_command(Command::Init {
+                    force: false,
+                    interactive: false,
+                    template: None,
+                })
             },
             CliArgs {
-                command: Some(Command::Init { force: false }),
-                pid: None,
-                name: None,
-                port: None,
-                signal: "SIGTERM".to_string(),
-                list: false,
                 dry_run: true,
+                ..make_args_with_command(Command::Init {
+                    force: false,
+                    interactive: false,
+                    template: None,
+                })
             },
         ];
 
@@ -586,18 +961,503 @@ mod tests {
     #[test]
     fn test_init_command_rejects_signal_option_value() {
         let args = CliArgs {
-            command: Some(Command::Init { force: false }),
-            pid: None,
-            name: None,
-            port: None,
             signal: "SIGKILL".to_string(),
-            list: false,
-            dry_run: false,
+            ..make_args_with_command(Command::Init {
+                force: false,
+                interactive: false,
+                template: None,
+            })
         };
         let result = args.validate();
         assert!(matches!(result, Err(SafeKillError::InvalidUsage(_))));
     }
 
+    // Root サブコマンドテスト
+    #[test]
+    fn test_root_command() {
+        let args = make_args_with_command(Command::Root);
+        assert_eq!(args.validate(), Ok(ExecutionMode::ShowRoot));
+    }
+
+    #[test]
+    fn test_root_command_rejects_runtime_options() {
+        let mut args = make_args_with_command(Command::Root);
+        args.dry_run = true;
+        match args.validate() {
+            Err(SafeKillError::InvalidUsage(msg)) => assert!(msg.starts_with("root ")),
+            other => panic!("Expected InvalidUsage, got {:?}", other),
+        }
+    }
+
+    // ポート絞り込みオプションのテスト
+    #[test]
+    fn test_port_filter_options_parse() {
+        let args = CliArgs::try_parse_from([
+            "safe-kill",
+            "--port",
+            "5353",
+            "--udp",
+            "-4",
+            "--bind",
+            "127.0.0.1",
+        ])
+        .unwrap();
+        assert_eq!(
+            args.validate(),
+            Ok(ExecutionMode::KillByPort(PortSelection::single(5353)))
+        );
+        assert_eq!(
+            args.port_filter(),
+            Ok(PortFilter {
+                protocol: Some(PortProtocol::Udp),
+                family: Some(AddressFamily::Ipv4),
+                bind: Some("127.0.0.1".parse().unwrap()),
+            })
+        );
+    }
+
+    #[test]
+    fn test_port_filter_defaults_to_no_filter() {
+        let args = CliArgs::try_parse_from(["safe-kill", "--port", "3000"]).unwrap();
+        assert_eq!(args.port_filter(), Ok(PortFilter::default()));
+    }
+
+    #[test]
+    fn test_port_filter_conflicting_flags_rejected() {
+        assert!(CliArgs::try_parse_from(["safe-kill", "-p", "3000", "--tcp", "--udp"]).is_err());
+        assert!(CliArgs::try_parse_from(["safe-kill", "-p", "3000", "-4", "-6"]).is_err());
+        assert!(
+            CliArgs::try_parse_from(["safe-kill", "-p", "3000", "--bind", "localhost"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_port_filter_bind_family_mismatch_rejected() {
+        let args =
+            CliArgs::try_parse_from(["safe-kill", "-p", "3000", "-6", "--bind", "127.0.0.1"])
+                .unwrap();
+        match args.validate() {
+            Err(SafeKillError::InvalidUsage(msg)) => assert!(msg.contains("-6")),
+            other => panic!("Expected InvalidUsage, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_port_filter_requires_port() {
+        let args = CliArgs::try_parse_from(["safe-kill", "--tcp", "1234"]).unwrap();
+        match args.validate() {
+            Err(SafeKillError::InvalidUsage(msg)) => assert!(msg.contains("--port")),
+            other => panic!("Expected InvalidUsage, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_subcommand_rejects_port_filter() {
+        let mut args = make_args_with_command(Command::Root);
+        args.port_filter.udp = true;
+        assert!(matches!(
+            args.validate(),
+            Err(SafeKillError::InvalidUsage(_))
+        ));
+    }
+
+    #[test]
+    fn test_cli_parser_accepts_root_subcommand() {
+        let args = CliArgs::try_parse_from(["safe-kill", "root"]).unwrap();
+        assert_eq!(args.command, Some(Command::Root));
+    }
+
+    // Signals サブコマンドテスト
+    #[test]
+    fn test_signals_command() {
+        let args = CliArgs::try_parse_from(["safe-kill", "signals"]).unwrap();
+        assert_eq!(args.command, Some(Command::Signals));
+        assert_eq!(args.validate(), Ok(ExecutionMode::ListSignals));
+    }
+
+    #[test]
+    fn test_signals_command_rejects_signal_option() {
+        let mut args = make_args_with_command(Command::Signals);
+        args.signal = "KILL".to_string();
+        match args.validate() {
+            Err(SafeKillError::InvalidUsage(msg)) => assert!(msg.starts_with("signals ")),
+            other => panic!("Expected InvalidUsage, got {:?}", other),
+        }
+    }
+
+    // --socket テスト
+    #[test]
+    fn test_socket_option() {
+        let args = CliArgs::try_parse_from(["safe-kill", "--socket", "/run/dev.sock"]).unwrap();
+        assert_eq!(
+            args.validate(),
+            Ok(ExecutionMode::KillBySocket(PathBuf::from("/run/dev.sock")))
+        );
+    }
+
+    #[test]
+    fn test_socket_option_conflicts_with_port() {
+        let args =
+            CliArgs::try_parse_from(["safe-kill", "--socket", "/run/dev.sock", "-p", "3000"])
+                .unwrap();
+        match args.validate() {
+            Err(SafeKillError::InvalidUsage(msg)) => assert!(msg.contains("--socket")),
+            other => panic!("Expected InvalidUsage, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_socket_option_conflicts_with_name() {
+        let args =
+            CliArgs::try_parse_from(["safe-kill", "--socket", "@dev", "-N", "node"]).unwrap();
+        match args.validate() {
+            Err(SafeKillError::InvalidUsage(msg)) => {
+                assert_eq!(
+                    msg,
+                    "--socket cannot be combined with PID, --name, --holding, or --cwd"
+                )
+            }
+            other => panic!("Expected InvalidUsage, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_socket_option_rejects_port_filter() {
+        let args =
+            CliArgs::try_parse_from(["safe-kill", "--socket", "/run/dev.sock", "--tcp"]).unwrap();
+        assert!(matches!(
+            args.validate(),
+            Err(SafeKillError::InvalidUsage(_))
+        ));
+    }
+
+    // --holding テスト
+    #[test]
+    fn test_holding_option() {
+        let args = CliArgs::try_parse_from(["safe-kill", "--holding", "target"]).unwrap();
+        assert_eq!(
+            args.validate(),
+            Ok(ExecutionMode::KillByHolding(PathBuf::from("target")))
+        );
+    }
+
+    #[test]
+    fn test_holding_option_conflicts_with_pid() {
+        let args = CliArgs::try_parse_from(["safe-kill", "1234", "--holding", "target"]).unwrap();
+        match args.validate() {
+            Err(SafeKillError::InvalidUsage(msg)) => {
+                assert_eq!(
+                    msg,
+                    "--holding cannot be combined with PID, --name, or --cwd"
+                )
+            }
+            other => panic!("Expected InvalidUsage, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_holding_option_conflicts_with_list() {
+        let args = CliArgs::try_parse_from(["safe-kill", "-l", "--holding", "target"]).unwrap();
+        match args.validate() {
+            Err(SafeKillError::InvalidUsage(msg)) => assert!(msg.starts_with("--list ")),
+            other => panic!("Expected InvalidUsage, got {:?}", other),
+        }
+    }
+
+    // --cwd テスト
+    #[test]
+    fn test_cwd_option_with_yes() {
+        let args = CliArgs::try_parse_from(["safe-kill", "--cwd", "./app", "--yes"]).unwrap();
+        assert!(args.yes);
+        assert_eq!(
+            args.validate(),
+            Ok(ExecutionMode::KillByCwd(PathBuf::from("./app")))
+        );
+    }
+
+    #[test]
+    fn test_yes_requires_batch_target() {
+        let args = CliArgs::try_parse_from(["safe-kill", "-y", "1234"]).unwrap();
+        match args.validate() {
+            Err(SafeKillError::InvalidUsage(msg)) => assert_eq!(
+                msg,
+                "--yes can only be used with --name, --cwd, --port, --socket or --holding"
+            ),
+            other => panic!("Expected InvalidUsage, got {:?}", other),
+        }
+
+        for target in [
+            ["--port", "3000-3010"],
+            ["--socket", "/tmp/dev.sock"],
+            ["--holding", "/tmp/app.db"],
+        ] {
+            let args = CliArgs::try_parse_from(["safe-kill", "-y", target[0], target[1]]).unwrap();
+            assert!(args.validate().is_ok(), "{:?}", target);
+            let args = CliArgs::try_parse_from(["safe-kill", "-i", target[0], target[1]]).unwrap();
+            assert!(args.validate().is_ok(), "{:?}", target);
+        }
+    }
+
+    #[test]
+    fn test_interactive_option_with_name() {
+        let args = CliArgs::try_parse_from(["safe-kill", "-i", "-N", "node"]).unwrap();
+        assert!(args.interactive);
+        assert_eq!(
+            args.validate(),
+            Ok(ExecutionMode::KillByName("node".to_string()))
+        );
+
+        let args = CliArgs::try_parse_from(["safe-kill", "--yes", "-N", "node"]).unwrap();
+        assert!(args.validate().is_ok());
+    }
+
+    #[test]
+    fn test_interactive_requires_batch_target() {
+        let args = CliArgs::try_parse_from(["safe-kill", "-i", "1234"]).unwrap();
+        match args.validate() {
+            Err(SafeKillError::InvalidUsage(msg)) => {
+                assert_eq!(
+                    msg,
+                    "--interactive can only be used with --name, --cwd, --port, --socket or --holding"
+                )
+            }
+            other => panic!("Expected InvalidUsage, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_interactive_conflicts_with_yes() {
+        let args = CliArgs::try_parse_from(["safe-kill", "-i", "-y", "--cwd", "."]).unwrap();
+        match args.validate() {
+            Err(SafeKillError::InvalidUsage(msg)) => {
+                assert_eq!(msg, "--interactive cannot be combined with --yes")
+            }
+            other => panic!("Expected InvalidUsage, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_cwd_option_conflicts_with_name() {
+        let args = CliArgs::try_parse_from(["safe-kill", "--cwd", ".", "-N", "node"]).unwrap();
+        match args.validate() {
+            Err(SafeKillError::InvalidUsage(msg)) => {
+                assert_eq!(msg, "--cwd cannot be combined with PID or --name")
+            }
+            other => panic!("Expected InvalidUsage, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_target_conflict_message_lists_later_targets() {
+        assert_eq!(
+            target_conflict_message(0),
+            "--list cannot be combined with PID, --name, --port, --socket, --holding, or --cwd"
+        );
+        assert_eq!(
+            target_conflict_message(1),
+            "--port cannot be combined with PID, --name, --socket, --holding, or --cwd"
+        );
+    }
+
+    // Ports サブコマンドテスト
+    #[test]
+    fn test_ports_command() {
+        let args = CliArgs::try_parse_from(["safe-kill", "ports"]).unwrap();
+        assert_eq!(
+            args.validate(),
+            Ok(ExecutionMode::ListPorts {
+                json: false,
+                all: false
+            })
+        );
+    }
+
+    #[test]
+    fn test_ports_command_with_json_and_all() {
+        let args = CliArgs::try_parse_from(["safe-kill", "ports", "--json", "--all"]).unwrap();
+        assert_eq!(
+            args.validate(),
+            Ok(ExecutionMode::ListPorts {
+                json: true,
+                all: true
+            })
+        );
+    }
+
+    #[test]
+    fn test_ports_command_rejects_port_filter() {
+        let mut args = make_args_with_command(Command::Ports {
+            json: false,
+            all: false,
+        });
+        args.port_filter.tcp = true;
+        match args.validate() {
+            Err(SafeKillError::InvalidUsage(msg)) => assert!(msg.starts_with("ports ")),
+            other => panic!("Expected InvalidUsage, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_allow_command_with_name() {
+        let args =
+            CliArgs::try_parse_from(["safe-kill", "allow", "--name", "next-server", "--for", "2h"])
+                .unwrap();
+        assert_eq!(
+            args.validate(),
+            Ok(ExecutionMode::AllowTemporarily {
+                grant: Grant::Process("next-server".to_string()),
+                duration: Duration::from_secs(7200),
+            })
+        );
+    }
+
+    #[test]
+    fn test_allow_command_with_port() {
+        let args =
+            CliArgs::try_parse_from(["safe-kill", "allow", "-p", "tcp:9229", "--for", "30m"])
+                .unwrap();
+        assert_eq!(
+            args.validate(),
+            Ok(ExecutionMode::AllowTemporarily {
+                grant: Grant::Port("tcp:9229".to_string()),
+                duration: Duration::from_secs(1800),
+            })
+        );
+
+        let args =
+            CliArgs::try_parse_from(["safe-kill", "allow", "-p", "0", "--for", "30m"]).unwrap();
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    fn test_allow_command_requires_target_and_duration() {
+        for argv in [
+            vec!["safe-kill", "allow", "--for", "2h"],
+            vec!["safe-kill", "allow", "--name", "next-server"],
+            vec!["safe-kill", "allow", "--name", "x", "--for", "2 hours"],
+            vec!["safe-kill", "allow", "-N", "x", "-p", "9229", "--for", "2h"],
+        ] {
+            assert!(CliArgs::try_parse_from(&argv).is_err(), "{:?}", argv);
+        }
+    }
+
+    #[test]
+    fn test_config_command_actions() {
+        let cases = [
+            (
+                vec!["safe-kill", "config", "validate"],
+                ConfigAction::Validate {
+                    path: None,
+                    lint: false,
+                },
+            ),
+            (
+                vec!["safe-kill", "config", "validate", "--lint", "/tmp/c.toml"],
+                ConfigAction::Validate {
+                    path: Some(PathBuf::from("/tmp/c.toml")),
+                    lint: true,
+                },
+            ),
+            (vec!["safe-kill", "config", "show"], ConfigAction::Show),
+            (vec!["safe-kill", "config", "edit"], ConfigAction::Edit),
+            (vec!["safe-kill", "config", "schema"], ConfigAction::Schema),
+            (
+                vec!["safe-kill", "config", "add-port", "4000-4010"],
+                ConfigAction::AddPort {
+                    spec: "4000-4010".to_string(),
+                },
+            ),
+            (
+                vec!["safe-kill", "config", "add-deny", "postgres"],
+                ConfigAction::AddDeny {
+                    name: "postgres".to_string(),
+                },
+            ),
+        ];
+        for (argv, action) in cases {
+            let args = CliArgs::try_parse_from(&argv).unwrap();
+            assert_eq!(args.validate(), Ok(ExecutionMode::ManageConfig(action)));
+        }
+    }
+
+    #[test]
+    fn test_config_add_port_rejects_invalid_spec() {
+        let args =
+            CliArgs::try_parse_from(["safe-kill", "config", "add-port", "4010-4000"]).unwrap();
+        assert!(args.validate().is_err());
+        assert!(CliArgs::try_parse_from(["safe-kill", "config"]).is_err());
+        assert!(CliArgs::try_parse_from(["safe-kill", "config", "add-deny"]).is_err());
+    }
+
+    #[test]
+    fn test_config_and_set_are_global_options() {
+        // サブコマンドと併用する場合はサブコマンドの後に書く
+        let args = CliArgs::try_parse_from([
+            "safe-kill",
+            "ports",
+            "--config",
+            "/tmp/ci.toml",
+            "--set",
+            "allowed_ports.ports=3000",
+            "--set",
+            "confirm.sigkill=true",
+            "--profile",
+            "frontend",
+        ])
+        .unwrap();
+        assert_eq!(
+            args.validate(),
+            Ok(ExecutionMode::ListPorts {
+                json: false,
+                all: false
+            })
+        );
+        assert_eq!(
+            args.load_options(),
+            LoadOptions {
+                path: Some(PathBuf::from("/tmp/ci.toml")),
+                sets: vec![
+                    "allowed_ports.ports=3000".to_string(),
+                    "confirm.sigkill=true".to_string()
+                ],
+                profile: Some("frontend".to_string()),
+            }
+        );
+
+        let args = CliArgs::try_parse_from(["safe-kill", "--config", "/tmp/ci.toml", "-p", "3000"])
+            .unwrap();
+        assert!(matches!(args.validate(), Ok(ExecutionMode::KillByPort(_))));
+    }
+
+    #[test]
+    fn test_cli_parser_rejects_pid_with_root_subcommand() {
+        let result = CliArgs::try_parse_from(["safe-kill", "1234", "root"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_command_name() {
+        assert_eq!(
+            Command::Init {
+                force: false,
+                interactive: false,
+                template: None,
+            }
+            .name(),
+            "init"
+        );
+        assert_eq!(Command::Root.name(), "root");
+        assert_eq!(Command::Signals.name(), "signals");
+        assert_eq!(
+            Command::Ports {
+                json: false,
+                all: false
+            }
+            .name(),
+            "ports"
+        );
+    }
+
     #[test]
     fn test_cli_parser_rejects_list_with_init_subcommand() {
         let result = CliArgs::try_parse_from(["safe-kill", "--list", "init"]);
@@ -3,12 +3,36 @@
 //! ~/.config/safe-kill/config.toml 設定ファイルの読み込みと解析を行う。
 
 use crate::error::SafeKillError;
-use serde::Deserialize;
+use crate::grants::{self, Grant, GrantStore, Grants};
+use crate::overrides;
+use crate::port::{PortProtocol, PortSelection};
+use crate::signal::{Signal, SignalSender};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+
+/// 設定ディレクトリに置く JSON Schema のファイル名（config.toml の `#:schema` から参照する）
+pub const SCHEMA_FILE_NAME: &str = "config.schema.json";
+
+/// 設定ファイルのパスを指定する環境変数（`--config` が優先する）
+pub const CONFIG_ENV_VAR: &str = "SAFE_KILL_CONFIG";
+
+/// 使用するプロファイルを指定する環境変数（`--profile` が優先する）
+pub const PROFILE_ENV_VAR: &str = "SAFE_KILL_PROFILE";
+
+/// 管理者が全ユーザーに適用するシステム設定
+pub const SYSTEM_CONFIG_PATH: &str = "/etc/safe-kill/config.toml";
+
+/// `--cwd` でこの数を超えるプロセスが kill 対象になる場合は確認を求める
+/// （`[confirm] above` で上書きできる）
+pub const CWD_CONFIRM_THRESHOLD: usize = 5;
 
 /// メイン設定構造体
-#[derive(Debug, Deserialize, Default, Clone, PartialEq, Eq)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Default, Clone, PartialEq, Eq)]
 #[serde(deny_unknown_fields)]
 pub struct Config {
     /// ancestry チェックをバイパスするプロセス（子孫検証なしで kill 可能）
@@ -17,22 +41,798 @@ pub struct Config {
     pub denylist: Option<ProcessList>,
     /// --port kill 操作で許可されるポート
     pub allowed_ports: Option<AllowedPorts>,
+    /// --socket kill 操作で許可される Unix ドメインソケットのパス（glob）
+    pub allowed_sockets: Option<AllowedSockets>,
+    /// --holding kill 操作で ancestry チェックをバイパスできるパス（glob）
+    pub allowed_paths: Option<AllowedPaths>,
+    /// 信頼ルート（ancestry 判定の起点）の探索方法
+    pub root: Option<RootConfig>,
+    /// kill 対象プロセスの所有者チェック
+    pub ownership: Option<OwnershipConfig>,
+    /// root などの特権付きで実行する場合のポリシー（特権実行時は必須）
+    pub privileged: Option<PrivilegedConfig>,
+    /// 送信を許可・禁止するシグナル
+    pub signals: Option<SignalsConfig>,
+    /// `--name` / `--cwd` で kill 前に確認を求める条件
+    pub confirm: Option<ConfirmConfig>,
+    /// 信頼ルートごとの kill 回数の上限
+    pub limits: Option<LimitsConfig>,
+    /// `--profile` / `SAFE_KILL_PROFILE` で選んでベースの設定に重ねる名前付きの設定
+    pub profiles: Option<BTreeMap<String, ProfileConfig>>,
+    /// ユーザー設定で変更できないセクション（システム設定でのみ指定できる）
+    pub lock: Option<LockConfig>,
+    /// 読み込んだシステム設定（`merge_defaults` でユーザー設定より前の層として適用する）
+    #[serde(skip)]
+    #[schemars(skip)]
+    pub system: Option<Box<SystemLayer>>,
 }
 
-/// プロセス名リスト
-#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+/// システム設定（/etc/safe-kill/config.toml）の層
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SystemLayer {
+    /// 読み込んだファイル
+    pub path: PathBuf,
+    /// ファイルの内容（デフォルトは合流していない）
+    pub config: Config,
+}
+
+/// 判定の根拠になった設定の層
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigLayer {
+    /// safe-kill に組み込まれた保護（既定の denylist）
+    BuiltIn,
+    /// 管理者のシステム設定
+    System(PathBuf),
+    /// ユーザーの設定（`--config`、環境変数、`safe-kill allow` を含む）
+    User,
+}
+
+impl fmt::Display for ConfigLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigLayer::BuiltIn => write!(f, "built-in"),
+            ConfigLayer::System(path) => write!(f, "system config {}", path.display()),
+            ConfigLayer::User => write!(f, "user config"),
+        }
+    }
+}
+
+/// `[profiles.<name>]` で定義する、呼び出しごとに選べる設定
+///
+/// 選ばれたプロファイルはベースの設定に重ねる。`allowlist`・`allowed_ports`・`signals` は
+/// ベースの値を置き換え、`denylist` はベースのエントリに追加する。
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Default, Clone, PartialEq, Eq)]
 #[serde(deny_unknown_fields)]
+pub struct ProfileConfig {
+    /// ベースの `[allowlist]` の代わりに使う allowlist
+    pub allowlist: Option<ProcessList>,
+    /// ベースの `[denylist]` に追加するプロセス
+    pub denylist: Option<ProcessList>,
+    /// ベースの `[allowed_ports]` の代わりに使うポート
+    pub allowed_ports: Option<AllowedPorts>,
+    /// ベースの `[signals]` の代わりに使うシグナルのルール
+    pub signals: Option<SignalsConfig>,
+}
+
+impl ProfileConfig {
+    /// プロファイルの値だけを持つ設定（ベースと同じ検証に使う）
+    fn as_config(&self) -> Config {
+        Config {
+            allowlist: self.allowlist.clone(),
+            denylist: self.denylist.clone(),
+            allowed_ports: self.allowed_ports.clone(),
+            signals: self.signals.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// ユーザー設定で変更できないようにするセクション
+///
+/// ロックされたセクションはシステム設定の値をそのまま使い、ユーザー設定の値は無視する。
+/// ただし `allowed_ports` はシステム設定のエントリを上限として、
+/// その範囲に収まるユーザー設定のエントリだけを残す。
+/// `[denylist]` はロックしなくても常にすべての層のエントリを合わせて適用する。
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Default, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct LockConfig {
+    /// ロックするセクション
+    #[serde(default)]
+    pub sections: Vec<LockedSection>,
+}
+
+/// `[lock] sections` に指定できるセクション
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LockedSection {
+    /// ユーザーは allowlist のエントリを追加できない
+    Allowlist,
+    /// ユーザーはシステム設定のポートの範囲内でしか許可できない
+    AllowedPorts,
+    /// ユーザーは `[allowed_sockets]` を変更できない
+    AllowedSockets,
+    /// ユーザーは `[allowed_paths]` を変更できない
+    AllowedPaths,
+    /// ユーザーは `[root]` を変更できない
+    Root,
+    /// ユーザーは `[ownership]` を変更できない
+    Ownership,
+    /// ユーザーは `[privileged]` を変更できない
+    Privileged,
+    /// ユーザーは `[signals]` を変更できない
+    Signals,
+    /// ユーザーは `[confirm]` を変更できない
+    Confirm,
+    /// ユーザーは `[limits]` を変更できない
+    Limits,
+}
+
+impl LockedSection {
+    /// 設定ファイル上のセクション名を返す
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LockedSection::Allowlist => "allowlist",
+            LockedSection::AllowedPorts => "allowed_ports",
+            LockedSection::AllowedSockets => "allowed_sockets",
+            LockedSection::AllowedPaths => "allowed_paths",
+            LockedSection::Root => "root",
+            LockedSection::Ownership => "ownership",
+            LockedSection::Privileged => "privileged",
+            LockedSection::Signals => "signals",
+            LockedSection::Confirm => "confirm",
+            LockedSection::Limits => "limits",
+        }
+    }
+}
+
+/// プロセス名リスト
+///
+/// `processes` には名前の文字列のほか、期限付きのエントリ
+/// `{ name = "next-server", expires = "2026-12-01T00:00:00Z" }` も書ける。
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Default, Clone, PartialEq, Eq)]
+#[serde(try_from = "RawProcessList", into = "RawProcessList")]
 pub struct ProcessList {
     /// リスト内のプロセス名
     pub processes: Vec<String>,
+    /// 期限付きのプロセス名
+    pub expiring: Vec<ExpiringEntry>,
+}
+
+impl ProcessList {
+    /// 空のプロセス名を探し、該当エントリと設定エラーを返す
+    pub fn entry_errors(&self, section: &str) -> Vec<(String, SafeKillError)> {
+        self.processes
+            .iter()
+            .chain(self.expiring.iter().map(|entry| &entry.value))
+            .filter(|name| name.trim().is_empty())
+            .map(|name| {
+                (
+                    name.clone(),
+                    SafeKillError::ConfigError(format!("[{}] empty process name", section)),
+                )
+            })
+            .collect()
+    }
+
+    /// 期限切れでないプロセス名に一致するか
+    pub fn contains_active(&self, name: &str, now: u64) -> bool {
+        self.processes.iter().any(|p| p == name)
+            || self
+                .expiring
+                .iter()
+                .any(|entry| entry.value == name && !entry.is_expired_at(now))
+    }
 }
 
 /// 許可ポート設定
-#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
-#[serde(deny_unknown_fields)]
+///
+/// `ports` にはポート指定の文字列のほか、期限付きのエントリ
+/// `{ port = "9229", expires = "2026-12-01T00:00:00Z" }` も書ける。
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Default, Clone, PartialEq, Eq)]
+#[serde(try_from = "RawAllowedPorts", into = "RawAllowedPorts")]
 pub struct AllowedPorts {
     /// ポート指定（単一ポート "3306" または範囲 "3000-3100"）
     pub ports: Vec<String>,
+    /// 期限付きのポート指定
+    pub expiring: Vec<ExpiringEntry>,
+}
+
+impl AllowedPorts {
+    /// 解析できないポート指定と、重複・重なり合うエントリを探し、該当エントリと設定エラーを返す
+    ///
+    /// プロトコル・アドレスの指定が同じエントリ同士のポート範囲が重なる場合を重なりとみなす
+    /// （"tcp:3000" と "udp:3000" は別の対象なので重ならない）。
+    pub fn entry_errors(&self) -> Vec<(String, SafeKillError)> {
+        let mut errors = Vec::new();
+        let mut parsed: Vec<(&str, PortSpec)> = Vec::new();
+        let entries = self
+            .ports
+            .iter()
+            .chain(self.expiring.iter().map(|entry| &entry.value));
+        for entry in entries {
+            let spec = match PortSpec::parse(entry) {
+                Ok(spec) => spec,
+                Err(e) => {
+                    errors.push((
+                        entry.clone(),
+                        SafeKillError::ConfigError(format!(
+                            "[allowed_ports] invalid entry {:?}: {}",
+                            entry, e
+                        )),
+                    ));
+                    continue;
+                }
+            };
+            let earlier = parsed.iter().find(|(_, other)| {
+                other.protocol == spec.protocol
+                    && other.address == spec.address
+                    && other.range.start() <= spec.range.end()
+                    && spec.range.start() <= other.range.end()
+            });
+            if let Some((other_entry, other)) = earlier {
+                let message = if *other == spec {
+                    format!(
+                        "[allowed_ports] duplicate entry {:?} (same as {:?})",
+                        entry, other_entry
+                    )
+                } else {
+                    format!(
+                        "[allowed_ports] entry {:?} overlaps {:?}",
+                        entry, other_entry
+                    )
+                };
+                errors.push((entry.clone(), SafeKillError::ConfigError(message)));
+            }
+            parsed.push((entry, spec));
+        }
+        errors
+    }
+
+    /// 期限切れでないポート指定
+    pub fn active(&self, now: u64) -> impl Iterator<Item = &str> {
+        self.ports.iter().map(String::as_str).chain(
+            self.expiring
+                .iter()
+                .filter(move |entry| !entry.is_expired_at(now))
+                .map(|entry| entry.value.as_str()),
+        )
+    }
+}
+
+/// 期限付きの許可エントリ
+///
+/// 設定ファイルでは絶対時刻の `expires` だけを受け付ける。ファイルには起点となる時刻が
+/// ないため、期間（TTL）による指定は `safe-kill allow --for` が実行時刻から期限を計算して
+/// grants.toml に書き込む形でのみ扱う。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpiringEntry {
+    /// プロセス名またはポート指定
+    pub value: String,
+    /// 有効期限（UNIX エポック秒）
+    pub expires: u64,
+}
+
+impl ExpiringEntry {
+    /// 指定時刻の時点で期限切れか
+    pub fn is_expired_at(&self, now: u64) -> bool {
+        now >= self.expires
+    }
+}
+
+/// `[allowlist]` / `[denylist]` の解析前の形
+#[derive(Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct RawProcessList {
+    /// プロセス名、または `{ name, expires }` の期限付きエントリ
+    processes: Vec<RawEntry<RawNamedEntry>>,
+}
+
+/// `[allowed_ports]` の解析前の形
+#[derive(Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct RawAllowedPorts {
+    /// ポート指定（"3000"、"3000-3010"、"tcp:127.0.0.1:3000"）、または `{ port, expires }` の期限付きエントリ
+    ports: Vec<RawEntry<RawPortEntry>>,
+}
+
+/// 文字列、または期限付きのエントリ
+#[derive(Deserialize, Serialize, JsonSchema)]
+#[serde(untagged)]
+#[schemars(rename = "Entry_{T}")]
+enum RawEntry<T> {
+    Plain(String),
+    Expiring(T),
+}
+
+/// `{ name = "...", expires = "..." }`
+#[derive(Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+#[schemars(rename = "ExpiringName")]
+struct RawNamedEntry {
+    /// プロセス名
+    name: String,
+    /// 有効期限（RFC 3339 の絶対時刻。`2h` のような期間は `safe-kill allow --for` でのみ指定できる）
+    #[schemars(extend("format" = "date-time"))]
+    expires: String,
+}
+
+/// `{ port = "...", expires = "..." }`
+#[derive(Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+#[schemars(rename = "ExpiringPort")]
+struct RawPortEntry {
+    /// ポート指定
+    port: String,
+    /// 有効期限（RFC 3339 の絶対時刻。`2h` のような期間は `safe-kill allow --for` でのみ指定できる）
+    #[schemars(extend("format" = "date-time"))]
+    expires: String,
+}
+
+impl From<RawNamedEntry> for (String, String) {
+    fn from(entry: RawNamedEntry) -> Self {
+        (entry.name, entry.expires)
+    }
+}
+
+impl From<RawPortEntry> for (String, String) {
+    fn from(entry: RawPortEntry) -> Self {
+        (entry.port, entry.expires)
+    }
+}
+
+/// 通常のエントリと期限付きのエントリに分ける（期限の表記が不正ならエラー）
+fn split_entries<T: Into<(String, String)>>(
+    entries: Vec<RawEntry<T>>,
+) -> Result<(Vec<String>, Vec<ExpiringEntry>), String> {
+    let mut plain = Vec::new();
+    let mut expiring = Vec::new();
+    for entry in entries {
+        match entry {
+            RawEntry::Plain(value) => plain.push(value),
+            RawEntry::Expiring(entry) => {
+                let (value, expires) = entry.into();
+                expiring.push(ExpiringEntry {
+                    value,
+                    expires: grants::parse_timestamp(&expires)?,
+                });
+            }
+        }
+    }
+    Ok((plain, expiring))
+}
+
+impl TryFrom<RawProcessList> for ProcessList {
+    type Error = String;
+
+    fn try_from(raw: RawProcessList) -> Result<Self, Self::Error> {
+        let (processes, expiring) = split_entries(raw.processes)?;
+        Ok(Self {
+            processes,
+            expiring,
+        })
+    }
+}
+
+impl TryFrom<RawAllowedPorts> for AllowedPorts {
+    type Error = String;
+
+    fn try_from(raw: RawAllowedPorts) -> Result<Self, Self::Error> {
+        let (ports, expiring) = split_entries(raw.ports)?;
+        Ok(Self { ports, expiring })
+    }
+}
+
+impl From<ProcessList> for RawProcessList {
+    fn from(list: ProcessList) -> Self {
+        Self {
+            processes: join_entries(list.processes, list.expiring, |name, expires| {
+                RawNamedEntry { name, expires }
+            }),
+        }
+    }
+}
+
+impl From<AllowedPorts> for RawAllowedPorts {
+    fn from(ports: AllowedPorts) -> Self {
+        Self {
+            ports: join_entries(ports.ports, ports.expiring, |port, expires| RawPortEntry {
+                port,
+                expires,
+            }),
+        }
+    }
+}
+
+/// 通常のエントリと期限付きのエントリを設定ファイル上の形に戻す
+fn join_entries<T>(
+    plain: Vec<String>,
+    expiring: Vec<ExpiringEntry>,
+    entry: impl Fn(String, String) -> T,
+) -> Vec<RawEntry<T>> {
+    plain
+        .into_iter()
+        .map(RawEntry::Plain)
+        .chain(
+            expiring
+                .into_iter()
+                .map(|e| RawEntry::Expiring(entry(e.value, grants::format_timestamp(e.expires)))),
+        )
+        .collect()
+}
+
+/// 許可 Unix ドメインソケット設定
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct AllowedSockets {
+    /// ソケットパスの glob（例: "/run/user/*/dev-*.sock"、抽象名前空間は "@name"）
+    pub paths: Vec<String>,
+}
+
+impl AllowedSockets {
+    /// glob がすべて解釈できるか検証する
+    pub fn validate(&self) -> Result<(), SafeKillError> {
+        validate_path_globs("allowed_sockets", &self.paths)
+    }
+
+    /// 正規化済みのソケットパスがいずれかの glob に一致するか
+    pub fn matches(&self, path: &Path) -> bool {
+        path_globs_match(&self.paths, path)
+    }
+}
+
+/// 許可パス設定（`--holding` で ancestry チェックをバイパスできるパス）
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct AllowedPaths {
+    /// ファイル・ディレクトリパスの glob（例: "/home/me/project/target"、"/tmp/*.sqlite"）
+    pub paths: Vec<String>,
+}
+
+impl AllowedPaths {
+    /// glob がすべて解釈できるか検証する
+    pub fn validate(&self) -> Result<(), SafeKillError> {
+        validate_path_globs("allowed_paths", &self.paths)
+    }
+
+    /// 正規化済みのパスがいずれかの glob に一致するか
+    pub fn matches(&self, path: &Path) -> bool {
+        path_globs_match(&self.paths, path)
+    }
+}
+
+/// `Option` から生成される null の型指定を取り除く
+fn strip_null_types(schema: &mut serde_json::Value) {
+    let null = serde_json::json!({ "type": "null" });
+    if let Some(object) = schema.as_object_mut() {
+        // { "anyOf": [{ "$ref": ... }, { "type": "null" }] } は参照先だけにする
+        let nullable_ref = object
+            .get("anyOf")
+            .and_then(|any_of| any_of.as_array())
+            .filter(|any_of| any_of.len() == 2 && any_of.contains(&null))
+            .and_then(|any_of| any_of.iter().find(|s| **s != null).cloned());
+        if let Some(serde_json::Value::Object(inner)) = nullable_ref {
+            object.remove("anyOf");
+            object.extend(inner);
+        }
+        // { "type": ["integer", "null"] } は { "type": "integer" } にする
+        if let Some(serde_json::Value::Array(types)) = object.get_mut("type") {
+            types.retain(|t| t != "null");
+            if let [only] = types.as_slice() {
+                let only = only.clone();
+                object.insert("type".to_string(), only);
+            }
+        }
+        object.values_mut().for_each(strip_null_types);
+    } else if let Some(array) = schema.as_array_mut() {
+        array.iter_mut().for_each(strip_null_types);
+    }
+}
+
+/// パス glob のリストがすべて解釈できるか検証する
+fn validate_path_globs(section: &str, globs: &[String]) -> Result<(), SafeKillError> {
+    for path in globs {
+        glob::Pattern::new(path).map_err(|e| {
+            SafeKillError::ConfigError(format!("[{}] invalid glob '{}': {}", section, path, e))
+        })?;
+    }
+    Ok(())
+}
+
+/// パスがいずれかの glob に一致するか
+///
+/// `*` と `?` はパス区切り `/` に一致しない。ディレクトリをまたぐには `**` を使う。
+fn path_globs_match(globs: &[String], path: &Path) -> bool {
+    let options = glob::MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    };
+    globs.iter().any(|pattern| {
+        glob::Pattern::new(pattern).is_ok_and(|pattern| pattern.matches_path_with(path, options))
+    })
+}
+
+/// 信頼ルートの探索戦略
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RootStrategy {
+    /// 祖父 → 親 → 自プロセスの順に採用する（従来の自動検出）
+    #[default]
+    Default,
+    /// 現在プロセスから `levels` 階層上の祖先を採用する
+    Levels,
+    /// `names` のいずれかに名前が一致する最も近い祖先を採用する
+    Name,
+    /// 最も近いセッションリーダーの祖先を採用する
+    SessionLeader,
+}
+
+impl RootStrategy {
+    /// 設定ファイル上の表記を返す
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RootStrategy::Default => "default",
+            RootStrategy::Levels => "levels",
+            RootStrategy::Name => "name",
+            RootStrategy::SessionLeader => "session_leader",
+        }
+    }
+}
+
+/// 信頼ルート探索設定
+///
+/// `sh -c` や `npx` などのラッパー経由で起動される環境では、祖父プロセスが
+/// エージェント本体ではないことがある。その場合に探索方法を切り替える。
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Default, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct RootConfig {
+    /// 探索戦略（省略時は `default`）
+    #[serde(default)]
+    pub strategy: RootStrategy,
+    /// `levels` 戦略でさかのぼる階層数（1 = 親、2 = 祖父）
+    pub levels: Option<u32>,
+    /// `name` 戦略で一致させる祖先のプロセス名
+    pub names: Option<Vec<String>>,
+}
+
+impl RootConfig {
+    /// 戦略に必要な値がそろっているか検証する
+    pub fn validate(&self) -> Result<(), SafeKillError> {
+        match self.strategy {
+            RootStrategy::Levels => match self.levels {
+                Some(levels) if levels >= 1 => Ok(()),
+                _ => Err(SafeKillError::ConfigError(
+                    "[root] strategy = \"levels\" requires levels >= 1".to_string(),
+                )),
+            },
+            RootStrategy::Name => {
+                let has_names = self
+                    .names
+                    .as_ref()
+                    .is_some_and(|names| names.iter().any(|name| !name.trim().is_empty()));
+                if has_names {
+                    Ok(())
+                } else {
+                    Err(SafeKillError::ConfigError(
+                        "[root] strategy = \"name\" requires a non-empty names list".to_string(),
+                    ))
+                }
+            }
+            RootStrategy::Default | RootStrategy::SessionLeader => Ok(()),
+        }
+    }
+}
+
+/// プロセス所有者チェック設定
+///
+/// 起動ユーザー以外が所有するプロセスは、ここで明示したユーザーのものに限り kill できる。
+/// root など特権付きで実行されている場合、このチェックは `enforce` に関わらず常に有効。
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Default, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct OwnershipConfig {
+    /// 非特権実行時にも所有者チェックを有効にする
+    #[serde(default)]
+    pub enforce: bool,
+    /// 起動ユーザーに加えて kill を許可するユーザー（ユーザー名または UID）
+    #[serde(default)]
+    pub users: Vec<String>,
+}
+
+impl OwnershipConfig {
+    /// 指定されたユーザーがすべて解決できるか検証する
+    pub fn validate(&self) -> Result<(), SafeKillError> {
+        for user in &self.users {
+            if crate::privilege::resolve_user(user).is_none() {
+                return Err(SafeKillError::ConfigError(format!(
+                    "[ownership] unknown user: {:?}",
+                    user
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 特権付き実行時のポリシー
+///
+/// euid 0 や CAP_KILL で実行すると任意のプロセスにシグナルを送れるため、
+/// このセクションがない限り kill を拒否する。既定値は通常実行より厳しい。
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Default, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct PrivilegedConfig {
+    /// allowlist による ancestry チェックのバイパスを許可する（既定: false）
+    #[serde(default)]
+    pub allowlist_bypass: bool,
+    /// ポート指定 kill で `[ownership] users` のプロセスも対象にする
+    /// （既定: false。起動ユーザーのプロセスのみ）
+    #[serde(default)]
+    pub port_other_users: bool,
+}
+
+/// kill 前に確認を求める条件
+///
+/// `--name` と `--cwd` で、条件に当てはまる場合は端末で確認を求める。
+/// 端末がない場合は `--yes` を付けない限りシグナルを送らない。
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Default, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ConfirmConfig {
+    /// kill 可能なプロセスがこの数を超えたら確認する
+    pub above: Option<usize>,
+    /// SIGKILL を送る前は件数に関わらず確認する
+    #[serde(default)]
+    pub sigkill: bool,
+}
+
+impl ConfirmConfig {
+    /// 確認が必要か判定する
+    ///
+    /// `default_above` は `above` が未設定の場合に使う件数（`--cwd` の既定値など）。
+    pub fn requires(&self, killable: usize, signal: Signal, default_above: Option<usize>) -> bool {
+        let over_limit = self
+            .above
+            .or(default_above)
+            .is_some_and(|above| killable > above);
+        over_limit || (self.sigkill && signal == Signal::SIGKILL)
+    }
+}
+
+/// 信頼ルート（エージェントのセッション）ごとの kill 回数の上限
+///
+/// 送信回数は `~/.local/state/safe-kill/limits.json` に記録し、
+/// 上限を超えた送信は `RateLimited` で拒否する。dry-run は数えない。
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Default, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct LimitsConfig {
+    /// 直近 1 分間に送れるシグナルの数
+    pub kills_per_minute: Option<u32>,
+    /// 1 セッションで送れる SIGKILL の数
+    pub sigkills_per_session: Option<u32>,
+}
+
+/// シグナルの許可・禁止リスト
+///
+/// `allow` が空なら全シグナルを許可し、空でなければ列挙したシグナルのみ許可する。
+/// `forbid` に含まれるシグナルは `allow` に関わらず禁止する。
+/// 値はシグナル名（"SIGKILL"、"KILL"）または番号（"9"）。
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Default, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct SignalRules {
+    /// 許可するシグナル（空なら制限なし）
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// 禁止するシグナル
+    #[serde(default)]
+    pub forbid: Vec<String>,
+}
+
+impl SignalRules {
+    /// シグナルがこのルールで許可されるか判定する
+    ///
+    /// 解析できない値は読み込み時の `validate` で弾かれる前提のため、ここでは無視する。
+    pub fn permits(&self, signal: Signal) -> bool {
+        let matches = |spec: &String| SignalSender::parse_signal(spec).is_ok_and(|s| s == signal);
+        let allowed = self.allow.is_empty() || self.allow.iter().any(matches);
+        allowed && !self.forbid.iter().any(matches)
+    }
+
+    /// すべての値がシグナルとして解析できるか検証する
+    fn validate(&self, section: &str) -> Result<(), SafeKillError> {
+        for spec in self.allow.iter().chain(&self.forbid) {
+            SignalSender::parse_signal(spec).map_err(|_| {
+                SafeKillError::ConfigError(format!("[{}] unknown signal: {:?}", section, spec))
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// `[signals]` の追加ルールを適用する対象の区分
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignalTarget {
+    /// ancestry 判定で許可された子孫プロセス
+    Descendant,
+    /// allowlist によって許可されたプロセス
+    Allowlisted,
+    /// `--port` で指定されたポートを使用するプロセス
+    Port,
+}
+
+impl SignalTarget {
+    /// すべての区分（表示順）
+    pub const ALL: [SignalTarget; 3] = [
+        SignalTarget::Descendant,
+        SignalTarget::Allowlisted,
+        SignalTarget::Port,
+    ];
+
+    /// 設定のセクション名と表示に使う名前
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SignalTarget::Descendant => "descendant",
+            SignalTarget::Allowlisted => "allowlisted",
+            SignalTarget::Port => "port",
+        }
+    }
+}
+
+/// シグナル送信ポリシー
+///
+/// トップレベルの `allow` / `forbid` はすべての kill に適用する。
+/// `[signals.descendant]` / `[signals.allowlisted]` / `[signals.port]` は
+/// 対応する区分の対象にだけ追加で適用する。
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Default, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct SignalsConfig {
+    /// すべての対象で許可するシグナル（空なら制限なし）
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// すべての対象で禁止するシグナル
+    #[serde(default)]
+    pub forbid: Vec<String>,
+    /// ancestry 判定で許可された子孫プロセスへの追加ルール
+    pub descendant: Option<SignalRules>,
+    /// allowlist で許可された対象への追加ルール
+    pub allowlisted: Option<SignalRules>,
+    /// `--port` の対象への追加ルール
+    pub port: Option<SignalRules>,
+}
+
+impl SignalsConfig {
+    /// 全対象に適用するルール
+    pub fn global_rules(&self) -> SignalRules {
+        SignalRules {
+            allow: self.allow.clone(),
+            forbid: self.forbid.clone(),
+        }
+    }
+
+    /// 区分ごとの追加ルール
+    pub fn rules_for(&self, target: SignalTarget) -> Option<&SignalRules> {
+        match target {
+            SignalTarget::Descendant => self.descendant.as_ref(),
+            SignalTarget::Allowlisted => self.allowlisted.as_ref(),
+            SignalTarget::Port => self.port.as_ref(),
+        }
+    }
+
+    /// すべての値がシグナルとして解析できるか検証する
+    pub fn validate(&self) -> Result<(), SafeKillError> {
+        self.global_rules().validate("signals")?;
+        for target in SignalTarget::ALL {
+            if let Some(rules) = self.rules_for(target) {
+                rules.validate(&format!("signals.{}", target.as_str()))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 指定区分の対象へのシグナル送信が許可されているか判定する
+    pub fn permits(&self, signal: Signal, target: SignalTarget) -> bool {
+        self.global_rules().permits(signal)
+            && self
+                .rules_for(target)
+                .is_none_or(|rules| rules.permits(signal))
+    }
 }
 
 /// ポート範囲または単一ポートを表す
@@ -87,6 +887,22 @@ impl PortRange {
         Ok(port)
     }
 
+    /// 範囲の先頭ポート
+    pub fn start(&self) -> u16 {
+        match self {
+            PortRange::Single(p) => *p,
+            PortRange::Range { start, .. } => *start,
+        }
+    }
+
+    /// 範囲の末尾ポート（両端含む）
+    pub fn end(&self) -> u16 {
+        match self {
+            PortRange::Single(p) => *p,
+            PortRange::Range { end, .. } => *end,
+        }
+    }
+
     /// ポートがこの範囲内に含まれるか確認する
     pub fn contains(&self, port: u16) -> bool {
         match self {
@@ -96,52 +912,436 @@ impl PortRange {
     }
 }
 
-impl Config {
-    /// ~/.config/safe-kill/config.toml から設定を読み込む
-    ///
-    /// ファイルが存在しない場合はデフォルト設定を返す。
-    /// 解析エラー時は警告付きでデフォルト設定を返す。
-    pub fn load() -> Self {
-        Self::load_from_path(Self::config_path())
-    }
+/// `[allowed_ports]` の 1 エントリ（プロトコル・アドレス付きのポート指定）
+///
+/// サポートする形式:
+/// - ポートのみ: "3000"、"3000-3010"
+/// - プロトコル付き: "tcp:3000"、"udp:5353"
+/// - アドレス付き: "127.0.0.1:3000"、"[::1]:3000"
+/// - 両方: "tcp:127.0.0.1:3000"、"udp:[::1]:5353-5360"
+///
+/// プロトコル・アドレスを省略した場合はそれぞれ任意のものに一致する。
+/// アドレスはソケットのバインドアドレスと完全一致で比較する（0.0.0.0 は 127.0.0.1 に一致しない）。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortSpec {
+    /// 許可するプロトコル（None なら TCP/UDP とも）
+    pub protocol: Option<PortProtocol>,
+    /// 許可するバインドアドレス（None なら任意）
+    pub address: Option<IpAddr>,
+    /// 許可するポート範囲
+    pub range: PortRange,
+}
 
-    /// ~/.config/safe-kill/config.toml から設定を厳格に読み込む
-    ///
-    /// 設定ファイルが存在するのに読めない、または解析できない場合は
-    /// デフォルトにフォールバックせず、設定エラーとして返す。
-    pub fn try_load() -> Result<Self, SafeKillError> {
-        Self::try_load_from_path(Self::config_path())
-    }
+impl PortSpec {
+    /// ポート指定文字列を PortSpec に解析する
+    pub fn parse(spec: &str) -> Result<Self, SafeKillError> {
+        let trimmed = spec.trim();
+        let invalid = || SafeKillError::InvalidPortRange(trimmed.to_string());
 
-    /// 指定されたパスから設定を読み込む
-    pub fn load_from_path(path: Option<PathBuf>) -> Self {
-        match Self::try_load_from_path(path.clone()) {
-            Ok(config) => config,
-            Err(e) => {
-                if let Some(path) = path {
-                    eprintln!(
-                        "Warning: Failed to load config file {:?}: {}. Using defaults.",
-                        path, e
-                    );
-                } else {
-                    eprintln!(
-                        "Warning: Failed to load config file: {}. Using defaults.",
-                        e
-                    );
-                }
-                Self::with_defaults()
+        let (protocol, rest) = match trimmed.split_once(':') {
+            Some((prefix, rest)) if PortProtocol::parse(prefix).is_some() => {
+                (PortProtocol::parse(prefix), rest)
             }
-        }
-    }
+            _ => (None, trimmed),
+        };
 
-    /// 指定されたパスから設定を厳格に読み込む
-    pub fn try_load_from_path(path: Option<PathBuf>) -> Result<Self, SafeKillError> {
-        let Some(path) = path else {
+        let (address, ports) = if let Some(bracketed) = rest.strip_prefix('[') {
+            // IPv6 は "[::1]:3000" のように角括弧で囲む
+            let (addr, ports) = bracketed.split_once("]:").ok_or_else(invalid)?;
+            let addr: IpAddr = addr.parse().map_err(|_| invalid())?;
+            if !addr.is_ipv6() {
+                return Err(invalid());
+            }
+            (Some(addr), ports)
+        } else if let Some((addr, ports)) = rest.rsplit_once(':') {
+            // 角括弧なしは IPv4 のみ（"::1:3000" のような曖昧な表記は受け付けない）
+            let addr: IpAddr = addr.parse().map_err(|_| invalid())?;
+            if !addr.is_ipv4() {
+                return Err(invalid());
+            }
+            (Some(addr), ports)
+        } else {
+            (None, rest)
+        };
+
+        let range = PortRange::parse(ports).map_err(|e| match e {
+            SafeKillError::InvalidPortRange(_) => invalid(),
+            SafeKillError::InvalidPort(_) => SafeKillError::InvalidPort(trimmed.to_string()),
+            other => other,
+        })?;
+
+        Ok(Self {
+            protocol,
+            address,
+            range,
+        })
+    }
+
+    /// ポートがこの指定の範囲に含まれるか確認する（プロトコル・アドレスは問わない）
+    pub fn contains_port(&self, port: u16) -> bool {
+        self.range.contains(port)
+    }
+
+    /// 指定プロトコル・アドレスでバインドされたソケットがこの指定に一致するか確認する
+    pub fn matches(&self, port: u16, protocol: PortProtocol, address: &IpAddr) -> bool {
+        self.range.contains(port)
+            && self.protocol.is_none_or(|p| p == protocol)
+            && self.address.is_none_or(|a| a == *address)
+    }
+
+    /// `other` に一致するソケットがすべてこの指定にも一致するか確認する
+    pub fn covers(&self, other: &PortSpec) -> bool {
+        self.range.start() <= other.range.start()
+            && other.range.end() <= self.range.end()
+            && self.protocol.is_none_or(|p| other.protocol == Some(p))
+            && self.address.is_none_or(|a| other.address == Some(a))
+    }
+}
+
+/// ロックされていればシステム設定の値、そうでなければユーザー設定の値
+/// （なければシステム設定の値）を使う
+fn merge_section<T: Clone>(user: &mut Option<T>, system: &Option<T>, locked: bool) {
+    if locked || user.is_none() {
+        *user = system.clone();
+    }
+}
+
+/// ロックされた `[allowed_ports]` の上限になる、システム設定のポート指定
+fn port_caps(ports: &AllowedPorts) -> Vec<PortSpec> {
+    ports
+        .active(grants::now())
+        .filter_map(|entry| PortSpec::parse(entry).ok())
+        .collect()
+}
+
+/// ポート指定が上限のいずれかに収まるか確認する
+fn is_capped(caps: &[PortSpec], entry: &str) -> bool {
+    PortSpec::parse(entry).is_ok_and(|spec| caps.iter().any(|cap| cap.covers(&spec)))
+}
+
+/// 設定の値の検証で見つかった問題
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// 問題のあったプロファイル（`[profiles.<name>]` の中でなければ None）
+    pub profile: Option<String>,
+    /// 問題のあったセクション
+    pub section: &'static str,
+    /// 問題のあったエントリ（セクション全体の問題なら None）
+    pub entry: Option<String>,
+    /// 問題の内容
+    pub error: SafeKillError,
+}
+
+/// 設定の読み込み元と CLI からの上書き
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoadOptions {
+    /// `--config` で指定された設定ファイル
+    pub path: Option<PathBuf>,
+    /// `--set` で指定された `section.key=value`
+    pub sets: Vec<String>,
+    /// `--profile` で指定されたプロファイル
+    pub profile: Option<String>,
+}
+
+impl LoadOptions {
+    /// 使用する設定ファイルのパス（`--config` > `SAFE_KILL_CONFIG` > デフォルト）
+    pub fn config_path(&self) -> Option<PathBuf> {
+        self.path.clone().or_else(Config::config_path)
+    }
+
+    /// 設定ファイルが `--config` か `SAFE_KILL_CONFIG` で明示されているか
+    fn is_explicit(&self) -> bool {
+        self.path.is_some() || env_config_path().is_some()
+    }
+
+    /// 使用するプロファイル（`--profile` > `SAFE_KILL_PROFILE`、どちらもなければ None）
+    pub fn profile_name(&self) -> Option<String> {
+        self.profile.clone().or_else(|| {
+            std::env::var(PROFILE_ENV_VAR)
+                .ok()
+                .filter(|name| !name.is_empty())
+        })
+    }
+}
+
+/// `SAFE_KILL_CONFIG` で指定された設定ファイル（空なら未指定）
+fn env_config_path() -> Option<PathBuf> {
+    std::env::var_os(CONFIG_ENV_VAR)
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+impl Config {
+    /// ~/.config/safe-kill/config.toml から設定を読み込む
+    ///
+    /// `try_load` と同じく厳格に読み込む。設定エラーはデフォルトにフォールバックせず
+    /// パニックするため、エラーを扱う場合は `try_load` を使う。
+    #[deprecated(note = "use `Config::try_load`, which returns config errors instead of panicking")]
+    pub fn load() -> Self {
+        Self::try_load().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// ~/.config/safe-kill/config.toml から設定を厳格に読み込む
+    ///
+    /// 設定ファイルが存在するのに読めない、または解析できない場合は
+    /// デフォルトにフォールバックせず、設定エラーとして返す。
+    /// `safe-kill allow` の期限付き許可（grants.toml）も合流する。
+    pub fn try_load() -> Result<Self, SafeKillError> {
+        Self::try_load_with(&LoadOptions::default())
+    }
+
+    /// `--config` / `--set` と `SAFE_KILL_*` 環境変数を反映して設定を厳格に読み込む
+    ///
+    /// 明示された設定ファイルが存在しない場合はデフォルトにフォールバックせずエラーにする。
+    /// 上書きを反映した後も通常と同じ検証を行い、既定の denylist は常に合流する。
+    pub fn try_load_with(options: &LoadOptions) -> Result<Self, SafeKillError> {
+        let grants = match GrantStore::default_path() {
+            Some(path) => GrantStore::new(path).load()?,
+            None => Grants::default(),
+        };
+        Self::try_load_layered(
+            Path::new(SYSTEM_CONFIG_PATH),
+            options,
+            std::env::vars(),
+            grants,
+        )
+    }
+
+    /// システム設定、ユーザー設定、プロファイル、期限付き許可、上書きの順に層を重ねて読み込む
+    ///
+    /// 上書きは期限付き許可の後に合成するため、許可で広げたリストも上書きで絞り込める
+    /// （許可が上書きの絞り込みを広げ直すことはない）。ユーザー側の層をすべて反映してから
+    /// `merge_defaults` でシステム設定のロックと既定の denylist を適用する。
+    fn try_load_layered(
+        system_path: &Path,
+        options: &LoadOptions,
+        vars: impl IntoIterator<Item = (String, String)>,
+        grants: Grants,
+    ) -> Result<Self, SafeKillError> {
+        let system = Self::read_system_layer(system_path)?;
+
+        let path = options.config_path();
+        if let Some(path) = &path
+            && options.is_explicit()
+            && !path.exists()
+        {
+            return Err(SafeKillError::ConfigError(format!(
+                "Config file {} does not exist",
+                path.display()
+            )));
+        }
+        let user = match &path {
+            Some(path) => Self::read_layer(path)?,
+            None => None,
+        };
+        if let (Some(path), Some(Config { lock: Some(_), .. })) = (&path, &user) {
+            return Err(SafeKillError::ConfigError(format!(
+                "{}: [lock] is only allowed in the system config {}",
+                path.display(),
+                system_path.display()
+            )));
+        }
+        if let Some(SystemLayer {
+            config: Config {
+                profiles: Some(_), ..
+            },
+            ..
+        }) = &system
+        {
+            return Err(SafeKillError::ConfigError(format!(
+                "{}: [profiles] is only allowed in the user config",
+                system_path.display()
+            )));
+        }
+
+        let mut config = user.unwrap_or_default();
+        if let Some(name) = options.profile_name() {
+            config = config.apply_profile(&name)?;
+        }
+        config.warn_expired_entries(grants::now());
+        config.apply_grants(grants, grants::now());
+        let mut config = config.apply_overrides(vars, &options.sets)?;
+        config.system = system.map(Box::new);
+        config.warn_lock_conflicts();
+        config.merge_defaults();
+        Ok(config)
+    }
+
+    /// システム設定を読み込む（存在しなければ None）
+    fn read_system_layer(path: &Path) -> Result<Option<SystemLayer>, SafeKillError> {
+        let config = Self::read_layer(path).map_err(|e| match e {
+            SafeKillError::ConfigError(message) if !message.contains(&*path.to_string_lossy()) => {
+                SafeKillError::ConfigError(format!("{}: {}", path.display(), message))
+            }
+            other => other,
+        })?;
+        Ok(config.map(|config| SystemLayer {
+            path: path.to_path_buf(),
+            config,
+        }))
+    }
+
+    /// `[profiles.<name>]` をベースの設定に重ねる
+    ///
+    /// `allowlist`・`allowed_ports`・`signals` はプロファイルにあればその値で置き換え、
+    /// `denylist` はベースのエントリに追加する。定義されていない名前はエラーにする。
+    pub fn apply_profile(mut self, name: &str) -> Result<Self, SafeKillError> {
+        let Some(profile) = self
+            .profiles
+            .as_ref()
+            .and_then(|profiles| profiles.get(name))
+            .cloned()
+        else {
+            let available: Vec<&str> = self
+                .profiles
+                .iter()
+                .flat_map(|profiles| profiles.keys().map(String::as_str))
+                .collect();
+            return Err(SafeKillError::ConfigError(if available.is_empty() {
+                format!(
+                    "Profile '{}' is not defined (no [profiles] in the config)",
+                    name
+                )
+            } else {
+                format!(
+                    "Profile '{}' is not defined (available: {})",
+                    name,
+                    available.join(", ")
+                )
+            }));
+        };
+
+        if profile.allowlist.is_some() {
+            self.allowlist = profile.allowlist;
+        }
+        if let Some(additions) = profile.denylist {
+            let denylist = self.denylist.get_or_insert_with(ProcessList::default);
+            for name in additions.processes {
+                if !denylist.processes.contains(&name) {
+                    denylist.processes.push(name);
+                }
+            }
+        }
+        if profile.allowed_ports.is_some() {
+            self.allowed_ports = profile.allowed_ports;
+        }
+        if profile.signals.is_some() {
+            self.signals = profile.signals;
+        }
+        Ok(self)
+    }
+
+    /// 環境変数（`SAFE_KILL_*`）と `--set` の上書きを反映する
+    ///
+    /// 上書きは設定を厳しくする方向にだけ合成し（緩める上書きは `ConfigError`）、
+    /// 結果は設定ファイルと同じように検証する。
+    /// 既定の denylist とシステム設定は後から `merge_defaults` で合流するため、
+    /// 上書きでは取り除けない。`[privileged]` は上書きできない。
+    pub fn apply_overrides(
+        self,
+        vars: impl IntoIterator<Item = (String, String)>,
+        sets: &[String],
+    ) -> Result<Self, SafeKillError> {
+        let mut table = toml::Table::try_from(&self).map_err(|e| {
+            SafeKillError::SystemError(format!("Failed to encode config as TOML: {}", e))
+        })?;
+        if !overrides::apply(&mut table, vars, sets)? {
+            return Ok(self);
+        }
+        let config: Config = toml::Value::Table(table)
+            .try_into()
+            .map_err(|e| SafeKillError::ConfigError(format!("Invalid override: {}", e)))?;
+        if let Some(issue) = config.section_errors().into_iter().next() {
+            return Err(issue.error);
+        }
+        Ok(config)
+    }
+
+    /// 期限内の `safe-kill allow` の許可を `[allowlist]` / `[allowed_ports]` に合流する
+    pub fn apply_grants(&mut self, grants: Grants, now: u64) {
+        let active = |entries: Vec<ExpiringEntry>| {
+            entries
+                .into_iter()
+                .filter(|entry| !entry.is_expired_at(now))
+                .collect::<Vec<_>>()
+        };
+        let processes = active(grants.processes);
+        if !processes.is_empty() {
+            self.allowlist
+                .get_or_insert_with(ProcessList::default)
+                .expiring
+                .extend(processes);
+        }
+        let ports = active(grants.ports);
+        if !ports.is_empty() {
+            self.allowed_ports
+                .get_or_insert_with(AllowedPorts::default)
+                .expiring
+                .extend(ports);
+        }
+    }
+
+    /// 期限切れのエントリを警告する（エントリ自体は判定で無視される）
+    fn warn_expired_entries(&self, now: u64) {
+        let sections = [
+            (
+                "allowlist",
+                self.allowlist.as_ref().map(|list| &list.expiring),
+            ),
+            (
+                "allowed_ports",
+                self.allowed_ports.as_ref().map(|ports| &ports.expiring),
+            ),
+        ];
+        for (section, entries) in sections {
+            for entry in entries.into_iter().flatten() {
+                if entry.is_expired_at(now) {
+                    eprintln!(
+                        "Warning: [{}] entry '{}' expired at {} and is ignored",
+                        section,
+                        entry.value,
+                        grants::format_timestamp(entry.expires)
+                    );
+                }
+            }
+        }
+    }
+
+    /// 指定されたパスから設定を読み込む
+    pub fn load_from_path(path: Option<PathBuf>) -> Self {
+        match Self::try_load_from_path(path.clone()) {
+            Ok(config) => config,
+            Err(e) => {
+                if let Some(path) = path {
+                    eprintln!(
+                        "Warning: Failed to load config file {:?}: {}. Using defaults.",
+                        path, e
+                    );
+                } else {
+                    eprintln!(
+                        "Warning: Failed to load config file: {}. Using defaults.",
+                        e
+                    );
+                }
+                Self::with_defaults()
+            }
+        }
+    }
+
+    /// 指定されたパスから設定を厳格に読み込む
+    pub fn try_load_from_path(path: Option<PathBuf>) -> Result<Self, SafeKillError> {
+        let Some(mut config) = path.map_or(Ok(None), |path| Self::read_layer(&path))? else {
             return Ok(Self::with_defaults());
         };
+        config.warn_expired_entries(grants::now());
+        config.merge_defaults();
+        Ok(config)
+    }
 
+    /// 1 つの設定ファイルを解析・検証する（存在しなければ None、デフォルトは合流しない）
+    fn read_layer(path: &Path) -> Result<Option<Self>, SafeKillError> {
         match path.try_exists() {
-            Ok(false) => return Ok(Self::with_defaults()),
+            Ok(false) => return Ok(None),
             Ok(true) => {}
             Err(e) => {
                 return Err(SafeKillError::ConfigError(format!(
@@ -152,21 +1352,126 @@ impl Config {
             }
         }
 
-        let content = fs::read_to_string(&path).map_err(|e| {
+        let content = fs::read_to_string(path).map_err(|e| {
             SafeKillError::ConfigError(format!("Failed to read {}: {}", path.display(), e))
         })?;
-        let mut config = toml::from_str::<Config>(&content).map_err(|e| {
+        let config = toml::from_str::<Config>(&content).map_err(|e| {
             SafeKillError::ConfigError(format!("Failed to parse {}: {}", path.display(), e))
         })?;
-        config.merge_defaults();
-        Ok(config)
+        if let Some(issue) = config.section_errors().into_iter().next() {
+            return Err(issue.error);
+        }
+        Ok(Some(config))
+    }
+
+    /// 各セクションの値を検証し、見つかった問題をすべて返す
+    ///
+    /// TOML として解析できた後の検証（未知のユーザーやシグナル名、
+    /// 解析できない・重なり合うポート指定、空のプロセス名など）を行う。
+    pub fn section_errors(&self) -> Vec<ConfigIssue> {
+        let checks = [
+            ("root", self.root.as_ref().map(RootConfig::validate)),
+            (
+                "ownership",
+                self.ownership.as_ref().map(OwnershipConfig::validate),
+            ),
+            (
+                "signals",
+                self.signals.as_ref().map(SignalsConfig::validate),
+            ),
+            (
+                "allowed_sockets",
+                self.allowed_sockets.as_ref().map(AllowedSockets::validate),
+            ),
+            (
+                "allowed_paths",
+                self.allowed_paths.as_ref().map(AllowedPaths::validate),
+            ),
+            (
+                "denylist",
+                self.denylist
+                    .as_ref()
+                    .filter(|list| !list.expiring.is_empty())
+                    .map(|_| {
+                        Err(SafeKillError::ConfigError(
+                            "[denylist] entries cannot expire".to_string(),
+                        ))
+                    }),
+            ),
+        ];
+        let mut issues: Vec<ConfigIssue> = checks
+            .into_iter()
+            .filter_map(|(section, result)| {
+                Some(ConfigIssue {
+                    profile: None,
+                    section,
+                    entry: None,
+                    error: result?.err()?,
+                })
+            })
+            .collect();
+
+        let entry_errors = [
+            (
+                "allowlist",
+                self.allowlist
+                    .as_ref()
+                    .map(|list| list.entry_errors("allowlist")),
+            ),
+            (
+                "denylist",
+                self.denylist
+                    .as_ref()
+                    .map(|list| list.entry_errors("denylist")),
+            ),
+            (
+                "allowed_ports",
+                self.allowed_ports.as_ref().map(AllowedPorts::entry_errors),
+            ),
+        ];
+        for (section, errors) in entry_errors {
+            issues.extend(
+                errors
+                    .into_iter()
+                    .flatten()
+                    .map(|(entry, error)| ConfigIssue {
+                        profile: None,
+                        section,
+                        entry: Some(entry),
+                        error,
+                    }),
+            );
+        }
+
+        for (name, profile) in self.profiles.iter().flatten() {
+            issues.extend(
+                profile
+                    .as_config()
+                    .section_errors()
+                    .into_iter()
+                    .map(|issue| ConfigIssue {
+                        profile: Some(name.clone()),
+                        error: match issue.error {
+                            SafeKillError::ConfigError(message) => SafeKillError::ConfigError(
+                                format!("[profiles.{}] {}", name, message),
+                            ),
+                            other => other,
+                        },
+                        ..issue
+                    }),
+            );
+        }
+        issues
     }
 
-    /// デフォルトの設定ファイルパスを取得する
+    /// 設定ファイルパスを取得する
     ///
+    /// `SAFE_KILL_CONFIG` が設定されていればそのパスを、なければ
     /// Linux/macOS では `~/.config/safe-kill/config.toml` を返す
     pub fn config_path() -> Option<PathBuf> {
-        dirs::home_dir().map(|home| home.join(".config").join("safe-kill").join("config.toml"))
+        env_config_path().or_else(|| {
+            dirs::home_dir().map(|home| home.join(".config").join("safe-kill").join("config.toml"))
+        })
     }
 
     /// 設定ディレクトリのパスを取得する
@@ -174,36 +1479,305 @@ impl Config {
         dirs::home_dir().map(|home| home.join(".config").join("safe-kill"))
     }
 
+    /// 設定ファイルの JSON Schema（エディタでの補完・検証用）
+    ///
+    /// TOML には null がないため、省略可能なセクションやキーは null を含めずに表す。
+    pub fn json_schema() -> serde_json::Value {
+        let mut schema = schemars::schema_for!(Config).to_value();
+        if let Some(root) = schema.as_object_mut() {
+            root.insert("title".to_string(), "safe-kill config.toml".into());
+        }
+        strip_null_types(&mut schema);
+        schema
+    }
+
     /// デフォルト denylist 付きの設定を生成する
     fn with_defaults() -> Self {
         Config {
             allowlist: None,
             denylist: Some(ProcessList {
                 processes: Self::default_denylist(),
+                expiring: Vec::new(),
             }),
             allowed_ports: None,
+            allowed_sockets: None,
+            allowed_paths: None,
+            root: None,
+            ownership: None,
+            privileged: None,
+            signals: None,
+            confirm: None,
+            limits: None,
+            profiles: None,
+            lock: None,
+            system: None,
         }
     }
 
-    /// 既存の設定にデフォルト値をマージする
+    /// 既存の設定にデフォルト値とシステム設定をマージする
+    ///
+    /// 組み込みの保護、システム設定（`system`）、ユーザー設定（自分自身）の順に層を重ねる。
+    /// denylist はすべての層のエントリを合わせる。ロックされたセクションはシステム設定の値
+    /// （`allowed_ports` はその範囲に収まるユーザー設定のエントリ）を使い、
+    /// ロックされていないセクションはユーザー設定になければシステム設定の値を使う。
+    /// 何度呼んでも結果は変わらない。
     pub(crate) fn merge_defaults(&mut self) {
+        let layer = self.system.take();
+        let system = layer.as_ref().map(|layer| &layer.config);
+
         let mut processes = self
             .denylist
             .take()
             .map(|list| list.processes)
             .unwrap_or_default();
 
-        // システム保護対象は常に denylist に含める。
-        for default_process in Self::default_denylist() {
-            if !processes.iter().any(|process| process == &default_process) {
-                processes.push(default_process);
+        // 管理者とシステムの保護対象は常に denylist に含める。
+        let mandatory = system
+            .and_then(|system| system.denylist.as_ref())
+            .map(|list| list.processes.clone())
+            .unwrap_or_default()
+            .into_iter()
+            .chain(Self::default_denylist());
+        for name in mandatory {
+            if !processes.contains(&name) {
+                processes.push(name);
             }
         }
 
-        self.denylist = Some(ProcessList { processes });
+        self.denylist = Some(ProcessList {
+            processes,
+            expiring: Vec::new(),
+        });
 
         // 注意: allowed_ports はデフォルトでは設定されない。
         // ポート指定 kill は明示的に設定しない限り無効。
+        if let Some(system) = system {
+            let locked = |section| system.is_locked(section);
+            merge_section(
+                &mut self.allowlist,
+                &system.allowlist,
+                locked(LockedSection::Allowlist),
+            );
+            self.allowed_ports = if locked(LockedSection::AllowedPorts) {
+                system.allowed_ports.as_ref().map(|system_ports| {
+                    let caps = port_caps(system_ports);
+                    match self.allowed_ports.take() {
+                        Some(ports) => AllowedPorts {
+                            ports: ports
+                                .ports
+                                .into_iter()
+                                .filter(|entry| is_capped(&caps, entry))
+                                .collect(),
+                            expiring: ports
+                                .expiring
+                                .into_iter()
+                                .filter(|entry| is_capped(&caps, &entry.value))
+                                .collect(),
+                        },
+                        None => system_ports.clone(),
+                    }
+                })
+            } else {
+                self.allowed_ports
+                    .take()
+                    .or_else(|| system.allowed_ports.clone())
+            };
+            merge_section(
+                &mut self.allowed_sockets,
+                &system.allowed_sockets,
+                locked(LockedSection::AllowedSockets),
+            );
+            merge_section(
+                &mut self.allowed_paths,
+                &system.allowed_paths,
+                locked(LockedSection::AllowedPaths),
+            );
+            merge_section(&mut self.root, &system.root, locked(LockedSection::Root));
+            merge_section(
+                &mut self.ownership,
+                &system.ownership,
+                locked(LockedSection::Ownership),
+            );
+            merge_section(
+                &mut self.privileged,
+                &system.privileged,
+                locked(LockedSection::Privileged),
+            );
+            merge_section(
+                &mut self.signals,
+                &system.signals,
+                locked(LockedSection::Signals),
+            );
+            merge_section(
+                &mut self.confirm,
+                &system.confirm,
+                locked(LockedSection::Confirm),
+            );
+            merge_section(
+                &mut self.limits,
+                &system.limits,
+                locked(LockedSection::Limits),
+            );
+            self.lock = system.lock.clone();
+        }
+        self.system = layer;
+    }
+
+    /// システム設定のロックによって無視されるユーザー設定を説明する
+    ///
+    /// `merge_defaults` の前（ユーザー設定の層に `system` を設定した状態）で使う。
+    pub fn lock_conflicts(&self) -> Vec<String> {
+        let Some(layer) = &self.system else {
+            return Vec::new();
+        };
+        let system = &layer.config;
+        let source = ConfigLayer::System(layer.path.clone());
+        let sections = system
+            .lock
+            .as_ref()
+            .map(|lock| lock.sections.clone())
+            .unwrap_or_default();
+
+        let mut conflicts = Vec::new();
+        for section in sections {
+            match section {
+                LockedSection::Allowlist => {
+                    let entries = self.allowlist.iter().flat_map(|list| {
+                        list.processes
+                            .iter()
+                            .chain(list.expiring.iter().map(|entry| &entry.value))
+                    });
+                    for entry in entries.filter(|entry| !system.is_allowed(entry)) {
+                        conflicts.push(format!(
+                            "[allowlist] is locked by the {}; ignoring '{}'",
+                            source, entry
+                        ));
+                    }
+                }
+                LockedSection::AllowedPorts => {
+                    let caps = system
+                        .allowed_ports
+                        .as_ref()
+                        .map(port_caps)
+                        .unwrap_or_default();
+                    let entries = self.allowed_ports.iter().flat_map(|ports| {
+                        ports
+                            .ports
+                            .iter()
+                            .chain(ports.expiring.iter().map(|entry| &entry.value))
+                    });
+                    for entry in entries.filter(|entry| !is_capped(&caps, entry)) {
+                        conflicts.push(format!(
+                            "[allowed_ports] is capped by the {}; ignoring '{}'",
+                            source, entry
+                        ));
+                    }
+                }
+                section => {
+                    let value = self.section_value(section);
+                    if value.is_some() && value != system.section_value(section) {
+                        conflicts.push(format!(
+                            "[{}] is locked by the {}; ignoring your settings",
+                            section.as_str(),
+                            source
+                        ));
+                    }
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// ロックによって無視されるユーザー設定を警告する
+    fn warn_lock_conflicts(&self) {
+        for conflict in self.lock_conflicts() {
+            eprintln!("Warning: {}", conflict);
+        }
+    }
+
+    /// セクションが `[lock]` でロックされているか
+    pub fn is_locked(&self, section: LockedSection) -> bool {
+        self.lock
+            .as_ref()
+            .is_some_and(|lock| lock.sections.contains(&section))
+    }
+
+    /// セクションの値を TOML で返す（層の比較用。未設定なら None）
+    fn section_value(&self, section: LockedSection) -> Option<toml::Value> {
+        toml::Value::try_from(self)
+            .ok()?
+            .get(section.as_str())
+            .cloned()
+    }
+
+    /// セクションの値がどの層から来たかを返す
+    ///
+    /// ロックされているか、システム設定と同じ値ならシステム設定とみなす。
+    pub fn section_layer(&self, section: LockedSection) -> ConfigLayer {
+        match &self.system {
+            Some(layer)
+                if self.is_locked(section)
+                    || layer
+                        .config
+                        .section_value(section)
+                        .is_some_and(|value| self.section_value(section) == Some(value)) =>
+            {
+                ConfigLayer::System(layer.path.clone())
+            }
+            _ => ConfigLayer::User,
+        }
+    }
+
+    /// denylist のエントリがどの層から来たかを返す（denylist になければ None）
+    pub fn denylist_layer(&self, name: &str) -> Option<ConfigLayer> {
+        if !self.is_denied(name) {
+            return None;
+        }
+        if Self::default_denylist()
+            .iter()
+            .any(|process| process == name)
+        {
+            return Some(ConfigLayer::BuiltIn);
+        }
+        Some(match &self.system {
+            Some(layer) if layer.config.is_denied(name) => ConfigLayer::System(layer.path.clone()),
+            _ => ConfigLayer::User,
+        })
+    }
+
+    /// `safe-kill allow` の許可がシステム設定のロックに反しないか確認する
+    pub fn check_grant_allowed(&self, grant: &Grant) -> Result<(), SafeKillError> {
+        let Some(layer) = &self.system else {
+            return Ok(());
+        };
+        let system = &layer.config;
+        let source = ConfigLayer::System(layer.path.clone());
+        match grant {
+            Grant::Process(name)
+                if system.is_locked(LockedSection::Allowlist) && !system.is_allowed(name) =>
+            {
+                Err(SafeKillError::ConfigError(format!(
+                    "[allowlist] is locked by the {}; '{}' cannot be allowed",
+                    source, name
+                )))
+            }
+            Grant::Port(spec) if system.is_locked(LockedSection::AllowedPorts) => {
+                let caps = system
+                    .allowed_ports
+                    .as_ref()
+                    .map(port_caps)
+                    .unwrap_or_default();
+                if is_capped(&caps, spec) {
+                    Ok(())
+                } else {
+                    Err(SafeKillError::ConfigError(format!(
+                        "[allowed_ports] is capped by the {}; port {} is outside it",
+                        source, spec
+                    )))
+                }
+            }
+            _ => Ok(()),
+        }
     }
 
     /// OS 固有のデフォルト denylist を取得する
@@ -260,12 +1834,11 @@ impl Config {
         ]
     }
 
-    /// プロセス名が allowlist に含まれるか確認する
+    /// プロセス名が allowlist に含まれるか確認する（期限切れのエントリは無視する）
     pub fn is_allowed(&self, name: &str) -> bool {
         self.allowlist
             .as_ref()
-            .map(|list| list.processes.iter().any(|p| p == name))
-            .unwrap_or(false)
+            .is_some_and(|list| list.contains_active(name, grants::now()))
     }
 
     /// プロセス名が denylist に含まれるか確認する
@@ -278,58 +1851,96 @@ impl Config {
 
     /// ポートが kill 操作に許可されているか確認する
     ///
-    /// 設定されたポート指定のいずれかに一致する場合 true を返す。
+    /// 設定されたポート指定のいずれかの範囲に含まれる場合 true を返す。
     /// allowed_ports 設定が存在しない場合は false を返す（ポート kill は無効）。
+    /// プロトコル・アドレス付きのエントリも範囲が一致すればここでは true になり、
+    /// 実際のソケットごとの照合は `is_socket_allowed` で行う。
     ///
     /// ポート指定 kill を有効にするには config.toml で allowed_ports を設定する:
     /// ```toml
     /// [allowed_ports]
-    /// ports = ["1420", "3000-3010", "5173", "8080"]
+    /// ports = ["1420", "3000-3010", "tcp:127.0.0.1:5173", "8080"]
     /// ```
     pub fn is_port_allowed(&self, port: u16) -> bool {
-        if port == 0 {
-            return false;
-        }
-
-        let Some(allowed_ports) = &self.allowed_ports else {
-            return false;
-        };
-
-        for spec in &allowed_ports.ports {
-            if let Ok(range) = PortRange::parse(spec) {
-                if range.contains(port) {
-                    return true;
-                }
-            }
-        }
+        port != 0
+            && self
+                .port_specs()
+                .iter()
+                .any(|spec| spec.contains_port(port))
+    }
 
-        false
+    /// 指定プロトコル・バインドアドレスのソケットが kill 操作に許可されているか確認する
+    ///
+    /// エントリにプロトコルやアドレスが指定されている場合はそれらも一致する必要がある。
+    pub fn is_socket_allowed(&self, port: u16, protocol: PortProtocol, address: &IpAddr) -> bool {
+        port != 0
+            && self
+                .port_specs()
+                .iter()
+                .any(|spec| spec.matches(port, protocol, address))
     }
 
-    /// 設定から解析済みのポート範囲を取得する
-    pub fn get_port_ranges(&self) -> Vec<PortRange> {
+    /// 設定から解析済みのポート指定を取得する
+    ///
+    /// 解析できないエントリと期限切れのエントリは無視する。
+    pub fn port_specs(&self) -> Vec<PortSpec> {
         self.allowed_ports
             .as_ref()
             .map(|ap| {
-                ap.ports
-                    .iter()
-                    .filter_map(|s| PortRange::parse(s).ok())
+                ap.active(grants::now())
+                    .filter_map(|s| PortSpec::parse(s).ok())
                     .collect()
             })
             .unwrap_or_default()
     }
 
+    /// 設定から解析済みのポート範囲を取得する
+    pub fn get_port_ranges(&self) -> Vec<PortRange> {
+        self.port_specs()
+            .into_iter()
+            .map(|spec| spec.range)
+            .collect()
+    }
+
     /// ポートが許可されていない場合のヒントメッセージを生成する
     ///
     /// 設定ファイルでポートを許可する方法を説明する
     /// ユーザーフレンドリーなメッセージを生成する。
     pub fn port_not_allowed_hint(&self, port: u16) -> String {
+        if let Some(layer) = &self.system
+            && self.is_locked(LockedSection::AllowedPorts)
+        {
+            return format!(
+                "[allowed_ports] is locked by the {}; ask an administrator to allow it",
+                ConfigLayer::System(layer.path.clone())
+            );
+        }
         format!(
             "Add {} to [allowed_ports] in config.toml or run 'safe-kill init' to create a config file",
             port
         )
     }
 
+    /// 指定されたポート集合がすべて許可されているか確認する
+    ///
+    /// 範囲指定では範囲内のすべてのポートが `[allowed_ports]` のいずれかに
+    /// 含まれている必要がある。最初に見つかった許可外のポートをエラーとして返す。
+    pub fn check_ports_allowed(&self, ports: &PortSelection) -> Result<(), SafeKillError> {
+        let allowed = self.get_port_ranges();
+        match ports
+            .ports()
+            .into_iter()
+            .find(|port| *port == 0 || !allowed.iter().any(|range| range.contains(*port)))
+        {
+            Some(0) => Err(SafeKillError::InvalidPort("0".to_string())),
+            Some(port) => Err(SafeKillError::PortNotAllowed {
+                port,
+                hint: self.port_not_allowed_hint(port),
+            }),
+            None => Ok(()),
+        }
+    }
+
     /// ポートが許可されているか確認し、許可されていない場合はヒント付きエラーを返す
     ///
     /// `is_port_allowed` とヒント付きエラー生成を組み合わせた
@@ -348,6 +1959,38 @@ impl Config {
             })
         }
     }
+
+    /// Unix ドメインソケットのパスが kill 操作に許可されているか確認する
+    ///
+    /// `path` は `unix_socket::resolve_socket_path` で正規化済みであること。
+    /// allowed_sockets 設定が存在しない場合は false を返す（ソケット kill は無効）。
+    pub fn is_socket_path_allowed(&self, path: &Path) -> bool {
+        self.allowed_sockets
+            .as_ref()
+            .is_some_and(|sockets| sockets.matches(path))
+    }
+
+    /// `--holding` の対象パスが `[allowed_paths]` に一致するか確認する
+    ///
+    /// 一致する場合、そのパスを保持するプロセスは ancestry チェックなしで kill できる
+    /// （`[allowed_ports]` と同じく自殺防止・denylist・祖先保護・所有者チェックは適用される）。
+    pub fn is_path_allowed(&self, path: &Path) -> bool {
+        self.allowed_paths
+            .as_ref()
+            .is_some_and(|paths| paths.matches(path))
+    }
+
+    /// Unix ドメインソケットのパスが許可されているか確認する（許可されていない場合はエラー）
+    pub fn check_socket_path_allowed(&self, path: &Path) -> Result<(), SafeKillError> {
+        if self.is_socket_path_allowed(path) {
+            Ok(())
+        } else {
+            Err(SafeKillError::SocketNotAllowed {
+                path: path.display().to_string(),
+                hint: "Add a matching glob to [allowed_sockets] paths in config.toml".to_string(),
+            })
+        }
+    }
 }
 
 #[cfg(test)]
@@ -564,20 +2207,488 @@ processes = ["node"]
         assert!(matches!(result, Err(SafeKillError::ConfigError(_))));
     }
 
-    // is_allowed のテスト
     #[test]
-    fn test_is_allowed_with_allowlist() {
-        let config = Config {
-            allowlist: Some(ProcessList {
-                processes: vec!["node".to_string(), "npm".to_string()],
-            }),
-            denylist: None,
-            allowed_ports: None,
+    fn test_try_load_with_missing_explicit_path_returns_error() {
+        let options = LoadOptions {
+            path: Some(PathBuf::from("/nonexistent/path/config.toml")),
+            sets: Vec::new(),
+            profile: None,
         };
-        assert!(config.is_allowed("node"));
-        assert!(config.is_allowed("npm"));
-        assert!(!config.is_allowed("python"));
-    }
+        assert_eq!(
+            Config::try_load_with(&options),
+            Err(SafeKillError::ConfigError(
+                "Config file /nonexistent/path/config.toml does not exist".to_string()
+            ))
+        );
+    }
+
+    fn overrides(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_apply_overrides_narrows_values() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            "[allowed_ports]\nports = [\"3000-3100\"]\n[allowlist]\nprocesses = [\"node\"]"
+        )
+        .unwrap();
+        let config = Config::try_load_from_path(Some(file.path().to_path_buf()))
+            .unwrap()
+            .apply_overrides(
+                overrides(&[("SAFE_KILL_ALLOWED_PORTS", "3000-3010,3050")]),
+                &["confirm.above=2".to_string()],
+            )
+            .unwrap();
+
+        assert!(config.is_port_allowed(3005));
+        assert!(config.is_port_allowed(3050));
+        assert!(!config.is_port_allowed(3020));
+        assert!(config.is_allowed("node"));
+        assert_eq!(config.confirm.unwrap().above, Some(2));
+    }
+
+    #[test]
+    fn test_apply_overrides_cannot_remove_default_denylist() {
+        let mut config = Config::with_defaults();
+        config.allowlist = Some(ProcessList {
+            processes: vec!["init".into(), "systemd".into(), "launchd".into()],
+            expiring: Vec::new(),
+        });
+        let config = config
+            .apply_overrides(
+                overrides(&[
+                    ("SAFE_KILL_DENYLIST", ""),
+                    ("SAFE_KILL_ALLOWLIST", "init,systemd,launchd"),
+                ]),
+                &[],
+            )
+            .unwrap();
+        let mut config = config;
+        config.merge_defaults();
+        for name in Config::default_denylist() {
+            assert!(config.is_denied(&name), "{} should stay denied", name);
+        }
+
+        let mut config = Config::with_defaults()
+            .apply_overrides(overrides(&[("SAFE_KILL_DENYLIST", "postgres")]), &[])
+            .unwrap();
+        config.merge_defaults();
+        assert!(config.is_denied("postgres"));
+        assert!(config.is_denied(&Config::default_denylist()[0]));
+    }
+
+    #[test]
+    fn test_apply_overrides_are_validated_like_the_file() {
+        let mut config = Config::with_defaults();
+        config.allowed_ports = Some(AllowedPorts {
+            ports: vec!["4000-4010".into()],
+            expiring: Vec::new(),
+        });
+        let result =
+            config.apply_overrides(overrides(&[("SAFE_KILL_ALLOWED_PORTS", "4010-4000")]), &[]);
+        assert!(result.is_err());
+
+        let result = Config::with_defaults()
+            .apply_overrides(overrides(&[("SAFE_KILL_ROOT_STRATEGY", "nope")]), &[]);
+        assert!(matches!(result, Err(SafeKillError::ConfigError(_))));
+
+        let result = Config::with_defaults().apply_overrides(
+            Vec::new(),
+            &["privileged.port_other_users=true".to_string()],
+        );
+        assert!(matches!(result, Err(SafeKillError::ConfigError(_))));
+    }
+
+    /// システム設定とユーザー設定を一時ファイルに書き、層を重ねて読み込む
+    fn load_layered(
+        system: &str,
+        user: &str,
+        vars: &[(&str, &str)],
+    ) -> Result<Config, SafeKillError> {
+        load_layered_profile(system, user, vars, None)
+    }
+
+    /// `load_layered` と同じく読み込み、`--profile` で指定したプロファイルを選ぶ
+    fn load_layered_profile(
+        system: &str,
+        user: &str,
+        vars: &[(&str, &str)],
+        profile: Option<&str>,
+    ) -> Result<Config, SafeKillError> {
+        let mut system_file = NamedTempFile::new().unwrap();
+        write!(system_file, "{}", system).unwrap();
+        let mut user_file = NamedTempFile::new().unwrap();
+        write!(user_file, "{}", user).unwrap();
+        let options = LoadOptions {
+            path: Some(user_file.path().to_path_buf()),
+            sets: Vec::new(),
+            profile: profile.map(str::to_string),
+        };
+        Config::try_load_layered(
+            system_file.path(),
+            &options,
+            overrides(vars),
+            Grants::default(),
+        )
+    }
+
+    const SYSTEM_CONFIG: &str = r#"
+[lock]
+sections = ["allowlist", "allowed_ports", "signals"]
+
+[allowlist]
+processes = ["node"]
+
+[denylist]
+processes = ["postgres"]
+
+[allowed_ports]
+ports = ["3000-3999", "tcp:127.0.0.1:8080"]
+
+[signals]
+forbid = ["SIGKILL"]
+
+[confirm]
+above = 3
+"#;
+
+    #[test]
+    fn test_system_layer_locks_sections() {
+        let config = load_layered(
+            SYSTEM_CONFIG,
+            r#"
+[allowlist]
+processes = ["node", "python"]
+
+[denylist]
+processes = []
+
+[allowed_ports]
+ports = ["3000", "4000", "8080", "tcp:127.0.0.1:8080"]
+
+[signals]
+forbid = []
+"#,
+            &[("SAFE_KILL_ALLOWLIST", "node,python")],
+        )
+        .unwrap();
+
+        // ロックされた allowlist はシステム設定の値
+        assert!(config.is_allowed("node"));
+        assert!(!config.is_allowed("python"));
+        assert!(!config.is_allowed("ruby"));
+        // 管理者の denylist はユーザーが空にしても残る
+        assert!(config.is_denied("postgres"));
+        for name in Config::default_denylist() {
+            assert!(config.is_denied(&name));
+        }
+        // ロックされた allowed_ports は上限に収まるエントリだけ残る
+        assert_eq!(
+            config.allowed_ports.as_ref().unwrap().ports,
+            vec!["3000".to_string(), "tcp:127.0.0.1:8080".to_string()]
+        );
+        assert_eq!(
+            config.signals.as_ref().unwrap().forbid,
+            vec!["SIGKILL".to_string()]
+        );
+        // ロックされていないセクションはユーザー設定がなければシステム設定の値
+        assert_eq!(config.confirm.as_ref().unwrap().above, Some(3));
+        assert!(config.is_locked(LockedSection::Allowlist));
+        assert!(!config.is_locked(LockedSection::Confirm));
+    }
+
+    #[test]
+    fn test_system_layer_uses_system_ports_when_user_has_none() {
+        let config = load_layered(SYSTEM_CONFIG, "", &[]).unwrap();
+        assert!(config.is_port_allowed(3500));
+        assert!(!config.is_port_allowed(4000));
+
+        let config = load_layered(
+            "[lock]\nsections = [\"allowed_ports\"]",
+            "[allowed_ports]\nports = [\"3000\"]",
+            &[],
+        )
+        .unwrap();
+        assert!(config.allowed_ports.is_none());
+    }
+
+    #[test]
+    fn test_system_layer_unlocked_sections_can_be_overridden() {
+        let config = load_layered(SYSTEM_CONFIG, "[confirm]\nabove = 10", &[]).unwrap();
+        assert_eq!(config.confirm.as_ref().unwrap().above, Some(10));
+        assert_eq!(
+            config.section_layer(LockedSection::Confirm),
+            ConfigLayer::User
+        );
+    }
+
+    #[test]
+    fn test_lock_is_rejected_in_user_config() {
+        let result = load_layered("", "[lock]\nsections = [\"allowlist\"]", &[]);
+        match result {
+            Err(SafeKillError::ConfigError(message)) => {
+                assert!(
+                    message.contains("[lock] is only allowed in the system config"),
+                    "{}",
+                    message
+                )
+            }
+            other => panic!("Expected ConfigError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_system_config_names_the_file() {
+        let result = load_layered("[lock]\nsections = [\"nope\"]", "", &[]);
+        assert!(matches!(result, Err(SafeKillError::ConfigError(_))));
+
+        let result = load_layered("[allowed_ports]\nports = [\"4010-4000\"]", "", &[]);
+        match result {
+            Err(SafeKillError::ConfigError(message)) => {
+                assert!(message.starts_with("/"), "{}", message)
+            }
+            other => panic!("Expected ConfigError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_lock_conflicts_describe_ignored_settings() {
+        let mut system_file = NamedTempFile::new().unwrap();
+        write!(system_file, "{}", SYSTEM_CONFIG).unwrap();
+        let system = Config::read_system_layer(system_file.path())
+            .unwrap()
+            .unwrap();
+        let path = system_file.path().display().to_string();
+
+        let mut user: Config = toml::from_str(
+            "[allowlist]\nprocesses = [\"node\", \"python\"]\n[allowed_ports]\nports = [\"3000\", \"4000\"]\n[signals]\nforbid = []",
+        )
+        .unwrap();
+        user.system = Some(Box::new(system));
+        assert_eq!(
+            user.lock_conflicts(),
+            vec![
+                format!(
+                    "[allowlist] is locked by the system config {}; ignoring 'python'",
+                    path
+                ),
+                format!(
+                    "[allowed_ports] is capped by the system config {}; ignoring '4000'",
+                    path
+                ),
+                format!(
+                    "[signals] is locked by the system config {}; ignoring your settings",
+                    path
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decisions_are_attributed_to_layers() {
+        let config =
+            load_layered(SYSTEM_CONFIG, "[denylist]\nprocesses = [\"redis\"]", &[]).unwrap();
+        let system = ConfigLayer::System(config.system.as_ref().unwrap().path.clone());
+
+        assert_eq!(config.denylist_layer("redis"), Some(ConfigLayer::User));
+        assert_eq!(config.denylist_layer("postgres"), Some(system.clone()));
+        assert_eq!(
+            config.denylist_layer(&Config::default_denylist()[0]),
+            Some(ConfigLayer::BuiltIn)
+        );
+        assert_eq!(config.denylist_layer("node"), None);
+        assert_eq!(config.section_layer(LockedSection::Signals), system);
+        assert_eq!(config.section_layer(LockedSection::Confirm), system);
+        assert_eq!(
+            config.section_layer(LockedSection::Limits),
+            ConfigLayer::User
+        );
+        assert_eq!(
+            Config::with_defaults().section_layer(LockedSection::Signals),
+            ConfigLayer::User
+        );
+    }
+
+    #[test]
+    fn test_merge_defaults_with_system_layer_is_idempotent() {
+        let config = load_layered(
+            SYSTEM_CONFIG,
+            "[allowed_ports]\nports = [\"3000\", \"4000\"]",
+            &[],
+        )
+        .unwrap();
+        let mut merged = config.clone();
+        merged.merge_defaults();
+        assert_eq!(merged, config);
+    }
+
+    #[test]
+    fn test_check_grant_allowed_respects_locks() {
+        let config = load_layered(SYSTEM_CONFIG, "", &[]).unwrap();
+        assert!(
+            config
+                .check_grant_allowed(&Grant::Process("node".to_string()))
+                .is_ok()
+        );
+        assert!(matches!(
+            config.check_grant_allowed(&Grant::Process("python".to_string())),
+            Err(SafeKillError::ConfigError(message)) if message.contains("'python' cannot be allowed")
+        ));
+        assert!(
+            config
+                .check_grant_allowed(&Grant::Port("3100-3200".to_string()))
+                .is_ok()
+        );
+        assert!(matches!(
+            config.check_grant_allowed(&Grant::Port("8080".to_string())),
+            Err(SafeKillError::ConfigError(message)) if message.contains("port 8080 is outside it")
+        ));
+        assert!(
+            Config::with_defaults()
+                .check_grant_allowed(&Grant::Process("python".to_string()))
+                .is_ok()
+        );
+    }
+
+    const PROFILES_CONFIG: &str = r#"
+[allowlist]
+processes = ["node"]
+
+[denylist]
+processes = ["postgres"]
+
+[allowed_ports]
+ports = ["8080"]
+
+[confirm]
+above = 2
+
+[profiles.frontend.allowed_ports]
+ports = ["3000-3010"]
+
+[profiles.data.allowlist]
+processes = ["python"]
+
+[profiles.data.denylist]
+processes = ["redis", "postgres"]
+
+[profiles.data.signals]
+forbid = ["SIGKILL"]
+"#;
+
+    #[test]
+    fn test_apply_profile_replaces_and_extends_base() {
+        let base: Config = toml::from_str(PROFILES_CONFIG).unwrap();
+
+        let frontend = base.clone().apply_profile("frontend").unwrap();
+        assert!(frontend.is_port_allowed(3005));
+        assert!(!frontend.is_port_allowed(8080));
+        // プロファイルにないセクションはベースの値のまま
+        assert!(frontend.is_allowed("node"));
+        assert_eq!(frontend.confirm, base.confirm);
+
+        let data = base.apply_profile("data").unwrap();
+        assert!(data.is_allowed("python"));
+        assert!(!data.is_allowed("node"));
+        assert!(data.is_port_allowed(8080));
+        assert_eq!(
+            data.denylist.unwrap().processes,
+            vec!["postgres".to_string(), "redis".to_string()]
+        );
+        assert_eq!(data.signals.unwrap().forbid, vec!["SIGKILL".to_string()]);
+    }
+
+    #[test]
+    fn test_apply_profile_rejects_unknown_names() {
+        let base: Config = toml::from_str(PROFILES_CONFIG).unwrap();
+        assert_eq!(
+            base.apply_profile("backend"),
+            Err(SafeKillError::ConfigError(
+                "Profile 'backend' is not defined (available: data, frontend)".to_string()
+            ))
+        );
+        assert_eq!(
+            Config::default().apply_profile("backend"),
+            Err(SafeKillError::ConfigError(
+                "Profile 'backend' is not defined (no [profiles] in the config)".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_profile_is_resolved_on_load_and_cannot_bypass_protections() {
+        let config =
+            load_layered_profile(SYSTEM_CONFIG, PROFILES_CONFIG, &[], Some("data")).unwrap();
+        // ロックされた allowlist はプロファイルでも変えられない
+        assert!(config.is_allowed("node"));
+        assert!(!config.is_allowed("python"));
+        assert!(config.is_denied("redis"));
+        for name in Config::default_denylist() {
+            assert!(config.is_denied(&name));
+        }
+
+        let config = load_layered_profile(
+            "",
+            PROFILES_CONFIG,
+            &[("SAFE_KILL_ALLOWED_PORTS", "3005")],
+            Some("frontend"),
+        )
+        .unwrap();
+        // 上書きはプロファイルを適用した後の値をさらに絞り込む
+        assert!(config.is_port_allowed(3005));
+        assert!(!config.is_port_allowed(3000));
+        assert!(matches!(
+            load_layered_profile(
+                "",
+                PROFILES_CONFIG,
+                &[("SAFE_KILL_ALLOWED_PORTS", "8080")],
+                Some("frontend"),
+            ),
+            Err(SafeKillError::ConfigError(_))
+        ));
+
+        assert!(matches!(
+            load_layered_profile("", PROFILES_CONFIG, &[], Some("nope")),
+            Err(SafeKillError::ConfigError(_))
+        ));
+    }
+
+    #[test]
+    fn test_profiles_are_rejected_in_system_config() {
+        let result = load_layered("[profiles.ci.allowlist]\nprocesses = [\"node\"]", "", &[]);
+        match result {
+            Err(SafeKillError::ConfigError(message)) => {
+                assert!(
+                    message.ends_with(": [profiles] is only allowed in the user config"),
+                    "{}",
+                    message
+                )
+            }
+            other => panic!("Expected ConfigError, got {:?}", other),
+        }
+    }
+
+    // is_allowed のテスト
+    #[test]
+    fn test_is_allowed_with_allowlist() {
+        let config = Config {
+            allowlist: Some(ProcessList {
+                processes: vec!["node".to_string(), "npm".to_string()],
+                ..Default::default()
+            }),
+            denylist: None,
+            allowed_ports: None,
+            ..Default::default()
+        };
+        assert!(config.is_allowed("node"));
+        assert!(config.is_allowed("npm"));
+        assert!(!config.is_allowed("python"));
+    }
 
     #[test]
     fn test_is_allowed_without_allowlist() {
@@ -585,6 +2696,7 @@ processes = ["node"]
             allowlist: None,
             denylist: None,
             allowed_ports: None,
+            ..Default::default()
         };
         assert!(!config.is_allowed("node"));
         assert!(!config.is_allowed("anything"));
@@ -597,8 +2709,10 @@ processes = ["node"]
             allowlist: None,
             denylist: Some(ProcessList {
                 processes: vec!["systemd".to_string(), "launchd".to_string()],
+                ..Default::default()
             }),
             allowed_ports: None,
+            ..Default::default()
         };
         assert!(config.is_denied("systemd"));
         assert!(config.is_denied("launchd"));
@@ -611,6 +2725,7 @@ processes = ["node"]
             allowlist: None,
             denylist: None,
             allowed_ports: None,
+            ..Default::default()
         };
         assert!(!config.is_denied("systemd"));
         assert!(!config.is_denied("anything"));
@@ -622,11 +2737,14 @@ processes = ["node"]
         let config = Config {
             allowlist: Some(ProcessList {
                 processes: vec!["node".to_string()],
+                ..Default::default()
             }),
             denylist: Some(ProcessList {
                 processes: vec!["systemd".to_string()],
+                ..Default::default()
             }),
             allowed_ports: None,
+            ..Default::default()
         };
         let cloned = config.clone();
         assert_eq!(config, cloned);
@@ -636,6 +2754,7 @@ processes = ["node"]
     fn test_process_list_clone() {
         let list = ProcessList {
             processes: vec!["a".to_string(), "b".to_string()],
+            ..Default::default()
         };
         let cloned = list.clone();
         assert_eq!(list, cloned);
@@ -724,6 +2843,7 @@ processes = ["node"]
     fn test_process_list_debug() {
         let list = ProcessList {
             processes: vec!["test".to_string()],
+            ..Default::default()
         };
         let debug_str = format!("{:?}", list);
         assert!(debug_str.contains("ProcessList"));
@@ -870,6 +2990,7 @@ processes = ["node"]
             allowlist: None,
             denylist: None,
             allowed_ports: None,
+            ..Default::default()
         };
         // allowed_ports 設定なしはポート kill 無効を意味する
         // すべてのポートで false を返す
@@ -888,7 +3009,9 @@ processes = ["node"]
             denylist: None,
             allowed_ports: Some(AllowedPorts {
                 ports: vec!["3306".to_string()],
+                ..Default::default()
             }),
+            ..Default::default()
         };
         assert!(config.is_port_allowed(3306));
         assert!(!config.is_port_allowed(3307));
@@ -902,7 +3025,9 @@ processes = ["node"]
             denylist: None,
             allowed_ports: Some(AllowedPorts {
                 ports: vec!["3000-3100".to_string()],
+                ..Default::default()
             }),
+            ..Default::default()
         };
         assert!(config.is_port_allowed(3000));
         assert!(config.is_port_allowed(3050));
@@ -922,7 +3047,9 @@ processes = ["node"]
                     "3306".to_string(),
                     "5432".to_string(),
                 ],
+                ..Default::default()
             }),
+            ..Default::default()
         };
         assert!(config.is_port_allowed(3050)); // 範囲内
         assert!(config.is_port_allowed(3306)); // 単一ポート
@@ -958,7 +3085,9 @@ ports = ["3000-3100", "3306", "5432"]
             denylist: None,
             allowed_ports: Some(AllowedPorts {
                 ports: vec!["3000-3100".to_string(), "3306".to_string()],
+                ..Default::default()
             }),
+            ..Default::default()
         };
         let ranges = config.get_port_ranges();
         assert_eq!(ranges.len(), 2);
@@ -970,6 +3099,7 @@ ports = ["3000-3100", "3306", "5432"]
             allowlist: None,
             denylist: None,
             allowed_ports: None,
+            ..Default::default()
         };
         let ranges = config.get_port_ranges();
         assert!(ranges.is_empty());
@@ -983,7 +3113,9 @@ ports = ["3000-3100", "3306", "5432"]
             denylist: None,
             allowed_ports: Some(AllowedPorts {
                 ports: vec!["3000-3100".to_string()],
+                ..Default::default()
             }),
+            ..Default::default()
         };
         let hint = config.port_not_allowed_hint(22);
         assert!(hint.contains("22"));
@@ -998,7 +3130,9 @@ ports = ["3000-3100", "3306", "5432"]
             denylist: None,
             allowed_ports: Some(AllowedPorts {
                 ports: vec!["8080".to_string()],
+                ..Default::default()
             }),
+            ..Default::default()
         };
         let hint = config.port_not_allowed_hint(3306);
         assert!(hint.contains("3306"));
@@ -1011,6 +3145,7 @@ ports = ["3000-3100", "3306", "5432"]
             allowlist: None,
             denylist: None,
             allowed_ports: None,
+            ..Default::default()
         };
         // allowed_ports 設定なしはすべてのポートチェックが失敗することを意味する
         assert!(config.check_port_allowed(1420).is_err());
@@ -1027,7 +3162,9 @@ ports = ["3000-3100", "3306", "5432"]
             denylist: None,
             allowed_ports: Some(AllowedPorts {
                 ports: vec!["3000-3100".to_string(), "3306".to_string()],
+                ..Default::default()
             }),
+            ..Default::default()
         };
         assert!(config.check_port_allowed(3050).is_ok());
         assert!(config.check_port_allowed(3306).is_ok());
@@ -1042,7 +3179,9 @@ ports = ["3000-3100", "3306", "5432"]
             denylist: None,
             allowed_ports: Some(AllowedPorts {
                 ports: vec!["3000-3100".to_string()],
+                ..Default::default()
             }),
+            ..Default::default()
         };
         let result = config.check_port_allowed(22);
         assert!(result.is_err());
@@ -1067,7 +3206,9 @@ ports = ["3000-3100", "3306", "5432"]
                     "8080".to_string(),
                     "abc".to_string(),
                 ],
+                ..Default::default()
             }),
+            ..Default::default()
         };
         assert!(config.is_port_allowed(8080));
         assert!(!config.is_port_allowed(3000));
@@ -1090,6 +3231,138 @@ ports = ["3000-3100", "3306", "5432"]
         assert!(!range.contains(2999));
     }
 
+    // PortSpec のテスト
+    #[test]
+    fn test_port_spec_parse_plain_port() {
+        let spec = PortSpec::parse("3000-3010").unwrap();
+        assert_eq!(spec.protocol, None);
+        assert_eq!(spec.address, None);
+        assert_eq!(
+            spec.range,
+            PortRange::Range {
+                start: 3000,
+                end: 3010
+            }
+        );
+    }
+
+    #[test]
+    fn test_port_spec_parse_protocol_and_address() {
+        let spec = PortSpec::parse("tcp:127.0.0.1:3000").unwrap();
+        assert_eq!(spec.protocol, Some(PortProtocol::Tcp));
+        assert_eq!(spec.address, Some("127.0.0.1".parse().unwrap()));
+        assert_eq!(spec.range, PortRange::Single(3000));
+
+        let spec = PortSpec::parse("UDP:5353").unwrap();
+        assert_eq!(spec.protocol, Some(PortProtocol::Udp));
+        assert_eq!(spec.address, None);
+
+        let spec = PortSpec::parse("0.0.0.0:8080").unwrap();
+        assert_eq!(spec.protocol, None);
+        assert_eq!(spec.address, Some("0.0.0.0".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_port_spec_parse_ipv6_requires_brackets() {
+        let spec = PortSpec::parse("udp:[::1]:5353-5360").unwrap();
+        assert_eq!(spec.address, Some("::1".parse().unwrap()));
+        assert_eq!(
+            spec.range,
+            PortRange::Range {
+                start: 5353,
+                end: 5360
+            }
+        );
+
+        assert!(PortSpec::parse("::1:3000").is_err());
+        assert!(PortSpec::parse("[127.0.0.1]:3000").is_err());
+        assert!(PortSpec::parse("[::1]3000").is_err());
+    }
+
+    #[test]
+    fn test_port_spec_covers() {
+        let spec = |s: &str| PortSpec::parse(s).unwrap();
+        assert!(spec("3000-3999").covers(&spec("3000")));
+        assert!(spec("3000-3999").covers(&spec("tcp:127.0.0.1:3100-3200")));
+        assert!(!spec("3000-3999").covers(&spec("3900-4000")));
+        assert!(spec("tcp:3000").covers(&spec("tcp:127.0.0.1:3000")));
+        assert!(!spec("tcp:3000").covers(&spec("3000")));
+        assert!(!spec("tcp:127.0.0.1:3000").covers(&spec("tcp:3000")));
+    }
+
+    #[test]
+    fn test_port_spec_parse_invalid() {
+        assert!(PortSpec::parse("sctp:3000").is_err());
+        assert!(PortSpec::parse("localhost:3000").is_err());
+        assert!(PortSpec::parse("tcp:").is_err());
+        assert!(matches!(
+            PortSpec::parse("tcp:127.0.0.1:0"),
+            Err(SafeKillError::InvalidPort(_))
+        ));
+    }
+
+    #[test]
+    fn test_is_socket_allowed_enforces_protocol_and_address() {
+        let config = Config {
+            allowed_ports: Some(AllowedPorts {
+                ports: vec!["tcp:127.0.0.1:3000".to_string(), "udp:5353".to_string()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let loopback: IpAddr = "127.0.0.1".parse().unwrap();
+        let any: IpAddr = "0.0.0.0".parse().unwrap();
+
+        // 範囲だけの事前チェックは通る
+        assert!(config.is_port_allowed(3000));
+        assert!(config.is_port_allowed(5353));
+
+        assert!(config.is_socket_allowed(3000, PortProtocol::Tcp, &loopback));
+        assert!(!config.is_socket_allowed(3000, PortProtocol::Udp, &loopback));
+        assert!(!config.is_socket_allowed(3000, PortProtocol::Tcp, &any));
+        assert!(config.is_socket_allowed(5353, PortProtocol::Udp, &any));
+        assert!(!config.is_socket_allowed(5353, PortProtocol::Tcp, &any));
+    }
+
+    #[test]
+    fn test_check_ports_allowed_requires_whole_range() {
+        let config = Config {
+            allowed_ports: Some(AllowedPorts {
+                ports: vec!["3000-3005".to_string(), "tcp:5173".to_string()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let ok = PortSelection::parse("3000-3005,5173").unwrap();
+        assert!(config.check_ports_allowed(&ok).is_ok());
+
+        let too_wide = PortSelection::parse("3000-3010").unwrap();
+        assert!(matches!(
+            config.check_ports_allowed(&too_wide),
+            Err(SafeKillError::PortNotAllowed { port: 3006, .. })
+        ));
+
+        let with_other = PortSelection::parse("3001,8080").unwrap();
+        assert!(matches!(
+            config.check_ports_allowed(&with_other),
+            Err(SafeKillError::PortNotAllowed { port: 8080, .. })
+        ));
+    }
+
+    #[test]
+    fn test_is_socket_allowed_plain_entry_matches_any_socket() {
+        let config = Config {
+            allowed_ports: Some(AllowedPorts {
+                ports: vec!["3000".to_string()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(config.is_socket_allowed(3000, PortProtocol::Udp, &"::".parse().unwrap()));
+        assert!(!config.is_socket_allowed(3001, PortProtocol::Tcp, &"::".parse().unwrap()));
+    }
+
     #[test]
     fn test_port_range_single_port_zero() {
         // ポート 0 は実サービスの終了対象としては無効
@@ -1120,7 +3393,9 @@ ports = ["3000-3100", "3306", "5432"]
                     "abc".to_string(),
                     "xxx-yyy".to_string(),
                 ],
+                ..Default::default()
             }),
+            ..Default::default()
         };
         // allowed_ports は存在するがすべて無効 → PortNotAllowed エラー
         let result = config.check_port_allowed(8080);
@@ -1144,7 +3419,9 @@ ports = ["3000-3100", "3306", "5432"]
             denylist: None,
             allowed_ports: Some(AllowedPorts {
                 ports: vec!["0-65535".to_string(), "0".to_string()],
+                ..Default::default()
             }),
+            ..Default::default()
         };
 
         assert!(!config.is_port_allowed(0));
@@ -1166,7 +3443,9 @@ ports = ["3000-3100", "3306", "5432"]
                     "3000-3010".to_string(),
                     "".to_string(),
                 ],
+                ..Default::default()
             }),
+            ..Default::default()
         };
         let ranges = config.get_port_ranges();
         // 有効な範囲のみ返される
@@ -1179,4 +3458,821 @@ ports = ["3000-3100", "3306", "5432"]
             }
         );
     }
+
+    // [root] セクションのテスト
+    #[test]
+    fn test_load_config_with_root_levels() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            r#"
+[root]
+strategy = "levels"
+levels = 3
+"#
+        )
+        .unwrap();
+
+        let config = Config::try_load_from_path(Some(file.path().to_path_buf())).unwrap();
+        let root = config.root.unwrap();
+        assert_eq!(root.strategy, RootStrategy::Levels);
+        assert_eq!(root.levels, Some(3));
+    }
+
+    #[test]
+    fn test_load_config_with_root_names() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            r#"
+[root]
+strategy = "name"
+names = ["claude", "node"]
+"#
+        )
+        .unwrap();
+
+        let config = Config::try_load_from_path(Some(file.path().to_path_buf())).unwrap();
+        let root = config.root.unwrap();
+        assert_eq!(root.strategy, RootStrategy::Name);
+        assert_eq!(
+            root.names,
+            Some(vec!["claude".to_string(), "node".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_load_config_root_strategy_defaults_to_default() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[root]").unwrap();
+
+        let config = Config::try_load_from_path(Some(file.path().to_path_buf())).unwrap();
+        assert_eq!(config.root.unwrap().strategy, RootStrategy::Default);
+    }
+
+    #[test]
+    fn test_try_load_config_root_levels_without_value_returns_error() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[root]\nstrategy = \"levels\"").unwrap();
+
+        let result = Config::try_load_from_path(Some(file.path().to_path_buf()));
+        assert!(matches!(result, Err(SafeKillError::ConfigError(msg)) if msg.contains("levels")));
+    }
+
+    #[test]
+    fn test_try_load_config_root_levels_zero_returns_error() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[root]\nstrategy = \"levels\"\nlevels = 0").unwrap();
+
+        let result = Config::try_load_from_path(Some(file.path().to_path_buf()));
+        assert!(matches!(result, Err(SafeKillError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_try_load_config_root_name_without_names_returns_error() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[root]\nstrategy = \"name\"\nnames = [\"  \"]").unwrap();
+
+        let result = Config::try_load_from_path(Some(file.path().to_path_buf()));
+        assert!(matches!(result, Err(SafeKillError::ConfigError(msg)) if msg.contains("names")));
+    }
+
+    #[test]
+    fn test_try_load_config_unknown_root_strategy_returns_error() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[root]\nstrategy = \"grandparent\"").unwrap();
+
+        let result = Config::try_load_from_path(Some(file.path().to_path_buf()));
+        assert!(matches!(result, Err(SafeKillError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_root_strategy_as_str() {
+        assert_eq!(RootStrategy::Default.as_str(), "default");
+        assert_eq!(RootStrategy::Levels.as_str(), "levels");
+        assert_eq!(RootStrategy::Name.as_str(), "name");
+        assert_eq!(RootStrategy::SessionLeader.as_str(), "session_leader");
+    }
+
+    // [ownership] セクションのテスト
+    #[test]
+    fn test_load_config_with_ownership() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            r#"
+[ownership]
+enforce = true
+users = ["root", "1001"]
+"#
+        )
+        .unwrap();
+
+        let config = Config::try_load_from_path(Some(file.path().to_path_buf())).unwrap();
+        let ownership = config.ownership.unwrap();
+        assert!(ownership.enforce);
+        assert_eq!(
+            ownership.users,
+            vec!["root".to_string(), "1001".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_load_config_ownership_defaults() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[ownership]").unwrap();
+
+        let config = Config::try_load_from_path(Some(file.path().to_path_buf())).unwrap();
+        assert_eq!(config.ownership, Some(OwnershipConfig::default()));
+    }
+
+    #[test]
+    fn test_try_load_config_ownership_unknown_user_returns_error() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            "[ownership]\nusers = [\"no_such_user_for_safe_kill\"]"
+        )
+        .unwrap();
+
+        let result = Config::try_load_from_path(Some(file.path().to_path_buf()));
+        assert!(
+            matches!(result, Err(SafeKillError::ConfigError(msg)) if msg.contains("no_such_user_for_safe_kill"))
+        );
+    }
+
+    // [privileged] セクションのテスト
+    #[test]
+    fn test_load_config_privileged_defaults_are_strict() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[privileged]").unwrap();
+
+        let config = Config::try_load_from_path(Some(file.path().to_path_buf())).unwrap();
+        let privileged = config.privileged.unwrap();
+        assert!(!privileged.allowlist_bypass);
+        assert!(!privileged.port_other_users);
+    }
+
+    #[test]
+    fn test_load_config_privileged_overrides() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            r#"
+[privileged]
+allowlist_bypass = true
+port_other_users = true
+"#
+        )
+        .unwrap();
+
+        let config = Config::try_load_from_path(Some(file.path().to_path_buf())).unwrap();
+        assert_eq!(
+            config.privileged,
+            Some(PrivilegedConfig {
+                allowlist_bypass: true,
+                port_other_users: true,
+            })
+        );
+    }
+
+    #[test]
+    fn test_try_load_config_privileged_unknown_field_returns_error() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[privileged]\nbypass_everything = true").unwrap();
+
+        let result = Config::try_load_from_path(Some(file.path().to_path_buf()));
+        assert!(matches!(result, Err(SafeKillError::ConfigError(_))));
+    }
+
+    // [signals] セクションのテスト
+    #[test]
+    fn test_load_config_with_signals() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            r#"
+[signals]
+forbid = ["SIGSTOP"]
+
+[signals.allowlisted]
+forbid = ["KILL", "9"]
+"#
+        )
+        .unwrap();
+
+        let config = Config::try_load_from_path(Some(file.path().to_path_buf())).unwrap();
+        let signals = config.signals.unwrap();
+        assert!(signals.permits(Signal::SIGKILL, SignalTarget::Descendant));
+        assert!(!signals.permits(Signal::SIGKILL, SignalTarget::Allowlisted));
+        assert!(signals.permits(Signal::SIGTERM, SignalTarget::Allowlisted));
+        assert!(!signals.permits(Signal::SIGSTOP, SignalTarget::Port));
+    }
+
+    #[test]
+    fn test_signal_rules_allow_list_restricts() {
+        let rules = SignalRules {
+            allow: vec!["TERM".to_string(), "SIGHUP".to_string()],
+            forbid: vec![],
+        };
+        assert!(rules.permits(Signal::SIGTERM));
+        assert!(rules.permits(Signal::SIGHUP));
+        assert!(!rules.permits(Signal::SIGKILL));
+    }
+
+    #[test]
+    fn test_signal_rules_forbid_wins_over_allow() {
+        let rules = SignalRules {
+            allow: vec!["KILL".to_string()],
+            forbid: vec!["SIGKILL".to_string()],
+        };
+        assert!(!rules.permits(Signal::SIGKILL));
+    }
+
+    #[test]
+    fn test_signal_rules_empty_permits_all() {
+        let rules = SignalRules::default();
+        assert!(rules.permits(Signal::SIGKILL));
+        assert!(rules.permits(Signal::SIGCONT));
+    }
+
+    #[test]
+    fn test_try_load_config_unknown_signal_returns_error() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[signals.allowlisted]\nforbid = [\"SIGFOO\"]").unwrap();
+
+        let result = Config::try_load_from_path(Some(file.path().to_path_buf()));
+        assert!(
+            matches!(result, Err(SafeKillError::ConfigError(msg)) if msg.contains("signals.allowlisted") && msg.contains("SIGFOO"))
+        );
+    }
+
+    #[test]
+    fn test_load_config_with_per_target_signal_rules() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            r#"
+[signals.descendant]
+allow = ["TERM", "INT", "KILL"]
+
+[signals.port]
+forbid = ["STOP"]
+"#
+        )
+        .unwrap();
+
+        let config = Config::try_load_from_path(Some(file.path().to_path_buf())).unwrap();
+        let signals = config.signals.unwrap();
+        assert!(signals.permits(Signal::SIGKILL, SignalTarget::Descendant));
+        assert!(!signals.permits(Signal::SIGHUP, SignalTarget::Descendant));
+        assert!(signals.permits(Signal::SIGHUP, SignalTarget::Port));
+        assert!(!signals.permits(Signal::SIGSTOP, SignalTarget::Port));
+        assert!(signals.permits(Signal::SIGSTOP, SignalTarget::Allowlisted));
+    }
+
+    #[test]
+    fn test_try_load_config_unknown_port_signal_returns_error() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[signals.port]\nallow = [\"99\"]").unwrap();
+
+        let result = Config::try_load_from_path(Some(file.path().to_path_buf()));
+        assert!(
+            matches!(result, Err(SafeKillError::ConfigError(msg)) if msg.contains("signals.port"))
+        );
+    }
+
+    fn socket_config(paths: &[&str]) -> Config {
+        Config {
+            allowed_sockets: Some(AllowedSockets {
+                paths: paths.iter().map(|p| p.to_string()).collect(),
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_socket_path_not_allowed_without_config() {
+        let config = Config::default();
+        assert!(!config.is_socket_path_allowed(Path::new("/run/dev.sock")));
+        assert!(matches!(
+            config.check_socket_path_allowed(Path::new("/run/dev.sock")),
+            Err(SafeKillError::SocketNotAllowed { path, .. }) if path == "/run/dev.sock"
+        ));
+    }
+
+    #[test]
+    fn test_socket_path_glob_does_not_cross_directories() {
+        let config = socket_config(&["/run/user/*/dev-*.sock", "/srv/**/lsp.sock"]);
+        assert!(config.is_socket_path_allowed(Path::new("/run/user/1000/dev-api.sock")));
+        assert!(!config.is_socket_path_allowed(Path::new("/run/user/1000/x/dev-api.sock")));
+        assert!(!config.is_socket_path_allowed(Path::new("/run/user/1000/docker.sock")));
+        assert!(config.is_socket_path_allowed(Path::new("/srv/a/b/lsp.sock")));
+        assert!(
+            config
+                .check_socket_path_allowed(Path::new("/srv/lsp.sock"))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_socket_path_glob_matches_abstract_name() {
+        let config = socket_config(&["@dev-*"]);
+        assert!(config.is_socket_path_allowed(Path::new("@dev-server")));
+        assert!(!config.is_socket_path_allowed(Path::new("/tmp/dev-server")));
+    }
+
+    #[test]
+    fn test_try_load_config_invalid_socket_glob_returns_error() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[allowed_sockets]\npaths = [\"/run/[dev.sock\"]").unwrap();
+
+        let result = Config::try_load_from_path(Some(file.path().to_path_buf()));
+        assert!(
+            matches!(result, Err(SafeKillError::ConfigError(msg)) if msg.contains("[allowed_sockets]"))
+        );
+    }
+
+    #[test]
+    fn test_path_allowed_by_glob() {
+        let config = Config {
+            allowed_paths: Some(AllowedPaths {
+                paths: vec!["/work/*/target".to_string(), "/tmp/*.sqlite".to_string()],
+            }),
+            ..Default::default()
+        };
+        assert!(config.is_path_allowed(Path::new("/work/app/target")));
+        assert!(config.is_path_allowed(Path::new("/tmp/dev.sqlite")));
+        assert!(!config.is_path_allowed(Path::new("/work/app/src")));
+        assert!(!Config::default().is_path_allowed(Path::new("/tmp/dev.sqlite")));
+    }
+
+    #[test]
+    fn test_try_load_config_invalid_path_glob_returns_error() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[allowed_paths]\npaths = [\"/work/***\"]").unwrap();
+
+        let result = Config::try_load_from_path(Some(file.path().to_path_buf()));
+        assert!(
+            matches!(result, Err(SafeKillError::ConfigError(msg)) if msg.contains("[allowed_paths]"))
+        );
+    }
+
+    #[test]
+    fn test_try_load_config_with_confirm_section() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[confirm]\nabove = 3\nsigkill = true").unwrap();
+
+        let config = Config::try_load_from_path(Some(file.path().to_path_buf())).unwrap();
+        assert_eq!(
+            config.confirm,
+            Some(ConfirmConfig {
+                above: Some(3),
+                sigkill: true,
+            })
+        );
+    }
+
+    #[test]
+    fn test_try_load_config_with_limits_section() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            "[limits]\nkills_per_minute = 10\nsigkills_per_session = 3"
+        )
+        .unwrap();
+
+        let config = Config::try_load_from_path(Some(file.path().to_path_buf())).unwrap();
+        assert_eq!(
+            config.limits,
+            Some(LimitsConfig {
+                kills_per_minute: Some(10),
+                sigkills_per_session: Some(3),
+            })
+        );
+    }
+
+    #[test]
+    fn test_try_load_config_with_expiring_entries() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            r#"[allowlist]
+processes = ["node", {{ name = "next-server", expires = "2000-01-01T00:00:00Z" }}, {{ name = "vite", expires = "2999-01-01T00:00:00Z" }}]
+
+[allowed_ports]
+ports = ["3000", {{ port = "9229", expires = "2000-01-01T00:00:00Z" }}, {{ port = "5173", expires = "2999-01-01T00:00:00Z" }}]"#
+        )
+        .unwrap();
+
+        let config = Config::try_load_from_path(Some(file.path().to_path_buf())).unwrap();
+        let allowlist = config.allowlist.as_ref().unwrap();
+        assert_eq!(allowlist.processes, vec!["node"]);
+        assert_eq!(
+            allowlist.expiring[0],
+            ExpiringEntry {
+                value: "next-server".to_string(),
+                expires: 946_684_800,
+            }
+        );
+
+        // 期限切れのエントリは判定で無視する
+        assert!(config.is_allowed("node"));
+        assert!(config.is_allowed("vite"));
+        assert!(!config.is_allowed("next-server"));
+        assert!(config.is_port_allowed(3000));
+        assert!(config.is_port_allowed(5173));
+        assert!(!config.is_port_allowed(9229));
+    }
+
+    #[test]
+    fn test_try_load_config_rejects_invalid_expiry() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            r#"[allowlist]
+processes = [{{ name = "next-server", expires = "next week" }}]"#
+        )
+        .unwrap();
+
+        let err = Config::try_load_from_path(Some(file.path().to_path_buf())).unwrap_err();
+        assert!(err.to_string().contains("invalid timestamp 'next week'"));
+    }
+
+    #[test]
+    fn test_try_load_config_rejects_expiring_denylist_entry() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            r#"[denylist]
+processes = [{{ name = "postgres", expires = "2999-01-01T00:00:00Z" }}]"#
+        )
+        .unwrap();
+
+        assert_eq!(
+            Config::try_load_from_path(Some(file.path().to_path_buf())),
+            Err(SafeKillError::ConfigError(
+                "[denylist] entries cannot expire".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_section_errors_reports_every_section() {
+        let config: Config = toml::from_str(
+            r#"[root]
+strategy = "levels"
+
+[signals]
+forbid = ["SIGFOO"]"#,
+        )
+        .unwrap();
+
+        let sections: Vec<_> = config
+            .section_errors()
+            .into_iter()
+            .map(|issue| issue.section)
+            .collect();
+        assert_eq!(sections, vec!["root", "signals"]);
+        assert!(Config::default().section_errors().is_empty());
+    }
+
+    #[test]
+    fn test_try_load_config_rejects_invalid_port_entry() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[allowed_ports]\nports = [\"3000-30l0\", \"8080\"]").unwrap();
+
+        let result = Config::try_load_from_path(Some(file.path().to_path_buf()));
+        assert_eq!(
+            result,
+            Err(SafeKillError::ConfigError(
+                "[allowed_ports] invalid entry \"3000-30l0\": Invalid port range format: 3000-30l0"
+                    .to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_try_load_config_rejects_overlapping_and_duplicate_ports() {
+        for (ports, message) in [
+            (
+                r#"["3000-3010", "3005-3020"]"#,
+                r#"[allowed_ports] entry "3005-3020" overlaps "3000-3010""#,
+            ),
+            (
+                r#"["8080", " 8080 "]"#,
+                r#"[allowed_ports] duplicate entry " 8080 " (same as "8080")"#,
+            ),
+            (
+                r#"["tcp:127.0.0.1:9229", "tcp:127.0.0.1:9000-9300"]"#,
+                r#"[allowed_ports] entry "tcp:127.0.0.1:9000-9300" overlaps "tcp:127.0.0.1:9229""#,
+            ),
+        ] {
+            let mut file = NamedTempFile::new().unwrap();
+            writeln!(file, "[allowed_ports]\nports = {}", ports).unwrap();
+            assert_eq!(
+                Config::try_load_from_path(Some(file.path().to_path_buf())),
+                Err(SafeKillError::ConfigError(message.to_string())),
+                "{}",
+                ports
+            );
+        }
+    }
+
+    #[test]
+    fn test_try_load_config_allows_same_port_for_different_targets() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            r#"[allowed_ports]
+ports = ["tcp:5353", "udp:5353", "127.0.0.1:3000", "[::1]:3000", "3001-3002"]"#
+        )
+        .unwrap();
+
+        assert!(Config::try_load_from_path(Some(file.path().to_path_buf())).is_ok());
+    }
+
+    #[test]
+    fn test_try_load_config_rejects_empty_process_name() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[allowlist]\nprocesses = [\"node\", \" \"]").unwrap();
+
+        assert_eq!(
+            Config::try_load_from_path(Some(file.path().to_path_buf())),
+            Err(SafeKillError::ConfigError(
+                "[allowlist] empty process name".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_section_errors_reports_profile_entries() {
+        let config: Config = toml::from_str(
+            r#"[profiles.frontend.allowed_ports]
+ports = ["3000", "70000"]
+
+[profiles.frontend.signals]
+forbid = ["SIGNOPE"]"#,
+        )
+        .unwrap();
+
+        let issues = config.section_errors();
+        assert_eq!(issues.len(), 2);
+        assert!(
+            issues
+                .iter()
+                .all(|issue| issue.profile.as_deref() == Some("frontend"))
+        );
+        let messages: Vec<String> = issues.iter().map(|issue| issue.error.to_string()).collect();
+        assert!(
+            messages
+                .iter()
+                .all(|message| message.contains("[profiles.frontend] ")),
+            "{:?}",
+            messages
+        );
+        assert_eq!(issues[1].entry.as_deref(), Some("70000"));
+    }
+
+    #[test]
+    fn test_section_errors_reports_each_bad_entry() {
+        let config: Config = toml::from_str(
+            r#"[denylist]
+processes = [""]
+
+[allowed_ports]
+ports = ["0", "3000", "3000", "70000"]"#,
+        )
+        .unwrap();
+
+        let entries: Vec<_> = config
+            .section_errors()
+            .into_iter()
+            .map(|issue| (issue.section, issue.entry.unwrap()))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("denylist", "".to_string()),
+                ("allowed_ports", "0".to_string()),
+                ("allowed_ports", "3000".to_string()),
+                ("allowed_ports", "70000".to_string()),
+            ]
+        );
+    }
+
+    /// TOML を JSON に変換して JSON Schema で検証する
+    fn schema_accepts(content: &str) -> bool {
+        let value: toml::Value = toml::from_str(content).unwrap();
+        let instance = serde_json::to_value(value).unwrap();
+        jsonschema::validator_for(&Config::json_schema())
+            .unwrap()
+            .is_valid(&instance)
+    }
+
+    #[test]
+    fn test_json_schema_agrees_with_deserializer() {
+        let cases = [
+            "",
+            r#"[allowlist]
+processes = ["node", { name = "vite", expires = "2999-01-01T00:00:00Z" }]
+
+[denylist]
+processes = ["postgres"]
+
+[allowed_ports]
+ports = ["3000-3010", { port = "tcp:9229", expires = "2999-01-01T00:00:00Z" }]
+
+[allowed_sockets]
+paths = ["/run/user/*/dev.sock"]
+
+[allowed_paths]
+paths = ["/work/*/target"]
+
+[root]
+strategy = "levels"
+levels = 2
+
+[ownership]
+enforce = true
+users = ["root"]
+
+[privileged]
+allowlist_bypass = false
+
+[signals]
+forbid = ["SIGKILL"]
+
+[signals.port]
+allow = ["SIGTERM"]
+
+[confirm]
+above = 3
+sigkill = true
+
+[limits]
+kills_per_minute = 10
+sigkills_per_session = 2"#,
+            "typo = 1",
+            "[allowlist]\nprocesses = \"node\"",
+            "[allowlist]\nprocess = [\"node\"]",
+            "[allowlist]\nprocesses = [{ name = \"vite\" }]",
+            "[allowlist]\nprocesses = [{ name = \"vite\", expires = \"2999-01-01T00:00:00Z\", note = \"x\" }]",
+            "[allowed_ports]\nports = [3000]",
+            "[allowed_ports]\nports = [{ name = \"3000\", expires = \"2999-01-01T00:00:00Z\" }]",
+            "[allowed_ports]",
+            "[root]\nstrategy = \"levelz\"",
+            "[root]\nlevels = -1",
+            "[confirm]\nabove = -1",
+            "[signals.parent]\nallow = []",
+            "[limits]\nkills_per_minute = \"10\"",
+            "[profiles.frontend.allowed_ports]\nports = [\"3000-3010\"]\n\n[profiles.frontend.signals]\nforbid = [\"SIGKILL\"]",
+            "[profiles.frontend]\nconfirm = { above = 3 }",
+            "[profiles]\nfrontend = 1",
+        ];
+        for content in cases {
+            assert_eq!(
+                schema_accepts(content),
+                toml::from_str::<Config>(content).is_ok(),
+                "{}",
+                content
+            );
+        }
+    }
+
+    #[test]
+    fn test_json_schema_has_no_null_types() {
+        let schema = Config::json_schema();
+        assert_eq!(schema["title"], "safe-kill config.toml");
+        assert!(!schema.to_string().contains("\"null\""));
+        assert!(schema["properties"]["allowed_ports"]["$ref"].is_string());
+    }
+
+    #[test]
+    fn test_config_serializes_back_to_toml() {
+        let content = r#"[allowlist]
+processes = ["node", { name = "vite", expires = "2999-01-01T00:00:00Z" }]
+
+[allowed_ports]
+ports = ["3000", { port = "9229", expires = "2999-01-01T00:00:00Z" }]
+
+[root]
+strategy = "session_leader"
+"#;
+        let config: Config = toml::from_str(content).unwrap();
+        let rendered = toml::to_string(&config).unwrap();
+        assert!(rendered.contains("strategy = \"session_leader\""));
+        assert!(rendered.contains("expires = \"2999-01-01T00:00:00Z\""));
+        assert_eq!(toml::from_str::<Config>(&rendered).unwrap(), config);
+    }
+
+    #[test]
+    fn test_apply_grants_merges_only_active_grants() {
+        let mut config = Config::default();
+        config.apply_grants(
+            Grants {
+                processes: vec![
+                    ExpiringEntry {
+                        value: "next-server".to_string(),
+                        expires: 2000,
+                    },
+                    ExpiringEntry {
+                        value: "old".to_string(),
+                        expires: 500,
+                    },
+                ],
+                ports: vec![ExpiringEntry {
+                    value: "9229".to_string(),
+                    expires: 2000,
+                }],
+            },
+            1000,
+        );
+
+        let allowlist = config.allowlist.unwrap();
+        assert_eq!(allowlist.expiring.len(), 1);
+        assert_eq!(allowlist.expiring[0].value, "next-server");
+        assert_eq!(config.allowed_ports.unwrap().expiring[0].value, "9229");
+    }
+
+    #[test]
+    fn test_grants_cannot_widen_narrowing_override() {
+        let system_file = NamedTempFile::new().unwrap();
+        let mut user_file = NamedTempFile::new().unwrap();
+        writeln!(
+            user_file,
+            "[allowlist]\nprocesses = [\"node\"]\n[allowed_ports]\nports = [\"3000-3010\"]"
+        )
+        .unwrap();
+        let grant = |value: &str| ExpiringEntry {
+            value: value.to_string(),
+            expires: grants::now() + 3600,
+        };
+        let options = LoadOptions {
+            path: Some(user_file.path().to_path_buf()),
+            sets: Vec::new(),
+            profile: None,
+        };
+        let load = |vars| {
+            Config::try_load_layered(
+                system_file.path(),
+                &options,
+                overrides(vars),
+                Grants {
+                    processes: vec![grant("next-server")],
+                    ports: vec![grant("9229")],
+                },
+            )
+            .unwrap()
+        };
+
+        // 絞り込む上書きの後から許可が広げ直すことはない
+        let config = load(&[
+            ("SAFE_KILL_ALLOWLIST", "node"),
+            ("SAFE_KILL_ALLOWED_PORTS", "3000"),
+        ]);
+        assert!(config.is_allowed("node"));
+        assert!(!config.is_allowed("next-server"));
+        assert!(config.is_port_allowed(3000));
+        assert!(!config.is_port_allowed(9229));
+
+        // 許可したエントリは上書きで残せ、期限も引き継ぐ
+        let config = load(&[
+            ("SAFE_KILL_ALLOWLIST", "next-server"),
+            ("SAFE_KILL_ALLOWED_PORTS", "9229"),
+        ]);
+        assert!(!config.is_allowed("node"));
+        assert!(config.is_allowed("next-server"));
+        assert!(!config.is_port_allowed(3000));
+        assert!(config.is_port_allowed(9229));
+        assert_eq!(config.allowlist.unwrap().expiring.len(), 1);
+    }
+
+    #[test]
+    fn test_try_load_config_rejects_unknown_limit() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[limits]\nkills_per_hour = 10").unwrap();
+
+        assert!(matches!(
+            Config::try_load_from_path(Some(file.path().to_path_buf())),
+            Err(SafeKillError::ConfigError(_))
+        ));
+    }
+
+    #[test]
+    fn test_confirm_requires_above_limit_or_sigkill() {
+        let confirm = ConfirmConfig {
+            above: Some(3),
+            sigkill: true,
+        };
+        assert!(!confirm.requires(3, Signal::SIGTERM, None));
+        assert!(confirm.requires(4, Signal::SIGTERM, None));
+        assert!(confirm.requires(1, Signal::SIGKILL, None));
+
+        // above が未設定なら呼び出し側の既定値を使う
+        let defaults = ConfirmConfig::default();
+        assert!(!defaults.requires(100, Signal::SIGKILL, None));
+        assert!(!defaults.requires(5, Signal::SIGTERM, Some(5)));
+        assert!(defaults.requires(6, Signal::SIGTERM, Some(5)));
+    }
 }
@@ -0,0 +1,528 @@
+//! safe-kill の config コマンドモジュール
+//!
+//! 設定ファイルの検証（問題を行・列付きですべて報告）、有効な設定の表示、
+//! コメントと書式を保ったままのエントリ追加を行う。
+
+use std::fmt;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use toml_edit::{Array, DocumentMut, Item, Table, TableLike, Value};
+
+use crate::config::{Config, LoadOptions, PortSpec};
+use crate::error::SafeKillError;
+
+/// 設定ファイル中の 1 つの問題
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigProblem {
+    /// 1 始まりの行番号
+    pub line: usize,
+    /// 1 始まりの列番号（文字単位）
+    pub column: usize,
+    /// 問題の内容
+    pub message: String,
+}
+
+impl ConfigProblem {
+    /// 内容中のバイト位置から問題を作る
+    fn at(content: &str, offset: usize, message: impl Into<String>) -> Self {
+        let offset = (0..=offset.min(content.len()))
+            .rev()
+            .find(|&i| content.is_char_boundary(i))
+            .unwrap_or(0);
+        let before = &content[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// `config add-port` / `config add-deny` の実行結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddOutcome {
+    /// エントリを追加して設定ファイルを書き込んだ
+    Added(PathBuf),
+    /// 同じエントリがすでにあるため変更しなかった
+    AlreadyPresent(PathBuf),
+}
+
+/// 設定ファイルを操作する config コマンド
+pub struct ConfigCommand;
+
+impl ConfigCommand {
+    /// 設定ファイルを検証し、見つかった問題をすべて返す
+    ///
+    /// # 戻り値
+    /// * `Ok(problems)` - 問題の一覧（空なら有効な設定）
+    /// * `Err(SafeKillError)` - ファイルを読めない場合
+    pub fn validate(path: &Path) -> Result<Vec<ConfigProblem>, SafeKillError> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            SafeKillError::ConfigError(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        Ok(validate_content(&content))
+    }
+
+    /// デフォルトと期限付きの許可を合流した、実際に使われる設定を TOML で返す
+    pub fn show(options: &LoadOptions) -> Result<String, SafeKillError> {
+        let config = Config::try_load_with(options)?;
+        toml::to_string_pretty(&config).map_err(|e| {
+            SafeKillError::SystemError(format!("Failed to encode config as TOML: {}", e))
+        })
+    }
+
+    /// 設定ファイルの JSON Schema を整形済みの文字列で返す
+    pub fn schema() -> String {
+        let mut schema = serde_json::to_string_pretty(&Config::json_schema())
+            .expect("JSON Schema should always serialize");
+        schema.push('\n');
+        schema
+    }
+
+    /// `[allowed_ports] ports` にポート指定を追加する
+    pub fn add_port(path: &Path, spec: &str) -> Result<AddOutcome, SafeKillError> {
+        PortSpec::parse(spec)?;
+        Self::add_entry(path, "allowed_ports", "ports", "port", spec)
+    }
+
+    /// `[denylist] processes` にプロセス名を追加する
+    pub fn add_deny(path: &Path, name: &str) -> Result<AddOutcome, SafeKillError> {
+        if name.trim().is_empty() {
+            return Err(SafeKillError::InvalidUsage(
+                "Process name must not be empty".to_string(),
+            ));
+        }
+        Self::add_entry(path, "denylist", "processes", "name", name)
+    }
+
+    /// `$VISUAL` / `$EDITOR`（どちらもなければ vi）で設定ファイルを開き、終了後に検証する
+    pub fn edit(path: &Path) -> Result<Vec<ConfigProblem>, SafeKillError> {
+        let editor = std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .ok()
+            .filter(|editor| !editor.trim().is_empty())
+            .unwrap_or_else(|| "vi".to_string());
+        // "code --wait" のように引数付きで指定されることがあるため空白で分割する
+        let mut words = editor.split_whitespace();
+        let program = words.next().unwrap_or("vi");
+        let status = process::Command::new(program)
+            .args(words)
+            .arg(path)
+            .status()
+            .map_err(|e| {
+                SafeKillError::SystemError(format!("Failed to start editor {:?}: {}", editor, e))
+            })?;
+        if !status.success() {
+            return Err(SafeKillError::SystemError(format!(
+                "Editor {:?} exited with {}",
+                editor, status
+            )));
+        }
+        Self::validate(path)
+    }
+
+    /// セクション内の配列に文字列エントリを追加して書き戻す
+    ///
+    /// ファイルがなければ新規に作る。書き込む前に結果を検証し、
+    /// 問題があればファイルを変更しない。
+    fn add_entry(
+        path: &Path,
+        section: &str,
+        key: &str,
+        entry_key: &str,
+        value: &str,
+    ) -> Result<AddOutcome, SafeKillError> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => {
+                return Err(SafeKillError::ConfigError(format!(
+                    "Failed to read {}: {}",
+                    path.display(),
+                    e
+                )));
+            }
+        };
+        let mut document = content.parse::<DocumentMut>().map_err(|e| {
+            SafeKillError::ConfigError(format!("Failed to parse {}: {}", path.display(), e))
+        })?;
+
+        let not_array = || {
+            SafeKillError::ConfigError(format!(
+                "[{}] {} in {} is not an array",
+                section,
+                key,
+                path.display()
+            ))
+        };
+        let table = document
+            .entry(section)
+            .or_insert_with(toml_edit::table)
+            .as_table_like_mut()
+            .ok_or_else(not_array)?;
+        let array = table
+            .entry(key)
+            .or_insert(Item::Value(Value::Array(Array::new())))
+            .as_array_mut()
+            .ok_or_else(not_array)?;
+
+        if array
+            .iter()
+            .any(|entry| entry_value(entry, entry_key) == Some(value))
+        {
+            return Ok(AddOutcome::AlreadyPresent(path.to_path_buf()));
+        }
+        push_formatted(array, value);
+
+        let updated = document.to_string();
+        if let Some(problem) = validate_content(&updated).into_iter().next() {
+            return Err(SafeKillError::ConfigError(format!(
+                "{} would be invalid after the change ({}); run `safe-kill config validate`",
+                path.display(),
+                problem
+            )));
+        }
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| {
+                SafeKillError::ConfigCreationError(format!(
+                    "Failed to create directory {}: {}",
+                    dir.display(),
+                    e
+                ))
+            })?;
+        }
+        fs::write(path, updated).map_err(|e| {
+            SafeKillError::ConfigCreationError(format!(
+                "Failed to write config file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        Ok(AddOutcome::Added(path.to_path_buf()))
+    }
+}
+
+/// 設定ファイルの内容を検証し、見つかった問題を位置順にすべて返す
+///
+/// TOML の構文エラーは最初の 1 件で解析が止まるためそれだけを返す。
+/// 構文が正しければ、型や未知のキーの誤りをセクションごとに集め、
+/// 最後に値の検証（シグナル名、ユーザー、glob、ポート指定など）を行う。
+pub fn validate_content(content: &str) -> Vec<ConfigProblem> {
+    let document = match toml_edit::Document::parse(content) {
+        Ok(document) => document,
+        Err(e) => {
+            let offset = e.span().map_or(0, |span| span.start);
+            return vec![ConfigProblem::at(content, offset, e.message())];
+        }
+    };
+
+    let mut starts = vec![0];
+    collect_table_starts(document.as_table(), &mut starts);
+    starts.sort_unstable();
+    starts.dedup();
+
+    // エラーの出たセクションを空白で塗りつぶして解析し直し、残りのセクションの誤りも集める。
+    // 改行は残すため、以降のエラー位置も元の内容の位置と一致する。
+    let mut problems = Vec::new();
+    let mut masked = content.to_string();
+    let config = loop {
+        match toml::from_str::<Config>(&masked) {
+            Ok(config) => break Some(config),
+            Err(e) => {
+                let offset = e.span().map_or(0, |span| span.start);
+                problems.push((offset, ConfigProblem::at(content, offset, e.message())));
+                if !mask(&mut masked, section_around(&starts, offset, content.len())) {
+                    break None;
+                }
+            }
+        }
+    };
+
+    for issue in config.iter().flat_map(Config::section_errors) {
+        // `[profiles.<name>]` の問題はそのプロファイルのテーブルの中で探す
+        let table: Option<&dyn TableLike> = match &issue.profile {
+            Some(name) => document
+                .get("profiles")
+                .and_then(|profiles| profiles.get(name.as_str()))
+                .and_then(Item::as_table_like),
+            None => Some(document.as_table()),
+        };
+        let offset = table
+            .and_then(|table| {
+                issue
+                    .entry
+                    .as_deref()
+                    .and_then(|entry| entry_span(table, issue.section, entry))
+                    .or_else(|| table.get(issue.section).and_then(Item::span))
+            })
+            .map_or(0, |span| span.start);
+        let message = match issue.error {
+            SafeKillError::ConfigError(message) => message,
+            other => other.to_string(),
+        };
+        problems.push((offset, ConfigProblem::at(content, offset, message)));
+    }
+
+    problems.sort_by_key(|(offset, _)| *offset);
+    problems.into_iter().map(|(_, problem)| problem).collect()
+}
+
+/// 明示的に書かれたテーブル見出しの開始位置を集める
+fn collect_table_starts(table: &Table, starts: &mut Vec<usize>) {
+    for (_, item) in table.iter() {
+        let tables: Vec<&Table> = match item {
+            Item::Table(table) => vec![table],
+            Item::ArrayOfTables(array) => array.iter().collect(),
+            _ => Vec::new(),
+        };
+        for table in tables {
+            if let Some(span) = table.span().filter(|_| !table.is_implicit()) {
+                starts.push(span.start);
+            }
+            collect_table_starts(table, starts);
+        }
+    }
+}
+
+/// 指定位置を含むセクション（見出しから次の見出しの直前まで）の範囲
+fn section_around(starts: &[usize], offset: usize, len: usize) -> Range<usize> {
+    let index = starts.partition_point(|&start| start <= offset);
+    let start = starts[index.saturating_sub(1)];
+    let end = starts.get(index).copied().unwrap_or(len);
+    start..end
+}
+
+/// 範囲内の改行以外を空白に置き換える（変化がなければ false）
+///
+/// 複数バイト文字はバイト数分の空白にし、以降の位置がずれないようにする。
+fn mask(content: &mut String, range: Range<usize>) -> bool {
+    let blank: String = content[range.clone()]
+        .chars()
+        .map(|c| match c {
+            '\n' | '\r' => c.to_string(),
+            _ => " ".repeat(c.len_utf8()),
+        })
+        .collect();
+    if blank == content[range.clone()] {
+        return false;
+    }
+    content.replace_range(range, &blank);
+    true
+}
+
+/// セクションの配列で値が一致する最後のエントリの位置
+///
+/// 重複や重なりは後に書かれたエントリを指すため、後ろから探す。
+fn entry_span(table: &dyn TableLike, section: &str, entry: &str) -> Option<Range<usize>> {
+    let (key, entry_key) = match section {
+        "allowed_ports" => ("ports", "port"),
+        _ => ("processes", "name"),
+    };
+    table
+        .get(section)?
+        .get(key)?
+        .as_array()?
+        .iter()
+        .filter(|value| entry_value(value, entry_key) == Some(entry))
+        .last()?
+        .span()
+}
+
+/// 配列のエントリが持つ値（文字列、または `{ name/port = ..., expires = ... }`）
+fn entry_value<'a>(entry: &'a Value, entry_key: &str) -> Option<&'a str> {
+    match entry {
+        Value::InlineTable(table) => table.get(entry_key).and_then(Value::as_str),
+        other => other.as_str(),
+    }
+}
+
+/// 既存のエントリと同じ改行・インデントで配列の末尾に追加する
+fn push_formatted(array: &mut Array, value: &str) {
+    let mut entry = Value::from(value);
+    if let Some(last) = array.iter().last() {
+        let prefix = last.decor().prefix().and_then(|p| p.as_str()).unwrap_or("");
+        let prefix = match prefix.rfind('\n') {
+            Some(i) => format!("\n{}", &prefix[i + 1..]),
+            None => " ".to_string(),
+        };
+        entry.decor_mut().set_prefix(prefix);
+    }
+    array.push_formatted(entry);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn positions(content: &str) -> Vec<(usize, usize)> {
+        validate_content(content)
+            .iter()
+            .map(|p| (p.line, p.column))
+            .collect()
+    }
+
+    #[test]
+    fn test_problem_position_counts_characters() {
+        let content = "# 設定\nkey = 1\n";
+        let offset = content.find("key").unwrap();
+        let problem = ConfigProblem::at(content, offset, "bad");
+        assert_eq!((problem.line, problem.column), (2, 1));
+        assert_eq!(problem.to_string(), "2:1: bad");
+
+        let offset = content.find('定').unwrap() + 1;
+        let problem = ConfigProblem::at(content, offset, "mid-char");
+        assert_eq!((problem.line, problem.column), (1, 4));
+    }
+
+    #[test]
+    fn test_validate_accepts_valid_config() {
+        let content = "[allowlist]\nprocesses = [\"node\"]\n\n[allowed_ports]\nports = [\"3000\", \"8080-8090\"]\n";
+        assert!(validate_content(content).is_empty());
+        assert!(validate_content("").is_empty());
+    }
+
+    #[test]
+    fn test_validate_reports_syntax_error_position() {
+        let problems = validate_content("[allowlist]\nprocesses = [\"node\" \"npm\"]\n");
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].line, 2);
+    }
+
+    #[test]
+    fn test_validate_reports_all_problems_in_order() {
+        let content = "typo = 1\n\n[allowlist]\nprocesses = \"node\"\n\n[allowed_ports]\nports = [\n  \"3000\",\n  \"4010-4000\",\n]\n\n[signals]\nforbid = [\"SIGFOO\"]\n\n[root]\nstrategy = \"levelz\"\n";
+        let problems = validate_content(content);
+        assert_eq!(
+            positions(content),
+            vec![(1, 1), (4, 13), (9, 3), (12, 1), (16, 12)],
+            "{:?}",
+            problems
+        );
+        assert!(problems[0].message.contains("typo"));
+        assert!(problems[2].message.contains("[allowed_ports]"));
+        assert!(problems[3].message.contains("SIGFOO"));
+        assert!(problems[4].message.contains("levelz"));
+    }
+
+    #[test]
+    fn test_validate_reports_problems_in_nested_tables() {
+        let content = "[signals.port]\nallow = 9\n\n[confirm]\nabove = \"x\"\n";
+        assert_eq!(positions(content), vec![(2, 9), (5, 9)]);
+    }
+
+    #[test]
+    fn test_validate_points_at_later_duplicate_and_overlap() {
+        let content = "[allowlist]\nprocesses = [\"node\", \"\"]\n\n[allowed_ports]\nports = [\n  \"3000\",\n  \"3000-3010\",\n  \"3000\",\n]\n";
+        let problems = validate_content(content);
+        assert_eq!(positions(content), vec![(2, 22), (7, 3), (8, 3)]);
+        assert_eq!(problems[0].message, "[allowlist] empty process name");
+        assert!(problems[1].message.contains("overlaps"));
+        assert!(problems[2].message.contains("duplicate"));
+    }
+
+    #[test]
+    fn test_validate_points_into_profiles() {
+        let content = "[allowed_ports]\nports = [\"3000\"]\n\n[profiles.frontend.allowed_ports]\nports = [\"3000\", \"0\"]\n\n[profiles.data.signals]\nforbid = [\"SIGFOO\"]\n";
+        let problems = validate_content(content);
+        assert_eq!(positions(content), vec![(5, 18), (7, 1)], "{:?}", problems);
+        assert!(
+            problems[0]
+                .message
+                .starts_with("[profiles.frontend] [allowed_ports]")
+        );
+        assert!(problems[1].message.starts_with("[profiles.data] "));
+    }
+
+    #[test]
+    fn test_validate_rejects_expiring_denylist_entry() {
+        let content =
+            "\n[denylist]\nprocesses = [{ name = \"x\", expires = \"2030-01-01T00:00:00Z\" }]\n";
+        let problems = validate_content(content);
+        assert_eq!(problems.len(), 1);
+        assert_eq!((problems[0].line, problems[0].column), (2, 1));
+        assert!(problems[0].message.contains("cannot expire"));
+    }
+
+    #[test]
+    fn test_validate_checks_expiring_port_entries() {
+        let content =
+            "[allowed_ports]\nports = [{ port = \"70000\", expires = \"2030-01-01T00:00:00Z\" }]\n";
+        assert_eq!(positions(content), vec![(2, 10)]);
+    }
+
+    #[test]
+    fn test_add_port_preserves_comments_and_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(
+            &path,
+            "# mine\n[allowed_ports]\nports = [\n  \"3000\", # next\n  \"5173\",\n]\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            ConfigCommand::add_port(&path, "4000-4010").unwrap(),
+            AddOutcome::Added(path.clone())
+        );
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# mine\n[allowed_ports]\nports = [\n  \"3000\", # next\n  \"5173\",\n  \"4000-4010\",\n]\n"
+        );
+
+        assert_eq!(
+            ConfigCommand::add_port(&path, "4000-4010").unwrap(),
+            AddOutcome::AlreadyPresent(path.clone())
+        );
+        assert!(ConfigCommand::add_port(&path, "0").is_err());
+    }
+
+    #[test]
+    fn test_add_deny_creates_section_and_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("safe-kill").join("config.toml");
+
+        ConfigCommand::add_deny(&path, "postgres").unwrap();
+        ConfigCommand::add_deny(&path, "redis-server").unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "[denylist]\nprocesses = [\"postgres\", \"redis-server\"]\n"
+        );
+        assert!(ConfigCommand::add_deny(&path, " ").is_err());
+    }
+
+    #[test]
+    fn test_add_entry_refuses_non_array_and_keeps_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let content = "[denylist]\nprocesses = \"postgres\"\n";
+        fs::write(&path, content).unwrap();
+
+        assert!(matches!(
+            ConfigCommand::add_deny(&path, "redis-server"),
+            Err(SafeKillError::ConfigError(_))
+        ));
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+    }
+
+    #[test]
+    fn test_add_entry_refuses_to_write_invalid_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let content = "[signals]\nforbid = [\"SIGFOO\"]\n";
+        fs::write(&path, content).unwrap();
+
+        assert!(ConfigCommand::add_deny(&path, "postgres").is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+    }
+}
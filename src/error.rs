@@ -5,6 +5,8 @@
 use std::process::ExitCode;
 use thiserror::Error;
 
+use crate::config::ConfigLayer;
+
 /// `safe-kill` コマンドの終了コード
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SafeKillExitCode {
@@ -16,8 +18,14 @@ pub enum SafeKillExitCode {
     PermissionDenied = 2,
     /// 設定ファイルエラー
     ConfigError = 3,
-    /// 設定上許可されていないポート
+    /// 設定上許可されていないポートまたはソケット
     PortNotAllowed = 4,
+    /// 特権付きで実行されたが `[privileged]` 設定がない
+    PrivilegedPolicyRequired = 5,
+    /// `[signals]` 設定で禁止されたシグナル
+    SignalNotAllowed = 6,
+    /// `[limits]` の上限に達した
+    RateLimited = 7,
     /// その他の一般エラー
     GeneralError = 255,
 }
@@ -57,14 +65,22 @@ pub enum SafeKillError {
     #[error("Process {0} ({1}) is not a descendant of the current session")]
     NotDescendant(u32, String),
 
-    /// denylist に含まれている
-    #[error("Process {0} is in denylist and cannot be killed")]
-    Denylisted(String),
+    /// denylist に含まれている（どの層の denylist かを添える）
+    #[error("Process {0} is in denylist ({1}) and cannot be killed")]
+    Denylisted(String, ConfigLayer),
 
     /// 自分または親を kill しようとした
     #[error("Cannot kill self or parent process (PID: {0})")]
     SuicidePrevention(u32),
 
+    /// safe-kill 自身の祖先（エージェント本体や信頼ルートを含む）を kill しようとした
+    #[error("Process {0} ({1}) is an ancestor of safe-kill and cannot be killed")]
+    AncestorProtected(u32, String),
+
+    /// 起動ユーザー（または設定で許可したユーザー）以外が所有するプロセスを kill しようとした
+    #[error("Process {0} ({1}) is owned by {2}, not by the invoking user")]
+    NotOwned(u32, String, String),
+
     /// プロセスが見つからない
     #[error("Process {0} not found")]
     ProcessNotFound(u32),
@@ -73,6 +89,14 @@ pub enum SafeKillError {
     #[error("No process found with name: {0}")]
     ProcessNameNotFound(String),
 
+    /// カレントディレクトリが指定ディレクトリ配下にあるプロセスが見つからない
+    #[error("No process found with working directory in {0}")]
+    NoProcessInCwd(String),
+
+    /// 確認プロンプトで中止された、または確認できなかった
+    #[error("Aborted: {0}")]
+    Aborted(String),
+
     /// 一致はしたがポリシー上 kill できる対象がなかった
     #[error("No killable process found for {0}")]
     NoKillableTarget(String),
@@ -82,10 +106,26 @@ pub enum SafeKillError {
     #[error("No process found on port {0}")]
     NoProcessOnPort(u16),
 
+    /// 指定したポート集合（範囲・リスト）で待ち受けるプロセスが見つからない
+    #[error("No process found on ports {0}")]
+    NoProcessOnPorts(String),
+
+    /// 指定パスの Unix ドメインソケットを保持するプロセスが見つからない
+    #[error("No process found on socket {0}")]
+    NoProcessOnSocket(String),
+
+    /// 指定パスを開いている・マップしているプロセスが見つからない
+    #[error("No process found holding {0}")]
+    NoProcessHoldingPath(String),
+
     /// 設定上許可されていないポート
     #[error("Port {port} is not allowed. {hint}")]
     PortNotAllowed { port: u16, hint: String },
 
+    /// `[allowed_sockets]` に一致しない Unix ドメインソケット
+    #[error("Socket {path} is not allowed. {hint}")]
+    SocketNotAllowed { path: String, hint: String },
+
     /// ポート使用プロセスの検出に失敗
     #[error("Failed to detect process on port {port}: {reason}")]
     PortDetectionError { port: u16, reason: String },
@@ -94,6 +134,20 @@ pub enum SafeKillError {
     #[error("Invalid port range format: {0}")]
     InvalidPortRange(String),
 
+    /// 特権付きで実行されたが `[privileged]` 設定がない
+    #[error(
+        "Refusing to run with elevated privileges ({0}) without a [privileged] section in config.toml"
+    )]
+    PrivilegedPolicyRequired(String),
+
+    /// `[signals]` 設定で対象の区分に対して禁止されたシグナル（どの層の設定かを添える）
+    #[error("Signal {0} is not allowed for {1} targets by [signals] ({2})")]
+    SignalNotAllowed(String, String, ConfigLayer),
+
+    /// `[limits]` で設定した kill 回数の上限に達した
+    #[error("Rate limit exceeded: {0}")]
+    RateLimited(String),
+
     /// 設定ファイル作成に失敗
     #[error("Failed to create config file: {0}")]
     ConfigCreationError(String),
@@ -120,12 +174,24 @@ impl SafeKillError {
             | SafeKillError::ProcessNotFound(_)
             | SafeKillError::ProcessNameNotFound(_)
             | SafeKillError::NoKillableTarget(_)
-            | SafeKillError::NoProcessOnPort(_) => SafeKillExitCode::NoTarget,
+            | SafeKillError::NoProcessOnPort(_)
+            | SafeKillError::NoProcessOnPorts(_)
+            | SafeKillError::NoProcessOnSocket(_)
+            | SafeKillError::NoProcessHoldingPath(_)
+            | SafeKillError::NoProcessInCwd(_)
+            | SafeKillError::Aborted(_) => SafeKillExitCode::NoTarget,
             SafeKillError::PermissionDenied(_) => SafeKillExitCode::PermissionDenied,
             SafeKillError::ConfigError(_) | SafeKillError::ConfigCreationError(_) => {
                 SafeKillExitCode::ConfigError
             }
-            SafeKillError::PortNotAllowed { .. } => SafeKillExitCode::PortNotAllowed,
+            SafeKillError::PortNotAllowed { .. } | SafeKillError::SocketNotAllowed { .. } => {
+                SafeKillExitCode::PortNotAllowed
+            }
+            SafeKillError::PrivilegedPolicyRequired(_) => {
+                SafeKillExitCode::PrivilegedPolicyRequired
+            }
+            SafeKillError::SignalNotAllowed(..) => SafeKillExitCode::SignalNotAllowed,
+            SafeKillError::RateLimited(_) => SafeKillExitCode::RateLimited,
             _ => SafeKillExitCode::GeneralError,
         }
     }
@@ -142,6 +208,9 @@ mod tests {
         assert_eq!(SafeKillExitCode::PermissionDenied as u8, 2);
         assert_eq!(SafeKillExitCode::ConfigError as u8, 3);
         assert_eq!(SafeKillExitCode::PortNotAllowed as u8, 4);
+        assert_eq!(SafeKillExitCode::PrivilegedPolicyRequired as u8, 5);
+        assert_eq!(SafeKillExitCode::SignalNotAllowed as u8, 6);
+        assert_eq!(SafeKillExitCode::RateLimited as u8, 7);
         assert_eq!(SafeKillExitCode::GeneralError as u8, 255);
     }
 
@@ -190,10 +259,18 @@ mod tests {
 
     #[test]
     fn test_denylisted_error_message() {
-        let err = SafeKillError::Denylisted("systemd".to_string());
+        let err = SafeKillError::Denylisted("systemd".to_string(), ConfigLayer::BuiltIn);
+        assert_eq!(
+            err.to_string(),
+            "Process systemd is in denylist (built-in) and cannot be killed"
+        );
+        let err = SafeKillError::Denylisted(
+            "postgres".to_string(),
+            ConfigLayer::System(std::path::PathBuf::from("/etc/safe-kill/config.toml")),
+        );
         assert_eq!(
             err.to_string(),
-            "Process systemd is in denylist and cannot be killed"
+            "Process postgres is in denylist (system config /etc/safe-kill/config.toml) and cannot be killed"
         );
     }
 
@@ -206,6 +283,59 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_ancestor_protected_error_message() {
+        let err = SafeKillError::AncestorProtected(4321, "node".to_string());
+        assert_eq!(
+            err.to_string(),
+            "Process 4321 (node) is an ancestor of safe-kill and cannot be killed"
+        );
+    }
+
+    #[test]
+    fn test_not_owned_error_message() {
+        let err = SafeKillError::NotOwned(4321, "node".to_string(), "UID 1001".to_string());
+        assert_eq!(
+            err.to_string(),
+            "Process 4321 (node) is owned by UID 1001, not by the invoking user"
+        );
+        assert_eq!(err.exit_code(), SafeKillExitCode::GeneralError);
+    }
+
+    #[test]
+    fn test_signal_not_allowed_error() {
+        let err = SafeKillError::SignalNotAllowed(
+            "SIGKILL".to_string(),
+            "allowlisted".to_string(),
+            ConfigLayer::User,
+        );
+        assert_eq!(
+            err.to_string(),
+            "Signal SIGKILL is not allowed for allowlisted targets by [signals] (user config)"
+        );
+        assert_eq!(err.exit_code(), SafeKillExitCode::SignalNotAllowed);
+    }
+
+    #[test]
+    fn test_rate_limited_error() {
+        let err = SafeKillError::RateLimited("3 signal(s) sent in the last minute".to_string());
+        assert_eq!(
+            err.to_string(),
+            "Rate limit exceeded: 3 signal(s) sent in the last minute"
+        );
+        assert_eq!(err.exit_code(), SafeKillExitCode::RateLimited);
+    }
+
+    #[test]
+    fn test_privileged_policy_required_error() {
+        let err = SafeKillError::PrivilegedPolicyRequired("effective UID 0".to_string());
+        assert_eq!(
+            err.to_string(),
+            "Refusing to run with elevated privileges (effective UID 0) without a [privileged] section in config.toml"
+        );
+        assert_eq!(err.exit_code(), SafeKillExitCode::PrivilegedPolicyRequired);
+    }
+
     #[test]
     fn test_process_not_found_error_message() {
         let err = SafeKillError::ProcessNotFound(9999);
@@ -252,6 +382,13 @@ mod tests {
         assert_eq!(err.to_string(), "No process found on port 8080");
     }
 
+    #[test]
+    fn test_no_process_on_ports_error() {
+        let err = SafeKillError::NoProcessOnPorts("3000-3010,5173".to_string());
+        assert_eq!(err.to_string(), "No process found on ports 3000-3010,5173");
+        assert_eq!(err.exit_code(), SafeKillExitCode::NoTarget);
+    }
+
     #[test]
     fn test_port_not_allowed_error_message() {
         let err = SafeKillError::PortNotAllowed {
@@ -264,6 +401,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_socket_errors() {
+        let err = SafeKillError::NoProcessOnSocket("/run/dev.sock".to_string());
+        assert_eq!(err.to_string(), "No process found on socket /run/dev.sock");
+        assert_eq!(err.exit_code(), SafeKillExitCode::NoTarget);
+
+        let err = SafeKillError::SocketNotAllowed {
+            path: "/run/dev.sock".to_string(),
+            hint: "hint".to_string(),
+        };
+        assert_eq!(err.to_string(), "Socket /run/dev.sock is not allowed. hint");
+        assert_eq!(err.exit_code(), SafeKillExitCode::PortNotAllowed);
+    }
+
+    #[test]
+    fn test_no_process_holding_path_error() {
+        let err = SafeKillError::NoProcessHoldingPath("/work/target".to_string());
+        assert_eq!(err.to_string(), "No process found holding /work/target");
+        assert_eq!(err.exit_code(), SafeKillExitCode::NoTarget);
+    }
+
+    #[test]
+    fn test_cwd_errors() {
+        let err = SafeKillError::NoProcessInCwd("/work/app".to_string());
+        assert_eq!(
+            err.to_string(),
+            "No process found with working directory in /work/app"
+        );
+        assert_eq!(err.exit_code(), SafeKillExitCode::NoTarget);
+
+        let err = SafeKillError::Aborted("no signal was sent".to_string());
+        assert_eq!(err.to_string(), "Aborted: no signal was sent");
+        assert_eq!(err.exit_code(), SafeKillExitCode::NoTarget);
+    }
+
     #[test]
     fn test_port_detection_error_message() {
         let err = SafeKillError::PortDetectionError {
@@ -406,13 +578,17 @@ mod tests {
             SafeKillExitCode::GeneralError
         );
         assert_eq!(
-            SafeKillError::Denylisted("x".to_string()).exit_code(),
+            SafeKillError::Denylisted("x".to_string(), ConfigLayer::User).exit_code(),
             SafeKillExitCode::GeneralError
         );
         assert_eq!(
             SafeKillError::SuicidePrevention(1).exit_code(),
             SafeKillExitCode::GeneralError
         );
+        assert_eq!(
+            SafeKillError::AncestorProtected(1, "x".to_string()).exit_code(),
+            SafeKillExitCode::GeneralError
+        );
         assert_eq!(
             SafeKillError::SystemError("x".to_string()).exit_code(),
             SafeKillExitCode::GeneralError
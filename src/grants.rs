@@ -0,0 +1,440 @@
+//! 期限付きの許可（`safe-kill allow --for`）
+//!
+//! `safe-kill allow` で追加した一時的な許可を `~/.config/safe-kill/grants.toml` に保存する。
+//! このファイルは config.toml と同じ形式の `[allowlist]` / `[allowed_ports]` だけを持ち、
+//! 設定の読み込み時に期限内のエントリだけが config.toml に合流される。
+//! 期限の表記（RFC 3339）と期間（"2h" など）の解析もここで扱う。
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::{Config, ExpiringEntry};
+use crate::error::SafeKillError;
+
+/// `safe-kill allow` で許可する対象
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Grant {
+    /// `[allowlist]` に追加するプロセス名
+    Process(String),
+    /// `[allowed_ports]` に追加するポート指定
+    Port(String),
+}
+
+impl fmt::Display for Grant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Grant::Process(name) => write!(f, "process '{}'", name),
+            Grant::Port(port) => write!(f, "port {}", port),
+        }
+    }
+}
+
+/// grants.toml に保存されている期限付きの許可
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Grants {
+    /// `[allowlist]` の期限付きエントリ
+    pub processes: Vec<ExpiringEntry>,
+    /// `[allowed_ports]` の期限付きエントリ
+    pub ports: Vec<ExpiringEntry>,
+}
+
+/// grants.toml の読み書き
+#[derive(Debug, Clone)]
+pub struct GrantStore {
+    path: PathBuf,
+}
+
+impl GrantStore {
+    /// 指定パスのファイルを使う
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// デフォルトのパス（`~/.config/safe-kill/grants.toml`）
+    pub fn default_path() -> Option<PathBuf> {
+        Config::config_dir().map(|dir| dir.join("grants.toml"))
+    }
+
+    /// 保存先のパス
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 保存されている許可を読む（ファイルがなければ空）
+    pub fn load(&self) -> Result<Grants, SafeKillError> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Grants::default()),
+            Err(e) => {
+                return Err(SafeKillError::ConfigError(format!(
+                    "Failed to read {}: {}",
+                    self.path.display(),
+                    e
+                )));
+            }
+        };
+        let config = toml::from_str::<Config>(&content).map_err(|e| {
+            SafeKillError::ConfigError(format!("Failed to parse {}: {}", self.path.display(), e))
+        })?;
+
+        Ok(Grants {
+            processes: config
+                .allowlist
+                .map(|list| list.expiring)
+                .unwrap_or_default(),
+            ports: config
+                .allowed_ports
+                .map(|ports| ports.expiring)
+                .unwrap_or_default(),
+        })
+    }
+
+    /// 許可を追加して保存する
+    ///
+    /// 同じ対象の既存の許可は置き換え、期限切れの許可はこのとき削除する。
+    pub fn add(&self, grant: &Grant, expires: u64, now: u64) -> Result<(), SafeKillError> {
+        let mut grants = self.load()?;
+        let (entries, value) = match grant {
+            Grant::Process(name) => (&mut grants.processes, name),
+            Grant::Port(port) => (&mut grants.ports, port),
+        };
+        entries.retain(|entry| &entry.value != value);
+        entries.push(ExpiringEntry {
+            value: value.clone(),
+            expires,
+        });
+        grants.processes.retain(|entry| !entry.is_expired_at(now));
+        grants.ports.retain(|entry| !entry.is_expired_at(now));
+
+        let write_error = |e: std::io::Error| {
+            SafeKillError::ConfigCreationError(format!(
+                "Failed to write {}: {}",
+                self.path.display(),
+                e
+            ))
+        };
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(write_error)?;
+        }
+        fs::write(&self.path, render(&grants)).map_err(write_error)
+    }
+}
+
+/// grants.toml の内容を組み立てる
+fn render(grants: &Grants) -> String {
+    let mut content = String::from(
+        "# safe-kill allow で追加した期限付きの許可です。期限が過ぎたエントリは無視されます。\n",
+    );
+    let sections = [
+        ("allowlist", "processes", "name", &grants.processes),
+        ("allowed_ports", "ports", "port", &grants.ports),
+    ];
+    for (section, key, field, entries) in sections {
+        if entries.is_empty() {
+            continue;
+        }
+        content.push_str(&format!("\n[{}]\n{} = [\n", section, key));
+        for entry in entries {
+            content.push_str(&format!(
+                "    {{ {} = {}, expires = \"{}\" }},\n",
+                field,
+                toml::Value::String(entry.value.clone()),
+                format_timestamp(entry.expires)
+            ));
+        }
+        content.push_str("]\n");
+    }
+    content
+}
+
+/// 現在時刻（UNIX エポック秒）
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// 期間を解析する（"90s"、"30m"、"2h"、"1d"、"1h30m"）
+pub fn parse_duration(spec: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid duration '{}' (expected e.g. 30m, 2h, 1d)", spec);
+    let mut total: u64 = 0;
+    let mut digits = String::new();
+    for c in spec.trim().chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            _ => return Err(invalid()),
+        };
+        let value: u64 = digits.parse().map_err(|_| invalid())?;
+        total = value
+            .checked_mul(unit)
+            .and_then(|secs| total.checked_add(secs))
+            .ok_or_else(invalid)?;
+        digits.clear();
+    }
+    if !digits.is_empty() || total == 0 {
+        return Err(invalid());
+    }
+    Ok(Duration::from_secs(total))
+}
+
+/// RFC 3339 形式の時刻（"2026-12-01T00:00:00Z"、"2026-12-01T09:00:00+09:00"）を
+/// UNIX エポック秒に変換する
+pub fn parse_timestamp(spec: &str) -> Result<u64, String> {
+    let invalid = || {
+        format!(
+            "invalid timestamp '{}' (expected RFC 3339, e.g. 2026-12-01T00:00:00Z)",
+            spec
+        )
+    };
+    let bytes = spec.as_bytes();
+    if bytes.len() < 20
+        || bytes[4] != b'-'
+        || bytes[7] != b'-'
+        || !matches!(bytes[10], b'T' | b't' | b' ')
+        || bytes[13] != b':'
+        || bytes[16] != b':'
+    {
+        return Err(invalid());
+    }
+    let number = |range: std::ops::Range<usize>| -> Result<u32, String> {
+        let part = spec.get(range).ok_or_else(invalid)?;
+        if !part.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        part.parse().map_err(|_| invalid())
+    };
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let (hour, minute, second) = (number(11..13)?, number(14..16)?, number(17..19)?);
+    if !(1..=12).contains(&month)
+        || day == 0
+        || day > days_in_month(year, month)
+        || hour > 23
+        || minute > 59
+        || second > 60
+    {
+        return Err(invalid());
+    }
+
+    // 小数秒は切り捨てる
+    let mut rest = &spec[19..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let digits = fraction.bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            return Err(invalid());
+        }
+        rest = &fraction[digits..];
+    }
+    let offset: i64 = match rest.as_bytes() {
+        [b'Z' | b'z'] => 0,
+        [sign @ (b'+' | b'-'), h1, h2, b':', m1, m2] => {
+            let field = |a: u8, b: u8| -> Result<i64, String> {
+                if a.is_ascii_digit() && b.is_ascii_digit() {
+                    Ok(i64::from((a - b'0') * 10 + (b - b'0')))
+                } else {
+                    Err(invalid())
+                }
+            };
+            let (hours, minutes) = (field(*h1, *h2)?, field(*m1, *m2)?);
+            if hours > 23 || minutes > 59 {
+                return Err(invalid());
+            }
+            let offset = hours * 3600 + minutes * 60;
+            if *sign == b'+' { offset } else { -offset }
+        }
+        _ => return Err(invalid()),
+    };
+
+    let days = days_from_civil(i64::from(year), month, day);
+    let local = days * 86_400 + i64::from(hour) * 3600 + i64::from(minute) * 60 + i64::from(second);
+    u64::try_from(local - offset).map_err(|_| invalid())
+}
+
+/// UNIX エポック秒を RFC 3339 形式（UTC）にする
+pub fn format_timestamp(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+/// 月の日数
+fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// グレゴリオ暦の日付から 1970-01-01 からの日数を求める
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month_from_march = i64::from((month + 9) % 12);
+    let day_of_year = (153 * month_from_march + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// 1970-01-01 からの日数をグレゴリオ暦の日付にする
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u32;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("1970-01-01T00:00:00Z"), Ok(0));
+        assert_eq!(parse_timestamp("2026-12-01T00:00:00Z"), Ok(1_796_083_200));
+        assert_eq!(
+            parse_timestamp("2026-12-01T09:00:00+09:00"),
+            Ok(1_796_083_200)
+        );
+        assert_eq!(
+            parse_timestamp("2026-11-30T19:00:00.123-05:00"),
+            Ok(1_796_083_200)
+        );
+        assert_eq!(parse_timestamp("2024-02-29T12:00:00Z"), Ok(1_709_208_000));
+    }
+
+    #[test]
+    fn test_parse_timestamp_rejects_invalid() {
+        for spec in [
+            "2026-12-01",
+            "2026-12-01T00:00:00",
+            "2026-13-01T00:00:00Z",
+            "2025-02-29T00:00:00Z",
+            "2026-12-01T24:00:00Z",
+            "2026-12-01T00:00:00+9:00",
+            "2026-12-01T00:00:00.Z",
+            "tomorrow",
+        ] {
+            assert!(parse_timestamp(spec).is_err(), "{} は不正", spec);
+        }
+    }
+
+    #[test]
+    fn test_format_timestamp_round_trips() {
+        for spec in [
+            "1970-01-01T00:00:00Z",
+            "2000-02-29T23:59:59Z",
+            "2026-12-01T00:00:00Z",
+            "2100-03-01T12:34:56Z",
+        ] {
+            assert_eq!(format_timestamp(parse_timestamp(spec).unwrap()), spec);
+        }
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30m"), Ok(Duration::from_secs(1800)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(7200)));
+        assert_eq!(parse_duration("1d"), Ok(Duration::from_secs(86_400)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+        for spec in ["", "2", "0h", "2x", "h", "-1h"] {
+            assert!(parse_duration(spec).is_err(), "{} は不正", spec);
+        }
+    }
+
+    #[test]
+    fn test_grant_store_add_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = GrantStore::new(dir.path().join("safe-kill").join("grants.toml"));
+        assert_eq!(store.load().unwrap(), Grants::default());
+
+        store
+            .add(&Grant::Process("next-server".to_string()), 2000, 1000)
+            .unwrap();
+        store
+            .add(&Grant::Port("tcp:127.0.0.1:9229".to_string()), 3000, 1000)
+            .unwrap();
+        // 同じ対象は期限を置き換える
+        store
+            .add(&Grant::Process("next-server".to_string()), 4000, 1000)
+            .unwrap();
+
+        assert_eq!(
+            store.load().unwrap(),
+            Grants {
+                processes: vec![ExpiringEntry {
+                    value: "next-server".to_string(),
+                    expires: 4000,
+                }],
+                ports: vec![ExpiringEntry {
+                    value: "tcp:127.0.0.1:9229".to_string(),
+                    expires: 3000,
+                }],
+            }
+        );
+    }
+
+    #[test]
+    fn test_grant_store_prunes_expired_on_add() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = GrantStore::new(dir.path().join("grants.toml"));
+        store
+            .add(&Grant::Process("old".to_string()), 1500, 1000)
+            .unwrap();
+        store
+            .add(&Grant::Process("new".to_string()), 5000, 2000)
+            .unwrap();
+
+        let grants = store.load().unwrap();
+        let names: Vec<&str> = grants
+            .processes
+            .iter()
+            .map(|entry| entry.value.as_str())
+            .collect();
+        assert_eq!(names, vec!["new"]);
+    }
+
+    #[test]
+    fn test_grant_store_rejects_broken_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("grants.toml");
+        fs::write(&path, "[allowlist\n").unwrap();
+
+        assert!(matches!(
+            GrantStore::new(path).load(),
+            Err(SafeKillError::ConfigError(_))
+        ));
+    }
+}
@@ -1,13 +1,19 @@
 //! safe-kill の init コマンドモジュール
 //!
-//! サンプル設定を含む設定ファイルを生成する。
+//! サンプル設定を含む設定ファイルを生成する。`--template` で開発スタックごとの
+//! プリセットを選べ、`--interactive` ではセッション内で待ち受け中のポートを検出して
+//! 許可するポートと allowlist を対話的に決める。
 
+use std::collections::BTreeSet;
+use std::fmt;
 use std::fs;
-use std::io::{self, Write};
+use std::io::{self, BufRead, Write};
 use std::path::{Path, PathBuf};
 
-use crate::config::Config;
+use crate::config::{LoadOptions, PortSpec, SCHEMA_FILE_NAME};
+use crate::config_command::ConfigCommand;
 use crate::error::SafeKillError;
+use crate::port::PortProcess;
 
 /// `safe-kill init` の実行結果
 ///
@@ -21,6 +27,257 @@ pub enum InitOutcome {
     SkippedExisting(PathBuf),
 }
 
+/// `init --template` で選べる開発スタックのプリセット
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitTemplate {
+    /// Node.js（Next.js、Vite、Angular など）
+    Node,
+    /// Python（Django、Flask、uvicorn、Jupyter など）
+    Python,
+    /// Rust（axum、actix-web、trunk など）
+    Rust,
+    /// Tauri（Tauri の開発サーバーとフロントエンド）
+    Tauri,
+}
+
+impl InitTemplate {
+    /// すべてのプリセット
+    pub const ALL: [InitTemplate; 4] = [
+        InitTemplate::Node,
+        InitTemplate::Python,
+        InitTemplate::Rust,
+        InitTemplate::Tauri,
+    ];
+
+    /// `--template` に指定する名前
+    pub fn name(&self) -> &'static str {
+        match self {
+            InitTemplate::Node => "node",
+            InitTemplate::Python => "python",
+            InitTemplate::Rust => "rust",
+            InitTemplate::Tauri => "tauri",
+        }
+    }
+
+    /// `--template` の値を解析する（clap の value_parser 用）
+    pub fn parse(name: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|template| template.name().eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::ALL.iter().map(InitTemplate::name).collect();
+                format!(
+                    "unknown template {:?} (expected one of: {})",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+
+    /// プリセットの許可ポートと説明
+    fn ports(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            InitTemplate::Node => &[
+                ("3000-3010", "Next.js / Express などの開発サーバー"),
+                ("4200", "Angular 開発サーバー"),
+                ("5173", "Vite 開発サーバー"),
+                ("9229", "Node.js インスペクタ"),
+            ],
+            InitTemplate::Python => &[
+                ("5000", "Flask 開発サーバー"),
+                ("8000", "Django / uvicorn 開発サーバー"),
+                ("8501", "Streamlit"),
+                ("8888", "Jupyter"),
+            ],
+            InitTemplate::Rust => &[
+                ("3000", "axum などの開発サーバー"),
+                ("8000", "Rocket 開発サーバー"),
+                ("8080", "actix-web / trunk 開発サーバー"),
+            ],
+            InitTemplate::Tauri => &[
+                ("1420", "Tauri 開発サーバー"),
+                ("1421", "Tauri の HMR"),
+                ("5173", "Vite 開発サーバー"),
+            ],
+        }
+    }
+
+    /// allowlist の候補として示すプロセス名
+    fn allowlist_suggestions(&self) -> &'static [&'static str] {
+        match self {
+            InitTemplate::Node => &["next-server", "vite"],
+            InitTemplate::Python => &["uvicorn", "gunicorn"],
+            InitTemplate::Rust => &["cargo-watch", "trunk"],
+            InitTemplate::Tauri => &["cargo-tauri"],
+        }
+    }
+}
+
+impl fmt::Display for InitTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// init で書き出す設定の内容（許可ポートと allowlist）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InitPlan {
+    /// 元にしたプリセット（標準の内容なら None）
+    pub template: Option<InitTemplate>,
+    /// `[allowed_ports]` に書くポート指定と説明コメント
+    pub ports: Vec<(String, String)>,
+    /// `[allowlist]` に書くプロセス名（空ならコメントアウトした例を書く）
+    pub allowlist: Vec<String>,
+    /// コメントアウトした allowlist の例に使うプロセス名
+    pub suggestions: Vec<String>,
+}
+
+impl Default for InitPlan {
+    /// 標準の内容（Tauri / Node.js / Vite / HTTP 代替ポート）
+    fn default() -> Self {
+        Self {
+            template: None,
+            ports: vec![
+                ("1420".to_string(), "Tauri 開発サーバー".to_string()),
+                ("3000-3010".to_string(), "Node.js 開発サーバー".to_string()),
+                ("5173".to_string(), "Vite 開発サーバー".to_string()),
+                ("8080".to_string(), "HTTP 代替ポート".to_string()),
+            ],
+            allowlist: Vec::new(),
+            suggestions: vec!["next-server".to_string()],
+        }
+    }
+}
+
+impl InitPlan {
+    /// プリセットの内容
+    pub fn for_template(template: InitTemplate) -> Self {
+        Self {
+            template: Some(template),
+            ports: template
+                .ports()
+                .iter()
+                .map(|(spec, description)| (spec.to_string(), description.to_string()))
+                .collect(),
+            allowlist: Vec::new(),
+            suggestions: template
+                .allowlist_suggestions()
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+        }
+    }
+
+    /// ポートがすでにいずれかのポート指定に含まれているか
+    fn covers_port(&self, port: u16) -> bool {
+        let Ok(target) = PortSpec::parse(&port.to_string()) else {
+            return false;
+        };
+        self.ports
+            .iter()
+            .any(|(spec, _)| PortSpec::parse(spec).is_ok_and(|spec| spec.covers(&target)))
+    }
+
+    /// コメント付きの設定ファイルの内容を生成する
+    pub fn render(&self) -> String {
+        let mut content = format!(
+            "#:schema ./{}
+# safe-kill 設定ファイル
+# safe-kill で終了を許可するプロセスやポートをこのファイルで制御します。
+",
+            SCHEMA_FILE_NAME
+        );
+        if let Some(template) = self.template {
+            content.push_str(&format!(
+                "# `safe-kill init --template {}` で生成しました。\n",
+                template
+            ));
+        }
+
+        content.push_str(
+            "
+# 許可リスト: ここに書いたプロセス名は親子関係チェックをバイパスできます。
+# 指定しない場合は、拒否リスト以外のプロセスが通常の安全チェック対象になります。
+# { name = \"...\", expires = \"2026-12-01T00:00:00Z\" } の形で有効期限を付けられます。
+# 一時的な許可は `safe-kill allow --name next-server --for 2h` でも追加できます。
+",
+        );
+        if self.allowlist.is_empty() {
+            content.push_str("# [allowlist]\n");
+            content.push_str(&format!(
+                "# processes = {}\n",
+                toml_array(&self.suggestions)
+            ));
+        } else {
+            content.push_str("[allowlist]\n");
+            content.push_str(&format!("processes = {}\n", toml_array(&self.allowlist)));
+        }
+
+        content.push_str(
+            r#"
+# 拒否リスト: ここに書いたプロセス名は常に終了できません。
+# システムプロセスはデフォルトでも保護され、ここに書いた内容はその保護対象に追加されます。
+# [denylist]
+# processes = ["systemd", "launchd", "init"]
+
+# 許可ポート: --port オプションで対象にできるポートです。
+# 指定しない場合、--port オプションは無効です。
+# 単一ポート ("3000") と範囲 ("8080-8090") の両方を指定できます。
+# プロトコルとバインドアドレスで限定することもできます（例: "tcp:127.0.0.1:3000"、"udp:[::1]:5353"）。
+"#,
+        );
+        for (spec, description) in &self.ports {
+            content.push_str(&format!("#   - {}: {}\n", spec, description));
+        }
+        let specs: Vec<String> = self.ports.iter().map(|(spec, _)| spec.clone()).collect();
+        if specs.is_empty() {
+            content.push_str("# [allowed_ports]\n# ports = [\"3000\"]\n");
+        } else {
+            content.push_str("[allowed_ports]\n");
+            content.push_str(&format!("ports = {}\n", toml_array(&specs)));
+        }
+
+        content.push_str(
+            r#"
+# 許可 Unix ソケット: --socket オプションで対象にできるソケットパスの glob です。
+# 指定しない場合、--socket オプションは無効です。抽象名前空間は "@name" で指定します。
+# [allowed_sockets]
+# paths = ["/run/user/*/dev/*.sock"]
+
+# 許可パス: --holding で子孫以外のプロセスも解放できるパスの glob です。
+# [allowed_paths]
+# paths = ["/home/me/work/*/target"]
+
+# 確認: --name / --cwd でシグナルを送る前に確認を求める条件です。
+# --yes で確認を省略できます。
+# [confirm]
+# above = 10
+# sigkill = true
+
+# 回数制限: セッションごとに送れるシグナルの上限です。超えると終了コード 7 で拒否します。
+# [limits]
+# kills_per_minute = 20
+# sigkills_per_session = 3
+
+# プロファイル: --profile <name> または SAFE_KILL_PROFILE で選んだときだけ上の設定に重ねます。
+# allowlist / allowed_ports / signals は置き換え、denylist は追加になります。
+# [profiles.frontend.allowed_ports]
+# ports = ["3000-3010"]
+"#,
+        );
+        content
+    }
+}
+
+/// 文字列の TOML 配列（`["a", "b"]`）
+fn toml_array(values: &[String]) -> String {
+    let items: Vec<String> = values
+        .iter()
+        .map(|value| toml::Value::String(value.clone()).to_string())
+        .collect();
+    format!("[{}]", items.join(", "))
+}
+
 /// 設定ファイル生成のための init コマンド
 pub struct InitCommand;
 
@@ -28,20 +285,26 @@ impl InitCommand {
     /// init コマンドを実行して設定ファイルを生成する
     ///
     /// # 引数
+    /// * `options` - 生成先（`--config` > `SAFE_KILL_CONFIG` > `~/.config/safe-kill/config.toml`）
     /// * `force` - true の場合、確認なしで既存ファイルを上書き
+    /// * `plan` - 書き出す内容（標準、`--template` のプリセット、`--interactive` の結果）
     ///
     /// # 戻り値
     /// * `Ok(InitOutcome::Created)` - 設定ファイルを生成した
     /// * `Ok(InitOutcome::SkippedExisting)` - ユーザーが上書きを拒否し、既存ファイルを残した
     /// * `Err(SafeKillError)` - 生成に失敗した場合
-    pub fn execute(force: bool) -> Result<InitOutcome, SafeKillError> {
-        let config_dir = Config::config_dir().ok_or_else(|| {
-            SafeKillError::ConfigCreationError("Unable to determine config directory".to_string())
-        })?;
-
-        let config_path = Config::config_path().ok_or_else(|| {
+    pub fn execute(
+        options: &LoadOptions,
+        force: bool,
+        plan: &InitPlan,
+    ) -> Result<InitOutcome, SafeKillError> {
+        let config_path = options.config_path().ok_or_else(|| {
             SafeKillError::ConfigCreationError("Unable to determine config path".to_string())
         })?;
+        let config_dir = config_path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
 
         // 既存ファイルがあり force でない場合は上書き確認する。
         // ユーザーが拒否した場合は作成失敗ではなく「正常なスキップ」（no-op）として扱い、
@@ -60,7 +323,7 @@ impl InitCommand {
         })?;
 
         // 設定ファイルを書き込み
-        let content = Self::default_config_content();
+        let content = plan.render();
         fs::write(&config_path, content).map_err(|e| {
             SafeKillError::ConfigCreationError(format!(
                 "Failed to write config file {}: {}",
@@ -69,35 +332,101 @@ impl InitCommand {
             ))
         })?;
 
+        // エディタの補完・検証用に、`#:schema` で参照する JSON Schema を隣に置く
+        let schema_path = config_dir.join(SCHEMA_FILE_NAME);
+        fs::write(&schema_path, ConfigCommand::schema()).map_err(|e| {
+            SafeKillError::ConfigCreationError(format!(
+                "Failed to write schema file {}: {}",
+                schema_path.display(),
+                e
+            ))
+        })?;
+
         Ok(InitOutcome::Created(config_path))
     }
 
     /// コメント付きのデフォルト設定内容を生成
     pub fn default_config_content() -> String {
-        r#"# safe-kill 設定ファイル
-# safe-kill で終了を許可するプロセスやポートをこのファイルで制御します。
+        InitPlan::default().render()
+    }
 
-# 許可リスト: ここに書いたプロセス名は親子関係チェックをバイパスできます。
-# 指定しない場合は、拒否リスト以外のプロセスが通常の安全チェック対象になります。
-# [allowlist]
-# processes = ["next-server"]
+    /// `init --interactive`: 検出したソケットをもとに許可するポートと allowlist を尋ねる
+    ///
+    /// `listeners` はセッションの子孫が待ち受け中のソケット。プランにまだ含まれない
+    /// ポートごとに追加するか（既定は追加する）、続いて検出したプロセス名と
+    /// プリセットの候補ごとに allowlist に入れるか（既定は入れない）を尋ねる。
+    /// 入力が終わった場合は残りの質問に既定の答えを使う。
+    pub fn interview(
+        mut plan: InitPlan,
+        listeners: &[PortProcess],
+        input: &mut impl BufRead,
+        output: &mut impl Write,
+    ) -> Result<InitPlan, SafeKillError> {
+        let mut ports: Vec<(u16, BTreeSet<&str>)> = Vec::new();
+        for listener in listeners {
+            match ports.iter_mut().find(|(port, _)| *port == listener.port) {
+                Some((_, names)) => {
+                    names.insert(&listener.name);
+                }
+                None => ports.push((listener.port, BTreeSet::from([listener.name.as_str()]))),
+            }
+        }
+        ports.sort_by_key(|(port, _)| *port);
+
+        if ports.is_empty() {
+            say(output, "No listening sockets were found in this session.")?;
+        } else {
+            say(output, "Listening sockets started from this session:")?;
+            for listener in listeners {
+                say(
+                    output,
+                    &format!(
+                        "  {:>5}/{}  PID {:<7} {}",
+                        listener.port, listener.protocol, listener.pid, listener.name
+                    ),
+                )?;
+            }
+        }
 
-# 拒否リスト: ここに書いたプロセス名は常に終了できません。
-# システムプロセスはデフォルトでも保護され、ここに書いた内容はその保護対象に追加されます。
-# [denylist]
-# processes = ["systemd", "launchd", "init"]
+        for (port, names) in &ports {
+            if plan.covers_port(*port) {
+                continue;
+            }
+            let names: Vec<&str> = names.iter().copied().collect();
+            let question = format!("Allow --port {} ({})?", port, names.join(", "));
+            if ask(input, output, &question, true)? {
+                plan.ports
+                    .push((port.to_string(), format!("{}（検出）", names.join(", "))));
+            }
+        }
 
-# 許可ポート: --port オプションで対象にできるポートです。
-# 指定しない場合、--port オプションは無効です。
-# 単一ポート ("3000") と範囲 ("8080-8090") の両方を指定できます。
-#   - 1420: Tauri 開発サーバー
-#   - 3000-3010: Node.js 開発サーバー
-#   - 5173: Vite 開発サーバー
-#   - 8080: HTTP 代替ポート
-[allowed_ports]
-ports = ["1420", "3000-3010", "5173", "8080"]
-"#
-        .to_string()
+        let mut candidates: Vec<String> = Vec::new();
+        let detected = ports.iter().flat_map(|(_, names)| names.iter().copied());
+        for name in detected.chain(plan.suggestions.iter().map(String::as_str)) {
+            if !candidates.iter().any(|candidate| candidate == name) {
+                candidates.push(name.to_string());
+            }
+        }
+        if !candidates.is_empty() {
+            say(
+                output,
+                "Allowlisted names can be killed even when they were not started from this session.",
+            )?;
+        }
+        for name in candidates {
+            if plan.allowlist.contains(&name) {
+                continue;
+            }
+            if ask(
+                input,
+                output,
+                &format!("Add {:?} to [allowlist]?", name),
+                false,
+            )? {
+                plan.allowlist.push(name);
+            }
+        }
+        Ok(plan)
     }
 
     /// 既存ファイルの上書き確認をユーザーに求める
@@ -120,9 +449,45 @@ ports = ["1420", "3000-3010", "5173", "8080"]
     }
 }
 
+/// 対話中のメッセージを 1 行出力する
+fn say(output: &mut impl Write, line: &str) -> Result<(), SafeKillError> {
+    writeln!(output, "{}", line)
+        .map_err(|e| SafeKillError::SystemError(format!("Failed to write prompt: {}", e)))
+}
+
+/// y/n で尋ねる（空行は既定の答え、入力の終わりも既定の答え）
+fn ask(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    question: &str,
+    default: bool,
+) -> Result<bool, SafeKillError> {
+    let choices = if default { "[Y/n]" } else { "[y/N]" };
+    loop {
+        write!(output, "{} {}: ", question, choices)
+            .and_then(|_| output.flush())
+            .map_err(|e| SafeKillError::SystemError(format!("Failed to write prompt: {}", e)))?;
+        let mut line = String::new();
+        let read = input
+            .read_line(&mut line)
+            .map_err(|e| SafeKillError::SystemError(format!("Failed to read input: {}", e)))?;
+        if read == 0 {
+            say(output, "")?;
+            return Ok(default);
+        }
+        match line.trim().to_lowercase().as_str() {
+            "" => return Ok(default),
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => say(output, "Please answer y or n.")?,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::Config;
 
     #[test]
     fn test_default_config_content_not_empty() {
@@ -235,6 +600,133 @@ mod tests {
         assert!(parsed.is_ok());
     }
 
+    #[test]
+    fn test_default_config_matches_schema() {
+        let content = InitCommand::default_config_content();
+        let value: toml::Value = toml::from_str(&content).unwrap();
+        let instance = serde_json::to_value(value).unwrap();
+        assert!(
+            jsonschema::validator_for(&Config::json_schema())
+                .unwrap()
+                .is_valid(&instance)
+        );
+    }
+
+    #[test]
+    fn test_default_config_content_references_schema() {
+        let content = InitCommand::default_config_content();
+        assert_eq!(
+            content.lines().next(),
+            Some(format!("#:schema ./{}", SCHEMA_FILE_NAME).as_str())
+        );
+    }
+
+    #[test]
+    fn test_template_parse() {
+        assert_eq!(InitTemplate::parse("node"), Ok(InitTemplate::Node));
+        assert_eq!(InitTemplate::parse("Tauri"), Ok(InitTemplate::Tauri));
+        assert_eq!(
+            InitTemplate::parse("perl"),
+            Err(
+                "unknown template \"perl\" (expected one of: node, python, rust, tauri)"
+                    .to_string()
+            )
+        );
+        for template in InitTemplate::ALL {
+            assert_eq!(InitTemplate::parse(template.name()), Ok(template));
+        }
+    }
+
+    #[test]
+    fn test_templates_render_valid_config() {
+        let validator = jsonschema::validator_for(&Config::json_schema()).unwrap();
+        for template in InitTemplate::ALL {
+            let content = InitPlan::for_template(template).render();
+            let config: Config = toml::from_str(&content).unwrap();
+            assert!(config.section_errors().is_empty(), "{}", template);
+            let value: toml::Value = toml::from_str(&content).unwrap();
+            assert!(validator.is_valid(&serde_json::to_value(value).unwrap()));
+            assert!(content.contains(&format!("--template {}", template)));
+            assert!(config.allowlist.is_none());
+        }
+
+        let python: Config =
+            toml::from_str(&InitPlan::for_template(InitTemplate::Python).render()).unwrap();
+        assert!(python.is_port_allowed(8000));
+        assert!(!python.is_port_allowed(1420));
+    }
+
+    #[test]
+    fn test_render_writes_allowlist_and_comments_out_empty_ports() {
+        let plan = InitPlan {
+            template: None,
+            ports: Vec::new(),
+            allowlist: vec!["vite".to_string(), "my \"server\"".to_string()],
+            suggestions: Vec::new(),
+        };
+        let content = plan.render();
+        assert!(content.contains("# [allowed_ports]"));
+        let config: Config = toml::from_str(&content).unwrap();
+        assert!(config.allowed_ports.is_none());
+        assert!(config.is_allowed("vite"));
+        assert!(config.is_allowed("my \"server\""));
+    }
+
+    fn listener(port: u16, pid: u32, name: &str) -> PortProcess {
+        PortProcess {
+            pid,
+            name: name.to_string(),
+            port,
+            protocol: crate::port::PortProtocol::Tcp,
+            address: "127.0.0.1".parse().unwrap(),
+        }
+    }
+
+    fn interview(plan: InitPlan, listeners: &[PortProcess], answers: &str) -> (InitPlan, String) {
+        let mut output = Vec::new();
+        let plan =
+            InitCommand::interview(plan, listeners, &mut answers.as_bytes(), &mut output).unwrap();
+        (plan, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn test_interview_adds_detected_ports_and_allowlist() {
+        let listeners = [
+            listener(5173, 200, "node"),
+            listener(4000, 100, "node"),
+            listener(4000, 101, "bun"),
+            listener(9000, 300, "python3"),
+        ];
+        // 5173 は既定の内容に含まれるので尋ねない。
+        // 4000 は追加（空行 = 既定の yes）、9000 は断る。
+        // allowlist: bun は yes、node は no、python3 は不正な入力の後に no、next-server は入力終わりで既定の no
+        let (plan, output) = interview(InitPlan::default(), &listeners, "\nn\ny\nn\nmaybe\nn\n");
+
+        assert_eq!(
+            plan.ports.last(),
+            Some(&("4000".to_string(), "bun, node（検出）".to_string()))
+        );
+        assert_eq!(plan.ports.len(), InitPlan::default().ports.len() + 1);
+        assert_eq!(plan.allowlist, vec!["bun".to_string()]);
+        assert!(output.contains("Allow --port 4000 (bun, node)? [Y/n]: "));
+        assert!(!output.contains("Allow --port 5173"));
+        assert!(output.contains("Add \"next-server\" to [allowlist]? [y/N]: "));
+        assert!(output.contains("Please answer y or n."));
+
+        let config: Config = toml::from_str(&plan.render()).unwrap();
+        assert!(config.is_port_allowed(4000));
+        assert!(!config.is_port_allowed(9000));
+        assert!(config.is_allowed("bun"));
+    }
+
+    #[test]
+    fn test_interview_without_listeners_asks_only_about_suggestions() {
+        let (plan, output) = interview(InitPlan::for_template(InitTemplate::Rust), &[], "y\n");
+        assert!(output.contains("No listening sockets were found"));
+        assert_eq!(plan.allowlist, vec!["cargo-watch".to_string()]);
+        assert_eq!(plan.ports, InitPlan::for_template(InitTemplate::Rust).ports);
+    }
+
     #[test]
     fn test_default_config_content_line_count() {
         let content = InitCommand::default_config_content();
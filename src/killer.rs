@@ -18,6 +18,8 @@ pub struct KillResult {
     pub message: String,
     /// エラー本体。成功時と dry-run 時は `None`
     pub error: Option<SafeKillError>,
+    /// ポート指定 kill で対象になったポート（それ以外は `None`）
+    pub port: Option<u16>,
 }
 
 impl KillResult {
@@ -29,6 +31,7 @@ impl KillResult {
             success: true,
             message: format!("Sent {} to process", signal.name()),
             error: None,
+            port: None,
         }
     }
 
@@ -40,9 +43,16 @@ impl KillResult {
             success: false,
             message: error.to_string(),
             error: Some(error.clone()),
+            port: None,
         }
     }
 
+    /// ポート指定 kill の対象ポートを付与する
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
     /// dry-run 結果を生成する
     pub fn dry_run(pid: u32, name: impl Into<String>, signal: Signal) -> Self {
         Self {
@@ -51,6 +61,7 @@ impl KillResult {
             success: true,
             message: format!("Would send {} to process (dry run)", signal.name()),
             error: None,
+            port: None,
         }
     }
 }
@@ -104,9 +115,11 @@ impl BatchKillResult {
             .find(|error| {
                 !matches!(
                     error,
-                    SafeKillError::Denylisted(_)
+                    SafeKillError::Denylisted(_, _)
                         | SafeKillError::NotDescendant(_, _)
                         | SafeKillError::SuicidePrevention(_)
+                        | SafeKillError::AncestorProtected(_, _)
+                        | SafeKillError::NotOwned(_, _, _)
                 )
             })
     }
@@ -158,6 +171,7 @@ impl Default for ProcessKiller {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::ConfigLayer;
 
     // KillResult のテスト
     #[test]
@@ -252,7 +266,7 @@ mod tests {
         batch.add(KillResult::failure(
             100,
             "denylisted",
-            &SafeKillError::Denylisted("denylisted".to_string()),
+            &SafeKillError::Denylisted("denylisted".to_string(), ConfigLayer::User),
         ));
         batch.add(KillResult::failure(
             200,
@@ -272,7 +286,7 @@ mod tests {
         batch.add(KillResult::failure(
             100,
             "denylisted",
-            &SafeKillError::Denylisted("denylisted".to_string()),
+            &SafeKillError::Denylisted("denylisted".to_string(), ConfigLayer::User),
         ));
         batch.add(KillResult::failure(
             200,
@@ -412,7 +426,7 @@ mod tests {
         let errors = [
             SafeKillError::ProcessNotFound(100),
             SafeKillError::PermissionDenied(100),
-            SafeKillError::Denylisted("test".to_string()),
+            SafeKillError::Denylisted("test".to_string(), ConfigLayer::User),
             SafeKillError::SuicidePrevention(100),
             SafeKillError::NotDescendant(100, "test".to_string()),
         ];
@@ -479,7 +493,7 @@ mod tests {
         batch.add(KillResult::failure(
             1,
             "a",
-            &SafeKillError::Denylisted("a".to_string()),
+            &SafeKillError::Denylisted("a".to_string(), ConfigLayer::User),
         ));
         batch.add(KillResult::failure(
             2,
@@ -505,7 +519,7 @@ mod tests {
         batch.add(KillResult::failure(
             2,
             "b",
-            &SafeKillError::Denylisted("b".to_string()),
+            &SafeKillError::Denylisted("b".to_string(), ConfigLayer::User),
         ));
         // 成功結果には error がなく、Denylisted はポリシーエラー → None
         assert_eq!(batch.first_operational_error(), None);
@@ -6,10 +6,19 @@
 pub mod ancestry;
 pub mod cli;
 pub mod config;
+pub mod config_command;
 pub mod error;
+pub mod grants;
 pub mod init;
 pub mod killer;
+pub mod limits;
+pub mod open_files;
+pub mod overrides;
 pub mod policy;
 pub mod port;
+pub mod privilege;
 pub mod process_info;
+#[cfg(target_os = "linux")]
+pub mod procfs;
 pub mod signal;
+pub mod unix_socket;
@@ -0,0 +1,403 @@
+//! `[limits]` による kill 回数の制限
+//!
+//! 信頼ルートごとのシグナル送信回数を小さな状態ファイル
+//! （`~/.local/state/safe-kill/limits.json`）に記録し、上限を超えた送信を
+//! `RateLimited` で拒否する。状態は「ブート ID + ルート PID」をキーに保持するため、
+//! 再起動後や別セッションの記録とは混ざらない。
+
+use crate::config::{ConfigLayer, LimitsConfig};
+use crate::error::SafeKillError;
+use crate::signal::Signal;
+use nix::fcntl::{Flock, FlockArg};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// `kills_per_minute` を数える時間幅（秒）
+const WINDOW_SECS: u64 = 60;
+
+/// 状態ファイルに保存する内容
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+struct LimitState {
+    /// `<ブート ID>:<ルート PID>` ごとの送信記録
+    #[serde(default)]
+    sessions: BTreeMap<String, SessionUsage>,
+}
+
+/// 1 セッション（信頼ルート）分の送信記録
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+struct SessionUsage {
+    /// 直近のシグナル送信時刻（UNIX エポック秒）
+    #[serde(default)]
+    kills: Vec<u64>,
+    /// これまでに送った SIGKILL の数
+    #[serde(default)]
+    sigkills: u32,
+}
+
+/// 信頼ルート単位で kill 回数を制限する
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    limits: LimitsConfig,
+    path: Option<PathBuf>,
+    boot_id: String,
+    root_pid: u32,
+    source: ConfigLayer,
+}
+
+impl RateLimiter {
+    /// 現在のブートと指定ルート PID の記録を使う制限器を生成する
+    ///
+    /// `path` が None（ホームディレクトリ不明など）の場合、送信はすべて拒否する。
+    pub fn new(limits: LimitsConfig, path: Option<PathBuf>, root_pid: u32) -> Self {
+        Self {
+            limits,
+            path,
+            boot_id: boot_id(),
+            root_pid,
+            source: ConfigLayer::User,
+        }
+    }
+
+    /// 上限を超えたときのメッセージで示す、`[limits]` の出どころを設定する
+    pub fn attributed_to(mut self, source: ConfigLayer) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// デフォルトの状態ファイルパス（`~/.local/state/safe-kill/limits.json`）
+    pub fn state_path() -> Option<PathBuf> {
+        dirs::home_dir().map(|home| {
+            home.join(".local")
+                .join("state")
+                .join("safe-kill")
+                .join("limits.json")
+        })
+    }
+
+    /// シグナルを 1 回送ってよいか確認し、よければ送信として記録する
+    ///
+    /// 確認と記録は状態ファイルの排他ロック下で行うため、同時に実行された
+    /// safe-kill 同士でも上限を超えない。送信に失敗した場合も 1 回として数える。
+    pub fn acquire(&self, signal: Signal) -> Result<(), SafeKillError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.acquire_at(signal, now)
+    }
+
+    fn acquire_at(&self, signal: Signal, now: u64) -> Result<(), SafeKillError> {
+        let path = self.path.as_ref().ok_or_else(|| {
+            SafeKillError::SystemError(
+                "Cannot locate the state file for [limits]; refusing to send".to_string(),
+            )
+        })?;
+        let state_error = |e: std::io::Error| {
+            SafeKillError::SystemError(format!("Failed to update {}: {}", path.display(), e))
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(state_error)?;
+        }
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(state_error)?;
+        let mut file = Flock::lock(file, FlockArg::LockExclusive)
+            .map_err(|(_, errno)| state_error(errno.into()))?;
+
+        let mut state = read_state(&mut file).map_err(state_error)?;
+        self.check_and_record(&mut state, signal, now)?;
+        write_state(&mut file, &state).map_err(state_error)
+    }
+
+    /// 上限を確認し、超えていなければ送信を記録する
+    ///
+    /// 別のブートの記録と時間幅を過ぎた送信時刻はここで破棄する。
+    fn check_and_record(
+        &self,
+        state: &mut LimitState,
+        signal: Signal,
+        now: u64,
+    ) -> Result<(), SafeKillError> {
+        let boot_prefix = format!("{}:", self.boot_id);
+        state
+            .sessions
+            .retain(|key, _| key.starts_with(&boot_prefix));
+        let usage = state
+            .sessions
+            .entry(format!("{}{}", boot_prefix, self.root_pid))
+            .or_default();
+        usage
+            .kills
+            .retain(|&at| at <= now && now - at < WINDOW_SECS);
+
+        let per_minute = self.limits.kills_per_minute;
+        if let Some(max) = per_minute.filter(|&max| usage.kills.len() >= max as usize) {
+            return Err(SafeKillError::RateLimited(format!(
+                "{} signal(s) sent in the last minute ([limits] kills_per_minute = {} in {})",
+                usage.kills.len(),
+                max,
+                self.source
+            )));
+        }
+        let is_sigkill = signal == Signal::SIGKILL;
+        let per_session = self.limits.sigkills_per_session;
+        if let Some(max) = per_session.filter(|&max| is_sigkill && usage.sigkills >= max) {
+            return Err(SafeKillError::RateLimited(format!(
+                "{} SIGKILL(s) sent in this session ([limits] sigkills_per_session = {} in {})",
+                usage.sigkills, max, self.source
+            )));
+        }
+
+        usage.kills.push(now);
+        if is_sigkill {
+            usage.sigkills += 1;
+        }
+        Ok(())
+    }
+}
+
+/// 状態ファイルを読む（空の場合は記録なしとして扱う）
+///
+/// 壊れた状態ファイルを記録なしとして扱うと上限が外れてしまうため、エラーにする。
+fn read_state(file: &mut File) -> std::io::Result<LimitState> {
+    let mut content = String::new();
+    file.read_to_string(&mut content)?;
+    if content.trim().is_empty() {
+        return Ok(LimitState::default());
+    }
+    serde_json::from_str(&content).map_err(|e| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "the state file is corrupt ({}); remove it to reset the counters",
+                e
+            ),
+        )
+    })
+}
+
+/// 状態ファイルを書き直す
+fn write_state(file: &mut File, state: &LimitState) -> std::io::Result<()> {
+    let content = serde_json::to_string(state).map_err(std::io::Error::other)?;
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(content.as_bytes())?;
+    file.flush()
+}
+
+/// 現在のブートを識別する文字列
+#[cfg(target_os = "linux")]
+fn boot_id() -> String {
+    fs::read_to_string("/proc/sys/kernel/random/boot_id")
+        .map(|id| id.trim().to_string())
+        .unwrap_or_default()
+}
+
+/// 現在のブートを識別する文字列（起動時刻で代用する）
+#[cfg(not(target_os = "linux"))]
+fn boot_id() -> String {
+    sysinfo::System::boot_time().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(limits: LimitsConfig, root_pid: u32) -> RateLimiter {
+        RateLimiter {
+            limits,
+            path: None,
+            boot_id: "boot-a".to_string(),
+            root_pid,
+            source: ConfigLayer::User,
+        }
+    }
+
+    #[test]
+    fn test_kills_per_minute_window() {
+        let limiter = limiter(
+            LimitsConfig {
+                kills_per_minute: Some(2),
+                sigkills_per_session: None,
+            },
+            100,
+        );
+        let mut state = LimitState::default();
+
+        assert!(
+            limiter
+                .check_and_record(&mut state, Signal::SIGTERM, 1000)
+                .is_ok()
+        );
+        assert!(
+            limiter
+                .check_and_record(&mut state, Signal::SIGTERM, 1010)
+                .is_ok()
+        );
+        let err = limiter
+            .check_and_record(&mut state, Signal::SIGTERM, 1059)
+            .unwrap_err();
+        assert!(matches!(err, SafeKillError::RateLimited(_)));
+
+        // 最初の送信から 60 秒経てば 1 枠空く
+        assert!(
+            limiter
+                .check_and_record(&mut state, Signal::SIGTERM, 1060)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_sigkills_per_session_counts_only_sigkill() {
+        let limiter = limiter(
+            LimitsConfig {
+                kills_per_minute: None,
+                sigkills_per_session: Some(1),
+            },
+            100,
+        );
+        let mut state = LimitState::default();
+
+        assert!(
+            limiter
+                .check_and_record(&mut state, Signal::SIGKILL, 1000)
+                .is_ok()
+        );
+        assert!(
+            limiter
+                .check_and_record(&mut state, Signal::SIGTERM, 5000)
+                .is_ok()
+        );
+        assert!(matches!(
+            limiter.check_and_record(&mut state, Signal::SIGKILL, 9000),
+            Err(SafeKillError::RateLimited(_))
+        ));
+    }
+
+    #[test]
+    fn test_limit_message_names_its_layer() {
+        let limiter = limiter(
+            LimitsConfig {
+                kills_per_minute: None,
+                sigkills_per_session: Some(0),
+            },
+            100,
+        )
+        .attributed_to(ConfigLayer::System(PathBuf::from(
+            "/etc/safe-kill/config.toml",
+        )));
+        let mut state = LimitState::default();
+
+        assert_eq!(
+            limiter.check_and_record(&mut state, Signal::SIGKILL, 1000),
+            Err(SafeKillError::RateLimited(
+                "0 SIGKILL(s) sent in this session ([limits] sigkills_per_session = 0 in system config /etc/safe-kill/config.toml)"
+                    .to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_sessions_are_keyed_by_root_and_boot() {
+        let limits = LimitsConfig {
+            kills_per_minute: Some(1),
+            sigkills_per_session: None,
+        };
+        let mut state = LimitState::default();
+
+        assert!(
+            limiter(limits.clone(), 100)
+                .check_and_record(&mut state, Signal::SIGTERM, 1000)
+                .is_ok()
+        );
+        // 別のルートは独立して数える
+        assert!(
+            limiter(limits.clone(), 200)
+                .check_and_record(&mut state, Signal::SIGTERM, 1000)
+                .is_ok()
+        );
+
+        // 別のブートでは以前の記録を破棄する
+        let mut rebooted = limiter(limits, 100);
+        rebooted.boot_id = "boot-b".to_string();
+        assert!(
+            rebooted
+                .check_and_record(&mut state, Signal::SIGTERM, 1000)
+                .is_ok()
+        );
+        assert_eq!(state.sessions.len(), 1);
+        assert!(state.sessions.contains_key("boot-b:100"));
+    }
+
+    #[test]
+    fn test_acquire_persists_state_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join("limits.json");
+        let limits = LimitsConfig {
+            kills_per_minute: Some(1),
+            sigkills_per_session: None,
+        };
+
+        let first = RateLimiter::new(limits.clone(), Some(path.clone()), 100);
+        assert!(first.acquire(Signal::SIGTERM).is_ok());
+
+        // 別の実行（新しい制限器）でも記録を引き継ぐ
+        let second = RateLimiter::new(limits, Some(path.clone()), 100);
+        assert!(matches!(
+            second.acquire(Signal::SIGTERM),
+            Err(SafeKillError::RateLimited(_))
+        ));
+        assert!(fs::read_to_string(&path).unwrap().contains(":100"));
+    }
+
+    #[test]
+    fn test_acquire_refuses_with_corrupt_state_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("limits.json");
+        fs::write(&path, "not json").unwrap();
+        let limiter = RateLimiter::new(
+            LimitsConfig {
+                kills_per_minute: Some(1),
+                sigkills_per_session: None,
+            },
+            Some(path.clone()),
+            100,
+        );
+
+        // 壊れた記録で上限が外れないよう、送信を拒否して状態ファイルも残す
+        match limiter.acquire(Signal::SIGTERM) {
+            Err(SafeKillError::SystemError(message)) => {
+                assert!(message.contains("the state file is corrupt"), "{}", message)
+            }
+            other => panic!("Expected SystemError, got {:?}", other),
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "not json");
+
+        // 空のファイルは記録なしとして扱う
+        fs::write(&path, "").unwrap();
+        assert!(limiter.acquire(Signal::SIGTERM).is_ok());
+    }
+
+    #[test]
+    fn test_acquire_without_state_path_is_refused() {
+        let limiter = limiter(LimitsConfig::default(), 100);
+        assert!(matches!(
+            limiter.acquire(Signal::SIGTERM),
+            Err(SafeKillError::SystemError(_))
+        ));
+    }
+
+    #[test]
+    fn test_boot_id_is_stable() {
+        assert_eq!(boot_id(), boot_id());
+    }
+}
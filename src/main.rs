@@ -3,14 +3,23 @@
 //! ancestry ベースのアクセス制御で、現在セッションの子孫プロセスのみを
 //! 安全に終了できるようにする。
 
+use std::collections::HashMap;
+use std::io::{self, IsTerminal, Write};
 use std::process::ExitCode;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use safe_kill::cli::{CliArgs, ExecutionMode};
+use safe_kill::ancestry::RootResolution;
+use safe_kill::cli::{CliArgs, ConfigAction, ExecutionMode};
+use safe_kill::config::{CWD_CONFIRM_THRESHOLD, Config, LoadOptions, SignalTarget};
+use safe_kill::config_command::{AddOutcome, ConfigCommand, ConfigProblem};
 use safe_kill::error::SafeKillError;
-use safe_kill::init::{InitCommand, InitOutcome};
+use safe_kill::grants::{self, Grant, GrantStore};
+use safe_kill::init::{InitCommand, InitOutcome, InitPlan};
 use safe_kill::killer::{BatchKillResult, KillResult};
-use safe_kill::policy::PolicyEngine;
+use safe_kill::policy::{Approval, KillCandidate, PolicyEngine, PortHolder};
+use safe_kill::port::PortSelection;
 use safe_kill::process_info;
+use safe_kill::signal::Signal;
 
 fn main() -> ExitCode {
     match run() {
@@ -29,11 +38,12 @@ fn run() -> Result<(), SafeKillError> {
 
     // 実行モードを検証して確定する
     let mode = args.validate()?;
+    let options = args.load_options();
 
     // 実行モードごとに処理する
     match mode {
         ExecutionMode::KillByPid(pid) => {
-            let engine = PolicyEngine::try_with_defaults()?;
+            let engine = load_engine_for_kill(&options)?;
             let signal = args.parse_signal()?;
             let result = engine.kill_by_pid(pid, signal, args.dry_run)?;
             print_kill_result(&result.name, result.pid, result.success, &result.message);
@@ -44,9 +54,12 @@ fn run() -> Result<(), SafeKillError> {
             }
         }
         ExecutionMode::KillByName(name) => {
-            let engine = PolicyEngine::try_with_defaults()?;
+            let engine = load_engine_for_kill(&options)?;
             let signal = args.parse_signal()?;
-            let batch_result = engine.kill_by_name(&name, signal, args.dry_run)?;
+            let batch_result =
+                engine.kill_by_name_confirmed(&name, signal, args.dry_run, |candidates| {
+                    confirm_candidates(&args, &engine, candidates, signal, None)
+                })?;
             print_batch_result(&batch_result, args.dry_run);
             if batch_result.any_success() {
                 Ok(())
@@ -58,26 +71,122 @@ fn run() -> Result<(), SafeKillError> {
             }
         }
         ExecutionMode::ListKillable => {
-            let engine = PolicyEngine::try_with_defaults()?;
+            let engine = PolicyEngine::new(Config::try_load_with(&options)?);
             let processes = engine.list_killable();
             print_killable_list(&processes);
             Ok(())
         }
-        ExecutionMode::KillByPort(port) => {
-            let engine = PolicyEngine::try_with_defaults()?;
+        ExecutionMode::KillByPort(ports) => {
+            let engine = load_engine_for_kill(&options)?;
             let signal = args.parse_signal()?;
-            let batch_result = engine.kill_by_port(port, signal, args.dry_run)?;
-            print_port_kill_result(port, &batch_result, args.dry_run);
+            let filter = args.port_filter()?;
+            let batch_result =
+                engine.kill_by_ports(&ports, &filter, signal, args.dry_run, |candidates| {
+                    confirm_candidates(&args, &engine, candidates, signal, None)
+                })?;
+            print_port_kill_result(&ports, &batch_result, args.dry_run);
             if batch_result.any_success() {
                 Ok(())
             } else if batch_result.results.is_empty() {
-                Err(SafeKillError::NoProcessOnPort(port))
+                Err(match ports.as_single() {
+                    Some(port) => SafeKillError::NoProcessOnPort(port),
+                    None => SafeKillError::NoProcessOnPorts(ports.to_string()),
+                })
             } else {
-                Err(batch_result_error(format!("port {}", port), &batch_result))
+                Err(batch_result_error(port_label(&ports), &batch_result))
             }
         }
-        ExecutionMode::InitConfig { force } => {
-            match InitCommand::execute(force)? {
+        ExecutionMode::KillBySocket(path) => {
+            let engine = load_engine_for_kill(&options)?;
+            let signal = args.parse_signal()?;
+            let batch_result =
+                engine.kill_by_socket(&path, signal, args.dry_run, |candidates| {
+                    confirm_candidates(&args, &engine, candidates, signal, None)
+                })?;
+            print_resource_kill_result(
+                &format!("Socket {}", path.display()),
+                &batch_result,
+                args.dry_run,
+            );
+            if batch_result.any_success() {
+                Ok(())
+            } else {
+                Err(batch_result_error(
+                    format!("socket {}", path.display()),
+                    &batch_result,
+                ))
+            }
+        }
+        ExecutionMode::KillByHolding(path) => {
+            let engine = load_engine_for_kill(&options)?;
+            let signal = args.parse_signal()?;
+            let batch_result =
+                engine.kill_by_holding(&path, signal, args.dry_run, |candidates| {
+                    confirm_candidates(&args, &engine, candidates, signal, None)
+                })?;
+            print_resource_kill_result(
+                &format!("Path {}", path.display()),
+                &batch_result,
+                args.dry_run,
+            );
+            if batch_result.any_success() {
+                Ok(())
+            } else {
+                Err(batch_result_error(
+                    format!("path {}", path.display()),
+                    &batch_result,
+                ))
+            }
+        }
+        ExecutionMode::KillByCwd(dir) => {
+            let engine = load_engine_for_kill(&options)?;
+            let signal = args.parse_signal()?;
+            let batch_result = engine.kill_by_cwd(&dir, signal, args.dry_run, |candidates| {
+                confirm_candidates(
+                    &args,
+                    &engine,
+                    candidates,
+                    signal,
+                    Some(CWD_CONFIRM_THRESHOLD),
+                )
+            })?;
+            print_resource_kill_result(
+                &format!("Directory {}", dir.display()),
+                &batch_result,
+                args.dry_run,
+            );
+            if batch_result.any_success() {
+                Ok(())
+            } else {
+                Err(batch_result_error(
+                    format!("directory {}", dir.display()),
+                    &batch_result,
+                ))
+            }
+        }
+        ExecutionMode::InitConfig {
+            force,
+            interactive,
+            template,
+        } => {
+            let mut plan = template.map_or_else(InitPlan::default, InitPlan::for_template);
+            if interactive {
+                if !io::stdin().is_terminal() {
+                    return Err(SafeKillError::Aborted(
+                        "init --interactive requires a terminal; nothing was written".to_string(),
+                    ));
+                }
+                // 既存の設定が壊れていても作り直せるよう、読めなければデフォルトで検出する
+                let engine = PolicyEngine::new(Config::try_load_with(&options).unwrap_or_default());
+                let listeners = engine.session_listeners()?;
+                plan = InitCommand::interview(
+                    plan,
+                    &listeners,
+                    &mut io::stdin().lock(),
+                    &mut io::stderr(),
+                )?;
+            }
+            match InitCommand::execute(&options, force, &plan)? {
                 InitOutcome::Created(path) => {
                     println!("Created: {}", path.display());
                     println!();
@@ -96,9 +205,232 @@ fn run() -> Result<(), SafeKillError> {
             }
             Ok(())
         }
+        ExecutionMode::ShowRoot => {
+            let engine = PolicyEngine::new(Config::try_load_with(&options)?);
+            print_root(engine.root_resolution(), &engine.root_chain());
+            Ok(())
+        }
+        ExecutionMode::ListSignals => {
+            let engine = PolicyEngine::new(Config::try_load_with(&options)?);
+            print_signals(&engine);
+            Ok(())
+        }
+        ExecutionMode::ListPorts { json, all } => {
+            let engine = PolicyEngine::new(Config::try_load_with(&options)?);
+            let holders = engine.list_ports(all)?;
+            if json {
+                let output = serde_json::to_string_pretty(&holders).map_err(|e| {
+                    SafeKillError::SystemError(format!("Failed to encode JSON: {}", e))
+                })?;
+                println!("{}", output);
+            } else {
+                print_port_holders(&holders, all);
+            }
+            Ok(())
+        }
+        ExecutionMode::AllowTemporarily { grant, duration } => {
+            // denylist は allowlist より優先されるため、許可しても効果がない
+            let config = Config::try_load_with(&options)?;
+            if let Grant::Process(name) = &grant
+                && let Some(layer) = config.denylist_layer(name)
+            {
+                return Err(SafeKillError::Denylisted(name.clone(), layer));
+            }
+            // システム設定でロックされた allowlist / allowed_ports は広げられない
+            config.check_grant_allowed(&grant)?;
+
+            let path = GrantStore::default_path().ok_or_else(|| {
+                SafeKillError::ConfigCreationError("Unable to determine config path".to_string())
+            })?;
+            let store = GrantStore::new(path);
+            let now = grants::now();
+            let expires = now.saturating_add(duration.as_secs());
+            store.add(&grant, expires, now)?;
+            println!(
+                "Allowed {} until {}",
+                grant,
+                grants::format_timestamp(expires)
+            );
+            println!("Saved to {}", store.path().display());
+            Ok(())
+        }
+        ExecutionMode::ManageConfig(action) => run_config_action(action, &options),
     }
 }
 
+/// `safe-kill config` の各操作を実行する
+fn run_config_action(action: ConfigAction, options: &LoadOptions) -> Result<(), SafeKillError> {
+    let default_path = || {
+        options.config_path().ok_or_else(|| {
+            SafeKillError::ConfigError("Unable to determine config path".to_string())
+        })
+    };
+    match action {
+        ConfigAction::Validate { path, lint } => {
+            let path = path.map_or_else(default_path, Ok)?;
+            let problems = ConfigCommand::validate(&path)?;
+            if lint {
+                warn_problems(&path, &problems);
+                Ok(())
+            } else {
+                report_problems(&path, &problems)
+            }
+        }
+        ConfigAction::Show => {
+            print!("{}", ConfigCommand::show(options)?);
+            Ok(())
+        }
+        ConfigAction::Schema => {
+            print!("{}", ConfigCommand::schema());
+            Ok(())
+        }
+        ConfigAction::Edit => {
+            let path = default_path()?;
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir).map_err(|e| {
+                    SafeKillError::ConfigCreationError(format!(
+                        "Failed to create directory {}: {}",
+                        dir.display(),
+                        e
+                    ))
+                })?;
+            }
+            report_problems(&path, &ConfigCommand::edit(&path)?)
+        }
+        ConfigAction::AddPort { spec } => {
+            let outcome = ConfigCommand::add_port(&default_path()?, &spec)?;
+            print_add_outcome(&outcome, &format!("port {}", spec), "[allowed_ports]");
+            Ok(())
+        }
+        ConfigAction::AddDeny { name } => {
+            let outcome = ConfigCommand::add_deny(&default_path()?, &name)?;
+            print_add_outcome(&outcome, &format!("process '{}'", name), "[denylist]");
+            Ok(())
+        }
+    }
+}
+
+/// 検証結果を表示し、問題があれば設定エラーにする
+fn report_problems(
+    path: &std::path::Path,
+    problems: &[ConfigProblem],
+) -> Result<(), SafeKillError> {
+    if problems.is_empty() {
+        println!("{}: OK", path.display());
+        return Ok(());
+    }
+    for problem in problems {
+        eprintln!("{}:{}", path.display(), problem);
+    }
+    Err(SafeKillError::ConfigError(format!(
+        "{} problem(s) found in {}",
+        problems.len(),
+        path.display()
+    )))
+}
+
+/// 検証結果を警告として表示する（`config validate --lint`）
+fn warn_problems(path: &std::path::Path, problems: &[ConfigProblem]) {
+    if problems.is_empty() {
+        println!("{}: OK", path.display());
+        return;
+    }
+    for problem in problems {
+        eprintln!("Warning: {}:{}", path.display(), problem);
+    }
+    eprintln!(
+        "{} warning(s) in {}; loading this file will fail until they are fixed",
+        problems.len(),
+        path.display()
+    );
+}
+
+/// `config add-port` / `config add-deny` の結果を表示する
+fn print_add_outcome(outcome: &AddOutcome, entry: &str, section: &str) {
+    match outcome {
+        AddOutcome::Added(path) => {
+            println!("Added {} to {} in {}", entry, section, path.display());
+        }
+        AddOutcome::AlreadyPresent(path) => {
+            println!(
+                "Unchanged: {} is already in {} in {}",
+                entry,
+                section,
+                path.display()
+            );
+        }
+    }
+}
+
+/// kill 系の操作に使う PolicyEngine を読み込む
+///
+/// 特権付きで実行されている場合は `[privileged]` 設定がなければここで拒否する。
+fn load_engine_for_kill(options: &LoadOptions) -> Result<PolicyEngine, SafeKillError> {
+    let engine = PolicyEngine::new(Config::try_load_with(options)?);
+    engine.ensure_privileged_policy()?;
+    Ok(engine)
+}
+
+/// 信頼ルートの解決結果を表示する
+fn print_root(resolution: &RootResolution, chain: &[process_info::ProcessInfo]) {
+    let root_name = chain
+        .iter()
+        .find(|p| p.pid == resolution.pid)
+        .map(|p| p.name.as_str())
+        .unwrap_or("?");
+    println!("Root PID: {} ({})", resolution.pid, root_name);
+    println!("Strategy: {}", resolution.strategy.as_str());
+    println!("Reason:   {}", resolution.source);
+
+    if !chain.is_empty() {
+        let path: Vec<String> = chain
+            .iter()
+            .map(|p| format!("{} ({})", p.pid, p.name))
+            .collect();
+        println!("Chain:    {}", path.join(" -> "));
+    }
+}
+
+/// 対応シグナルと区分ごとの可否を表示する
+fn print_signals(engine: &PolicyEngine) {
+    let header: Vec<String> = SignalTarget::ALL
+        .iter()
+        .map(|target| target.as_str().to_uppercase())
+        .collect();
+    println!("{:>4}  {:<14}  {}", "NUM", "NAME", signal_columns(&header));
+    println!("{}", "-".repeat(56));
+
+    for signal in Signal::all() {
+        let statuses: Vec<String> = SignalTarget::ALL
+            .iter()
+            .map(|&target| {
+                if engine.is_signal_allowed(signal, target) {
+                    "allowed".to_string()
+                } else {
+                    "forbidden".to_string()
+                }
+            })
+            .collect();
+        println!(
+            "{:>4}  {:<14}  {}",
+            signal.number(),
+            signal.name(),
+            signal_columns(&statuses)
+        );
+    }
+}
+
+/// 区分ごとの列を固定幅で連結する（末尾の空白は付けない）
+fn signal_columns(values: &[String]) -> String {
+    values
+        .iter()
+        .map(|v| format!("{:<11}", v))
+        .collect::<Vec<_>>()
+        .join("  ")
+        .trim_end()
+        .to_string()
+}
+
 /// 1 件分の実行結果から返却用エラーを復元する
 fn single_result_error(result: &KillResult) -> SafeKillError {
     result
@@ -144,29 +476,248 @@ fn print_batch_result(result: &BatchKillResult, dry_run: bool) {
     }
 }
 
+/// ポート指定の表示名（単一なら "port 3000"、複数なら "ports 3000-3010,5173"）
+fn port_label(ports: &PortSelection) -> String {
+    match ports.as_single() {
+        Some(port) => format!("port {}", port),
+        None => format!("ports {}", ports),
+    }
+}
+
 /// ポート指定実行時の要約行を組み立てる
-fn port_result_summary(port: u16, result: &BatchKillResult, dry_run: bool) -> String {
+fn port_result_summary(ports: &PortSelection, result: &BatchKillResult, dry_run: bool) -> String {
+    let label = match ports.as_single() {
+        Some(port) => format!("Port {}", port),
+        None => format!("Ports {}", ports),
+    };
     if dry_run {
         format!(
-            "Port {}: Found {} process(es), would kill {}:",
-            port, result.total_matched, result.total_killed
+            "{}: Found {} process(es), would kill {}:",
+            label, result.total_matched, result.total_killed
         )
     } else {
         format!(
-            "Port {}: Found {} process(es), killed {}:",
-            port, result.total_matched, result.total_killed
+            "{}: Found {} process(es), killed {}:",
+            label, result.total_matched, result.total_killed
         )
     }
 }
 
 /// ポート指定の結果を表示する
-fn print_port_kill_result(port: u16, result: &BatchKillResult, dry_run: bool) {
-    println!("{}", port_result_summary(port, result, dry_run));
+///
+/// 範囲・リスト指定ではポートごとに見出しを付けて表示する。
+fn print_port_kill_result(ports: &PortSelection, result: &BatchKillResult, dry_run: bool) {
+    println!("{}", port_result_summary(ports, result, dry_run));
+    let grouped = ports.as_single().is_none();
+    let mut current_port = None;
+    for r in &result.results {
+        if grouped && r.port != current_port {
+            current_port = r.port;
+            if let Some(port) = r.port {
+                println!("Port {}:", port);
+            }
+        }
+        print_kill_result(&r.name, r.pid, r.success, &r.message);
+    }
+}
+
+/// Unix ドメインソケット・パス指定の結果を `label` を見出しにして表示する
+fn print_resource_kill_result(label: &str, result: &BatchKillResult, dry_run: bool) {
+    let action = if dry_run { "would kill" } else { "killed" };
+    println!(
+        "{}: Found {} process(es), {} {}:",
+        label, result.total_matched, action, result.total_killed
+    );
     for r in &result.results {
         print_kill_result(&r.name, r.pid, r.success, &r.message);
     }
 }
 
+/// 一括 kill（`--name`、`--cwd`、`--port`、`--socket`、`--holding`）の前に、フラグと `[confirm]` 設定に従って確認する
+///
+/// `--interactive` なら候補ごとに確認する。それ以外では `--yes` と `--dry-run` を除き、
+/// `[confirm]` の条件（`--cwd` では既定で 5 件超）に当てはまれば一括で確認する。
+fn confirm_candidates(
+    args: &CliArgs,
+    engine: &PolicyEngine,
+    candidates: &[KillCandidate],
+    signal: Signal,
+    default_above: Option<usize>,
+) -> Result<Approval, SafeKillError> {
+    if candidates.is_empty() {
+        return Ok(Approval::All);
+    }
+    if args.interactive {
+        // ポートは表示用なので、取得できなくても確認は続ける
+        let ports = engine.listening_ports().unwrap_or_default();
+        return confirm_each(candidates, signal, &ports);
+    }
+
+    let required = !args.yes
+        && !args.dry_run
+        && engine
+            .config()
+            .confirm
+            .clone()
+            .unwrap_or_default()
+            .requires(candidates.len(), signal, default_above);
+    if required {
+        confirm_many(candidates, signal)?;
+    }
+    Ok(Approval::All)
+}
+
+/// kill 対象の一覧を表示し、まとめて送ってよいか確認する
+///
+/// 端末から読めない場合は何も送らずに `Aborted` を返す（`--yes` で省略できる）。
+fn confirm_many(candidates: &[KillCandidate], signal: Signal) -> Result<(), SafeKillError> {
+    eprintln!(
+        "The following {} processes would be signaled:",
+        candidates.len()
+    );
+    for candidate in candidates {
+        let p = &candidate.process;
+        eprintln!(
+            "{:>8}  {:<20}  {}",
+            p.pid,
+            truncate(&p.name, 20),
+            truncate(&p.cmd.join(" "), 30)
+        );
+    }
+
+    if !io::stdin().is_terminal() {
+        return Err(SafeKillError::Aborted(format!(
+            "{} processes matched; re-run with --yes to confirm",
+            candidates.len()
+        )));
+    }
+
+    let answer = prompt(&format!(
+        "Send {} to {} processes? [y/N]: ",
+        signal.name(),
+        candidates.len()
+    ))?;
+    match parse_answer(answer.as_deref()) {
+        Some(Answer::Yes) | Some(Answer::All) => Ok(()),
+        _ => Err(SafeKillError::Aborted("no signal was sent".to_string())),
+    }
+}
+
+/// `--interactive`: 候補ごとに y/n/a/q で確認する
+///
+/// `a` はその候補以降をすべて承認し、`q`（または入力の終わり）は残りを送らずに終える。
+/// 端末から読めない場合は何も送らずに `Aborted` を返す。
+fn confirm_each(
+    candidates: &[KillCandidate],
+    signal: Signal,
+    ports: &HashMap<u32, Vec<u16>>,
+) -> Result<Approval, SafeKillError> {
+    if !io::stdin().is_terminal() {
+        return Err(SafeKillError::Aborted(
+            "--interactive requires a terminal; no signal was sent".to_string(),
+        ));
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let mut approved = Vec::new();
+    for (index, candidate) in candidates.iter().enumerate() {
+        eprintln!("{}", describe_candidate(candidate, ports, now));
+        loop {
+            let answer = prompt(&format!(
+                "Send {} to {} (PID {})? [y/n/a/q]: ",
+                signal.name(),
+                candidate.preview.name,
+                candidate.preview.pid
+            ))?;
+            match parse_answer(answer.as_deref()) {
+                Some(Answer::Yes) => {
+                    approved.push(candidate.preview.pid);
+                    break;
+                }
+                Some(Answer::No) => break,
+                Some(Answer::All) => {
+                    approved.extend(candidates[index..].iter().map(|c| c.preview.pid));
+                    return Ok(Approval::Only(approved));
+                }
+                Some(Answer::Quit) => return Ok(Approval::Only(approved)),
+                None => eprintln!("Please answer y, n, a or q."),
+            }
+        }
+    }
+    Ok(Approval::Only(approved))
+}
+
+/// 確認プロンプトへの応答
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Answer {
+    Yes,
+    No,
+    All,
+    Quit,
+}
+
+/// 応答を解析する（`None` は入力の終わり。空行は No、解釈できない入力は `None` を返す）
+fn parse_answer(input: Option<&str>) -> Option<Answer> {
+    let Some(input) = input else {
+        return Some(Answer::Quit);
+    };
+    match input.trim().to_lowercase().as_str() {
+        "y" | "yes" => Some(Answer::Yes),
+        "" | "n" | "no" => Some(Answer::No),
+        "a" | "all" => Some(Answer::All),
+        "q" | "quit" => Some(Answer::Quit),
+        _ => None,
+    }
+}
+
+/// プロンプトを表示して 1 行読む（入力の終わりなら `None`）
+fn prompt(message: &str) -> Result<Option<String>, SafeKillError> {
+    eprint!("{}", message);
+    io::stderr()
+        .flush()
+        .map_err(|e| SafeKillError::SystemError(format!("Failed to flush stderr: {}", e)))?;
+    let mut input = String::new();
+    let read = io::stdin()
+        .read_line(&mut input)
+        .map_err(|e| SafeKillError::SystemError(format!("Failed to read input: {}", e)))?;
+    Ok((read > 0).then_some(input))
+}
+
+/// 候補 1 件分の表示（PID・名前・経過時間・待ち受けポート、次の行にコマンドライン）
+fn describe_candidate(
+    candidate: &KillCandidate,
+    ports: &HashMap<u32, Vec<u16>>,
+    now: u64,
+) -> String {
+    let mut header = format!(
+        "{:>8}  {}  age {}",
+        candidate.preview.pid,
+        candidate.preview.name,
+        format_age(now.saturating_sub(candidate.process.start_time))
+    );
+    if let Some(ports) = ports.get(&candidate.preview.pid) {
+        let ports: Vec<String> = ports.iter().map(u16::to_string).collect();
+        header.push_str(&format!("  ports {}", ports.join(",")));
+    }
+    format!(
+        "{}\n          {}",
+        header,
+        truncate(&candidate.process.cmd.join(" "), 70)
+    )
+}
+
+/// 経過秒数を "45s"、"12m05s"、"2h03m"、"3d04h" の形式にする
+fn format_age(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m{:02}s", secs / 60, secs % 60),
+        3600..86400 => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+        _ => format!("{}d{:02}h", secs / 86400, secs % 86400 / 3600),
+    }
+}
+
 /// 終了可能なプロセス一覧を表示する
 fn print_killable_list(processes: &[process_info::ProcessInfo]) {
     if processes.is_empty() {
@@ -195,6 +746,41 @@ fn print_killable_list(processes: &[process_info::ProcessInfo]) {
     }
 }
 
+/// 待ち受け中のソケットと kill 可否を表示する
+fn print_port_holders(holders: &[PortHolder], all: bool) {
+    if holders.is_empty() {
+        if all {
+            println!("No listening sockets found.");
+        } else {
+            println!("No listening sockets on allowed ports.");
+        }
+        return;
+    }
+
+    println!(
+        "{:>5}  {:<5}  {:<24}  {:>8}  {:<20}  {:<4}  COMMAND",
+        "PORT", "PROTO", "ADDRESS", "PID", "NAME", "KILL"
+    );
+    println!("{}", "-".repeat(100));
+
+    for holder in holders {
+        let protocol = holder.protocol.to_string().to_lowercase();
+        println!(
+            "{:>5}  {:<5}  {:<24}  {:>8}  {:<20}  {:<4}  {}",
+            holder.port,
+            protocol,
+            holder.address,
+            holder.pid,
+            truncate(&holder.name, 20),
+            if holder.killable { "yes" } else { "no" },
+            truncate(&holder.command.join(" "), 30)
+        );
+        if let Some(reason) = &holder.reason {
+            println!("{:>7}-> {}", "", reason);
+        }
+    }
+}
+
 /// 文字数上限で文字列を切り詰める
 fn truncate(s: &str, max_len: usize) -> String {
     let char_count = s.chars().count();
@@ -228,6 +814,53 @@ mod tests {
         assert!(version.contains('.'), "Version should contain dots");
     }
 
+    #[test]
+    fn test_parse_answer() {
+        assert_eq!(parse_answer(Some("y\n")), Some(Answer::Yes));
+        assert_eq!(parse_answer(Some("YES")), Some(Answer::Yes));
+        assert_eq!(parse_answer(Some("\n")), Some(Answer::No));
+        assert_eq!(parse_answer(Some("a")), Some(Answer::All));
+        assert_eq!(parse_answer(Some("quit")), Some(Answer::Quit));
+        assert_eq!(parse_answer(None), Some(Answer::Quit));
+        assert_eq!(parse_answer(Some("maybe")), None);
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(45), "45s");
+        assert_eq!(format_age(725), "12m05s");
+        assert_eq!(format_age(7380), "2h03m");
+        assert_eq!(format_age(273_600), "3d04h");
+    }
+
+    #[test]
+    fn test_describe_candidate_shows_ports_and_command() {
+        let candidate = KillCandidate {
+            process: process_info::ProcessInfo {
+                pid: 4242,
+                parent_pid: Some(1),
+                name: "node".to_string(),
+                cmd: vec!["node".to_string(), "server.js".to_string()],
+                start_time: 1_000,
+                uid: None,
+                euid: None,
+                cwd: None,
+                start_ticks: None,
+            },
+            preview: KillResult::dry_run(4242, "node", Signal::SIGTERM),
+        };
+        let ports = HashMap::from([(4242, vec![3000, 5173])]);
+
+        assert_eq!(
+            describe_candidate(&candidate, &ports, 1_090),
+            "    4242  node  age 1m30s  ports 3000,5173\n          node server.js"
+        );
+        assert_eq!(
+            describe_candidate(&candidate, &HashMap::new(), 1_000),
+            "    4242  node  age 0s\n          node server.js"
+        );
+    }
+
     #[test]
     fn test_truncate_short_string() {
         let result = truncate("hello", 10);
@@ -412,7 +1045,7 @@ mod tests {
         ));
 
         assert_eq!(
-            port_result_summary(3000, &batch, true),
+            port_result_summary(&PortSelection::single(3000), &batch, true),
             "Port 3000: Found 1 process(es), would kill 1:"
         );
     }
@@ -426,6 +1059,7 @@ mod tests {
             success: false,
             message: "unexpected failure".to_string(),
             error: None,
+            port: None,
         };
         assert_eq!(
             single_result_error(&result),
@@ -452,8 +1086,24 @@ mod tests {
         ));
 
         assert_eq!(
-            port_result_summary(3000, &batch, false),
+            port_result_summary(&PortSelection::single(3000), &batch, false),
             "Port 3000: Found 1 process(es), killed 1:"
         );
     }
+
+    #[test]
+    fn test_port_result_summary_for_port_range() {
+        let mut batch = BatchKillResult::new();
+        batch.add(
+            KillResult::dry_run(20, "server", safe_kill::signal::Signal::SIGTERM).with_port(3001),
+        );
+
+        let ports = PortSelection::parse("3000-3010,5173").unwrap();
+        assert_eq!(
+            port_result_summary(&ports, &batch, true),
+            "Ports 3000-3010,5173: Found 1 process(es), would kill 1:"
+        );
+        assert_eq!(port_label(&ports), "ports 3000-3010,5173");
+        assert_eq!(port_label(&PortSelection::single(3000)), "port 3000");
+    }
 }
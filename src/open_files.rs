@@ -0,0 +1,219 @@
+//! safe-kill の開いているファイルの検出モジュール
+//!
+//! Linux では `/proc/<pid>/fd` のリンク先と `/proc/<pid>/maps` のマッピングを調べ、
+//! 指定パス（ディレクトリの場合はその配下）を開いている・マップしているプロセスを
+//! `fuser` のように検出する。
+
+use std::fmt;
+use std::path::Path;
+
+use crate::error::SafeKillError;
+use crate::process_info::ProcessInfoProvider;
+
+/// プロセスがパスを保持している方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoldKind {
+    /// ファイルディスクリプタで開いている
+    Open,
+    /// mmap でマップしている（共有ライブラリや SQLite の共有メモリなど）
+    Mapped,
+}
+
+impl fmt::Display for HoldKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HoldKind::Open => write!(f, "open"),
+            HoldKind::Mapped => write!(f, "mapped"),
+        }
+    }
+}
+
+/// パスを保持するプロセスの情報
+#[derive(Debug, Clone)]
+pub struct FileHolder {
+    /// プロセス ID
+    pub pid: u32,
+    /// プロセス名（取得できない場合は "pid:<pid>"）
+    pub name: String,
+    /// 保持している方法（fd とマッピングの両方がある場合は `Open`）
+    pub kind: HoldKind,
+}
+
+/// 指定パスを開いているプロセスを検出する検出器
+pub struct OpenFileDetector {
+    provider: ProcessInfoProvider,
+}
+
+impl OpenFileDetector {
+    /// 新しい OpenFileDetector を作成
+    pub fn new() -> Self {
+        Self {
+            provider: ProcessInfoProvider::new(),
+        }
+    }
+
+    /// プロセス情報を更新する
+    pub fn refresh(&mut self) {
+        self.provider.refresh();
+    }
+
+    /// 指定パス（ディレクトリなら配下すべて）を保持するプロセスを PID 順に返す
+    ///
+    /// `path` は正規化済み（絶対パス・シンボリックリンク解決済み）であることを想定する。
+    /// fd や maps を読めないプロセス（他ユーザーのプロセスなど）は検出されない。
+    pub fn find_holders(&self, path: &Path) -> Result<Vec<FileHolder>, SafeKillError> {
+        if !cfg!(target_os = "linux") {
+            return Err(SafeKillError::SystemError(
+                "--holding is only supported on Linux".to_string(),
+            ));
+        }
+
+        let entries = std::fs::read_dir("/proc")
+            .map_err(|e| SafeKillError::SystemError(format!("Failed to read /proc: {}", e)))?;
+        let mut holders: Vec<FileHolder> = entries
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+            .filter_map(|pid| {
+                let kind = holding_kind(pid, path)?;
+                // 表示用の名前。ポリシー判定では呼び出し側で最新の情報を取り直すこと。
+                let name = self
+                    .provider
+                    .get(pid)
+                    .map(|p| p.name)
+                    .unwrap_or_else(|| format!("pid:{}", pid));
+                Some(FileHolder { pid, name, kind })
+            })
+            .collect();
+
+        holders.sort_by_key(|h| h.pid);
+        Ok(holders)
+    }
+
+    /// 指定 PID が指定パスをいま保持しているかを再確認する
+    ///
+    /// kill 直前の TOCTOU 緩和用。読み取りに失敗した場合は安全側に倒して false を返す。
+    pub fn pid_holds(&self, pid: u32, path: &Path) -> bool {
+        holding_kind(pid, path).is_some()
+    }
+}
+
+impl Default for OpenFileDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 指定 PID がパスを保持していればその方法を返す
+fn holding_kind(pid: u32, path: &Path) -> Option<HoldKind> {
+    if holds_fd(pid, path) {
+        return Some(HoldKind::Open);
+    }
+    let maps = std::fs::read_to_string(format!("/proc/{}/maps", pid)).ok()?;
+    maps.lines()
+        .filter_map(mapped_path)
+        .any(|mapped| covers(path, Path::new(mapped)))
+        .then_some(HoldKind::Mapped)
+}
+
+/// 指定 PID の fd のいずれかがパス（またはその配下）を指しているか
+fn holds_fd(pid: u32, path: &Path) -> bool {
+    let Ok(entries) = std::fs::read_dir(format!("/proc/{}/fd", pid)) else {
+        return false;
+    };
+    entries.flatten().any(|entry| {
+        std::fs::read_link(entry.path()).is_ok_and(|target| {
+            target
+                .to_str()
+                .is_some_and(|target| !is_deleted(target) && covers(path, Path::new(target)))
+        })
+    })
+}
+
+/// `path` が `candidate` 自身か、その祖先ディレクトリか（要素単位で比較する）
+fn covers(path: &Path, candidate: &Path) -> bool {
+    candidate.starts_with(path)
+}
+
+/// 削除済みファイルのリンク先（`/path (deleted)`）か
+///
+/// 同じパスに作り直されたファイルとは別物なので対象にしない。
+fn is_deleted(target: &str) -> bool {
+    target.ends_with(" (deleted)")
+}
+
+/// `/proc/<pid>/maps` の 1 行からマップされたファイルのパスを取り出す
+///
+/// 形式: `address perms offset dev inode pathname`。匿名マッピングや `[heap]` などの
+/// 疑似パス、削除済みファイルは除外する。
+fn mapped_path(line: &str) -> Option<&str> {
+    let mut rest = line;
+    for _ in 0..5 {
+        rest = rest.trim_start().split_once(char::is_whitespace)?.1;
+    }
+    let path = rest.trim();
+    (path.starts_with('/') && !is_deleted(path)).then_some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+
+    #[test]
+    fn test_mapped_path_extracts_file_mappings() {
+        assert_eq!(
+            mapped_path("7f0000000000-7f0000001000 r--p 00000000 08:01 1234   /usr/lib/libc.so.6"),
+            Some("/usr/lib/libc.so.6")
+        );
+        assert_eq!(
+            mapped_path("7f0000000000-7f0000001000 rw-s 00000000 08:01 99 /tmp/my db.sqlite-shm"),
+            Some("/tmp/my db.sqlite-shm")
+        );
+        assert_eq!(
+            mapped_path("55d0c0000000-55d0c0021000 rw-p 00000000 00:00 0      [heap]"),
+            None
+        );
+        assert_eq!(
+            mapped_path("7f0000000000-7f0000001000 rw-p 00000000 00:00 0"),
+            None
+        );
+        assert_eq!(
+            mapped_path("7f0000000000-7f0000001000 rw-s 00000000 00:05 7 /dev/shm/x (deleted)"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_covers_matches_whole_components() {
+        assert!(covers(Path::new("/work/target"), Path::new("/work/target")));
+        assert!(covers(
+            Path::new("/work/target"),
+            Path::new("/work/target/debug/.cargo-lock")
+        ));
+        assert!(!covers(
+            Path::new("/work/target"),
+            Path::new("/work/target2/x")
+        ));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_find_holders_detects_open_file_in_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir_path = dir.path().canonicalize().unwrap();
+        let file_path = dir_path.join("lock.db");
+        let file = File::create(&file_path).unwrap();
+        let detector = OpenFileDetector::new();
+        let current_pid = ProcessInfoProvider::current_pid();
+
+        for target in [&file_path, &dir_path] {
+            let holders = detector.find_holders(target).unwrap();
+            let own = holders.iter().find(|h| h.pid == current_pid);
+            assert_eq!(own.map(|h| h.kind), Some(HoldKind::Open));
+            assert!(detector.pid_holds(current_pid, target));
+        }
+
+        drop(file);
+        assert!(!detector.pid_holds(current_pid, &file_path));
+    }
+}
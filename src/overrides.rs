@@ -0,0 +1,641 @@
+//! 環境変数と `--set` による設定の上書き
+//!
+//! 設定ファイル（`--config` > `SAFE_KILL_CONFIG` > `~/.config/safe-kill/config.toml`）から
+//! 読み込んだ設定を TOML のテーブルに戻し、`SAFE_KILL_<セクション>[_<キー>]` 環境変数、
+//! 続いて `--set <section.key>=<value>` の値を該当キーに合成してから `Config` に変換し直す。
+//! 上書きは設定を厳しくする方向にしか働かない。拒否リストには追加だけ、許可リストは
+//! 設定ファイルにあるエントリへの絞り込みだけ、上限は小さくするだけができる。
+//! 上書き後も通常と同じ検証を行い、既定の denylist は `merge_defaults` で必ず合流される。
+//! `[privileged]` は特権実行を許可する明示的な同意なので、上書きでは設定できない。
+
+use crate::config::{CONFIG_ENV_VAR, CWD_CONFIRM_THRESHOLD, PROFILE_ENV_VAR, PortSpec};
+use crate::error::SafeKillError;
+
+/// 上書き対象ではない safe-kill の環境変数
+const RESERVED_ENV_VARS: [&str; 3] = [CONFIG_ENV_VAR, PROFILE_ENV_VAR, "SAFE_KILL_ROOT_PID"];
+
+/// 上書きの値を設定ファイルの値に合成する方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Rule {
+    /// カンマ区切りのリストを設定ファイルのリストに追加する
+    Extend,
+    /// カンマ区切りのリストで設定ファイルのリストを絞り込む
+    /// （どのエントリも設定ファイルのいずれかのエントリに含まれていなければならない）
+    Narrow(Cover),
+    /// 0 以上の整数で、設定ファイルの値（なければ第 1 要素の値）より小さい場合だけ採用する
+    Lower(Option<u32>),
+    /// 真偽値（true/false、1/0、yes/no）で、true にすることだけを認める
+    Enable,
+    /// 上書きできない（設定ファイルでのみ設定する）
+    Fixed,
+}
+
+/// 絞り込みで上書きのエントリが設定ファイルのエントリに含まれるかの判定方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cover {
+    /// 文字列が完全に一致する
+    Exact,
+    /// ポート指定として範囲・プロトコル・アドレスが含まれる
+    Port,
+}
+
+/// 上書きできる設定項目（環境変数名、`--set` のキー、合成方法）
+const OVERRIDES: &[(&str, &str, Rule)] = &[
+    (
+        "SAFE_KILL_ALLOWLIST",
+        "allowlist.processes",
+        Rule::Narrow(Cover::Exact),
+    ),
+    ("SAFE_KILL_DENYLIST", "denylist.processes", Rule::Extend),
+    (
+        "SAFE_KILL_ALLOWED_PORTS",
+        "allowed_ports.ports",
+        Rule::Narrow(Cover::Port),
+    ),
+    (
+        "SAFE_KILL_ALLOWED_SOCKETS",
+        "allowed_sockets.paths",
+        Rule::Narrow(Cover::Exact),
+    ),
+    (
+        "SAFE_KILL_ALLOWED_PATHS",
+        "allowed_paths.paths",
+        Rule::Narrow(Cover::Exact),
+    ),
+    ("SAFE_KILL_ROOT_STRATEGY", "root.strategy", Rule::Fixed),
+    ("SAFE_KILL_ROOT_LEVELS", "root.levels", Rule::Fixed),
+    ("SAFE_KILL_ROOT_NAMES", "root.names", Rule::Fixed),
+    (
+        "SAFE_KILL_OWNERSHIP_ENFORCE",
+        "ownership.enforce",
+        Rule::Enable,
+    ),
+    ("SAFE_KILL_OWNERSHIP_USERS", "ownership.users", Rule::Fixed),
+    ("SAFE_KILL_SIGNALS_ALLOW", "signals.allow", Rule::Fixed),
+    ("SAFE_KILL_SIGNALS_FORBID", "signals.forbid", Rule::Extend),
+    (
+        "SAFE_KILL_SIGNALS_DESCENDANT_ALLOW",
+        "signals.descendant.allow",
+        Rule::Fixed,
+    ),
+    (
+        "SAFE_KILL_SIGNALS_DESCENDANT_FORBID",
+        "signals.descendant.forbid",
+        Rule::Extend,
+    ),
+    (
+        "SAFE_KILL_SIGNALS_ALLOWLISTED_ALLOW",
+        "signals.allowlisted.allow",
+        Rule::Fixed,
+    ),
+    (
+        "SAFE_KILL_SIGNALS_ALLOWLISTED_FORBID",
+        "signals.allowlisted.forbid",
+        Rule::Extend,
+    ),
+    (
+        "SAFE_KILL_SIGNALS_PORT_ALLOW",
+        "signals.port.allow",
+        Rule::Fixed,
+    ),
+    (
+        "SAFE_KILL_SIGNALS_PORT_FORBID",
+        "signals.port.forbid",
+        Rule::Extend,
+    ),
+    // 設定ファイルにない場合も `--cwd` の既定の確認しきい値より緩めない
+    (
+        "SAFE_KILL_CONFIRM_ABOVE",
+        "confirm.above",
+        Rule::Lower(Some(CWD_CONFIRM_THRESHOLD as u32)),
+    ),
+    ("SAFE_KILL_CONFIRM_SIGKILL", "confirm.sigkill", Rule::Enable),
+    (
+        "SAFE_KILL_LIMITS_KILLS_PER_MINUTE",
+        "limits.kills_per_minute",
+        Rule::Lower(None),
+    ),
+    (
+        "SAFE_KILL_LIMITS_SIGKILLS_PER_SESSION",
+        "limits.sigkills_per_session",
+        Rule::Lower(None),
+    ),
+];
+
+/// 環境変数と `--set` の上書きを TOML のテーブルに合成する
+///
+/// 環境変数、`--set` の順に適用し、それぞれ直前の値をさらに厳しくする。
+/// 設定を緩める上書きは `ConfigError` になる。上書きを 1 つでも適用した場合は true を返す。
+pub fn apply(
+    table: &mut toml::Table,
+    vars: impl IntoIterator<Item = (String, String)>,
+    sets: &[String],
+) -> Result<bool, SafeKillError> {
+    let mut applied = false;
+    let mut vars: Vec<_> = vars
+        .into_iter()
+        .filter(|(name, _)| name.starts_with("SAFE_KILL_"))
+        .filter(|(name, _)| !RESERVED_ENV_VARS.contains(&name.as_str()))
+        .collect();
+    vars.sort();
+
+    for (name, value) in vars {
+        if name.starts_with("SAFE_KILL_PRIVILEGED") {
+            return Err(privileged_error(&name));
+        }
+        match OVERRIDES.iter().find(|(env, _, _)| *env == name) {
+            Some(&(_, key, rule)) => {
+                merge(table, key, rule, &value).map_err(|e| invalid(&name, e))?;
+                applied = true;
+            }
+            None => eprintln!("Warning: unknown environment variable {} is ignored", name),
+        }
+    }
+
+    for entry in sets {
+        let (key, value) = entry.split_once('=').ok_or_else(|| {
+            SafeKillError::InvalidUsage(format!("--set expects KEY=VALUE, got {:?}", entry))
+        })?;
+        let key = key.trim();
+        if key.starts_with("privileged") {
+            return Err(privileged_error(&format!("--set {}", key)));
+        }
+        let &(_, key, rule) = OVERRIDES
+            .iter()
+            .find(|(_, k, _)| *k == key)
+            .ok_or_else(|| {
+                SafeKillError::InvalidUsage(format!("--set: unknown setting {:?}", key))
+            })?;
+        merge(table, key, rule, value).map_err(|e| invalid(&format!("--set {}", key), e))?;
+        applied = true;
+    }
+    Ok(applied)
+}
+
+/// 上書きできる設定項目の一覧（環境変数名、`--set` のキー）
+pub fn names() -> impl Iterator<Item = (&'static str, &'static str)> {
+    OVERRIDES.iter().map(|&(env, key, _)| (env, key))
+}
+
+/// 上書きの値を合成方法に従って `section.key` の現在の値に合成する
+fn merge(table: &mut toml::Table, key: &str, rule: Rule, value: &str) -> Result<(), String> {
+    let section = key.split('.').next().unwrap_or(key);
+    let current = get(table, key);
+    let merged = match rule {
+        Rule::Fixed => {
+            return Err(format!("{} can only be set in the config file", key));
+        }
+        Rule::Extend => {
+            let mut list = current
+                .and_then(toml::Value::as_array)
+                .cloned()
+                .unwrap_or_default();
+            for item in parse_list(value) {
+                let item = toml::Value::String(item);
+                if !list.contains(&item) {
+                    list.push(item);
+                }
+            }
+            toml::Value::Array(list)
+        }
+        Rule::Narrow(cover) => {
+            let entries = current.and_then(toml::Value::as_array).ok_or_else(|| {
+                format!(
+                    "[{}] is not set in the config file, so there is nothing to narrow",
+                    section
+                )
+            })?;
+            let narrowed = parse_list(value)
+                .into_iter()
+                .map(|item| narrow_entry(entries, &item, cover, section))
+                .collect::<Result<_, _>>()?;
+            toml::Value::Array(narrowed)
+        }
+        Rule::Lower(fallback) => {
+            let value = value.trim();
+            let requested = value
+                .parse::<u32>()
+                .map_err(|_| format!("expected a non-negative integer, got {:?}", value))?;
+            let limit = current
+                .and_then(toml::Value::as_integer)
+                .or(fallback.map(i64::from))
+                .map_or(i64::from(requested), |limit| {
+                    limit.min(i64::from(requested))
+                });
+            toml::Value::Integer(limit)
+        }
+        Rule::Enable => {
+            if !parse_bool(value)? {
+                return Err(format!(
+                    "can only be set to true; turn {} off in the config file",
+                    key
+                ));
+            }
+            toml::Value::Boolean(true)
+        }
+    };
+    set(table, key, merged);
+    Ok(())
+}
+
+/// 絞り込みのエントリを、それを含む設定ファイルのエントリの形（期限付きなら期限も）で返す
+fn narrow_entry(
+    entries: &[toml::Value],
+    item: &str,
+    cover: Cover,
+    section: &str,
+) -> Result<toml::Value, String> {
+    let spec = match cover {
+        Cover::Exact => None,
+        Cover::Port => Some(PortSpec::parse(item).map_err(|e| e.to_string())?),
+    };
+    let covers = |entry: &str| match &spec {
+        None => entry == item,
+        Some(spec) => PortSpec::parse(entry).is_ok_and(|entry| entry.covers(spec)),
+    };
+    for entry in entries {
+        match entry {
+            toml::Value::String(value) if covers(value) => {
+                return Ok(toml::Value::String(item.to_string()));
+            }
+            toml::Value::Table(expiring) => {
+                let field = expiring
+                    .iter()
+                    .find(|(field, value)| *field != "expires" && value.is_str());
+                if let Some((field, value)) = field
+                    && value.as_str().is_some_and(covers)
+                {
+                    let mut narrowed = expiring.clone();
+                    narrowed.insert(field.clone(), toml::Value::String(item.to_string()));
+                    return Ok(toml::Value::Table(narrowed));
+                }
+            }
+            _ => {}
+        }
+    }
+    Err(format!(
+        "{:?} is not allowed by [{}] in the config file; overrides can only narrow it",
+        item, section
+    ))
+}
+
+/// カンマ区切りのリストを要素に分ける（空の要素は除く）
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// 真偽値（true/false、1/0、yes/no）を解析する
+fn parse_bool(value: &str) -> Result<bool, String> {
+    let value = value.trim();
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" => Ok(true),
+        "0" | "false" | "no" => Ok(false),
+        _ => Err(format!("expected true or false, got {:?}", value)),
+    }
+}
+
+/// `section.key` / `section.sub.key` の位置の値を返す
+fn get<'a>(table: &'a toml::Table, key: &str) -> Option<&'a toml::Value> {
+    let mut parts = key.split('.');
+    let mut value = table.get(parts.next()?)?;
+    for part in parts {
+        value = value.get(part)?;
+    }
+    Some(value)
+}
+
+/// `section.key` / `section.sub.key` の位置に値を置く（途中のテーブルは必要なら作る）
+fn set(table: &mut toml::Table, key: &str, value: toml::Value) {
+    let mut parts: Vec<&str> = key.split('.').collect();
+    let last = parts.pop().expect("override keys are never empty");
+    let mut current = table;
+    for part in parts {
+        let entry = current
+            .entry(part)
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+        if !entry.is_table() {
+            *entry = toml::Value::Table(toml::Table::new());
+        }
+        current = entry.as_table_mut().expect("entry was just made a table");
+    }
+    current.insert(last.to_string(), value);
+}
+
+fn invalid(source: &str, message: String) -> SafeKillError {
+    SafeKillError::ConfigError(format!("Invalid override {}: {}", source, message))
+}
+
+fn privileged_error(source: &str) -> SafeKillError {
+    SafeKillError::ConfigError(format!(
+        "{}: [privileged] can only be set in the config file",
+        source
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_apply_env_overrides_tighten_values() {
+        let mut table: toml::Table = toml::from_str(
+            r#"
+[allowed_ports]
+ports = ["3000-3010", { port = "5173", expires = "2030-01-01T00:00:00Z" }]
+[denylist]
+processes = ["sshd"]
+[confirm]
+above = 5
+"#,
+        )
+        .unwrap();
+        let applied = apply(
+            &mut table,
+            vars(&[
+                ("SAFE_KILL_ALLOWED_PORTS", "3005, 5173"),
+                ("SAFE_KILL_DENYLIST", "postgres, sshd"),
+                ("SAFE_KILL_CONFIRM_ABOVE", "3"),
+                ("SAFE_KILL_CONFIRM_SIGKILL", "yes"),
+                ("SAFE_KILL_SIGNALS_PORT_FORBID", "SIGKILL"),
+                ("PATH", "/usr/bin"),
+            ]),
+            &[],
+        )
+        .unwrap();
+
+        assert!(applied);
+        // 絞り込んだエントリは元のエントリの期限を引き継ぐ
+        let expected: toml::Table = toml::from_str(
+            r#"ports = ["3005", { port = "5173", expires = "2030-01-01T00:00:00Z" }]"#,
+        )
+        .unwrap();
+        assert_eq!(table["allowed_ports"]["ports"], expected["ports"]);
+        assert_eq!(
+            table["denylist"]["processes"],
+            toml::Value::Array(vec!["sshd".into(), "postgres".into()])
+        );
+        assert_eq!(table["confirm"]["above"], toml::Value::Integer(3));
+        assert_eq!(table["confirm"]["sigkill"], toml::Value::Boolean(true));
+        assert_eq!(
+            table["signals"]["port"]["forbid"],
+            toml::Value::Array(vec!["SIGKILL".into()])
+        );
+    }
+
+    #[test]
+    fn test_apply_refuses_to_loosen_allow_lists() {
+        let file = r#"
+[allowlist]
+processes = ["node"]
+[allowed_ports]
+ports = ["tcp:3000-3010"]
+[allowed_sockets]
+paths = ["/tmp/dev-*.sock"]
+"#;
+        for (name, value) in [
+            ("SAFE_KILL_ALLOWLIST", "node,python"),
+            ("SAFE_KILL_ALLOWED_PORTS", "1-65535"),
+            ("SAFE_KILL_ALLOWED_PORTS", "3000"),
+            ("SAFE_KILL_ALLOWED_PORTS", "tcp:3000-3011"),
+            ("SAFE_KILL_ALLOWED_SOCKETS", "/tmp/*.sock"),
+            ("SAFE_KILL_ALLOWED_PATHS", "/"),
+        ] {
+            let mut table: toml::Table = toml::from_str(file).unwrap();
+            let before = table.clone();
+            assert!(
+                matches!(
+                    apply(&mut table, vars(&[(name, value)]), &[]),
+                    Err(SafeKillError::ConfigError(_))
+                ),
+                "{}={}",
+                name,
+                value
+            );
+            assert_eq!(table, before, "{}={}", name, value);
+        }
+
+        let mut table = toml::Table::new();
+        assert_eq!(
+            apply(&mut table, vars(&[("SAFE_KILL_ALLOWED_PORTS", "1-65535")]), &[]),
+            Err(SafeKillError::ConfigError(
+                "Invalid override SAFE_KILL_ALLOWED_PORTS: [allowed_ports] is not set in the config file, so there is nothing to narrow"
+                    .to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_apply_cannot_clear_deny_lists() {
+        let mut table: toml::Table =
+            toml::from_str("[denylist]\nprocesses = [\"sshd\"]\n[signals]\nforbid = [\"SIGKILL\"]")
+                .unwrap();
+        let before = table.clone();
+        apply(
+            &mut table,
+            vars(&[("SAFE_KILL_DENYLIST", ""), ("SAFE_KILL_SIGNALS_FORBID", "")]),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(table, before);
+    }
+
+    #[test]
+    fn test_apply_refuses_to_loosen_flags_and_limits() {
+        let file = r#"
+[ownership]
+enforce = true
+[confirm]
+sigkill = true
+above = 3
+[limits]
+kills_per_minute = 5
+sigkills_per_session = 1
+"#;
+        for (name, value) in [
+            ("SAFE_KILL_OWNERSHIP_ENFORCE", "false"),
+            ("SAFE_KILL_CONFIRM_SIGKILL", "0"),
+        ] {
+            let mut table: toml::Table = toml::from_str(file).unwrap();
+            assert!(
+                matches!(
+                    apply(&mut table, vars(&[(name, value)]), &[]),
+                    Err(SafeKillError::ConfigError(_))
+                ),
+                "{}={}",
+                name,
+                value
+            );
+        }
+
+        // 上限は小さくする方向にしか変わらない
+        let mut table: toml::Table = toml::from_str(file).unwrap();
+        apply(
+            &mut table,
+            vars(&[
+                ("SAFE_KILL_CONFIRM_ABOVE", "100"),
+                ("SAFE_KILL_LIMITS_KILLS_PER_MINUTE", "1000"),
+                ("SAFE_KILL_LIMITS_SIGKILLS_PER_SESSION", "0"),
+            ]),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(table["confirm"]["above"], toml::Value::Integer(3));
+        assert_eq!(table["limits"]["kills_per_minute"], toml::Value::Integer(5));
+        assert_eq!(
+            table["limits"]["sigkills_per_session"],
+            toml::Value::Integer(0)
+        );
+
+        // 設定ファイルにない confirm.above は `--cwd` の既定値より緩められない
+        let mut table = toml::Table::new();
+        apply(&mut table, vars(&[("SAFE_KILL_CONFIRM_ABOVE", "100")]), &[]).unwrap();
+        assert_eq!(
+            table["confirm"]["above"],
+            toml::Value::Integer(CWD_CONFIRM_THRESHOLD as i64)
+        );
+    }
+
+    #[test]
+    fn test_apply_rejects_fixed_settings() {
+        for (name, value) in [
+            ("SAFE_KILL_OWNERSHIP_USERS", "root"),
+            ("SAFE_KILL_SIGNALS_ALLOW", "SIGKILL"),
+            ("SAFE_KILL_SIGNALS_DESCENDANT_ALLOW", "SIGKILL"),
+            ("SAFE_KILL_SIGNALS_ALLOWLISTED_ALLOW", "SIGKILL"),
+            ("SAFE_KILL_SIGNALS_PORT_ALLOW", "SIGKILL"),
+            ("SAFE_KILL_ROOT_STRATEGY", "init"),
+            ("SAFE_KILL_ROOT_LEVELS", "10"),
+            ("SAFE_KILL_ROOT_NAMES", "systemd"),
+        ] {
+            let mut table = toml::Table::new();
+            assert!(
+                matches!(
+                    apply(&mut table, vars(&[(name, value)]), &[]),
+                    Err(SafeKillError::ConfigError(_))
+                ),
+                "{}={}",
+                name,
+                value
+            );
+            assert!(table.is_empty());
+        }
+        assert_eq!(
+            apply(
+                &mut toml::Table::new(),
+                vec![],
+                &["ownership.users=root".to_string()]
+            ),
+            Err(SafeKillError::ConfigError(
+                "Invalid override --set ownership.users: ownership.users can only be set in the config file"
+                    .to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_apply_set_takes_precedence_over_env() {
+        let mut table = toml::Table::new();
+        apply(
+            &mut table,
+            vars(&[("SAFE_KILL_LIMITS_KILLS_PER_MINUTE", "10")]),
+            &["limits.kills_per_minute=3".to_string()],
+        )
+        .unwrap();
+        assert_eq!(table["limits"]["kills_per_minute"], toml::Value::Integer(3));
+
+        // `--set` も環境変数で厳しくした値より緩められない
+        let mut table: toml::Table =
+            toml::from_str("[allowlist]\nprocesses = [\"node\", \"vite\"]").unwrap();
+        assert!(matches!(
+            apply(
+                &mut table,
+                vars(&[("SAFE_KILL_ALLOWLIST", "node")]),
+                &["allowlist.processes=vite".to_string()],
+            ),
+            Err(SafeKillError::ConfigError(_))
+        ));
+    }
+
+    #[test]
+    fn test_apply_ignores_reserved_and_nothing_to_apply() {
+        let mut table = toml::Table::new();
+        let applied = apply(
+            &mut table,
+            vars(&[
+                ("SAFE_KILL_ROOT_PID", "1234"),
+                ("SAFE_KILL_CONFIG", "/x"),
+                ("SAFE_KILL_PROFILE", "frontend"),
+            ]),
+            &[],
+        )
+        .unwrap();
+        assert!(!applied);
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn test_apply_rejects_privileged_and_bad_values() {
+        let mut table = toml::Table::new();
+        assert!(matches!(
+            apply(
+                &mut table,
+                vars(&[("SAFE_KILL_PRIVILEGED_ALLOWLIST_BYPASS", "true")]),
+                &[]
+            ),
+            Err(SafeKillError::ConfigError(_))
+        ));
+        assert!(matches!(
+            apply(
+                &mut table,
+                vec![],
+                &["privileged.allowlist_bypass=true".to_string()]
+            ),
+            Err(SafeKillError::ConfigError(_))
+        ));
+        assert_eq!(
+            apply(
+                &mut table,
+                vars(&[("SAFE_KILL_CONFIRM_ABOVE", "-1")]),
+                &[]
+            ),
+            Err(SafeKillError::ConfigError(
+                "Invalid override SAFE_KILL_CONFIRM_ABOVE: expected a non-negative integer, got \"-1\""
+                    .to_string()
+            ))
+        );
+        assert!(matches!(
+            apply(&mut table, vec![], &["confirm.nope=1".to_string()]),
+            Err(SafeKillError::InvalidUsage(_))
+        ));
+        assert!(matches!(
+            apply(&mut table, vec![], &["confirm.above".to_string()]),
+            Err(SafeKillError::InvalidUsage(_))
+        ));
+    }
+
+    #[test]
+    fn test_override_names_follow_keys() {
+        for (env, key) in names() {
+            let expected = format!("SAFE_KILL_{}", key.replace('.', "_").to_uppercase());
+            // 1 つのキーしか持たないセクションはキー名を省略する
+            assert!(
+                env == expected || expected.starts_with(&format!("{}_", env)),
+                "{} / {}",
+                env,
+                key
+            );
+        }
+    }
+}
@@ -2,13 +2,23 @@
 //!
 //! ancestry、config、自殺防止を組み合わせた kill 許可判定を統括する。
 
-use crate::ancestry::AncestryChecker;
-use crate::config::Config;
+use crate::ancestry::{AncestryChecker, RootResolution};
+use crate::config::{Config, ConfigLayer, LockedSection, SignalTarget};
 use crate::error::SafeKillError;
 use crate::killer::{BatchKillResult, KillResult, ProcessKiller};
-use crate::port::PortDetector;
+use crate::limits::RateLimiter;
+use crate::open_files::OpenFileDetector;
+use crate::port::{
+    PortDetector, PortFilter, PortProcess, PortProtocol, PortSelection, dedup_by_pid,
+};
+use crate::privilege::{OwnershipCheck, PrivilegeContext, describe_uid};
 use crate::process_info::{ProcessInfo, ProcessInfoProvider};
 use crate::signal::Signal;
+use crate::unix_socket::{UnixSocketDetector, resolve_socket_path};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::Path;
 
 /// kill 許可判定の結果
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -17,12 +27,67 @@ pub enum KillPermission {
     Allowed,
     /// kill 許可（allowlist に含まれるプロセス）
     AllowedByAllowlist,
-    /// kill 拒否（denylist に含まれるプロセス）
-    DeniedByDenylist(String),
+    /// kill 拒否（denylist に含まれるプロセス。どの層の denylist か）
+    DeniedByDenylist(String, ConfigLayer),
     /// kill 拒否（root の子孫ではない）
     DeniedNotDescendant,
     /// kill 拒否（自プロセスまたは親プロセスの kill）
     DeniedSuicidePrevention,
+    /// kill 拒否（safe-kill の祖先チェーン上のプロセス）
+    DeniedAncestor,
+    /// kill 拒否（起動ユーザー以外が所有するプロセス。値は対象の実 UID）
+    DeniedNotOwner(Option<u32>),
+}
+
+/// `safe-kill ports` で表示する 1 ソケット分の情報
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PortHolder {
+    /// ポート番号
+    pub port: u16,
+    /// プロトコル
+    pub protocol: PortProtocol,
+    /// バインドアドレス
+    pub address: IpAddr,
+    /// 保持しているプロセスの PID
+    pub pid: u32,
+    /// プロセス名（取得できない場合は "pid:<pid>"）
+    pub name: String,
+    /// コマンドライン
+    pub command: Vec<String>,
+    /// `[allowed_ports]` のエントリ（プロトコル・アドレスを含む）に一致するか
+    pub allowed: bool,
+    /// `--port` で終了できるか
+    pub killable: bool,
+    /// 終了できない理由（終了できる場合は `None`）
+    pub reason: Option<String>,
+}
+
+/// kill 前の確認（`--interactive` など）に渡す候補
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KillCandidate {
+    /// 対象プロセス
+    pub process: ProcessInfo,
+    /// シグナルを送った場合の結果のプレビュー（dry-run と同じ内容）
+    pub preview: KillResult,
+}
+
+/// kill 候補に対する確認の結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Approval {
+    /// すべての候補にシグナルを送る
+    All,
+    /// 指定 PID の候補にだけ送る（それ以外は `Aborted` として結果に記録する）
+    Only(Vec<u32>),
+}
+
+impl Approval {
+    /// 指定 PID への送信が承認されているか
+    fn approves(&self, pid: u32) -> bool {
+        match self {
+            Approval::All => true,
+            Approval::Only(pids) => pids.contains(&pid),
+        }
+    }
 }
 
 impl KillPermission {
@@ -39,6 +104,16 @@ impl KillPermission {
         !self.is_allowed()
     }
 
+    /// `[signals]` のどの区分のルールを適用するかを返す
+    ///
+    /// 拒否系では kill に至らないため使われないが、便宜上子孫の区分を返す。
+    fn signal_target(&self) -> SignalTarget {
+        match self {
+            KillPermission::AllowedByAllowlist => SignalTarget::Allowlisted,
+            _ => SignalTarget::Descendant,
+        }
+    }
+
     /// 拒否系の判定結果を、対応する `SafeKillError` に変換する
     ///
     /// `pid` は対象 PID、`name` は対象プロセスの表示名。
@@ -47,13 +122,19 @@ impl KillPermission {
     /// 万一許可系が渡された場合は防御的に `SystemError` を返す（fail-closed）。
     fn to_error(&self, pid: u32, name: &str) -> SafeKillError {
         match self {
-            KillPermission::DeniedByDenylist(denied_name) => {
-                SafeKillError::Denylisted(denied_name.clone())
+            KillPermission::DeniedByDenylist(denied_name, layer) => {
+                SafeKillError::Denylisted(denied_name.clone(), layer.clone())
             }
             KillPermission::DeniedNotDescendant => {
                 SafeKillError::NotDescendant(pid, name.to_string())
             }
             KillPermission::DeniedSuicidePrevention => SafeKillError::SuicidePrevention(pid),
+            KillPermission::DeniedAncestor => {
+                SafeKillError::AncestorProtected(pid, name.to_string())
+            }
+            KillPermission::DeniedNotOwner(uid) => {
+                SafeKillError::NotOwned(pid, name.to_string(), describe_uid(*uid))
+            }
             KillPermission::Allowed | KillPermission::AllowedByAllowlist => {
                 SafeKillError::SystemError("Unexpected permission".to_string())
             }
@@ -68,6 +149,12 @@ pub struct PolicyEngine {
     killer: ProcessKiller,
     provider: ProcessInfoProvider,
     port_detector: PortDetector,
+    socket_detector: UnixSocketDetector,
+    file_detector: OpenFileDetector,
+    privilege: PrivilegeContext,
+    ownership: Option<OwnershipCheck>,
+    port_ownership: Option<OwnershipCheck>,
+    limiter: Option<RateLimiter>,
 }
 
 impl PolicyEngine {
@@ -76,9 +163,17 @@ impl PolicyEngine {
         config.merge_defaults();
 
         let provider = ProcessInfoProvider::new();
-        let ancestry = AncestryChecker::new(ProcessInfoProvider::new());
+        let ancestry =
+            AncestryChecker::with_root_config(ProcessInfoProvider::new(), config.root.as_ref());
         let killer = ProcessKiller::new();
         let port_detector = PortDetector::new();
+        let privilege = PrivilegeContext::detect();
+        let ownership = OwnershipCheck::from_config(config.ownership.as_ref(), &privilege);
+        let port_ownership = Self::port_ownership_for(&config, &privilege, ownership.as_ref());
+        let limiter = config.limits.clone().map(|limits| {
+            RateLimiter::new(limits, RateLimiter::state_path(), ancestry.root_pid())
+                .attributed_to(config.section_layer(LockedSection::Limits))
+        });
 
         Self {
             config,
@@ -86,12 +181,67 @@ impl PolicyEngine {
             killer,
             provider,
             port_detector,
+            socket_detector: UnixSocketDetector::new(),
+            file_detector: OpenFileDetector::new(),
+            privilege,
+            ownership,
+            port_ownership,
+            limiter,
+        }
+    }
+
+    /// ポート指定 kill に適用する所有者チェックを決める
+    ///
+    /// 特権実行時は `[privileged] port_other_users = true` でない限り、
+    /// `[ownership] users` を無視して起動ユーザーのプロセスだけに限定する。
+    fn port_ownership_for(
+        config: &Config,
+        privilege: &PrivilegeContext,
+        ownership: Option<&OwnershipCheck>,
+    ) -> Option<OwnershipCheck> {
+        let port_other_users = config
+            .privileged
+            .as_ref()
+            .is_some_and(|privileged| privileged.port_other_users);
+        if privilege.is_elevated() && !port_other_users {
+            return Some(OwnershipCheck::invoking_user_only(privilege));
         }
+        ownership.cloned()
+    }
+
+    /// 特権付き実行時に `[privileged]` 設定があるか検証する
+    ///
+    /// euid 0 や CAP_KILL で実行されているのに `[privileged]` がなければ、
+    /// kill 系の操作を始める前に `PrivilegedPolicyRequired` で拒否する。
+    pub fn ensure_privileged_policy(&self) -> Result<(), SafeKillError> {
+        match self.privilege.elevation_reason() {
+            Some(reason) if self.config.privileged.is_none() => {
+                Err(SafeKillError::PrivilegedPolicyRequired(reason.to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// allowlist による ancestry チェックのバイパスが有効か
+    ///
+    /// 特権実行時は `[privileged] allowlist_bypass = true` のときだけ有効。
+    fn is_allowlist_bypass_enabled(&self) -> bool {
+        !self.privilege.is_elevated()
+            || self
+                .config
+                .privileged
+                .as_ref()
+                .is_some_and(|privileged| privileged.allowlist_bypass)
     }
 
     /// デフォルト設定で PolicyEngine を生成する
+    ///
+    /// 設定エラーはパニックするため、エラーを扱う場合は `try_with_defaults` を使う。
+    #[deprecated(
+        note = "use `PolicyEngine::try_with_defaults`, which returns config errors instead of panicking"
+    )]
     pub fn with_defaults() -> Self {
-        Self::new(Config::load())
+        Self::new(Config::try_load().unwrap_or_else(|e| panic!("{}", e)))
     }
 
     /// 設定ファイルエラーを呼び出し元へ返して PolicyEngine を生成する
@@ -104,6 +254,8 @@ impl PolicyEngine {
         self.provider.refresh();
         self.ancestry.refresh();
         self.port_detector.refresh();
+        self.socket_detector.refresh();
+        self.file_detector.refresh();
     }
 
     /// プロセスを kill 可能か判定する
@@ -114,21 +266,31 @@ impl PolicyEngine {
         }
 
         // 2. denylist チェック（2番目の優先度）
-        if self.config.is_denied(&process.name) {
-            return KillPermission::DeniedByDenylist(process.name.clone());
+        if let Some(layer) = self.config.denylist_layer(&process.name) {
+            return KillPermission::DeniedByDenylist(process.name.clone(), layer);
+        }
+
+        // 3. 祖先チェーン保護（エージェント本体などを allowlist 経由でも kill させない）
+        if self.ancestry.is_ancestor_of_current(process.pid) {
+            return KillPermission::DeniedAncestor;
+        }
+
+        // 4. 所有者チェック（特権実行時は常に有効。allowlist より優先）
+        if !self.is_owner_permitted(process) {
+            return KillPermission::DeniedNotOwner(process.uid);
         }
 
-        // 3. 信頼ルート自体は子孫プロセスではないため保護する
+        // 5. 信頼ルート自体は子孫プロセスではないため保護する
         if process.pid == self.ancestry.root_pid() {
             return KillPermission::DeniedNotDescendant;
         }
 
-        // 4. allowlist チェック（ancestry チェックをバイパス）
-        if self.config.is_allowed(&process.name) {
+        // 6. allowlist チェック（ancestry チェックをバイパス。特権実行時は既定で無効）
+        if self.is_allowlist_bypass_enabled() && self.config.is_allowed(&process.name) {
             return KillPermission::AllowedByAllowlist;
         }
 
-        // 5. ancestry チェック（デフォルトのチェック）
+        // 7. ancestry チェック（デフォルトのチェック）
         if self.ancestry.is_descendant(process.pid) {
             return KillPermission::Allowed;
         }
@@ -157,13 +319,13 @@ impl PolicyEngine {
         // 許可判定
         let permission = self.can_kill(&process);
         if permission.is_allowed() {
+            // 対象の許可理由に応じた [signals] の制限を確認する
+            self.check_signal_allowed(signal, permission.signal_target())?;
             // 判定後・kill 前に、自殺防止（最新の親 PID 解決）と PID 再利用検出を
             // 最終ガードとしてまとめて再検証する。
             // dry-run でも、ユーザーへの誤った成功表示を避けるために検証する。
             self.verify_final_safety_before_kill(&process)?;
-            Ok(self
-                .killer
-                .kill_with_result(pid, &process.name, signal, dry_run))
+            Ok(self.send_signal(pid, &process.name, signal, dry_run))
         } else {
             Err(permission.to_error(pid, &process.name))
         }
@@ -181,8 +343,8 @@ impl PolicyEngine {
     ///   された場合は検出できない。完全に閉じるには Linux の `pidfd_open` +
     ///   `pidfd_send_signal` のような、PID ではなくプロセス実体に結びついた
     ///   識別子が必要。
-    /// - `start_time` は秒精度のため、同一秒内に同名プロセスへ PID が再利用
-    ///   された場合は検出できない。実用上は極めて稀。
+    /// - Linux 以外では `start_ticks` を取得できず `start_time` は秒精度のため、
+    ///   同一秒内に同名プロセスへ PID が再利用された場合は検出できない。実用上は極めて稀。
     fn verify_identity_before_kill(&self, expected: &ProcessInfo) -> Result<(), SafeKillError> {
         let fresh = ProcessInfoProvider::fetch_fresh(expected.pid)
             .ok_or(SafeKillError::ProcessNotFound(expected.pid))?;
@@ -244,23 +406,72 @@ impl PolicyEngine {
         Ok(())
     }
 
-    /// kill 直前の最終安全検証（自殺防止 + プロセス同一性）
+    /// kill 直前の祖先チェーン最終ガード
+    ///
+    /// 判定時のスナップショット以降に再ペアレントが起きても祖先を kill しないよう、
+    /// 現在プロセスから PID 1 までの親を fresh に辿り直して対象と照合する。
+    /// 途中でプロセス情報が取れなくなった場合は、それ以上の祖先は存在しないものとして扱う
+    /// （直近の親は `verify_not_suicide_before_kill` が fail-closed で保護済み）。
+    fn verify_not_ancestor_before_kill(expected: &ProcessInfo) -> Result<(), SafeKillError> {
+        let current_pid = ProcessInfoProvider::current_pid();
+        let is_ancestor = AncestryChecker::is_ancestor_with(current_pid, expected.pid, |pid| {
+            ProcessInfoProvider::fetch_fresh(pid).map(|info| info.parent_pid)
+        });
+
+        if is_ancestor {
+            return Err(SafeKillError::AncestorProtected(
+                expected.pid,
+                expected.name.clone(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// kill 直前の最終安全検証（自殺防止 + 祖先保護 + プロセス同一性）
     ///
     /// signal 送信直前の最終ガードとして、以下を fresh な OS 情報で再検証し
     /// fail-closed を保証する:
     /// 1. 自殺防止（最新の親 PID 解決による自プロセス・親プロセス保護）
-    /// 2. PID 再利用検出（`pid + start_time + name` の同一性）
+    /// 2. 祖先チェーン保護（最新の親を PID 1 まで辿り直す）
+    /// 3. PID 再利用検出（`pid + start_time + name` の同一性）
     fn verify_final_safety_before_kill(&self, expected: &ProcessInfo) -> Result<(), SafeKillError> {
         Self::verify_not_suicide_before_kill(expected.pid)?;
+        Self::verify_not_ancestor_before_kill(expected)?;
         self.verify_identity_before_kill(expected)
     }
 
+    /// `[limits]` の上限を確認してからシグナルを送る
+    ///
+    /// すべての判定と最終検証を通過した後にだけ呼ぶ。dry-run は送信として数えない。
+    fn send_signal(&self, pid: u32, name: &str, signal: Signal, dry_run: bool) -> KillResult {
+        if !dry_run {
+            if let Some(Err(err)) = self.limiter.as_ref().map(|l| l.acquire(signal)) {
+                return KillResult::failure(pid, name, &err);
+            }
+        }
+        self.killer.kill_with_result(pid, name, signal, dry_run)
+    }
+
     /// プロセス名を指定して kill する
     pub fn kill_by_name(
         &self,
         name: &str,
         signal: Signal,
         dry_run: bool,
+    ) -> Result<BatchKillResult, SafeKillError> {
+        self.kill_by_name_confirmed(name, signal, dry_run, |_| Ok(Approval::All))
+    }
+
+    /// プロセス名で kill する前に、kill 可能な候補を `confirm` で確認する
+    ///
+    /// `confirm` が承認しなかった候補にはシグナルを送らず、`Aborted` として結果に記録する。
+    /// `confirm` がエラーを返した場合は何も送らずにそのエラーを返す。
+    pub fn kill_by_name_confirmed(
+        &self,
+        name: &str,
+        signal: Signal,
+        dry_run: bool,
+        confirm: impl FnOnce(&[KillCandidate]) -> Result<Approval, SafeKillError>,
     ) -> Result<BatchKillResult, SafeKillError> {
         let processes = self.provider.find_by_name(name);
 
@@ -268,18 +479,119 @@ impl PolicyEngine {
             return Err(SafeKillError::ProcessNameNotFound(name.to_string()));
         }
 
+        let approval = confirm(&self.candidates(&processes, signal))?;
+        Ok(self.kill_matched(processes, signal, dry_run, &approval))
+    }
+
+    /// カレントディレクトリが指定ディレクトリ配下にあるプロセスを kill する
+    ///
+    /// 各プロセスには `can_kill` による通常の判定（ancestry・denylist など）を適用する。
+    /// safe-kill 自身とその祖先（エージェントのシェルなど）は同じディレクトリで
+    /// 動いていることが多いため、結果に含めずに除外する。
+    /// シグナルを送る前に、kill 可能な候補を `confirm` に渡す（`kill_by_name_confirmed` と同じ）。
+    pub fn kill_by_cwd(
+        &self,
+        dir: &Path,
+        signal: Signal,
+        dry_run: bool,
+        confirm: impl FnOnce(&[KillCandidate]) -> Result<Approval, SafeKillError>,
+    ) -> Result<BatchKillResult, SafeKillError> {
+        let dir = dir
+            .canonicalize()
+            .map_err(|_| SafeKillError::NoProcessInCwd(dir.display().to_string()))?;
+
+        let processes: Vec<ProcessInfo> = self
+            .provider
+            .find_by_cwd(&dir)
+            .into_iter()
+            .filter(|process| {
+                !matches!(
+                    self.can_kill(process),
+                    KillPermission::DeniedSuicidePrevention | KillPermission::DeniedAncestor
+                )
+            })
+            .collect();
+        if processes.is_empty() {
+            return Err(SafeKillError::NoProcessInCwd(dir.display().to_string()));
+        }
+
+        let approval = confirm(&self.candidates(&processes, signal))?;
+        Ok(self.kill_matched(processes, signal, dry_run, &approval))
+    }
+
+    /// 一致したプロセスのうち、`can_kill` と `[signals]` の判定を通過するものを候補にする
+    fn candidates(&self, processes: &[ProcessInfo], signal: Signal) -> Vec<KillCandidate> {
+        processes
+            .iter()
+            .filter(|process| {
+                let permission = self.can_kill(process);
+                permission.is_allowed()
+                    && self
+                        .check_signal_allowed(signal, permission.signal_target())
+                        .is_ok()
+            })
+            .map(|process| KillCandidate {
+                process: process.clone(),
+                preview: KillResult::dry_run(process.pid, &process.name, signal),
+            })
+            .collect()
+    }
+
+    /// 資源（ポート・ソケット・パス）の保持プロセスのうち、シグナルを送れる見込みのものを候補にする
+    ///
+    /// `bypass_ancestry` なら `can_kill_for_port` で、そうでなければ `candidates` と同じく
+    /// `can_kill` と `[signals]` で判定する（`bypass_ancestry` のシグナルは呼び出し側で確認済み）。
+    fn holder_candidates(
+        &self,
+        pids: impl IntoIterator<Item = u32>,
+        signal: Signal,
+        bypass_ancestry: bool,
+    ) -> Vec<KillCandidate> {
+        let processes: Vec<ProcessInfo> = pids
+            .into_iter()
+            .filter_map(|pid| self.provider.get(pid))
+            .collect();
+        if !bypass_ancestry {
+            return self.candidates(&processes, signal);
+        }
+        processes
+            .into_iter()
+            .filter(|process| {
+                self.can_kill_for_port(process.pid, &process.name)
+                    .is_allowed()
+            })
+            .map(|process| KillCandidate {
+                preview: KillResult::dry_run(process.pid, &process.name, signal),
+                process,
+            })
+            .collect()
+    }
+
+    /// 一致したプロセスそれぞれに `can_kill` の判定を適用して kill する
+    ///
+    /// `approval` で承認されていないプロセスにはシグナルを送らない。
+    fn kill_matched(
+        &self,
+        processes: Vec<ProcessInfo>,
+        signal: Signal,
+        dry_run: bool,
+        approval: &Approval,
+    ) -> BatchKillResult {
         let mut batch_result = BatchKillResult::new();
 
         for process in processes {
             let permission = self.can_kill(&process);
 
-            let result = if permission.is_allowed() {
-                // kill 直前の最終ガード（自殺防止の再確認 + PID 再利用検出）。
-                match self.verify_final_safety_before_kill(&process) {
-                    Ok(()) => {
-                        self.killer
-                            .kill_with_result(process.pid, &process.name, signal, dry_run)
-                    }
+            let result = if permission.is_allowed() && !approval.approves(process.pid) {
+                let error = SafeKillError::Aborted("skipped".to_string());
+                KillResult::failure(process.pid, &process.name, &error)
+            } else if permission.is_allowed() {
+                // [signals] の制限と kill 直前の最終ガード（自殺防止の再確認 + PID 再利用検出）。
+                let checked = self
+                    .check_signal_allowed(signal, permission.signal_target())
+                    .and_then(|()| self.verify_final_safety_before_kill(&process));
+                match checked {
+                    Ok(()) => self.send_signal(process.pid, &process.name, signal, dry_run),
                     Err(err) => KillResult::failure(process.pid, &process.name, &err),
                 }
             } else {
@@ -291,7 +603,7 @@ impl PolicyEngine {
             batch_result.add(result);
         }
 
-        Ok(batch_result)
+        batch_result
     }
 
     /// ポートを指定してプロセスを kill する
@@ -305,76 +617,316 @@ impl PolicyEngine {
         signal: Signal,
         dry_run: bool,
     ) -> Result<BatchKillResult, SafeKillError> {
-        // 1. config でポートが許可されているか確認
-        self.config.check_port_allowed(port)?;
+        self.kill_by_port_filtered(port, &PortFilter::default(), signal, dry_run)
+    }
+
+    /// プロトコル・アドレスファミリー・バインドアドレスで絞り込んでポート指定 kill する
+    ///
+    /// `filter` に一致するソケットのうち、`[allowed_ports]` のエントリ
+    /// （プロトコル・アドレス指定を含む）に一致するものだけを対象にする。
+    pub fn kill_by_port_filtered(
+        &self,
+        port: u16,
+        filter: &PortFilter,
+        signal: Signal,
+        dry_run: bool,
+    ) -> Result<BatchKillResult, SafeKillError> {
+        self.kill_by_ports(
+            &PortSelection::single(port),
+            filter,
+            signal,
+            dry_run,
+            |_| Ok(Approval::All),
+        )
+    }
 
-        // 2. ポート上のプロセスを検索
-        let port_processes = self.port_detector.find_by_port(port)?;
+    /// ポートの範囲・リストを指定して、待ち受けているプロセスをまとめて kill する
+    ///
+    /// 指定した範囲のすべてのポートが `[allowed_ports]` で許可されている必要がある。
+    /// ソケット一覧の取得は 1 回だけ行い、結果はポートごとに `KillResult::port` を付けて
+    /// 1 つの `BatchKillResult` にまとめる。複数のポートを保持する PID は最小のポートで 1 回だけ扱う。
+    /// シグナルを送る前に、kill 可能な候補を `confirm` に渡す（`kill_by_name_confirmed` と同じ）。
+    pub fn kill_by_ports(
+        &self,
+        ports: &PortSelection,
+        filter: &PortFilter,
+        signal: Signal,
+        dry_run: bool,
+        confirm: impl FnOnce(&[KillCandidate]) -> Result<Approval, SafeKillError>,
+    ) -> Result<BatchKillResult, SafeKillError> {
+        // 1. config でポートとシグナルが許可されているか確認
+        self.config.check_ports_allowed(ports)?;
+        self.check_signal_allowed(signal, SignalTarget::Port)?;
+
+        // 2. ポート上のソケットを検索し、config のプロトコル・アドレス指定で絞り込む
+        let sockets = self.port_detector.find_sockets_in(ports, filter)?;
+        if sockets.is_empty() {
+            return Err(match ports.as_single() {
+                Some(port) => SafeKillError::NoProcessOnPort(port),
+                None => SafeKillError::NoProcessOnPorts(ports.to_string()),
+            });
+        }
 
-        if port_processes.is_empty() {
-            return Err(SafeKillError::NoProcessOnPort(port));
+        let (allowed, rejected): (Vec<_>, Vec<_>) = sockets.into_iter().partition(|socket| {
+            self.config
+                .is_socket_allowed(socket.port, socket.protocol, &socket.address)
+        });
+        if allowed.is_empty() {
+            let socket = &rejected[0];
+            return Err(SafeKillError::PortNotAllowed {
+                port: socket.port,
+                hint: format!(
+                    "{} socket bound to {} does not match any [allowed_ports] entry",
+                    socket.protocol, socket.address
+                ),
+            });
         }
 
-        Ok(self.kill_port_processes(port, port_processes, signal, dry_run))
+        // `find_sockets_in` はポート順に返すため、PID ごとに最小のポートが残る
+        let mut targets = dedup_by_pid(allowed);
+        targets.sort_by_key(|p| (p.port, p.pid));
+
+        let approval =
+            confirm(&self.holder_candidates(targets.iter().map(|p| p.pid), signal, true))?;
+        Ok(self.kill_port_processes(targets, signal, dry_run, &approval))
     }
 
     /// 検出済みの `PortProcess` 一覧から kill を実行する内部ヘルパー
     ///
     /// `kill_by_port` の中身を切り出してテスト容易性を高めるために存在する。
-    /// 名前解決失敗時の fail-closed 挙動と、各 PID の kill 直前のポート保持再検証を担う。
+    /// 各 PID の kill 直前にポート保持を再検証する。
     fn kill_port_processes(
         &self,
-        port: u16,
         port_processes: Vec<crate::port::PortProcess>,
         signal: Signal,
         dry_run: bool,
+        approval: &Approval,
     ) -> BatchKillResult {
         let mut batch_result = BatchKillResult::new();
 
-        // 各プロセスに対して自殺防止と denylist チェックのみ適用
         for pp in port_processes {
-            // プロセス情報が取得できない PID は denylist 名前一致を回避するために
-            // 即座に失敗扱いにする（fail-closed）。
-            // PortDetector のフォールバック名（"pid:<pid>"）で denylist 判定すると
-            // 名前不明なプロセスがバイパスされてしまうため。
-            let Some(process) = self.provider.get(pp.pid) else {
-                let error = SafeKillError::ProcessNotFound(pp.pid);
-                batch_result.add(KillResult::failure(pp.pid, &pp.name, &error));
-                continue;
-            };
+            let approved = approval.approves(pp.pid);
+            let result =
+                self.kill_resource_holder(pp.pid, &pp.name, signal, dry_run, approved, || {
+                    if self
+                        .port_detector
+                        .pid_holds_socket(pp.pid, pp.port, pp.protocol, pp.address)
+                    {
+                        Ok(())
+                    } else {
+                        Err(SafeKillError::NoProcessOnPort(pp.port))
+                    }
+                });
+            batch_result.add(result.with_port(pp.port));
+        }
 
-            // 許可判定（自殺防止と denylist のみ）
-            let permission = self.can_kill_for_port(pp.pid, &process.name);
-
-            let result = if permission.is_allowed() {
-                // ポート kill 固有の TOCTOU 緩和は「保持確認 → 同一性確認 → kill」の順で行う。
-                // 1. ポート保持確認 (pid_holds_port): バッチ実行中に対象がポートを離した場合は kill しない。
-                //    取得失敗時は安全側に倒して fail-closed（NoProcessOnPort）。
-                // 2. 最終安全検証 (verify_final_safety_before_kill): 自殺防止（最新の親 PID 解決）と
-                //    `pid + start_time + name` の同一性を OS から取り直して再検証する。順序を最後に
-                //    することで、ポート確認に要する時間内に起きた再ペアレントや PID 再利用も検出できる。
-                // ポート指定 kill は ancestry をバイパスするため、PID/名前指定より TOCTOU リスクが高い。
-                if !self.port_detector.pid_holds_port(pp.pid, port, pp.protocol) {
-                    let err = SafeKillError::NoProcessOnPort(port);
-                    KillResult::failure(pp.pid, &process.name, &err)
-                } else {
-                    match self.verify_final_safety_before_kill(&process) {
-                        Ok(()) => {
-                            self.killer
-                                .kill_with_result(pp.pid, &process.name, signal, dry_run)
-                        }
-                        Err(err) => KillResult::failure(pp.pid, &process.name, &err),
+        batch_result
+    }
+
+    /// 設定で許可された資源（ポート・Unix ドメインソケット・パス）の保持プロセス 1 件を
+    /// ancestry チェックなしで kill する
+    ///
+    /// 名前解決失敗時の fail-closed 挙動と、kill 直前の保持再検証（`still_holds`）を担う。
+    /// 許可されたプロセスでも、確認で承認されていなければ（`approved` が false）送らない。
+    fn kill_resource_holder(
+        &self,
+        pid: u32,
+        detected_name: &str,
+        signal: Signal,
+        dry_run: bool,
+        approved: bool,
+        still_holds: impl FnOnce() -> Result<(), SafeKillError>,
+    ) -> KillResult {
+        // プロセス情報が取得できない PID は denylist 名前一致を回避するために
+        // 即座に失敗扱いにする（fail-closed）。
+        // 検出器のフォールバック名（"pid:<pid>"）で denylist 判定すると
+        // 名前不明なプロセスがバイパスされてしまうため。
+        let Some(process) = self.provider.get(pid) else {
+            return KillResult::failure(pid, detected_name, &SafeKillError::ProcessNotFound(pid));
+        };
+
+        // 許可判定（自殺防止と denylist のみ）
+        let permission = self.can_kill_for_port(pid, &process.name);
+        if !permission.is_allowed() {
+            let error = permission.to_error(pid, &process.name);
+            return KillResult::failure(pid, &process.name, &error);
+        }
+        if !approved {
+            let error = SafeKillError::Aborted("skipped".to_string());
+            return KillResult::failure(pid, &process.name, &error);
+        }
+
+        // ソケット指定 kill 固有の TOCTOU 緩和は「保持確認 → 同一性確認 → kill」の順で行う。
+        // 1. 保持確認 (still_holds): バッチ実行中に対象が検出時のソケットを離した場合は送らない。
+        //    取得失敗時は安全側に倒して fail-closed（NoProcessOnPort / NoProcessOnSocket）。
+        // 2. 最終安全検証 (verify_final_safety_before_kill): 自殺防止（最新の親 PID 解決）と
+        //    `pid + start_time + name` の同一性を OS から取り直して再検証する。順序を最後に
+        //    することで、保持確認に要する時間内に起きた再ペアレントや PID 再利用も検出できる。
+        // ソケット指定 kill は ancestry をバイパスするため、PID/名前指定より TOCTOU リスクが高い。
+        if let Err(err) = still_holds() {
+            return KillResult::failure(pid, &process.name, &err);
+        }
+        match self.verify_final_safety_before_kill(&process) {
+            Ok(()) => self.send_signal(pid, &process.name, signal, dry_run),
+            Err(err) => KillResult::failure(pid, &process.name, &err),
+        }
+    }
+
+    /// Unix ドメインソケットのパスを指定してプロセスを kill する
+    ///
+    /// パスは絶対パス化・シンボリックリンク解決をしてから `[allowed_sockets]` の glob と照合する。
+    /// 許可判定はポート指定 kill と同じ（ancestry をバイパスし、自殺防止・denylist・
+    /// 祖先保護・所有者チェックのみ適用）で、シグナルは `[signals.port]` の規則に従う。
+    /// シグナルを送る前に、kill 可能な候補を `confirm` に渡す（`kill_by_name_confirmed` と同じ）。
+    pub fn kill_by_socket(
+        &self,
+        path: &Path,
+        signal: Signal,
+        dry_run: bool,
+        confirm: impl FnOnce(&[KillCandidate]) -> Result<Approval, SafeKillError>,
+    ) -> Result<BatchKillResult, SafeKillError> {
+        // 1. config でパスとシグナルが許可されているか確認
+        let path = resolve_socket_path(path)?;
+        self.config.check_socket_path_allowed(&path)?;
+        self.check_signal_allowed(signal, SignalTarget::Port)?;
+
+        // 2. ソケットを保持するプロセスを検索（ソケット表は 1 回だけ読み、再確認にも使う）
+        let inodes = self.socket_detector.bound_inodes(&path)?;
+        let holders = self.socket_detector.find_by_inodes(&path, &inodes);
+        if holders.is_empty() {
+            return Err(SafeKillError::NoProcessOnSocket(path.display().to_string()));
+        }
+
+        let approval =
+            confirm(&self.holder_candidates(holders.iter().map(|h| h.pid), signal, true))?;
+        let mut batch_result = BatchKillResult::new();
+        for holder in holders {
+            let approved = approval.approves(holder.pid);
+            let result = self.kill_resource_holder(
+                holder.pid,
+                &holder.name,
+                signal,
+                dry_run,
+                approved,
+                || {
+                    if self.socket_detector.pid_holds_any(holder.pid, &inodes) {
+                        Ok(())
+                    } else {
+                        Err(SafeKillError::NoProcessOnSocket(
+                            holder.path.display().to_string(),
+                        ))
                     }
+                },
+            );
+            batch_result.add(result);
+        }
+
+        Ok(batch_result)
+    }
+
+    /// 指定パス（ディレクトリなら配下）を開いている・マップしているプロセスを kill する
+    ///
+    /// 通常は `can_kill` による ancestry チェックを適用する。パスが `[allowed_paths]` に
+    /// 一致する場合はポート指定 kill と同じく ancestry をバイパスし、シグナルは
+    /// `[signals.port]` の規則に従う。どちらの場合も kill 直前に保持を再確認する。
+    /// シグナルを送る前に、kill 可能な候補を `confirm` に渡す（`kill_by_name_confirmed` と同じ）。
+    pub fn kill_by_holding(
+        &self,
+        path: &Path,
+        signal: Signal,
+        dry_run: bool,
+        confirm: impl FnOnce(&[KillCandidate]) -> Result<Approval, SafeKillError>,
+    ) -> Result<BatchKillResult, SafeKillError> {
+        let path = path
+            .canonicalize()
+            .map_err(|_| SafeKillError::NoProcessHoldingPath(path.display().to_string()))?;
+        let bypass_ancestry = self.config.is_path_allowed(&path);
+        if bypass_ancestry {
+            self.check_signal_allowed(signal, SignalTarget::Port)?;
+        }
+
+        let holders = self.file_detector.find_holders(&path)?;
+        if holders.is_empty() {
+            return Err(SafeKillError::NoProcessHoldingPath(
+                path.display().to_string(),
+            ));
+        }
+
+        let approval = confirm(&self.holder_candidates(
+            holders.iter().map(|h| h.pid),
+            signal,
+            bypass_ancestry,
+        ))?;
+        let mut batch_result = BatchKillResult::new();
+        for holder in holders {
+            let approved = approval.approves(holder.pid);
+            let still_holds = || {
+                if self.file_detector.pid_holds(holder.pid, &path) {
+                    Ok(())
+                } else {
+                    Err(SafeKillError::NoProcessHoldingPath(
+                        path.display().to_string(),
+                    ))
                 }
-            } else {
-                let error = permission.to_error(pp.pid, &process.name);
-                KillResult::failure(pp.pid, &process.name, &error)
             };
 
+            let result = if bypass_ancestry {
+                self.kill_resource_holder(
+                    holder.pid,
+                    &holder.name,
+                    signal,
+                    dry_run,
+                    approved,
+                    still_holds,
+                )
+            } else {
+                self.kill_holder_of_descendant(
+                    holder.pid,
+                    &holder.name,
+                    signal,
+                    dry_run,
+                    approved,
+                    still_holds,
+                )
+            };
             batch_result.add(result);
         }
 
-        batch_result
+        Ok(batch_result)
+    }
+
+    /// 資源の保持プロセス 1 件を、PID 指定と同じ ancestry 込みの判定で kill する
+    fn kill_holder_of_descendant(
+        &self,
+        pid: u32,
+        detected_name: &str,
+        signal: Signal,
+        dry_run: bool,
+        approved: bool,
+        still_holds: impl FnOnce() -> Result<(), SafeKillError>,
+    ) -> KillResult {
+        let Some(process) = self.provider.get(pid) else {
+            return KillResult::failure(pid, detected_name, &SafeKillError::ProcessNotFound(pid));
+        };
+
+        let permission = self.can_kill(&process);
+        if !permission.is_allowed() {
+            let error = permission.to_error(pid, &process.name);
+            return KillResult::failure(pid, &process.name, &error);
+        }
+        if !approved {
+            let error = SafeKillError::Aborted("skipped".to_string());
+            return KillResult::failure(pid, &process.name, &error);
+        }
+
+        let checked = self
+            .check_signal_allowed(signal, permission.signal_target())
+            .and_then(|()| still_holds())
+            .and_then(|()| self.verify_final_safety_before_kill(&process));
+        match checked {
+            Ok(()) => self.send_signal(pid, &process.name, signal, dry_run),
+            Err(err) => KillResult::failure(pid, &process.name, &err),
+        }
     }
 
     /// ポート指定 kill 用のプロセス kill 可否判定
@@ -382,7 +934,9 @@ impl PolicyEngine {
     /// 以下の簡略化されたチェックのみ適用:
     /// 1. 自殺防止（自プロセス・親プロセスの kill 不可）
     /// 2. denylist チェック
-    /// 3. root PID 保護（信頼ルート自体の kill 不可）
+    /// 3. 祖先チェーン保護（safe-kill の祖先の kill 不可）
+    /// 4. 所有者チェック（有効時のみ。起動ユーザー以外のプロセスの kill 不可）
+    /// 5. root PID 保護（信頼ルート自体の kill 不可）
     ///
     /// ancestry 走査や allowlist は適用しない（ポート指定 kill 用）。
     fn can_kill_for_port(&self, pid: u32, name: &str) -> KillPermission {
@@ -392,11 +946,24 @@ impl PolicyEngine {
         }
 
         // 2. denylist チェック
-        if self.config.is_denied(name) {
-            return KillPermission::DeniedByDenylist(name.to_string());
+        if let Some(layer) = self.config.denylist_layer(name) {
+            return KillPermission::DeniedByDenylist(name.to_string(), layer);
+        }
+
+        // 3. 祖先チェーン保護
+        if self.ancestry.is_ancestor_of_current(pid) {
+            return KillPermission::DeniedAncestor;
+        }
+
+        // 4. 所有者チェック（プロセス情報が取れない場合は所有者不明として拒否）
+        if let Some(ownership) = &self.port_ownership {
+            let owner = self.provider.get(pid);
+            if !owner.as_ref().is_some_and(|info| ownership.permits(info)) {
+                return KillPermission::DeniedNotOwner(owner.and_then(|info| info.uid));
+            }
         }
 
-        // 3. 信頼ルート自体はポート指定でも終了対象にしない
+        // 5. 信頼ルート自体はポート指定でも終了対象にしない
         if pid == self.ancestry.root_pid() {
             return KillPermission::DeniedNotDescendant;
         }
@@ -405,6 +972,120 @@ impl PolicyEngine {
         KillPermission::Allowed
     }
 
+    /// `[signals]` 設定で対象の区分へのシグナル送信が許可されているか検証する
+    fn check_signal_allowed(
+        &self,
+        signal: Signal,
+        target: SignalTarget,
+    ) -> Result<(), SafeKillError> {
+        match &self.config.signals {
+            Some(signals) if !signals.permits(signal, target) => {
+                Err(SafeKillError::SignalNotAllowed(
+                    signal.name().to_string(),
+                    target.as_str().to_string(),
+                    self.config.section_layer(LockedSection::Signals),
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// シグナルが対象の区分で許可されているか判定する（`signals` サブコマンドの表示用）
+    pub fn is_signal_allowed(&self, signal: Signal, target: SignalTarget) -> bool {
+        self.check_signal_allowed(signal, target).is_ok()
+    }
+
+    /// 所有者チェックを通過するか判定する（チェック無効時は常に true）
+    fn is_owner_permitted(&self, process: &ProcessInfo) -> bool {
+        self.ownership
+            .as_ref()
+            .is_none_or(|ownership| ownership.permits(process))
+    }
+
+    /// 所有者チェックが有効な場合、その内容を返す
+    pub fn ownership(&self) -> Option<&OwnershipCheck> {
+        self.ownership.as_ref()
+    }
+
+    /// 待ち受け中のソケットと、それぞれを `--port` で終了できるかを一覧する
+    ///
+    /// `all` が false の場合は `[allowed_ports]` の範囲に含まれるポートのみを返す。
+    /// true の場合は許可されていないポートも（終了不可として）含める。
+    pub fn list_ports(&self, all: bool) -> Result<Vec<PortHolder>, SafeKillError> {
+        let sockets = self.port_detector.list_sockets(&PortFilter::default())?;
+
+        Ok(sockets
+            .into_iter()
+            .filter(|socket| all || self.config.is_port_allowed(socket.port))
+            .map(|socket| self.describe_port_holder(socket))
+            .collect())
+    }
+
+    /// 待ち受け中のポートを PID ごとに昇順で返す（確認時の表示用）
+    pub fn listening_ports(&self) -> Result<HashMap<u32, Vec<u16>>, SafeKillError> {
+        let mut ports: HashMap<u32, Vec<u16>> = HashMap::new();
+        for socket in self.port_detector.list_sockets(&PortFilter::default())? {
+            ports.entry(socket.pid).or_default().push(socket.port);
+        }
+        for list in ports.values_mut() {
+            list.sort_unstable();
+            list.dedup();
+        }
+        Ok(ports)
+    }
+
+    /// 信頼ルートの子孫が待ち受け中のソケットを返す（`init --interactive` の候補用）
+    ///
+    /// denylist に含まれるプロセスのソケットは含めない。
+    pub fn session_listeners(&self) -> Result<Vec<PortProcess>, SafeKillError> {
+        Ok(self
+            .port_detector
+            .list_sockets(&PortFilter::default())?
+            .into_iter()
+            .filter(|socket| self.ancestry.is_descendant(socket.pid))
+            .filter(|socket| !self.config.is_denied(&socket.name))
+            .collect())
+    }
+
+    /// ソケット 1 件について、ポート指定 kill の可否と理由を判定する
+    fn describe_port_holder(&self, socket: PortProcess) -> PortHolder {
+        let process = self.provider.get(socket.pid);
+        let allowed = self
+            .config
+            .is_socket_allowed(socket.port, socket.protocol, &socket.address);
+
+        let denial = if !self.config.is_port_allowed(socket.port) {
+            Some(format!("Port {} is not in [allowed_ports]", socket.port))
+        } else if !allowed {
+            Some(format!(
+                "{} socket bound to {} does not match any [allowed_ports] entry",
+                socket.protocol, socket.address
+            ))
+        } else {
+            match &process {
+                None => Some(SafeKillError::ProcessNotFound(socket.pid).to_string()),
+                Some(info) => {
+                    let permission = self.can_kill_for_port(socket.pid, &info.name);
+                    permission
+                        .is_denied()
+                        .then(|| permission.to_error(socket.pid, &info.name).to_string())
+                }
+            }
+        };
+
+        PortHolder {
+            port: socket.port,
+            protocol: socket.protocol,
+            address: socket.address,
+            pid: socket.pid,
+            name: socket.name,
+            command: process.map(|info| info.cmd).unwrap_or_default(),
+            allowed,
+            killable: denial.is_none(),
+            reason: denial,
+        }
+    }
+
     /// kill 可能な全プロセスを一覧する
     pub fn list_killable(&self) -> Vec<ProcessInfo> {
         self.provider
@@ -419,6 +1100,32 @@ impl PolicyEngine {
         self.ancestry.root_pid()
     }
 
+    /// 信頼ルートの解決結果（選ばれた理由を含む）を取得する
+    pub fn root_resolution(&self) -> &RootResolution {
+        self.ancestry.root_resolution()
+    }
+
+    /// 現在プロセスから信頼ルートまでの親チェーンを取得する
+    ///
+    /// 先頭が現在プロセス、末尾が信頼ルート。信頼ルートが祖先チェーン上に
+    /// ない場合（`SAFE_KILL_ROOT_PID` で無関係な PID を指定した等）は、
+    /// たどれたところまでを返す。
+    pub fn root_chain(&self) -> Vec<ProcessInfo> {
+        let root_pid = self.ancestry.root_pid();
+        let mut chain = Vec::new();
+        for info in self
+            .ancestry
+            .ancestor_chain(ProcessInfoProvider::current_pid())
+        {
+            let is_root = info.pid == root_pid;
+            chain.push(info);
+            if is_root {
+                break;
+            }
+        }
+        chain
+    }
+
     /// 設定への参照を取得する
     pub fn config(&self) -> &Config {
         &self.config
@@ -426,9 +1133,10 @@ impl PolicyEngine {
 }
 
 #[cfg(test)]
+#[allow(deprecated)]
 mod tests {
     use super::*;
-    use crate::config::ProcessList;
+    use crate::config::{PrivilegedConfig, ProcessList, SignalRules, SignalsConfig};
 
     // KillPermission のテスト
     #[test]
@@ -445,7 +1153,7 @@ mod tests {
 
     #[test]
     fn test_kill_permission_denied_by_denylist() {
-        let perm = KillPermission::DeniedByDenylist("systemd".to_string());
+        let perm = KillPermission::DeniedByDenylist("systemd".to_string(), ConfigLayer::BuiltIn);
         assert!(!perm.is_allowed());
         assert!(perm.is_denied());
     }
@@ -468,10 +1176,10 @@ mod tests {
     // 各バリアントの変換結果をここで一元的に保証する。
     #[test]
     fn test_kill_permission_to_error_denylist() {
-        let perm = KillPermission::DeniedByDenylist("systemd".to_string());
+        let perm = KillPermission::DeniedByDenylist("systemd".to_string(), ConfigLayer::BuiltIn);
         assert_eq!(
             perm.to_error(1234, "systemd"),
-            SafeKillError::Denylisted("systemd".to_string())
+            SafeKillError::Denylisted("systemd".to_string(), ConfigLayer::BuiltIn)
         );
     }
 
@@ -485,6 +1193,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_kill_permission_denied_ancestor() {
+        assert!(!KillPermission::DeniedAncestor.is_allowed());
+        assert!(KillPermission::DeniedAncestor.is_denied());
+    }
+
+    #[test]
+    fn test_kill_permission_to_error_ancestor() {
+        let perm = KillPermission::DeniedAncestor;
+        assert_eq!(
+            perm.to_error(4321, "node"),
+            SafeKillError::AncestorProtected(4321, "node".to_string())
+        );
+    }
+
     #[test]
     fn test_kill_permission_to_error_suicide() {
         let perm = KillPermission::DeniedSuicidePrevention;
@@ -549,9 +1272,11 @@ mod tests {
         let config = Config {
             allowlist: Some(ProcessList {
                 processes: vec!["node".to_string()],
+                ..Default::default()
             }),
             denylist: None,
             allowed_ports: None,
+            ..Default::default()
         };
         let engine = PolicyEngine::new(config);
         assert!(engine.config().is_allowed("node"));
@@ -578,44 +1303,416 @@ mod tests {
             killer: ProcessKiller::new(),
             provider: ProcessInfoProvider::new(),
             port_detector: PortDetector::new(),
+            socket_detector: UnixSocketDetector::new(),
+            file_detector: OpenFileDetector::new(),
+            privilege: PrivilegeContext::from_ids(1000, 1000, None),
+            ownership: None,
+            port_ownership: None,
+            limiter: None,
         }
     }
 
-    // can_kill のテスト
-    #[test]
-    fn test_can_kill_self_denied() {
-        let engine = PolicyEngine::with_defaults();
-        let current_pid = ProcessInfoProvider::current_pid();
-
-        if let Some(process) = engine.provider.get(current_pid) {
-            let permission = engine.can_kill(&process);
-            assert_eq!(permission, KillPermission::DeniedSuicidePrevention);
-        }
+    /// safe-kill を起動したユーザーの UID（テスト用の架空プロセスの所有者に使う）
+    fn invoking_uid() -> u32 {
+        PrivilegeContext::detect().invoking_uid
     }
 
-    #[test]
-    fn test_can_kill_parent_denied() {
-        let engine = PolicyEngine::with_defaults();
-        let current_pid = ProcessInfoProvider::current_pid();
+    /// 特権実行時の制限を外し、通常ユーザーとして実行した場合の判定にする
+    ///
+    /// root で実行される CI では所有者チェックや allowlist バイパス無効化が常に働くため、
+    /// それらを検証対象としないテストでは非特権の実行ユーザー情報に差し替える。
+    fn unprivileged(mut engine: PolicyEngine) -> PolicyEngine {
+        engine.privilege = PrivilegeContext::from_ids(1000, 1000, None);
+        engine.ownership = None;
+        engine.port_ownership = None;
+        engine
+    }
+
+    /// 指定 UID のみを許可する所有者チェック付きのエンジンを生成する
+    fn engine_with_owner_uid(config: Config, owner_uid: u32) -> PolicyEngine {
+        let mut engine = engine_with_root_pid(config, u32::MAX);
+        let context = PrivilegeContext::from_ids(owner_uid, owner_uid, None);
+        engine.ownership = OwnershipCheck::from_config(
+            Some(&crate::config::OwnershipConfig {
+                enforce: true,
+                users: vec![],
+            }),
+            &context,
+        );
+        engine.port_ownership = engine.ownership.clone();
+        engine
+    }
 
-        if let Some(current) = engine.provider.get(current_pid) {
-            if let Some(parent_pid) = current.parent_pid {
-                if let Some(parent) = engine.provider.get(parent_pid) {
-                    let permission = engine.can_kill(&parent);
-                    assert_eq!(permission, KillPermission::DeniedSuicidePrevention);
-                }
-            }
+    fn process_owned_by(pid: u32, name: &str, uid: u32) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            parent_pid: Some(1),
+            name: name.to_string(),
+            cmd: vec![],
+            start_time: 0,
+            uid: Some(uid),
+            euid: Some(uid),
+            cwd: None,
+            start_ticks: None,
         }
     }
 
+    // 所有者チェックのテスト
     #[test]
-    fn test_can_kill_denylisted() {
+    fn test_can_kill_allowlisted_foreign_owner_denied() {
+        let config = Config {
+            allowlist: Some(ProcessList {
+                processes: vec!["worker".to_string()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let engine = engine_with_owner_uid(config, 1000);
+
+        let permission = engine.can_kill(&process_owned_by(99999, "worker", 1001));
+        assert_eq!(permission, KillPermission::DeniedNotOwner(Some(1001)));
+    }
+
+    #[test]
+    fn test_can_kill_allowlisted_own_process_allowed() {
+        let config = Config {
+            allowlist: Some(ProcessList {
+                processes: vec!["worker".to_string()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let engine = engine_with_owner_uid(config, 1000);
+
+        let permission = engine.can_kill(&process_owned_by(99999, "worker", 1000));
+        assert_eq!(permission, KillPermission::AllowedByAllowlist);
+    }
+
+    #[test]
+    fn test_can_kill_without_ownership_check_ignores_uid() {
+        let config = Config {
+            allowlist: Some(ProcessList {
+                processes: vec!["worker".to_string()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let engine = engine_with_root_pid(config, u32::MAX);
+
+        let permission = engine.can_kill(&process_owned_by(99999, "worker", 1001));
+        assert_eq!(permission, KillPermission::AllowedByAllowlist);
+    }
+
+    #[test]
+    fn test_can_kill_for_port_foreign_owner_denied() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("5")
+            .spawn()
+            .expect("sleep プロセスの起動に失敗");
+        let current_uid = nix::unistd::getuid().as_raw();
+        let engine = engine_with_owner_uid(Config::default(), current_uid.wrapping_add(1));
+
+        let permission = engine.can_kill_for_port(child.id(), "sleep");
+        assert_eq!(
+            permission,
+            KillPermission::DeniedNotOwner(Some(current_uid))
+        );
+
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
+    #[test]
+    fn test_can_kill_for_port_unknown_process_denied_when_ownership_active() {
+        let engine = engine_with_owner_uid(Config::default(), 1000);
+
+        let permission = engine.can_kill_for_port(999_999_999, "ghost");
+        assert_eq!(permission, KillPermission::DeniedNotOwner(None));
+    }
+
+    /// root として実行されている（起動ユーザーは指定 UID）エンジンを生成する
+    fn elevated_engine(config: Config, invoking_uid: u32) -> PolicyEngine {
+        let mut engine = engine_with_root_pid(config, u32::MAX);
+        let privilege = PrivilegeContext::from_ids(0, 0, Some(&invoking_uid.to_string()));
+        engine.ownership =
+            OwnershipCheck::from_config(engine.config.ownership.as_ref(), &privilege);
+        engine.port_ownership =
+            PolicyEngine::port_ownership_for(&engine.config, &privilege, engine.ownership.as_ref());
+        engine.privilege = privilege;
+        engine
+    }
+
+    fn allowlist_config(name: &str, privileged: Option<PrivilegedConfig>) -> Config {
+        Config {
+            allowlist: Some(ProcessList {
+                processes: vec![name.to_string()],
+                ..Default::default()
+            }),
+            privileged,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_ensure_privileged_policy_requires_section_when_elevated() {
+        let engine = elevated_engine(Config::default(), 1000);
+        assert_eq!(
+            engine.ensure_privileged_policy(),
+            Err(SafeKillError::PrivilegedPolicyRequired(
+                "effective UID 0".to_string()
+            ))
+        );
+
+        let engine = elevated_engine(
+            allowlist_config("worker", Some(PrivilegedConfig::default())),
+            1000,
+        );
+        assert!(engine.ensure_privileged_policy().is_ok());
+    }
+
+    /// `[signals.allowlisted]` で SIGKILL を禁止した設定
+    fn allowlisted_forbid_kill_config() -> Config {
+        Config {
+            signals: Some(SignalsConfig {
+                allowlisted: Some(SignalRules {
+                    allow: vec![],
+                    forbid: vec!["KILL".to_string()],
+                }),
+                ..Default::default()
+            }),
+            ..allowlist_config("worker", None)
+        }
+    }
+
+    #[test]
+    fn test_signal_forbidden_for_allowlisted_target() {
+        let engine = unprivileged(engine_with_root_pid(
+            allowlisted_forbid_kill_config(),
+            u32::MAX,
+        ));
+
+        assert_eq!(
+            engine.check_signal_allowed(Signal::SIGKILL, SignalTarget::Allowlisted),
+            Err(SafeKillError::SignalNotAllowed(
+                "SIGKILL".to_string(),
+                "allowlisted".to_string(),
+                ConfigLayer::User,
+            ))
+        );
+        assert!(engine.is_signal_allowed(Signal::SIGTERM, SignalTarget::Allowlisted));
+        // 他の区分には allowlisted のルールを適用しない
+        assert!(engine.is_signal_allowed(Signal::SIGKILL, SignalTarget::Descendant));
+        assert!(engine.is_signal_allowed(Signal::SIGKILL, SignalTarget::Port));
+    }
+
+    #[test]
+    fn test_signal_target_follows_permission() {
+        assert_eq!(
+            KillPermission::Allowed.signal_target(),
+            SignalTarget::Descendant
+        );
+        assert_eq!(
+            KillPermission::AllowedByAllowlist.signal_target(),
+            SignalTarget::Allowlisted
+        );
+    }
+
+    #[test]
+    fn test_signal_global_forbid_applies_to_all_targets() {
+        let config = Config {
+            signals: Some(SignalsConfig {
+                forbid: vec!["STOP".to_string()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let engine = unprivileged(engine_with_root_pid(config, u32::MAX));
+
+        for target in SignalTarget::ALL {
+            assert!(!engine.is_signal_allowed(Signal::SIGSTOP, target));
+            assert!(engine.is_signal_allowed(Signal::SIGCONT, target));
+        }
+    }
+
+    #[test]
+    fn test_kill_by_pid_rejects_signal_forbidden_for_descendants() {
+        let config = Config {
+            signals: Some(SignalsConfig {
+                descendant: Some(SignalRules {
+                    allow: vec!["TERM".to_string()],
+                    forbid: vec![],
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .expect("failed to spawn sleep");
+        let engine = unprivileged(engine_with_root_pid(config, std::process::id()));
+
+        let result = engine.kill_by_pid(child.id(), Signal::SIGKILL, true);
+        assert_eq!(
+            result,
+            Err(SafeKillError::SignalNotAllowed(
+                "SIGKILL".to_string(),
+                "descendant".to_string(),
+                ConfigLayer::User,
+            ))
+        );
+        let result = engine.kill_by_pid(child.id(), Signal::SIGTERM, true);
+        assert!(result.is_ok_and(|r| r.success));
+
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
+    #[test]
+    fn test_kill_by_port_rejects_forbidden_signal_before_detection() {
+        let config = Config {
+            allowed_ports: Some(crate::config::AllowedPorts {
+                ports: vec!["3000".to_string()],
+                ..Default::default()
+            }),
+            signals: Some(SignalsConfig {
+                port: Some(SignalRules {
+                    allow: vec![],
+                    forbid: vec!["KILL".to_string()],
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let engine = unprivileged(engine_with_root_pid(config, u32::MAX));
+
+        let result = engine.kill_by_port(3000, Signal::SIGKILL, true);
+        assert!(matches!(
+            result,
+            Err(SafeKillError::SignalNotAllowed(_, target, _)) if target == "port"
+        ));
+    }
+
+    #[test]
+    fn test_ensure_privileged_policy_ok_when_unprivileged() {
+        let engine = engine_with_root_pid(Config::default(), u32::MAX);
+        assert!(engine.ensure_privileged_policy().is_ok());
+    }
+
+    #[test]
+    fn test_elevated_allowlist_bypass_disabled_by_default() {
+        let config = allowlist_config("worker", Some(PrivilegedConfig::default()));
+        let engine = elevated_engine(config, 1000);
+
+        // 自分の所有でも、特権実行時は allowlist だけでは ancestry をバイパスできない
+        let permission = engine.can_kill(&process_owned_by(99999, "worker", 1000));
+        assert_eq!(permission, KillPermission::DeniedNotDescendant);
+    }
+
+    #[test]
+    fn test_elevated_allowlist_bypass_enabled_explicitly() {
+        let config = allowlist_config(
+            "worker",
+            Some(PrivilegedConfig {
+                allowlist_bypass: true,
+                port_other_users: false,
+            }),
+        );
+        let engine = elevated_engine(config, 1000);
+
+        let permission = engine.can_kill(&process_owned_by(99999, "worker", 1000));
+        assert_eq!(permission, KillPermission::AllowedByAllowlist);
+    }
+
+    #[test]
+    fn test_elevated_port_mode_limited_to_invoking_user() {
+        let config = Config {
+            ownership: Some(crate::config::OwnershipConfig {
+                enforce: false,
+                users: vec!["2000".to_string()],
+            }),
+            privileged: Some(PrivilegedConfig::default()),
+            ..Default::default()
+        };
+        let engine = elevated_engine(config, 1000);
+
+        assert_eq!(
+            engine.port_ownership.as_ref().unwrap().allowed_uids(),
+            &[1000]
+        );
+        assert_eq!(
+            engine.ownership.as_ref().unwrap().allowed_uids(),
+            &[1000, 2000]
+        );
+    }
+
+    #[test]
+    fn test_elevated_port_mode_other_users_enabled_explicitly() {
+        let config = Config {
+            ownership: Some(crate::config::OwnershipConfig {
+                enforce: false,
+                users: vec!["2000".to_string()],
+            }),
+            privileged: Some(PrivilegedConfig {
+                allowlist_bypass: false,
+                port_other_users: true,
+            }),
+            ..Default::default()
+        };
+        let engine = elevated_engine(config, 1000);
+
+        assert_eq!(
+            engine.port_ownership.as_ref().unwrap().allowed_uids(),
+            &[1000, 2000]
+        );
+    }
+
+    #[test]
+    fn test_kill_permission_to_error_not_owner() {
+        let perm = KillPermission::DeniedNotOwner(None);
+        assert_eq!(
+            perm.to_error(4321, "node"),
+            SafeKillError::NotOwned(4321, "node".to_string(), "an unknown user".to_string())
+        );
+    }
+
+    // can_kill のテスト
+    #[test]
+    fn test_can_kill_self_denied() {
+        let engine = PolicyEngine::with_defaults();
+        let current_pid = ProcessInfoProvider::current_pid();
+
+        if let Some(process) = engine.provider.get(current_pid) {
+            let permission = engine.can_kill(&process);
+            assert_eq!(permission, KillPermission::DeniedSuicidePrevention);
+        }
+    }
+
+    #[test]
+    fn test_can_kill_parent_denied() {
+        let engine = PolicyEngine::with_defaults();
+        let current_pid = ProcessInfoProvider::current_pid();
+
+        if let Some(current) = engine.provider.get(current_pid) {
+            if let Some(parent_pid) = current.parent_pid {
+                if let Some(parent) = engine.provider.get(parent_pid) {
+                    let permission = engine.can_kill(&parent);
+                    assert_eq!(permission, KillPermission::DeniedSuicidePrevention);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_can_kill_denylisted() {
         let config = Config {
             allowlist: None,
             denylist: Some(ProcessList {
                 processes: vec!["test_denied_process".to_string()],
+                ..Default::default()
             }),
             allowed_ports: None,
+            ..Default::default()
         };
         let engine = PolicyEngine::new(config);
 
@@ -625,26 +1722,81 @@ mod tests {
             name: "test_denied_process".to_string(),
             cmd: vec![],
             start_time: 0,
+            uid: None,
+            euid: None,
+            cwd: None,
+            start_ticks: None,
         };
 
         match engine.can_kill(&process) {
-            KillPermission::DeniedByDenylist(name) => {
+            KillPermission::DeniedByDenylist(name, layer) => {
                 assert_eq!(name, "test_denied_process");
+                assert_eq!(layer, ConfigLayer::User);
             }
             _ => panic!("Expected DeniedByDenylist"),
         }
     }
 
+    #[test]
+    fn test_can_kill_denylisted_by_system_config() {
+        let path = std::path::PathBuf::from("/etc/safe-kill/config.toml");
+        let config = Config {
+            allowlist: Some(ProcessList {
+                processes: vec!["test_admin_denied".to_string()],
+                ..Default::default()
+            }),
+            system: Some(Box::new(crate::config::SystemLayer {
+                path: path.clone(),
+                config: Config {
+                    denylist: Some(ProcessList {
+                        processes: vec!["test_admin_denied".to_string()],
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+            })),
+            ..Default::default()
+        };
+        let engine = PolicyEngine::new(config);
+
+        let process = ProcessInfo {
+            pid: 99999,
+            parent_pid: Some(1),
+            name: "test_admin_denied".to_string(),
+            cmd: vec![],
+            start_time: 0,
+            uid: None,
+            euid: None,
+            cwd: None,
+            start_ticks: None,
+        };
+
+        let permission = engine.can_kill(&process);
+        assert_eq!(
+            permission,
+            KillPermission::DeniedByDenylist(
+                "test_admin_denied".to_string(),
+                ConfigLayer::System(path)
+            )
+        );
+        assert_eq!(
+            permission.to_error(99999, "test_admin_denied").to_string(),
+            "Process test_admin_denied is in denylist (system config /etc/safe-kill/config.toml) and cannot be killed"
+        );
+    }
+
     #[test]
     fn test_can_kill_allowlisted() {
         let config = Config {
             allowlist: Some(ProcessList {
                 processes: vec!["test_allowed_process".to_string()],
+                ..Default::default()
             }),
             denylist: None,
             allowed_ports: None,
+            ..Default::default()
         };
-        let engine = PolicyEngine::new(config);
+        let engine = unprivileged(PolicyEngine::new(config));
 
         let process = ProcessInfo {
             pid: 99999,
@@ -652,6 +1804,10 @@ mod tests {
             name: "test_allowed_process".to_string(),
             cmd: vec![],
             start_time: 0,
+            uid: Some(invoking_uid()),
+            euid: Some(invoking_uid()),
+            cwd: None,
+            start_ticks: None,
         };
 
         // 自プロセスの PID だと自殺防止チェックに引っかかるため
@@ -666,9 +1822,11 @@ mod tests {
         let config = Config {
             allowlist: Some(ProcessList {
                 processes: vec!["trusted_root".to_string()],
+                ..Default::default()
             }),
             denylist: None,
             allowed_ports: None,
+            ..Default::default()
         };
         let engine = engine_with_root_pid(config, root_pid);
 
@@ -678,6 +1836,10 @@ mod tests {
             name: "trusted_root".to_string(),
             cmd: vec![],
             start_time: 0,
+            uid: None,
+            euid: None,
+            cwd: None,
+            start_ticks: None,
         };
 
         // root PID は信頼境界であり、allowlist でも終了対象にしない。
@@ -690,11 +1852,14 @@ mod tests {
         let config = Config {
             allowlist: Some(ProcessList {
                 processes: vec!["conflicted_process".to_string()],
+                ..Default::default()
             }),
             denylist: Some(ProcessList {
                 processes: vec!["conflicted_process".to_string()],
+                ..Default::default()
             }),
             allowed_ports: None,
+            ..Default::default()
         };
         let engine = PolicyEngine::new(config);
 
@@ -704,10 +1869,14 @@ mod tests {
             name: "conflicted_process".to_string(),
             cmd: vec![],
             start_time: 0,
+            uid: None,
+            euid: None,
+            cwd: None,
+            start_ticks: None,
         };
 
         match engine.can_kill(&process) {
-            KillPermission::DeniedByDenylist(_) => {}
+            KillPermission::DeniedByDenylist(..) => {}
             other => panic!("Expected DeniedByDenylist, got {:?}", other),
         }
     }
@@ -805,6 +1974,39 @@ mod tests {
         assert!(root_pid > 0);
     }
 
+    #[test]
+    fn test_root_resolution_matches_root_pid() {
+        let engine = PolicyEngine::new(Config::default());
+        assert_eq!(engine.root_resolution().pid, engine.root_pid());
+    }
+
+    #[test]
+    fn test_root_chain_starts_at_current_process() {
+        let engine = PolicyEngine::new(Config::default());
+        let chain = engine.root_chain();
+        assert_eq!(
+            chain.first().map(|p| p.pid),
+            Some(ProcessInfoProvider::current_pid())
+        );
+    }
+
+    #[test]
+    fn test_root_chain_ends_at_root_with_levels_strategy() {
+        use crate::config::{RootConfig, RootStrategy};
+
+        let config = Config {
+            root: Some(RootConfig {
+                strategy: RootStrategy::Levels,
+                levels: Some(1),
+                names: None,
+            }),
+            ..Default::default()
+        };
+        let engine = PolicyEngine::new(config);
+        let chain = engine.root_chain();
+        assert_eq!(chain.last().map(|p| p.pid), Some(engine.root_pid()));
+    }
+
     // 許可優先順位のテスト
     #[test]
     fn test_permission_priority_suicide_over_denylist() {
@@ -812,8 +2014,10 @@ mod tests {
             allowlist: None,
             denylist: Some(ProcessList {
                 processes: vec!["safe-kill".to_string()], // 自プロセスを denylist に追加
+                ..Default::default()
             }),
             allowed_ports: None,
+            ..Default::default()
         };
         let engine = PolicyEngine::new(config);
         let current_pid = ProcessInfoProvider::current_pid();
@@ -830,11 +2034,14 @@ mod tests {
         let config = Config {
             allowlist: Some(ProcessList {
                 processes: vec!["both_listed".to_string()],
+                ..Default::default()
             }),
             denylist: Some(ProcessList {
                 processes: vec!["both_listed".to_string()],
+                ..Default::default()
             }),
             allowed_ports: None,
+            ..Default::default()
         };
         let engine = PolicyEngine::new(config);
 
@@ -844,10 +2051,14 @@ mod tests {
             name: "both_listed".to_string(),
             cmd: vec![],
             start_time: 0,
+            uid: None,
+            euid: None,
+            cwd: None,
+            start_ticks: None,
         };
 
         match engine.can_kill(&process) {
-            KillPermission::DeniedByDenylist(_) => {}
+            KillPermission::DeniedByDenylist(..) => {}
             other => panic!("Expected DeniedByDenylist, got {:?}", other),
         }
     }
@@ -863,7 +2074,9 @@ mod tests {
             denylist: None,
             allowed_ports: Some(AllowedPorts {
                 ports: vec!["3000-3010".to_string()],
+                ..Default::default()
             }),
+            ..Default::default()
         };
         let engine = PolicyEngine::new(config);
         // ポート 3009 は許可されているがプロセスが存在しない
@@ -878,6 +2091,7 @@ mod tests {
             allowlist: None,
             denylist: None,
             allowed_ports: None,
+            ..Default::default()
         };
         let engine = PolicyEngine::new(config);
 
@@ -895,7 +2109,9 @@ mod tests {
             denylist: None,
             allowed_ports: Some(AllowedPorts {
                 ports: vec!["3000".to_string(), "8080".to_string()],
+                ..Default::default()
             }),
+            ..Default::default()
         };
         let engine = PolicyEngine::new(config);
 
@@ -913,7 +2129,9 @@ mod tests {
             denylist: None,
             allowed_ports: Some(AllowedPorts {
                 ports: vec!["59995".to_string()],
+                ..Default::default()
             }),
+            ..Default::default()
         };
         let engine = PolicyEngine::new(config);
 
@@ -932,7 +2150,9 @@ mod tests {
             denylist: None,
             allowed_ports: Some(AllowedPorts {
                 ports: vec!["3000-3010".to_string()],
+                ..Default::default()
             }),
+            ..Default::default()
         };
         let engine = PolicyEngine::new(config);
         // dry_run でもプロセスの存在チェックは行われる
@@ -940,10 +2160,675 @@ mod tests {
         assert!(matches!(result, Err(SafeKillError::NoProcessOnPort(3008))));
     }
 
+    /// 自プロセスで UDP ソケットを 127.0.0.1 にバインドし、検出できるまで待つ
+    fn bind_detectable_udp_socket() -> (std::net::UdpSocket, u16) {
+        let socket = std::net::UdpSocket::bind("127.0.0.1:0").expect("UDP ソケットの作成に失敗");
+        let port = socket.local_addr().unwrap().port();
+        let detector = PortDetector::new();
+        let current_pid = std::process::id();
+        let detected = (0..10).any(|_| {
+            let found = detector
+                .find_by_port(port)
+                .is_ok_and(|found| found.iter().any(|p| p.pid == current_pid));
+            if !found {
+                std::thread::sleep(std::time::Duration::from_millis(50));
+            }
+            found
+        });
+        assert!(
+            detected,
+            "テスト前提として UDP ポート {} を検出できるべき",
+            port
+        );
+        (socket, port)
+    }
+
+    fn port_config(entry: String) -> Config {
+        Config {
+            allowed_ports: Some(crate::config::AllowedPorts {
+                ports: vec![entry],
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_kill_by_port_rejects_socket_not_matching_protocol_entry() {
+        let (_socket, port) = bind_detectable_udp_socket();
+        let engine = unprivileged(engine_with_root_pid(
+            port_config(format!("tcp:{}", port)),
+            u32::MAX,
+        ));
+
+        match engine.kill_by_port(port, Signal::SIGTERM, true) {
+            Err(SafeKillError::PortNotAllowed { hint, .. }) => {
+                assert!(hint.contains("UDP socket bound to 127.0.0.1"), "{}", hint);
+            }
+            other => panic!("Expected PortNotAllowed, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_kill_by_port_filtered_matches_protocol_and_bind_entry() {
+        let (_socket, port) = bind_detectable_udp_socket();
+        let engine = unprivileged(engine_with_root_pid(
+            port_config(format!("udp:127.0.0.1:{}", port)),
+            u32::MAX,
+        ));
+        let udp_loopback = PortFilter {
+            protocol: Some(crate::port::PortProtocol::Udp),
+            family: None,
+            bind: Some("127.0.0.1".parse().unwrap()),
+        };
+
+        // 対象は自プロセスなので自殺防止で拒否されるが、ソケットとしては選ばれる
+        let batch = engine
+            .kill_by_port_filtered(port, &udp_loopback, Signal::SIGTERM, true)
+            .unwrap();
+        assert_eq!(batch.total_matched, 1);
+        assert_eq!(batch.results[0].pid, std::process::id());
+        assert_eq!(
+            batch.results[0].error,
+            Some(SafeKillError::SuicidePrevention(std::process::id()))
+        );
+
+        let tcp_only = PortFilter {
+            protocol: Some(crate::port::PortProtocol::Tcp),
+            ..PortFilter::default()
+        };
+        assert_eq!(
+            engine
+                .kill_by_port_filtered(port, &tcp_only, Signal::SIGTERM, true)
+                .map(|b| b.total_matched),
+            Err(SafeKillError::NoProcessOnPort(port))
+        );
+    }
+
+    #[test]
+    fn test_list_ports_reports_own_socket_as_not_killable() {
+        let (_socket, port) = bind_detectable_udp_socket();
+        let engine = unprivileged(engine_with_root_pid(
+            port_config(format!("udp:{}", port)),
+            u32::MAX,
+        ));
+
+        let holders = engine.list_ports(false).unwrap();
+        let own = holders
+            .iter()
+            .find(|h| h.port == port && h.pid == std::process::id())
+            .expect("許可ポート上の自プロセスのソケットが一覧に含まれるべき");
+        assert!(own.allowed);
+        assert!(!own.killable);
+        assert_eq!(
+            own.reason,
+            Some(SafeKillError::SuicidePrevention(std::process::id()).to_string())
+        );
+        assert!(holders.iter().all(|h| h.port == port));
+    }
+
+    #[test]
+    fn test_list_ports_explains_entry_mismatch() {
+        let (_socket, port) = bind_detectable_udp_socket();
+        let engine = unprivileged(engine_with_root_pid(
+            port_config(format!("tcp:{}", port)),
+            u32::MAX,
+        ));
+
+        let holders = engine.list_ports(false).unwrap();
+        let own = holders
+            .iter()
+            .find(|h| h.port == port && h.pid == std::process::id())
+            .expect("範囲内のポートは一覧に含まれるべき");
+        assert!(!own.allowed);
+        assert!(!own.killable);
+        assert!(
+            own.reason
+                .as_deref()
+                .is_some_and(|r| r.contains("does not match any [allowed_ports] entry"))
+        );
+    }
+
+    #[test]
+    fn test_list_ports_all_includes_ports_outside_allowed_ranges() {
+        let (_socket, port) = bind_detectable_udp_socket();
+        let engine = unprivileged(engine_with_root_pid(Config::default(), u32::MAX));
+        assert!(!engine.config().is_port_allowed(port));
+
+        let allowed_only = engine.list_ports(false).unwrap();
+        assert!(allowed_only.iter().all(|h| h.port != port));
+
+        let all = engine.list_ports(true).unwrap();
+        let own = all
+            .iter()
+            .find(|h| h.port == port && h.pid == std::process::id())
+            .expect("--all では許可外のポートも含まれるべき");
+        assert!(!own.killable);
+        assert_eq!(
+            own.reason,
+            Some(format!("Port {} is not in [allowed_ports]", port))
+        );
+    }
+
+    fn socket_glob_config(glob: String) -> Config {
+        Config {
+            allowed_sockets: Some(crate::config::AllowedSockets { paths: vec![glob] }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_kill_by_socket_rejects_path_outside_allowed_sockets() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("dev.sock");
+        let _listener = std::os::unix::net::UnixListener::bind(&socket_path).unwrap();
+        let engine = unprivileged(engine_with_root_pid(
+            socket_glob_config("/nonexistent/*.sock".to_string()),
+            u32::MAX,
+        ));
+
+        assert!(matches!(
+            engine.kill_by_socket(&socket_path, Signal::SIGTERM, true, |_| Ok(Approval::All)),
+            Err(SafeKillError::SocketNotAllowed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_kill_by_socket_reports_missing_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let engine = unprivileged(engine_with_root_pid(
+            socket_glob_config(format!("{}/*", dir.path().display())),
+            u32::MAX,
+        ));
+
+        assert!(matches!(
+            engine.kill_by_socket(
+                &dir.path().join("missing.sock"),
+                Signal::SIGTERM,
+                true,
+                |_| Ok(Approval::All)
+            ),
+            Err(SafeKillError::NoProcessOnSocket(_))
+        ));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_kill_by_socket_finds_holder_and_applies_port_policy() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("dev.sock");
+        let listener = std::os::unix::net::UnixListener::bind(&socket_path).unwrap();
+        let resolved = socket_path.canonicalize().unwrap();
+        let engine = unprivileged(engine_with_root_pid(
+            socket_glob_config(format!("{}/*.sock", resolved.parent().unwrap().display())),
+            u32::MAX,
+        ));
+
+        // 保持しているのは自プロセスなので、検出はされるが自殺防止で拒否される
+        // （拒否される保持プロセスは確認の候補にも含めない）
+        let mut confirmed = None;
+        let batch = engine
+            .kill_by_socket(&socket_path, Signal::SIGTERM, true, |candidates| {
+                confirmed = Some(candidates.len());
+                Ok(Approval::All)
+            })
+            .unwrap();
+        assert_eq!(confirmed, Some(0));
+        assert_eq!(batch.total_matched, 1);
+        assert_eq!(batch.results[0].pid, std::process::id());
+        assert_eq!(
+            batch.results[0].error,
+            Some(SafeKillError::SuicidePrevention(std::process::id()))
+        );
+
+        // ソケットを閉じた後は保持プロセスなしになる
+        drop(listener);
+        assert!(matches!(
+            engine.kill_by_socket(&socket_path, Signal::SIGTERM, true, |_| Ok(Approval::All)),
+            Err(SafeKillError::NoProcessOnSocket(_))
+        ));
+    }
+
+    #[test]
+    fn test_kill_by_socket_uses_port_signal_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("dev.sock");
+        let _listener = std::os::unix::net::UnixListener::bind(&socket_path).unwrap();
+        let mut config = socket_glob_config(format!(
+            "{}/*.sock",
+            socket_path
+                .canonicalize()
+                .unwrap()
+                .parent()
+                .unwrap()
+                .display()
+        ));
+        config.signals = Some(crate::config::SignalsConfig {
+            port: Some(crate::config::SignalRules {
+                allow: Vec::new(),
+                forbid: vec!["KILL".to_string()],
+            }),
+            ..Default::default()
+        });
+        let engine = unprivileged(engine_with_root_pid(config, u32::MAX));
+
+        assert!(matches!(
+            engine.kill_by_socket(&socket_path, Signal::SIGKILL, true, |_| Ok(Approval::All)),
+            Err(SafeKillError::SignalNotAllowed(..))
+        ));
+    }
+
+    /// 一時ディレクトリ内のファイルを標準出力として開いた `sleep` を起動する
+    fn spawn_file_holder() -> (tempfile::TempDir, std::path::PathBuf, std::process::Child) {
+        let dir = tempfile::tempdir().unwrap();
+        let dir_path = dir.path().canonicalize().unwrap();
+        let file = std::fs::File::create(dir_path.join("app.db")).unwrap();
+        let child = std::process::Command::new("sleep")
+            .arg("30")
+            .stdout(file)
+            .spawn()
+            .expect("failed to spawn sleep");
+        (dir, dir_path, child)
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_kill_by_holding_allows_descendant_holder() {
+        let (_dir, dir_path, mut child) = spawn_file_holder();
+        let engine = unprivileged(engine_with_root_pid(Config::default(), std::process::id()));
+
+        let batch = engine
+            .kill_by_holding(&dir_path.join("app.db"), Signal::SIGTERM, true, |_| {
+                Ok(Approval::All)
+            })
+            .unwrap();
+        let result = batch.results.iter().find(|r| r.pid == child.id()).unwrap();
+        assert!(result.success, "{}", result.message);
+
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_kill_by_holding_enforces_ancestry_without_allowed_paths() {
+        let (_dir, dir_path, mut child) = spawn_file_holder();
+        let engine = unprivileged(engine_with_root_pid(Config::default(), u32::MAX));
+
+        let batch = engine
+            .kill_by_holding(&dir_path, Signal::SIGTERM, true, |_| Ok(Approval::All))
+            .unwrap();
+        let result = batch.results.iter().find(|r| r.pid == child.id()).unwrap();
+        assert!(matches!(
+            result.error,
+            Some(SafeKillError::NotDescendant(pid, _)) if pid == child.id()
+        ));
+
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_kill_by_holding_allowed_paths_bypasses_ancestry() {
+        let (_dir, dir_path, mut child) = spawn_file_holder();
+        let config = Config {
+            allowed_paths: Some(crate::config::AllowedPaths {
+                paths: vec![dir_path.display().to_string()],
+            }),
+            ..Default::default()
+        };
+        let engine = unprivileged(engine_with_root_pid(config, u32::MAX));
+
+        let batch = engine
+            .kill_by_holding(&dir_path, Signal::SIGTERM, true, |_| Ok(Approval::All))
+            .unwrap();
+        let result = batch.results.iter().find(|r| r.pid == child.id()).unwrap();
+        assert!(result.success, "{}", result.message);
+
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_kill_by_holding_confirms_candidates_before_sending() {
+        let (_dir, dir_path, mut child) = spawn_file_holder();
+        let allowed = Config {
+            allowed_paths: Some(crate::config::AllowedPaths {
+                paths: vec![dir_path.display().to_string()],
+            }),
+            ..Default::default()
+        };
+        // ancestry 込みの判定と `[allowed_paths]` によるバイパスのどちらでも確認を通す
+        for (config, root_pid) in [(Config::default(), std::process::id()), (allowed, u32::MAX)] {
+            let engine = unprivileged(engine_with_root_pid(config, root_pid));
+
+            let mut confirmed = Vec::new();
+            let batch = engine
+                .kill_by_holding(&dir_path, Signal::SIGKILL, true, |candidates| {
+                    confirmed = candidates.iter().map(|c| c.process.pid).collect();
+                    Ok(Approval::Only(Vec::new()))
+                })
+                .unwrap();
+            assert!(confirmed.contains(&child.id()));
+            let result = batch.results.iter().find(|r| r.pid == child.id()).unwrap();
+            assert_eq!(
+                result.error,
+                Some(SafeKillError::Aborted("skipped".to_string()))
+            );
+
+            assert_eq!(
+                engine
+                    .kill_by_holding(&dir_path, Signal::SIGKILL, true, |_| Err(
+                        SafeKillError::Aborted("declined".to_string())
+                    ))
+                    .map(|b| b.total_matched),
+                Err(SafeKillError::Aborted("declined".to_string()))
+            );
+        }
+
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
+    #[test]
+    fn test_kill_by_holding_reports_missing_path() {
+        let engine = unprivileged(engine_with_root_pid(Config::default(), u32::MAX));
+        assert_eq!(
+            engine
+                .kill_by_holding(
+                    Path::new("/nonexistent/app.db"),
+                    Signal::SIGTERM,
+                    true,
+                    |_| Ok(Approval::All)
+                )
+                .map(|b| b.total_matched),
+            Err(SafeKillError::NoProcessHoldingPath(
+                "/nonexistent/app.db".to_string()
+            ))
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_kill_by_cwd_confirms_and_kills_descendants() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .current_dir(dir.path())
+            .spawn()
+            .expect("failed to spawn sleep");
+        let engine = unprivileged(engine_with_root_pid(Config::default(), std::process::id()));
+
+        let mut confirmed = Vec::new();
+        let batch = engine
+            .kill_by_cwd(dir.path(), Signal::SIGTERM, true, |killable| {
+                confirmed = killable.iter().map(|c| c.process.pid).collect();
+                Ok(Approval::All)
+            })
+            .unwrap();
+        assert_eq!(confirmed, vec![child.id()]);
+        assert_eq!(batch.total_matched, 1);
+        assert!(batch.results[0].success);
+
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_kill_by_cwd_aborts_when_confirmation_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .current_dir(dir.path())
+            .spawn()
+            .expect("failed to spawn sleep");
+        let engine = unprivileged(engine_with_root_pid(Config::default(), std::process::id()));
+
+        let result = engine.kill_by_cwd(dir.path(), Signal::SIGKILL, false, |_| {
+            Err(SafeKillError::Aborted("declined".to_string()))
+        });
+        assert_eq!(
+            result.map(|b| b.total_matched),
+            Err(SafeKillError::Aborted("declined".to_string()))
+        );
+        assert!(
+            child.try_wait().unwrap().is_none(),
+            "中止時はシグナルを送らない"
+        );
+
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
+    #[test]
+    fn test_kill_by_name_confirmed_skips_unapproved_candidates() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .expect("failed to spawn sleep");
+        let engine = unprivileged(engine_with_root_pid(Config::default(), std::process::id()));
+
+        let mut offered = Vec::new();
+        let batch = engine
+            .kill_by_name_confirmed("sleep", Signal::SIGTERM, false, |candidates| {
+                offered = candidates.iter().map(|c| c.preview.clone()).collect();
+                Ok(Approval::Only(Vec::new()))
+            })
+            .unwrap();
+
+        let preview = offered
+            .iter()
+            .find(|p| p.pid == child.id())
+            .expect("子孫の sleep は候補に含まれるべき");
+        assert_eq!(
+            preview,
+            &KillResult::dry_run(child.id(), "sleep", Signal::SIGTERM)
+        );
+        let result = batch.results.iter().find(|r| r.pid == child.id()).unwrap();
+        assert_eq!(
+            result.error,
+            Some(SafeKillError::Aborted("skipped".to_string()))
+        );
+        assert!(
+            child.try_wait().unwrap().is_none(),
+            "未承認の候補には送らない"
+        );
+
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
+    #[test]
+    fn test_kill_by_pid_rate_limited_after_quota() {
+        let spawn = || {
+            std::process::Command::new("sleep")
+                .arg("30")
+                .spawn()
+                .expect("failed to spawn sleep")
+        };
+        let (mut first, mut second) = (spawn(), spawn());
+        let dir = tempfile::tempdir().unwrap();
+        let mut engine = unprivileged(engine_with_root_pid(Config::default(), std::process::id()));
+        engine.limiter = Some(RateLimiter::new(
+            crate::config::LimitsConfig {
+                kills_per_minute: Some(1),
+                sigkills_per_session: None,
+            },
+            Some(dir.path().join("limits.json")),
+            std::process::id(),
+        ));
+
+        // dry-run は数えない
+        assert!(
+            engine
+                .kill_by_pid(first.id(), Signal::SIGTERM, true)
+                .unwrap()
+                .success
+        );
+        assert!(
+            engine
+                .kill_by_pid(first.id(), Signal::SIGTERM, false)
+                .unwrap()
+                .success
+        );
+        let limited = engine
+            .kill_by_pid(second.id(), Signal::SIGTERM, false)
+            .unwrap();
+        assert!(!limited.success);
+        assert!(matches!(limited.error, Some(SafeKillError::RateLimited(_))));
+        assert!(
+            second.try_wait().unwrap().is_none(),
+            "上限を超えたら送らない"
+        );
+
+        for child in [&mut first, &mut second] {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+
+    #[test]
+    fn test_kill_by_name_confirmed_omits_forbidden_signal_from_candidates() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .expect("failed to spawn sleep");
+        let config = Config {
+            signals: Some(SignalsConfig {
+                forbid: vec!["SIGKILL".to_string()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let engine = unprivileged(engine_with_root_pid(config, std::process::id()));
+
+        let batch = engine
+            .kill_by_name_confirmed("sleep", Signal::SIGKILL, true, |candidates| {
+                assert!(candidates.iter().all(|c| c.process.pid != child.id()));
+                Ok(Approval::All)
+            })
+            .unwrap();
+        assert!(
+            batch
+                .results
+                .iter()
+                .any(|r| r.pid == child.id() && !r.success),
+            "禁止シグナルの対象は結果に失敗として残る"
+        );
+
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
+    #[test]
+    fn test_kill_by_cwd_skips_self_and_ancestors() {
+        let cwd = std::env::current_dir().unwrap();
+        let engine = unprivileged(engine_with_root_pid(Config::default(), u32::MAX));
+
+        match engine.kill_by_cwd(&cwd, Signal::SIGTERM, true, |_| Ok(Approval::All)) {
+            Ok(batch) => assert!(
+                batch.results.iter().all(|r| r.pid != std::process::id()),
+                "自プロセスは結果に含めない"
+            ),
+            Err(err) => assert!(matches!(err, SafeKillError::NoProcessInCwd(_))),
+        }
+    }
+
+    #[test]
+    fn test_kill_by_cwd_reports_missing_directory() {
+        let engine = unprivileged(engine_with_root_pid(Config::default(), u32::MAX));
+        assert_eq!(
+            engine
+                .kill_by_cwd(
+                    Path::new("/nonexistent/project"),
+                    Signal::SIGTERM,
+                    true,
+                    |_| Ok(Approval::All)
+                )
+                .map(|b| b.total_matched),
+            Err(SafeKillError::NoProcessInCwd(
+                "/nonexistent/project".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_kill_by_ports_rejects_range_not_fully_allowed() {
+        let engine = unprivileged(engine_with_root_pid(
+            port_config("59960-59964".to_string()),
+            u32::MAX,
+        ));
+
+        let ports = PortSelection::parse("59960-59966").unwrap();
+        let result = engine.kill_by_ports(
+            &ports,
+            &PortFilter::default(),
+            Signal::SIGTERM,
+            true,
+            |_| Ok(Approval::All),
+        );
+        assert!(matches!(
+            result,
+            Err(SafeKillError::PortNotAllowed { port: 59965, .. })
+        ));
+    }
+
+    #[test]
+    fn test_kill_by_ports_reports_no_process_for_selection() {
+        let engine = unprivileged(engine_with_root_pid(
+            port_config("59960-59964".to_string()),
+            u32::MAX,
+        ));
+
+        let ports = PortSelection::parse("59960-59962,59964").unwrap();
+        let result = engine.kill_by_ports(
+            &ports,
+            &PortFilter::default(),
+            Signal::SIGTERM,
+            true,
+            |_| Ok(Approval::All),
+        );
+        assert_eq!(
+            result.map(|b| b.total_matched),
+            Err(SafeKillError::NoProcessOnPorts(
+                "59960-59962,59964".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_kill_by_ports_tags_results_with_port() {
+        let (_socket, port) = bind_detectable_udp_socket();
+        let engine = unprivileged(engine_with_root_pid(
+            port_config(format!("{}-{}", port - 1, port)),
+            u32::MAX,
+        ));
+
+        let ports = PortSelection::parse(&format!("{}-{}", port - 1, port)).unwrap();
+        let batch = engine
+            .kill_by_ports(
+                &ports,
+                &PortFilter::default(),
+                Signal::SIGTERM,
+                true,
+                |_| Ok(Approval::All),
+            )
+            .unwrap();
+        let own = batch
+            .results
+            .iter()
+            .find(|r| r.pid == std::process::id())
+            .expect("自プロセスのソケットが対象に含まれるべき");
+        assert_eq!(own.port, Some(port));
+    }
+
     // can_kill_for_port のテスト
     #[test]
     fn test_can_kill_for_port_allowed() {
-        let engine = PolicyEngine::with_defaults();
+        let engine = unprivileged(PolicyEngine::with_defaults());
         // 自プロセスでも denylist にも含まれないランダムな PID
         let permission = engine.can_kill_for_port(99999, "random_process");
         assert_eq!(permission, KillPermission::Allowed);
@@ -963,14 +2848,16 @@ mod tests {
             allowlist: None,
             denylist: Some(ProcessList {
                 processes: vec!["denylisted_server".to_string()],
+                ..Default::default()
             }),
             allowed_ports: None,
+            ..Default::default()
         };
         let engine = PolicyEngine::new(config);
 
         let permission = engine.can_kill_for_port(99999, "denylisted_server");
         match permission {
-            KillPermission::DeniedByDenylist(name) => {
+            KillPermission::DeniedByDenylist(name, _) => {
                 assert_eq!(name, "denylisted_server");
             }
             other => panic!("Expected DeniedByDenylist, got {:?}", other),
@@ -986,11 +2873,81 @@ mod tests {
         assert_eq!(permission, KillPermission::DeniedNotDescendant);
     }
 
+    /// 現在プロセスの祖父 PID とそのプロセス名を取得する（取得できない環境では None）
+    fn grandparent_of_current(provider: &ProcessInfoProvider) -> Option<ProcessInfo> {
+        let current = provider.get(ProcessInfoProvider::current_pid())?;
+        let parent = provider.get(current.parent_pid?)?;
+        let grandparent = provider.get(parent.parent_pid?)?;
+        (grandparent.pid > 1).then_some(grandparent)
+    }
+
+    #[test]
+    fn test_can_kill_allowlisted_ancestor_denied() {
+        let provider = ProcessInfoProvider::new();
+        let Some(grandparent) = grandparent_of_current(&provider) else {
+            return;
+        };
+        // 祖父の名前を allowlist に入れても、祖先チェーン保護が優先される
+        let config = Config {
+            allowlist: Some(ProcessList {
+                processes: vec![grandparent.name.clone()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let engine = engine_with_root_pid(config, u32::MAX);
+
+        assert_eq!(
+            engine.can_kill(&grandparent),
+            KillPermission::DeniedAncestor
+        );
+    }
+
+    #[test]
+    fn test_can_kill_for_port_ancestor_denied() {
+        let engine = engine_with_root_pid(Config::default(), u32::MAX);
+        let Some(grandparent) = grandparent_of_current(&engine.provider) else {
+            return;
+        };
+
+        let permission = engine.can_kill_for_port(grandparent.pid, "agent_host");
+        assert_eq!(permission, KillPermission::DeniedAncestor);
+    }
+
+    #[test]
+    fn test_verify_not_ancestor_before_kill_rejects_grandparent() {
+        let provider = ProcessInfoProvider::new();
+        let Some(grandparent) = grandparent_of_current(&provider) else {
+            return;
+        };
+
+        let result = PolicyEngine::verify_not_ancestor_before_kill(&grandparent);
+        assert!(
+            matches!(result, Err(SafeKillError::AncestorProtected(pid, _)) if pid == grandparent.pid),
+            "最終安全検証は祖父プロセスを AncestorProtected で拒否すべき"
+        );
+    }
+
+    #[test]
+    fn test_verify_not_ancestor_before_kill_allows_child() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("5")
+            .spawn()
+            .expect("sleep プロセスの起動に失敗");
+        let info =
+            ProcessInfoProvider::fetch_fresh(child.id()).expect("子プロセス情報の取得に失敗");
+
+        assert!(PolicyEngine::verify_not_ancestor_before_kill(&info).is_ok());
+
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
     #[test]
     fn test_can_kill_for_port_no_ancestor_check() {
         // can_kill_for_port が ancestry チェックを行わないことを検証
         // 設計上の意図: ポート指定 kill は ancestry 走査を適用しない
-        let engine = PolicyEngine::with_defaults();
+        let engine = unprivileged(PolicyEngine::with_defaults());
 
         // 確実に子孫ではないランダムなプロセス
         // denylist に含まれていなければ許可されるべき
@@ -1009,6 +2966,10 @@ mod tests {
             name: "unrelated_process".to_string(),
             cmd: vec![],
             start_time: 0,
+            uid: Some(invoking_uid()),
+            euid: Some(invoking_uid()),
+            cwd: None,
+            start_ticks: None,
         };
         let permission = engine.can_kill(&process);
         // allowlist に含まれず、子孫でもない -> DeniedNotDescendant
@@ -1021,10 +2982,12 @@ mod tests {
         let engine = PolicyEngine::with_defaults();
         let result = engine.kill_by_pid(1, Signal::SIGTERM, false);
         assert!(result.is_err());
-        // DeniedByDenylist（launchd/systemd が denylist に含まれる）または SuicidePrevention の可能性
+        // DeniedByDenylist（launchd/systemd が denylist に含まれる）、SuicidePrevention、
+        // または PID 1 が祖先チェーン上にあるため AncestorProtected の可能性
         match result {
-            Err(SafeKillError::Denylisted(_))
+            Err(SafeKillError::Denylisted(..))
             | Err(SafeKillError::SuicidePrevention(_))
+            | Err(SafeKillError::AncestorProtected(_, _))
             | Err(SafeKillError::NotDescendant(_, _)) => {}
             other => panic!("Expected denial error, got {:?}", other),
         }
@@ -1049,11 +3012,13 @@ mod tests {
         let config = Config {
             allowlist: Some(ProcessList {
                 processes: vec!["allowlisted_server".to_string()],
+                ..Default::default()
             }),
             denylist: None,
             allowed_ports: None,
+            ..Default::default()
         };
-        let engine = PolicyEngine::new(config);
+        let engine = unprivileged(PolicyEngine::new(config));
 
         // allowlist に含まれるプロセスでも、Allowed（AllowedByAllowlist ではない）が返る
         let permission = engine.can_kill_for_port(99999, "allowlisted_server");
@@ -1086,9 +3051,9 @@ mod tests {
     #[test]
     fn test_kill_permission_eq_variants() {
         // 異なる DeniedByDenylist インスタンス間の等値性を検証
-        let a = KillPermission::DeniedByDenylist("proc_a".to_string());
-        let b = KillPermission::DeniedByDenylist("proc_a".to_string());
-        let c = KillPermission::DeniedByDenylist("proc_b".to_string());
+        let a = KillPermission::DeniedByDenylist("proc_a".to_string(), ConfigLayer::User);
+        let b = KillPermission::DeniedByDenylist("proc_a".to_string(), ConfigLayer::User);
+        let c = KillPermission::DeniedByDenylist("proc_b".to_string(), ConfigLayer::User);
         assert_eq!(a, b);
         assert_ne!(a, c);
     }
@@ -1155,8 +3120,10 @@ mod tests {
             allowlist: None,
             denylist: Some(ProcessList {
                 processes: vec![placeholder_name.clone()],
+                ..Default::default()
             }),
             allowed_ports: None,
+            ..Default::default()
         };
         let engine = PolicyEngine::new(config);
 
@@ -1165,9 +3132,11 @@ mod tests {
             name: placeholder_name.clone(),
             port: 3000,
             protocol: PortProtocol::Tcp,
+            address: "127.0.0.1".parse().unwrap(),
         }];
 
-        let batch = engine.kill_port_processes(3000, port_processes, Signal::SIGTERM, true);
+        let batch =
+            engine.kill_port_processes(port_processes, Signal::SIGTERM, true, &Approval::All);
 
         assert_eq!(batch.results.len(), 1);
         assert!(!batch.results[0].success);
@@ -1196,8 +3165,10 @@ mod tests {
             allowlist: None,
             denylist: Some(ProcessList {
                 processes: vec![placeholder_name.clone(), "denied_proc".to_string()],
+                ..Default::default()
             }),
             allowed_ports: None,
+            ..Default::default()
         };
         let engine = PolicyEngine::new(config);
 
@@ -1206,14 +3177,16 @@ mod tests {
             name: placeholder_name.clone(),
             port: 3000,
             protocol: PortProtocol::Tcp,
+            address: "127.0.0.1".parse().unwrap(),
         }];
 
-        let batch = engine.kill_port_processes(3000, port_processes, Signal::SIGTERM, true);
+        let batch =
+            engine.kill_port_processes(port_processes, Signal::SIGTERM, true, &Approval::All);
 
         // ProcessNotFound で fail-closed されるため、Denylisted エラーには
         // ならないことを確認（denylist 判定そのものに到達してはならない）。
         assert!(
-            !matches!(batch.results[0].error, Some(SafeKillError::Denylisted(_))),
+            !matches!(batch.results[0].error, Some(SafeKillError::Denylisted(..))),
             "プレースホルダ名で denylist 判定に到達してはならない"
         );
         assert_eq!(
@@ -1247,7 +3220,9 @@ mod tests {
             denylist: None,
             allowed_ports: Some(crate::config::AllowedPorts {
                 ports: vec!["59990".to_string()],
+                ..Default::default()
             }),
+            ..Default::default()
         };
         let engine = PolicyEngine::new(config);
 
@@ -1256,10 +3231,12 @@ mod tests {
             name: "sleep".to_string(),
             port: 59990,
             protocol: PortProtocol::Tcp,
+            address: "127.0.0.1".parse().unwrap(),
         }];
 
         // dry_run=true で副作用なく検証する
-        let batch = engine.kill_port_processes(59990, port_processes, Signal::SIGTERM, true);
+        let batch =
+            engine.kill_port_processes(port_processes, Signal::SIGTERM, true, &Approval::All);
 
         assert_eq!(batch.results.len(), 1);
         assert!(
@@ -1311,6 +3288,10 @@ mod tests {
             name: "ghost_process".to_string(),
             cmd: vec![],
             start_time: 1,
+            uid: None,
+            euid: None,
+            cwd: None,
+            start_ticks: None,
         };
         let result = engine.verify_identity_before_kill(&stale);
         assert!(
@@ -1339,6 +3320,28 @@ mod tests {
         );
     }
 
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_verify_identity_before_kill_fails_on_start_ticks_mismatch() {
+        // 同一秒内の PID 再利用シミュレーション: start_time と名前は一致し、
+        // クロックティック単位の起動時刻だけが異なる
+        let engine = PolicyEngine::with_defaults();
+        let current_pid = ProcessInfoProvider::current_pid();
+        let mut tampered = engine
+            .provider
+            .get(current_pid)
+            .expect("現在プロセスが取得できるべき");
+        assert!(tampered.start_ticks.is_some());
+        assert_eq!(engine.verify_identity_before_kill(&tampered), Ok(()));
+        tampered.start_ticks = tampered.start_ticks.map(|ticks| ticks + 1);
+
+        let result = engine.verify_identity_before_kill(&tampered);
+        assert!(
+            matches!(result, Err(SafeKillError::ProcessNotFound(p)) if p == current_pid),
+            "start_ticks が一致しなければ PID 再利用とみなして fail-closed すべき"
+        );
+    }
+
     #[test]
     fn test_verify_identity_before_kill_fails_on_name_mismatch() {
         // 同じ秒に PID 再利用された場合の補助検証として、名前が異なれば
@@ -0,0 +1,363 @@
+//! 実行ユーザーと特権状態の判定
+//!
+//! safe-kill を起動したユーザーの UID と、root など特権付きで実行されているかを解決し、
+//! プロセス所有者チェックに使う許可 UID 集合を組み立てる。
+
+use crate::config::OwnershipConfig;
+use crate::process_info::ProcessInfo;
+use nix::unistd::{Uid, User, geteuid, getuid};
+
+/// sudo 経由の実行時に起動元ユーザーの UID を示す環境変数
+pub const SUDO_UID_ENV: &str = "SUDO_UID";
+
+/// 任意のプロセスへのシグナル送信を許す capability の番号（Linux の CAP_KILL）
+const CAP_KILL: u32 = 5;
+
+/// 現在プロセスの実行ユーザー情報
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrivilegeContext {
+    /// 実ユーザー ID
+    pub real_uid: u32,
+    /// 実効ユーザー ID
+    pub effective_uid: u32,
+    /// safe-kill を起動したユーザーの UID（sudo 経由なら `SUDO_UID`）
+    pub invoking_uid: u32,
+    /// 実効 capability に CAP_KILL を持つか（Linux 以外では常に false）
+    pub has_cap_kill: bool,
+}
+
+impl PrivilegeContext {
+    /// 現在プロセスの UID と環境変数から実行ユーザー情報を解決する
+    pub fn detect() -> Self {
+        let sudo_uid = std::env::var(SUDO_UID_ENV).ok();
+        let mut context =
+            Self::from_ids(getuid().as_raw(), geteuid().as_raw(), sudo_uid.as_deref());
+        context.has_cap_kill = Self::detect_cap_kill();
+        context
+    }
+
+    /// 現在プロセスの実効 capability に CAP_KILL が含まれるか調べる
+    #[cfg(target_os = "linux")]
+    fn detect_cap_kill() -> bool {
+        std::fs::read_to_string("/proc/self/status")
+            .ok()
+            .and_then(|status| parse_cap_eff(&status))
+            .is_some_and(|caps| caps & (1 << CAP_KILL) != 0)
+    }
+
+    /// capability の概念がない OS では常に false
+    #[cfg(not(target_os = "linux"))]
+    fn detect_cap_kill() -> bool {
+        false
+    }
+
+    /// UID と `SUDO_UID` の値から実行ユーザー情報を組み立てる
+    ///
+    /// `SUDO_UID` は実 UID が 0 のとき（sudo で root になったとき）だけ採用する。
+    /// 一般ユーザーが環境変数を偽装して他人の UID を名乗れないようにするため。
+    pub fn from_ids(real_uid: u32, effective_uid: u32, sudo_uid: Option<&str>) -> Self {
+        let invoking_uid = if real_uid == 0 {
+            sudo_uid
+                .and_then(|value| value.trim().parse::<u32>().ok())
+                .unwrap_or(real_uid)
+        } else {
+            real_uid
+        };
+
+        Self {
+            real_uid,
+            effective_uid,
+            invoking_uid,
+            has_cap_kill: false,
+        }
+    }
+
+    /// 他ユーザーのプロセスにもシグナルを送れる特権付きで実行されているか
+    pub fn is_elevated(&self) -> bool {
+        self.elevation_reason().is_some()
+    }
+
+    /// 特権付きと判定した理由（特権がなければ None）
+    pub fn elevation_reason(&self) -> Option<&'static str> {
+        if self.effective_uid == 0 {
+            Some("effective UID 0")
+        } else if self.has_cap_kill {
+            Some("CAP_KILL capability")
+        } else {
+            None
+        }
+    }
+}
+
+/// `/proc/<pid>/status` の内容から実効 capability（`CapEff`）のビット列を取り出す
+pub fn parse_cap_eff(status: &str) -> Option<u64> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("CapEff:"))
+        .and_then(|value| u64::from_str_radix(value.trim(), 16).ok())
+}
+
+/// ユーザー名または数値 UID を UID に解決する
+///
+/// 数値として解釈できればそのまま UID とみなし、そうでなければユーザーデータベースを引く。
+pub fn resolve_user(spec: &str) -> Option<u32> {
+    let spec = spec.trim();
+    if spec.is_empty() {
+        return None;
+    }
+    if let Ok(uid) = spec.parse::<u32>() {
+        return Some(uid);
+    }
+    User::from_name(spec)
+        .ok()
+        .flatten()
+        .map(|user| user.uid.as_raw())
+}
+
+/// UID を表示用のユーザー名に変換する（解決できなければ `UID <n>`）
+pub fn describe_uid(uid: Option<u32>) -> String {
+    match uid {
+        Some(uid) => match User::from_uid(Uid::from_raw(uid)) {
+            Ok(Some(user)) => format!("user {} (UID {})", user.name, uid),
+            _ => format!("UID {}", uid),
+        },
+        None => "an unknown user".to_string(),
+    }
+}
+
+/// プロセス所有者チェック
+///
+/// 起動ユーザーと設定で明示したユーザーが所有するプロセスだけを kill 対象として許可する。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnershipCheck {
+    allowed_uids: Vec<u32>,
+}
+
+impl OwnershipCheck {
+    /// 設定と実行ユーザー情報から所有者チェックを構築する
+    ///
+    /// 特権実行時は設定に関わらず常に有効。非特権実行時は `[ownership] enforce = true`
+    /// のときだけ有効になる。無効な場合は `None` を返す。
+    pub fn from_config(
+        config: Option<&OwnershipConfig>,
+        context: &PrivilegeContext,
+    ) -> Option<Self> {
+        let enforce = config.is_some_and(|ownership| ownership.enforce);
+        if !enforce && !context.is_elevated() {
+            return None;
+        }
+
+        let mut allowed_uids = vec![context.invoking_uid];
+        if let Some(ownership) = config {
+            for uid in ownership.users.iter().filter_map(|spec| resolve_user(spec)) {
+                if !allowed_uids.contains(&uid) {
+                    allowed_uids.push(uid);
+                }
+            }
+        }
+
+        Some(Self { allowed_uids })
+    }
+
+    /// 起動ユーザーのプロセスだけを許可する所有者チェックを構築する
+    ///
+    /// 特権実行時のポート指定 kill で、`[ownership] users` を適用しない場合に使う。
+    pub fn invoking_user_only(context: &PrivilegeContext) -> Self {
+        Self {
+            allowed_uids: vec![context.invoking_uid],
+        }
+    }
+
+    /// 許可されている UID の一覧
+    pub fn allowed_uids(&self) -> &[u32] {
+        &self.allowed_uids
+    }
+
+    /// 対象プロセスの所有者が許可されているか判定する
+    ///
+    /// 実 UID が不明なプロセスは所有者を証明できないため拒否する（fail-closed）。
+    /// setuid バイナリのように実効 UID が異なる場合は、実効 UID も許可対象である必要がある。
+    pub fn permits(&self, process: &ProcessInfo) -> bool {
+        let Some(uid) = process.uid else {
+            return false;
+        };
+        if !self.allowed_uids.contains(&uid) {
+            return false;
+        }
+        process
+            .euid
+            .is_none_or(|euid| self.allowed_uids.contains(&euid))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process_owned_by(uid: Option<u32>, euid: Option<u32>) -> ProcessInfo {
+        ProcessInfo {
+            pid: 4242,
+            parent_pid: Some(1),
+            name: "worker".to_string(),
+            cmd: vec![],
+            start_time: 0,
+            uid,
+            euid,
+            cwd: None,
+            start_ticks: None,
+        }
+    }
+
+    fn ownership(enforce: bool, users: &[&str]) -> OwnershipConfig {
+        OwnershipConfig {
+            enforce,
+            users: users.iter().map(|user| user.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_from_ids_unprivileged_ignores_sudo_uid() {
+        let context = PrivilegeContext::from_ids(1000, 1000, Some("0"));
+        assert_eq!(context.invoking_uid, 1000);
+        assert!(!context.is_elevated());
+    }
+
+    #[test]
+    fn test_from_ids_sudo_uses_sudo_uid() {
+        let context = PrivilegeContext::from_ids(0, 0, Some("1000"));
+        assert_eq!(context.invoking_uid, 1000);
+        assert!(context.is_elevated());
+    }
+
+    #[test]
+    fn test_from_ids_root_without_sudo_is_root() {
+        let context = PrivilegeContext::from_ids(0, 0, None);
+        assert_eq!(context.invoking_uid, 0);
+
+        let context = PrivilegeContext::from_ids(0, 0, Some("not-a-number"));
+        assert_eq!(context.invoking_uid, 0);
+    }
+
+    #[test]
+    fn test_from_ids_setuid_root_uses_real_uid() {
+        let context = PrivilegeContext::from_ids(1000, 0, Some("2000"));
+        assert_eq!(context.invoking_uid, 1000);
+        assert!(context.is_elevated());
+    }
+
+    #[test]
+    fn test_elevation_reason() {
+        let mut context = PrivilegeContext::from_ids(1000, 1000, None);
+        assert_eq!(context.elevation_reason(), None);
+
+        context.has_cap_kill = true;
+        assert_eq!(context.elevation_reason(), Some("CAP_KILL capability"));
+        assert!(context.is_elevated());
+
+        let context = PrivilegeContext::from_ids(1000, 0, None);
+        assert_eq!(context.elevation_reason(), Some("effective UID 0"));
+    }
+
+    #[test]
+    fn test_parse_cap_eff() {
+        let status = "Name:\tsafe-kill\nCapInh:\t0000000000000000\nCapEff:\t0000000000000020\n";
+        assert_eq!(parse_cap_eff(status), Some(0x20));
+        assert_eq!(
+            parse_cap_eff("CapEff:\t000001ffffffffff\n"),
+            Some(0x1ff_ffff_ffff)
+        );
+        assert_eq!(parse_cap_eff("Name:\tsafe-kill\n"), None);
+        assert_eq!(parse_cap_eff("CapEff:\tzz\n"), None);
+    }
+
+    #[test]
+    fn test_detect_elevated_consistent_with_ids() {
+        let context = PrivilegeContext::detect();
+        if context.effective_uid == 0 {
+            assert!(context.is_elevated());
+        } else {
+            assert_eq!(context.is_elevated(), context.has_cap_kill);
+        }
+    }
+
+    #[test]
+    fn test_invoking_user_only() {
+        let context = PrivilegeContext::from_ids(0, 0, Some("1000"));
+        let check = OwnershipCheck::invoking_user_only(&context);
+        assert_eq!(check.allowed_uids(), &[1000]);
+    }
+
+    #[test]
+    fn test_detect_matches_current_ids() {
+        let context = PrivilegeContext::detect();
+        assert_eq!(context.real_uid, getuid().as_raw());
+        assert_eq!(context.effective_uid, geteuid().as_raw());
+    }
+
+    #[test]
+    fn test_resolve_user_numeric_and_name() {
+        assert_eq!(resolve_user("1001"), Some(1001));
+        assert_eq!(resolve_user(" 0 "), Some(0));
+        assert_eq!(resolve_user("root"), Some(0));
+        assert_eq!(resolve_user(""), None);
+        assert_eq!(resolve_user("no_such_user_for_safe_kill"), None);
+    }
+
+    #[test]
+    fn test_describe_uid() {
+        assert_eq!(describe_uid(Some(0)), "user root (UID 0)");
+        assert_eq!(describe_uid(None), "an unknown user");
+    }
+
+    #[test]
+    fn test_ownership_disabled_when_unprivileged_without_enforce() {
+        let context = PrivilegeContext::from_ids(1000, 1000, None);
+        assert!(OwnershipCheck::from_config(None, &context).is_none());
+        assert!(OwnershipCheck::from_config(Some(&ownership(false, &[])), &context).is_none());
+    }
+
+    #[test]
+    fn test_ownership_enabled_by_config() {
+        let context = PrivilegeContext::from_ids(1000, 1000, None);
+        let check = OwnershipCheck::from_config(Some(&ownership(true, &[])), &context).unwrap();
+        assert_eq!(check.allowed_uids(), &[1000]);
+    }
+
+    #[test]
+    fn test_ownership_always_enabled_when_elevated() {
+        let context = PrivilegeContext::from_ids(0, 0, Some("1000"));
+        let check = OwnershipCheck::from_config(Some(&ownership(false, &[])), &context).unwrap();
+        assert_eq!(check.allowed_uids(), &[1000]);
+
+        let check = OwnershipCheck::from_config(None, &context).unwrap();
+        assert!(check.permits(&process_owned_by(Some(1000), Some(1000))));
+        assert!(!check.permits(&process_owned_by(Some(0), Some(0))));
+    }
+
+    #[test]
+    fn test_ownership_additional_users() {
+        let context = PrivilegeContext::from_ids(1000, 1000, None);
+        let check =
+            OwnershipCheck::from_config(Some(&ownership(true, &["1001", "1000"])), &context)
+                .unwrap();
+        assert_eq!(check.allowed_uids(), &[1000, 1001]);
+        assert!(check.permits(&process_owned_by(Some(1001), Some(1001))));
+        assert!(!check.permits(&process_owned_by(Some(1002), Some(1002))));
+    }
+
+    #[test]
+    fn test_ownership_rejects_unknown_owner() {
+        let context = PrivilegeContext::from_ids(1000, 1000, None);
+        let check = OwnershipCheck::from_config(Some(&ownership(true, &[])), &context).unwrap();
+        assert!(!check.permits(&process_owned_by(None, None)));
+        assert!(check.permits(&process_owned_by(Some(1000), None)));
+    }
+
+    #[test]
+    fn test_ownership_rejects_foreign_effective_uid() {
+        // 実 UID が起動ユーザーでも、setuid で実効 UID が root のプロセスは拒否する
+        let context = PrivilegeContext::from_ids(1000, 1000, None);
+        let check = OwnershipCheck::from_config(Some(&ownership(true, &[])), &context).unwrap();
+        assert!(!check.permits(&process_owned_by(Some(1000), Some(0))));
+    }
+}
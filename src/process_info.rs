@@ -1,8 +1,15 @@
-//! sysinfo クレートを使用したプロセス情報プロバイダー
+//! プロセス情報プロバイダー
 //!
-//! クロスプラットフォームなプロセス情報取得を提供する。
+//! Linux では `/proc` を直接読む軽量なバックエンドを使い、それ以外のプラットフォームでは
+//! sysinfo クレートによるクロスプラットフォームな取得にフォールバックする。
 
-use sysinfo::{Pid, ProcessesToUpdate, System};
+use std::path::{Path, PathBuf};
+
+#[cfg(not(target_os = "linux"))]
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+#[cfg(target_os = "linux")]
+use crate::procfs::{self, ProcReader};
 
 /// 単一プロセスの情報
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -18,36 +25,117 @@ pub struct ProcessInfo {
     /// プロセスの起動時刻（UNIXエポック秒）。
     /// PID 再利用検出に使用する。同じ PID でも異なるプロセスは別の起動時刻を持つ。
     pub start_time: u64,
+    /// 実ユーザー ID（取得できない場合は None）
+    pub uid: Option<u32>,
+    /// 実効ユーザー ID（取得できない場合は None）
+    pub euid: Option<u32>,
+    /// カレントディレクトリ（取得できない場合は None。他ユーザーのプロセスなど）
+    pub cwd: Option<PathBuf>,
+    /// ブートからの起動時刻（クロックティック数、`/proc/<pid>/stat` の starttime）。
+    /// Linux のみ。`start_time` より細かい精度で PID 再利用を検出する。
+    pub start_ticks: Option<u64>,
 }
 
 impl ProcessInfo {
     /// 同一プロセスかを判定する（PID 再利用検出用）
     ///
     /// PID と `start_time` の両方が一致していれば同一プロセスとみなす。
-    /// `start_time` は同一秒内に再利用された場合に区別できないため、
-    /// 名前も補助的に検証する。これにより、ポリシー判定後に kill 直前で
-    /// PID 再利用を検出できる。
+    /// Linux では `start_ticks` も比較し、同一秒内の再利用もクロックティック精度で
+    /// 区別する。`start_ticks` を取得できないプラットフォームでは同一秒内の再利用を
+    /// 区別できないため、名前も補助的に検証する。これにより、ポリシー判定後に
+    /// kill 直前で PID 再利用を検出できる。
     pub fn is_same_process(&self, other: &ProcessInfo) -> bool {
-        self.pid == other.pid && self.start_time == other.start_time && self.name == other.name
+        self.pid == other.pid
+            && self.start_time == other.start_time
+            && self.start_ticks == other.start_ticks
+            && self.name == other.name
     }
 }
 
-/// sysinfo を使用したプロセス情報プロバイダー
+/// プロセス情報プロバイダー
+///
+/// Linux では `/proc` から必要な PID だけを遅延して読む。それ以外のプラットフォームでは
+/// sysinfo で全プロセスのスナップショットを取る。
 pub struct ProcessInfoProvider {
+    #[cfg(target_os = "linux")]
+    reader: ProcReader,
+    #[cfg(not(target_os = "linux"))]
     system: System,
 }
 
+#[cfg(target_os = "linux")]
+impl ProcessInfoProvider {
+    /// 新しい ProcessInfoProvider を作成
+    ///
+    /// 作成時には `/proc` を読まず、問い合わせのあった PID だけを読んでキャッシュする。
+    pub fn new() -> Self {
+        Self {
+            reader: ProcReader::new(),
+        }
+    }
+
+    /// 読み込み済みのプロセス情報を破棄し、次回の問い合わせで読み直す
+    pub fn refresh(&mut self) {
+        self.reader.clear();
+    }
+
+    /// PID でプロセス情報を取得
+    pub fn get(&self, pid: u32) -> Option<ProcessInfo> {
+        self.reader.entry(pid).map(|entry| entry.info)
+    }
+
+    /// 指定 PID の最新プロセス情報を OS から直接取得する
+    ///
+    /// kill 直前の TOCTOU 検証用。キャッシュを使わずに `/proc/<pid>` を読み直すため、
+    /// `ProcessInfoProvider` の保持する情報に依存しない。PID 再利用が発生した場合は
+    /// 新しいプロセスの `start_time` が返るため、判定時の `start_time` と比較
+    /// することで再利用を検出できる。
+    pub fn fetch_fresh(pid: u32) -> Option<ProcessInfo> {
+        procfs::read_entry(pid).map(|entry| entry.info)
+    }
+
+    /// すべてのプロセスを PID 昇順で取得
+    pub fn all(&self) -> Vec<ProcessInfo> {
+        self.reader
+            .all()
+            .into_iter()
+            .map(|entry| entry.info)
+            .collect()
+    }
+
+    /// 指定 PID のセッション ID を取得
+    ///
+    /// セッションリーダーは自身の PID とセッション ID が一致する。
+    pub fn session_id(&self, pid: u32) -> Option<u32> {
+        self.reader.entry(pid).map(|entry| entry.session_id)
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
 impl ProcessInfoProvider {
     /// プロセスリストを更新済みの新しい ProcessInfoProvider を作成
     pub fn new() -> Self {
-        let mut system = System::new_all();
-        system.refresh_processes(ProcessesToUpdate::All, true);
+        let mut system = System::new();
+        system.refresh_processes_specifics(ProcessesToUpdate::All, true, Self::refresh_kind());
         Self { system }
     }
 
     /// プロセスリストを更新
     pub fn refresh(&mut self) {
-        self.system.refresh_processes(ProcessesToUpdate::All, true);
+        self.system
+            .refresh_processes_specifics(ProcessesToUpdate::All, true, Self::refresh_kind());
+    }
+
+    /// refresh 時に取得する項目
+    ///
+    /// `refresh_processes` の既定ではユーザー ID が更新されないため、
+    /// 所有者チェックに必要な UID/EUID を明示的に要求する。
+    /// カレントディレクトリはプロセスの実行中に変わるため毎回取り直す。
+    fn refresh_kind() -> ProcessRefreshKind {
+        ProcessRefreshKind::nothing()
+            .with_cmd(UpdateKind::OnlyIfNotSet)
+            .with_user(UpdateKind::OnlyIfNotSet)
+            .with_cwd(UpdateKind::Always)
     }
 
     /// `sysinfo::Process` から `ProcessInfo` を構築する内部ヘルパー
@@ -62,6 +150,10 @@ impl ProcessInfoProvider {
                 .map(|s| s.to_string_lossy().to_string())
                 .collect(),
             start_time: proc.start_time(),
+            uid: proc.user_id().map(|uid| **uid),
+            euid: proc.effective_user_id().map(|uid| **uid),
+            cwd: proc.cwd().map(Path::to_path_buf),
+            start_ticks: None,
         }
     }
 
@@ -77,44 +169,67 @@ impl ProcessInfoProvider {
     ///
     /// kill 直前の TOCTOU 検証用。新しい `System` インスタンスを生成して
     /// 指定 PID のみ refresh するため、`ProcessInfoProvider` の保持する
-    /// スナップショットに依存しない。PID 再利用が発生した場合は新しい
-    /// プロセスの `start_time` が返るため、判定時の `start_time` と比較
-    /// することで再利用を検出できる。
+    /// スナップショットに依存しない。
     pub fn fetch_fresh(pid: u32) -> Option<ProcessInfo> {
         let mut sys = System::new();
         let sysinfo_pid = Pid::from_u32(pid);
-        sys.refresh_processes(ProcessesToUpdate::Some(&[sysinfo_pid]), true);
+        sys.refresh_processes_specifics(
+            ProcessesToUpdate::Some(&[sysinfo_pid]),
+            true,
+            Self::refresh_kind(),
+        );
         sys.process(sysinfo_pid)
             .map(|proc| Self::build_info(pid, proc))
     }
 
-    /// 指定名に一致するすべてのプロセスを検索（完全一致）
-    pub fn find_by_name(&self, name: &str) -> Vec<ProcessInfo> {
+    /// すべてのプロセスを PID 昇順で取得
+    pub fn all(&self) -> Vec<ProcessInfo> {
         let mut processes: Vec<_> = self
             .system
             .processes()
             .iter()
-            .filter(|(_, proc)| proc.name().to_string_lossy() == name)
             .map(|(pid, proc)| Self::build_info(pid.as_u32(), proc))
             .collect();
 
-        // `sysinfo` の内部マップ順に依存させず、複数一致時の処理順を安定させる。
+        // `sysinfo` の内部マップ順に依存させず、出力順と処理順を安定させる。
         processes.sort_by_key(|process| process.pid);
         processes
     }
 
-    /// すべてのプロセスを取得
-    pub fn all(&self) -> Vec<ProcessInfo> {
-        let mut processes: Vec<_> = self
-            .system
-            .processes()
-            .iter()
-            .map(|(pid, proc)| Self::build_info(pid.as_u32(), proc))
-            .collect();
+    /// 指定 PID のセッション ID を取得
+    ///
+    /// セッションリーダーは自身の PID とセッション ID が一致する。
+    pub fn session_id(&self, pid: u32) -> Option<u32> {
+        self.system
+            .process(Pid::from_u32(pid))
+            .and_then(|proc| proc.session_id())
+            .map(|sid| sid.as_u32())
+    }
+}
 
-        // 一覧表示の出力順が毎回ぶれないよう PID 昇順にそろえる。
-        processes.sort_by_key(|process| process.pid);
-        processes
+impl ProcessInfoProvider {
+    /// 指定名に一致するすべてのプロセスを PID 順に検索（完全一致）
+    pub fn find_by_name(&self, name: &str) -> Vec<ProcessInfo> {
+        self.all()
+            .into_iter()
+            .filter(|process| process.name == name)
+            .collect()
+    }
+
+    /// カレントディレクトリが指定ディレクトリ（またはその配下）にあるプロセスを PID 順に検索
+    ///
+    /// `dir` は正規化済み（絶対パス・シンボリックリンク解決済み）であることを想定する。
+    /// カレントディレクトリを取得できないプロセスは含まれない。
+    pub fn find_by_cwd(&self, dir: &Path) -> Vec<ProcessInfo> {
+        self.all()
+            .into_iter()
+            .filter(|process| {
+                process
+                    .cwd
+                    .as_deref()
+                    .is_some_and(|cwd| cwd.starts_with(dir))
+            })
+            .collect()
     }
 
     /// 現在のプロセスの PID を取得
@@ -146,6 +261,10 @@ mod tests {
             name: "test".to_string(),
             cmd: vec!["test".to_string(), "--arg".to_string()],
             start_time: 0,
+            uid: None,
+            euid: None,
+            cwd: None,
+            start_ticks: None,
         };
         assert_eq!(info.pid, 1234);
         assert_eq!(info.parent_pid, Some(1));
@@ -161,6 +280,10 @@ mod tests {
             name: "proc".to_string(),
             cmd: vec![],
             start_time: 0,
+            uid: None,
+            euid: None,
+            cwd: None,
+            start_ticks: None,
         };
         let cloned = info.clone();
         assert_eq!(info, cloned);
@@ -189,6 +312,31 @@ mod tests {
         assert_eq!(info.pid, current_pid);
     }
 
+    #[test]
+    fn test_get_current_process_has_owner() {
+        let provider = ProcessInfoProvider::new();
+        let info = provider
+            .get(ProcessInfoProvider::current_pid())
+            .expect("現在プロセスは取得できるべき");
+        assert_eq!(info.uid, Some(nix::unistd::getuid().as_raw()));
+        assert_eq!(info.euid, Some(nix::unistd::geteuid().as_raw()));
+    }
+
+    #[test]
+    fn test_fetch_fresh_has_owner_of_child() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("5")
+            .spawn()
+            .expect("sleep プロセスの起動に失敗");
+
+        let info =
+            ProcessInfoProvider::fetch_fresh(child.id()).expect("子プロセスは取得できるべき");
+        assert_eq!(info.uid, Some(nix::unistd::getuid().as_raw()));
+
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
     #[test]
     fn test_get_nonexistent_process() {
         let provider = ProcessInfoProvider::new();
@@ -211,6 +359,23 @@ mod tests {
         assert!(parent.is_some());
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_session_id_of_current_process() {
+        let provider = ProcessInfoProvider::new();
+        let sid = provider.session_id(ProcessInfoProvider::current_pid());
+        assert!(
+            sid.is_some(),
+            "現在プロセスのセッション ID は取得できるべき"
+        );
+    }
+
+    #[test]
+    fn test_session_id_nonexistent_process() {
+        let provider = ProcessInfoProvider::new();
+        assert_eq!(provider.session_id(u32::MAX - 1), None);
+    }
+
     #[test]
     fn test_all_processes_not_empty() {
         let provider = ProcessInfoProvider::new();
@@ -245,6 +410,30 @@ mod tests {
         assert!(results.is_empty());
     }
 
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_find_by_cwd_finds_process_in_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir_path = dir.path().canonicalize().unwrap();
+        std::fs::create_dir(dir_path.join("sub")).unwrap();
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .current_dir(dir_path.join("sub"))
+            .spawn()
+            .expect("failed to spawn sleep");
+
+        let provider = ProcessInfoProvider::new();
+        let found = provider.find_by_cwd(&dir_path);
+        assert_eq!(
+            found.iter().map(|p| p.pid).collect::<Vec<_>>(),
+            vec![child.id()]
+        );
+        assert_eq!(found[0].cwd, Some(dir_path.join("sub")));
+
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
     #[test]
     fn test_process_has_name() {
         let provider = ProcessInfoProvider::new();
@@ -319,6 +508,10 @@ mod tests {
             name: "test".to_string(),
             cmd: vec![],
             start_time: 0,
+            uid: None,
+            euid: None,
+            cwd: None,
+            start_ticks: None,
         };
         assert_eq!(info.parent_pid, Some(1));
     }
@@ -331,6 +524,10 @@ mod tests {
             name: "init".to_string(),
             cmd: vec![],
             start_time: 0,
+            uid: None,
+            euid: None,
+            cwd: None,
+            start_ticks: None,
         };
         assert_eq!(info.parent_pid, None);
     }
@@ -343,6 +540,10 @@ mod tests {
             name: "test".to_string(),
             cmd: vec!["arg1".to_string()],
             start_time: 0,
+            uid: None,
+            euid: None,
+            cwd: None,
+            start_ticks: None,
         };
         let b = ProcessInfo {
             pid: 100,
@@ -350,6 +551,10 @@ mod tests {
             name: "test".to_string(),
             cmd: vec!["arg1".to_string()],
             start_time: 0,
+            uid: None,
+            euid: None,
+            cwd: None,
+            start_ticks: None,
         };
         let c = ProcessInfo {
             pid: 200,
@@ -357,6 +562,10 @@ mod tests {
             name: "test".to_string(),
             cmd: vec!["arg1".to_string()],
             start_time: 0,
+            uid: None,
+            euid: None,
+            cwd: None,
+            start_ticks: None,
         };
         assert_eq!(a, b);
         assert_ne!(a, c);
@@ -370,6 +579,10 @@ mod tests {
             name: "test_proc".to_string(),
             cmd: vec!["test".to_string()],
             start_time: 0,
+            uid: None,
+            euid: None,
+            cwd: None,
+            start_ticks: None,
         };
         let debug_str = format!("{:?}", info);
         assert!(debug_str.contains("42"));
@@ -514,6 +727,10 @@ mod tests {
             name: "test".to_string(),
             cmd: vec!["arg".to_string()],
             start_time: 12345,
+            uid: None,
+            euid: None,
+            cwd: None,
+            start_ticks: None,
         };
         let cloned = info.clone();
         assert!(info.is_same_process(&cloned));
@@ -527,6 +744,10 @@ mod tests {
             name: "test".to_string(),
             cmd: vec![],
             start_time: 12345,
+            uid: None,
+            euid: None,
+            cwd: None,
+            start_ticks: None,
         };
         let b = ProcessInfo {
             pid: 101,
@@ -534,6 +755,10 @@ mod tests {
             name: "test".to_string(),
             cmd: vec![],
             start_time: 12345,
+            uid: None,
+            euid: None,
+            cwd: None,
+            start_ticks: None,
         };
         assert!(!a.is_same_process(&b), "PID 不一致は別プロセス");
     }
@@ -547,6 +772,10 @@ mod tests {
             name: "test".to_string(),
             cmd: vec![],
             start_time: 12345,
+            uid: None,
+            euid: None,
+            cwd: None,
+            start_ticks: None,
         };
         let reused = ProcessInfo {
             pid: 100,
@@ -554,6 +783,10 @@ mod tests {
             name: "test".to_string(),
             cmd: vec![],
             start_time: 99999,
+            uid: None,
+            euid: None,
+            cwd: None,
+            start_ticks: None,
         };
         assert!(
             !original.is_same_process(&reused),
@@ -561,6 +794,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_is_same_process_different_start_ticks() {
+        // 同じ秒・同じ名前で PID 再利用された場合もクロックティックで区別する
+        let original = ProcessInfo {
+            pid: 100,
+            parent_pid: Some(1),
+            name: "test".to_string(),
+            cmd: vec![],
+            start_time: 12345,
+            uid: None,
+            euid: None,
+            cwd: None,
+            start_ticks: Some(500_010),
+        };
+        let reused = ProcessInfo {
+            start_ticks: Some(500_042),
+            ..original.clone()
+        };
+        assert!(!original.is_same_process(&reused));
+        assert!(original.is_same_process(&original.clone()));
+    }
+
     #[test]
     fn test_is_same_process_different_name() {
         // 同じ秒に PID 再利用された場合に名前で識別する補助検証
@@ -570,6 +825,10 @@ mod tests {
             name: "process_a".to_string(),
             cmd: vec![],
             start_time: 12345,
+            uid: None,
+            euid: None,
+            cwd: None,
+            start_ticks: None,
         };
         let b = ProcessInfo {
             pid: 100,
@@ -577,6 +836,10 @@ mod tests {
             name: "process_b".to_string(),
             cmd: vec![],
             start_time: 12345,
+            uid: None,
+            euid: None,
+            cwd: None,
+            start_ticks: None,
         };
         assert!(
             !a.is_same_process(&b),
@@ -593,6 +856,10 @@ mod tests {
             name: "test".to_string(),
             cmd: vec!["arg1".to_string()],
             start_time: 12345,
+            uid: None,
+            euid: None,
+            cwd: None,
+            start_ticks: None,
         };
         let b = ProcessInfo {
             pid: 100,
@@ -600,6 +867,10 @@ mod tests {
             name: "test".to_string(),
             cmd: vec!["arg2".to_string()],
             start_time: 12345,
+            uid: None,
+            euid: None,
+            cwd: None,
+            start_ticks: None,
         };
         assert!(
             a.is_same_process(&b),
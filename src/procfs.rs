@@ -0,0 +1,261 @@
+//! Linux の `/proc` から直接プロセス情報を読むバックエンド
+//!
+//! 必要になった PID の `/proc/<pid>/stat`・`status`・`cmdline`・`cwd` だけを読み、
+//! 結果を PID ごとにキャッシュする。全プロセスを走査するのは名前やカレントディレクトリで
+//! 検索する場合と一覧表示の場合に限られる。
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::process_info::ProcessInfo;
+
+/// `/proc/<pid>` から読んだ 1 プロセス分の情報
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcEntry {
+    /// プロセス情報
+    pub info: ProcessInfo,
+    /// セッション ID
+    pub session_id: u32,
+}
+
+/// 読み込んだ PID をキャッシュする `/proc` リーダー
+///
+/// 一度読んだ PID は `clear` するまで同じ内容を返すため、1 回の判定の中では
+/// sysinfo のスナップショットと同じく一貫した情報を使える。
+#[derive(Debug, Default)]
+pub struct ProcReader {
+    cache: RefCell<HashMap<u32, Option<ProcEntry>>>,
+}
+
+impl ProcReader {
+    /// 空のキャッシュで作成する（この時点では `/proc` を読まない）
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 指定 PID の情報を返す（未読み込みなら `/proc/<pid>` を読んでキャッシュする）
+    pub fn entry(&self, pid: u32) -> Option<ProcEntry> {
+        self.cache
+            .borrow_mut()
+            .entry(pid)
+            .or_insert_with(|| read_entry(pid))
+            .clone()
+    }
+
+    /// `/proc` に見えているすべてのプロセスの情報を PID 順に返す
+    pub fn all(&self) -> Vec<ProcEntry> {
+        let mut pids = list_pids();
+        pids.sort_unstable();
+        pids.into_iter().filter_map(|pid| self.entry(pid)).collect()
+    }
+
+    /// キャッシュを破棄し、次回の問い合わせで読み直す
+    pub fn clear(&mut self) {
+        self.cache.get_mut().clear();
+    }
+}
+
+/// `/proc/<pid>` を読んでプロセス情報を構築する（キャッシュしない）
+///
+/// プロセスが存在しない、または `stat` を解析できない場合は None。
+/// `status` や `cwd` を読めない場合（他ユーザーのプロセスなど）は該当項目を None にする。
+pub fn read_entry(pid: u32) -> Option<ProcEntry> {
+    let dir = Path::new("/proc").join(pid.to_string());
+    let stat = parse_stat(&std::fs::read(dir.join("stat")).ok()?)?;
+    let (uid, euid) = std::fs::read_to_string(dir.join("status"))
+        .ok()
+        .and_then(|status| parse_status_uids(&status))
+        .unzip();
+    let cmd = std::fs::read(dir.join("cmdline"))
+        .map(|cmdline| parse_cmdline(&cmdline))
+        .unwrap_or_default();
+
+    Some(ProcEntry {
+        info: ProcessInfo {
+            pid,
+            parent_pid: (stat.ppid != 0).then_some(stat.ppid),
+            name: stat.name,
+            cmd,
+            start_time: boot_time().saturating_add(stat.start_ticks / clock_ticks()),
+            uid,
+            euid,
+            cwd: std::fs::read_link(dir.join("cwd")).ok(),
+            start_ticks: Some(stat.start_ticks),
+        },
+        session_id: stat.session_id,
+    })
+}
+
+/// `/proc` に見えている全 PID（スレッドは含まない）
+pub fn list_pids() -> Vec<u32> {
+    std::fs::read_dir("/proc")
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// `/proc/<pid>/stat` から使う項目
+#[derive(Debug, PartialEq, Eq)]
+struct StatFields {
+    name: String,
+    ppid: u32,
+    session_id: u32,
+    /// 起動時刻（ブートからのクロックティック数）
+    start_ticks: u64,
+}
+
+/// `/proc/<pid>/stat` を解析する
+///
+/// 形式: `pid (comm) state ppid pgrp session ... starttime ...`。comm には空白や `)` を
+/// 含められるため、最後の `)` までを名前として扱う。
+fn parse_stat(content: &[u8]) -> Option<StatFields> {
+    let open = content.iter().position(|&b| b == b'(')?;
+    let close = content.iter().rposition(|&b| b == b')')?;
+    let name = String::from_utf8_lossy(content.get(open + 1..close)?).to_string();
+    let rest = std::str::from_utf8(content.get(close + 1..)?).ok()?;
+    // `)` の後ろは 3 番目の項目（state）から始まる
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    let field = |n: usize| fields.get(n - 3);
+
+    Some(StatFields {
+        name,
+        ppid: field(4)?.parse().ok()?,
+        session_id: field(6)?.parse().ok()?,
+        start_ticks: field(22)?.parse().ok()?,
+    })
+}
+
+/// `/proc/<pid>/status` の `Uid:` 行から実 UID と実効 UID を取り出す
+fn parse_status_uids(content: &str) -> Option<(u32, u32)> {
+    let line = content.lines().find_map(|line| line.strip_prefix("Uid:"))?;
+    let mut ids = line.split_whitespace().map(|id| id.parse().ok());
+    Some((ids.next()??, ids.next()??))
+}
+
+/// `/proc/<pid>/cmdline`（NUL 区切り）を引数の一覧にする
+fn parse_cmdline(content: &[u8]) -> Vec<String> {
+    if content.is_empty() {
+        return Vec::new();
+    }
+    content
+        .strip_suffix(&[0])
+        .unwrap_or(content)
+        .split(|&b| b == 0)
+        .map(|arg| String::from_utf8_lossy(arg).to_string())
+        .collect()
+}
+
+/// システムの起動時刻（UNIX エポック秒、`/proc/stat` の `btime`）
+fn boot_time() -> u64 {
+    static BOOT_TIME: OnceLock<u64> = OnceLock::new();
+    *BOOT_TIME.get_or_init(|| {
+        std::fs::read_to_string("/proc/stat")
+            .ok()
+            .and_then(|stat| {
+                stat.lines()
+                    .find_map(|line| line.strip_prefix("btime"))
+                    .and_then(|btime| btime.trim().parse().ok())
+            })
+            .unwrap_or(0)
+    })
+}
+
+/// 1 秒あたりのクロックティック数（`sysconf(_SC_CLK_TCK)`）
+fn clock_ticks() -> u64 {
+    static CLOCK_TICKS: OnceLock<u64> = OnceLock::new();
+    *CLOCK_TICKS.get_or_init(|| {
+        // SAFETY: sysconf は引数の定数を参照するだけで、メモリ安全性に影響しない
+        let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+        u64::try_from(ticks).ok().filter(|&t| t > 0).unwrap_or(100)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stat_handles_spaces_and_parens_in_name() {
+        let stat = b"4242 (my (odd) name) S 100 4242 4200 34816 4242 4194304 120 0 0 0 \
+3 1 0 0 20 0 1 0 987654 12345678 512 18446744073709551615";
+        assert_eq!(
+            parse_stat(stat),
+            Some(StatFields {
+                name: "my (odd) name".to_string(),
+                ppid: 100,
+                session_id: 4200,
+                start_ticks: 987654,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_stat_rejects_truncated_content() {
+        assert_eq!(parse_stat(b"4242 (sleep) S 100"), None);
+        assert_eq!(parse_stat(b""), None);
+    }
+
+    #[test]
+    fn test_parse_status_uids() {
+        let status =
+            "Name:\tsleep\nUmask:\t0022\nUid:\t1000\t0\t0\t0\nGid:\t1000\t1000\t1000\t1000\n";
+        assert_eq!(parse_status_uids(status), Some((1000, 0)));
+        assert_eq!(parse_status_uids("Name:\tsleep\n"), None);
+    }
+
+    #[test]
+    fn test_parse_cmdline() {
+        assert_eq!(
+            parse_cmdline(b"node\0server.js\0--watch\0src\0"),
+            vec!["node", "server.js", "--watch", "src"]
+        );
+        assert_eq!(parse_cmdline(b"node\0\0x\0"), vec!["node", "", "x"]);
+        assert!(parse_cmdline(b"").is_empty());
+    }
+
+    #[test]
+    fn test_read_entry_current_process() {
+        let pid = std::process::id();
+        let entry = read_entry(pid).expect("現在プロセスは読めるべき");
+
+        assert_eq!(entry.info.pid, pid);
+        assert_eq!(
+            entry.info.parent_pid,
+            Some(nix::unistd::getppid().as_raw() as u32)
+        );
+        assert_eq!(entry.info.uid, Some(nix::unistd::getuid().as_raw()));
+        assert_eq!(entry.info.euid, Some(nix::unistd::geteuid().as_raw()));
+        assert_eq!(entry.info.cwd, std::env::current_dir().ok());
+        assert_eq!(
+            Some(entry.session_id),
+            nix::unistd::getsid(None)
+                .ok()
+                .map(|sid| sid.as_raw() as u32)
+        );
+        assert!(entry.info.start_time >= boot_time());
+    }
+
+    #[test]
+    fn test_reader_caches_until_cleared() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .expect("sleep プロセスの起動に失敗");
+        let pid = child.id();
+        let mut reader = ProcReader::new();
+        assert_eq!(reader.entry(pid).map(|e| e.info.name), Some("sleep".into()));
+
+        let _ = child.kill();
+        let _ = child.wait();
+        // 読み込み済みの PID は終了後も同じ内容を返す
+        assert!(reader.entry(pid).is_some());
+        reader.clear();
+        assert_eq!(reader.entry(pid), None);
+    }
+}
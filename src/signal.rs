@@ -5,58 +5,130 @@
 use crate::error::SafeKillError;
 use nix::sys::signal::{self, Signal as NixSignal};
 use nix::unistd::Pid;
-
-/// プロセス終了に使用するサポート対象シグナル
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+use std::fmt;
+
+/// リアルタイムシグナルの表示名（`SIGRTMIN` からのオフセット順）
+#[cfg(target_os = "linux")]
+const REALTIME_NAMES: [&str; 32] = [
+    "SIGRTMIN",
+    "SIGRTMIN+1",
+    "SIGRTMIN+2",
+    "SIGRTMIN+3",
+    "SIGRTMIN+4",
+    "SIGRTMIN+5",
+    "SIGRTMIN+6",
+    "SIGRTMIN+7",
+    "SIGRTMIN+8",
+    "SIGRTMIN+9",
+    "SIGRTMIN+10",
+    "SIGRTMIN+11",
+    "SIGRTMIN+12",
+    "SIGRTMIN+13",
+    "SIGRTMIN+14",
+    "SIGRTMIN+15",
+    "SIGRTMIN+16",
+    "SIGRTMIN+17",
+    "SIGRTMIN+18",
+    "SIGRTMIN+19",
+    "SIGRTMIN+20",
+    "SIGRTMIN+21",
+    "SIGRTMIN+22",
+    "SIGRTMIN+23",
+    "SIGRTMIN+24",
+    "SIGRTMIN+25",
+    "SIGRTMIN+26",
+    "SIGRTMIN+27",
+    "SIGRTMIN+28",
+    "SIGRTMIN+29",
+    "SIGRTMIN+30",
+    "SIGRTMIN+31",
+];
+
+/// プロセスに送信できるシグナル
+///
+/// 標準シグナルは実行中プラットフォームのシグナル表（nix が定義するもの）をすべて扱う。
+/// Linux では `SIGRTMIN+n` 形式のリアルタイムシグナルも扱う。
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum Signal {
+    /// プラットフォーム定義の標準シグナル
+    Standard(NixSignal),
+    /// リアルタイムシグナル（値は `SIGRTMIN` からのオフセット）
+    #[cfg(target_os = "linux")]
+    Realtime(u8),
+}
+
+impl fmt::Debug for Signal {
+    /// `Standard(SIGTERM)` ではなくシグナル名そのものを表示する
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl Default for Signal {
+    fn default() -> Self {
+        Signal::SIGTERM
+    }
+}
+
+impl Signal {
     /// SIGHUP (1) - ハングアップ
-    SIGHUP,
+    pub const SIGHUP: Signal = Signal::Standard(NixSignal::SIGHUP);
     /// SIGINT (2) - 割り込み
-    SIGINT,
+    pub const SIGINT: Signal = Signal::Standard(NixSignal::SIGINT);
     /// SIGQUIT (3) - 終了
-    SIGQUIT,
+    pub const SIGQUIT: Signal = Signal::Standard(NixSignal::SIGQUIT);
     /// SIGKILL (9) - 強制終了（捕捉不可）
-    SIGKILL,
+    pub const SIGKILL: Signal = Signal::Standard(NixSignal::SIGKILL);
     /// SIGTERM (15) - 終了要求
-    #[default]
-    SIGTERM,
+    pub const SIGTERM: Signal = Signal::Standard(NixSignal::SIGTERM);
     /// SIGUSR1 (10/30) - ユーザー定義シグナル 1
-    SIGUSR1,
+    pub const SIGUSR1: Signal = Signal::Standard(NixSignal::SIGUSR1);
     /// SIGUSR2 (12/31) - ユーザー定義シグナル 2
-    SIGUSR2,
-}
-
-impl Signal {
-    /// nix の Signal 型に変換する
-    fn to_nix(self) -> NixSignal {
-        match self {
-            Signal::SIGHUP => NixSignal::SIGHUP,
-            Signal::SIGINT => NixSignal::SIGINT,
-            Signal::SIGQUIT => NixSignal::SIGQUIT,
-            Signal::SIGKILL => NixSignal::SIGKILL,
-            Signal::SIGTERM => NixSignal::SIGTERM,
-            Signal::SIGUSR1 => NixSignal::SIGUSR1,
-            Signal::SIGUSR2 => NixSignal::SIGUSR2,
-        }
+    pub const SIGUSR2: Signal = Signal::Standard(NixSignal::SIGUSR2);
+    /// SIGALRM (14) - タイマー
+    pub const SIGALRM: Signal = Signal::Standard(NixSignal::SIGALRM);
+    /// SIGSTOP (19/17) - 一時停止（捕捉不可）
+    pub const SIGSTOP: Signal = Signal::Standard(NixSignal::SIGSTOP);
+    /// SIGCONT (18/19) - 一時停止からの再開
+    pub const SIGCONT: Signal = Signal::Standard(NixSignal::SIGCONT);
+    /// SIGTSTP (20/18) - 端末からの一時停止
+    pub const SIGTSTP: Signal = Signal::Standard(NixSignal::SIGTSTP);
+    /// SIGWINCH (28) - 端末サイズ変更
+    pub const SIGWINCH: Signal = Signal::Standard(NixSignal::SIGWINCH);
+
+    /// 実行中プラットフォームで扱えるすべてのシグナルを番号順に返す
+    pub fn all() -> Vec<Signal> {
+        let mut signals: Vec<Signal> = NixSignal::iterator().map(Signal::Standard).collect();
+        #[cfg(target_os = "linux")]
+        signals.extend((0..=Self::realtime_max_offset()).map(Signal::Realtime));
+        signals.sort_by_key(|signal| signal.number());
+        signals
     }
 
     /// シグナル番号を取得する
     pub fn number(&self) -> i32 {
-        self.to_nix() as i32
+        match self {
+            Signal::Standard(signal) => *signal as i32,
+            #[cfg(target_os = "linux")]
+            Signal::Realtime(offset) => libc::SIGRTMIN() + i32::from(*offset),
+        }
     }
 
     /// シグナル名を取得する
     pub fn name(&self) -> &'static str {
         match self {
-            Signal::SIGHUP => "SIGHUP",
-            Signal::SIGINT => "SIGINT",
-            Signal::SIGQUIT => "SIGQUIT",
-            Signal::SIGKILL => "SIGKILL",
-            Signal::SIGTERM => "SIGTERM",
-            Signal::SIGUSR1 => "SIGUSR1",
-            Signal::SIGUSR2 => "SIGUSR2",
+            Signal::Standard(signal) => signal.as_str(),
+            #[cfg(target_os = "linux")]
+            Signal::Realtime(offset) => REALTIME_NAMES[usize::from(*offset)],
         }
     }
+
+    /// リアルタイムシグナルとして使えるオフセットの上限（`SIGRTMAX - SIGRTMIN`）
+    #[cfg(target_os = "linux")]
+    fn realtime_max_offset() -> u8 {
+        let span = libc::SIGRTMAX() - libc::SIGRTMIN();
+        span.clamp(0, REALTIME_NAMES.len() as i32 - 1) as u8
+    }
 }
 
 /// Unix プロセス向けシグナル送信器
@@ -66,8 +138,9 @@ impl SignalSender {
     /// 文字列からシグナルを解析する（名前または番号）
     ///
     /// 受け付ける形式:
-    /// - シグナル名: "SIGTERM", "SIGKILL", "TERM", "KILL" など
+    /// - シグナル名: "SIGTERM", "SIGKILL", "TERM", "KILL", "STOP", "CONT" など
     /// - シグナル番号: "15", "9" など
+    /// - リアルタイムシグナル（Linux）: "SIGRTMIN", "RTMIN+3", "SIGRTMAX-1" など
     pub fn parse_signal(s: &str) -> Result<Signal, SafeKillError> {
         let s = s.trim().to_uppercase();
 
@@ -82,27 +155,19 @@ impl SignalSender {
 
     /// 番号からシグナルを解析する
     ///
-    /// USR1/USR2 のシグナル番号はプラットフォームごとに異なるため、
-    /// 各 OS のネイティブな番号のみ受け付ける:
-    /// - Linux: USR1=10, USR2=12
-    /// - macOS: USR1=30, USR2=31
+    /// 番号はプラットフォームごとに異なるため（USR1 は Linux で 10、macOS で 30 など）、
+    /// 実行中の OS のネイティブな番号のみ受け付ける。
     fn from_number(num: i32) -> Result<Signal, SafeKillError> {
-        match num {
-            1 => Ok(Signal::SIGHUP),
-            2 => Ok(Signal::SIGINT),
-            3 => Ok(Signal::SIGQUIT),
-            9 => Ok(Signal::SIGKILL),
-            15 => Ok(Signal::SIGTERM),
-            #[cfg(target_os = "linux")]
-            10 => Ok(Signal::SIGUSR1),
-            #[cfg(target_os = "linux")]
-            12 => Ok(Signal::SIGUSR2),
-            #[cfg(target_os = "macos")]
-            30 => Ok(Signal::SIGUSR1),
-            #[cfg(target_os = "macos")]
-            31 => Ok(Signal::SIGUSR2),
-            _ => Err(SafeKillError::InvalidSignal(num.to_string())),
+        if let Ok(signal) = NixSignal::try_from(num) {
+            return Ok(Signal::Standard(signal));
         }
+
+        #[cfg(target_os = "linux")]
+        if let Some(offset) = Self::realtime_offset(num - libc::SIGRTMIN()) {
+            return Ok(Signal::Realtime(offset));
+        }
+
+        Err(SafeKillError::InvalidSignal(num.to_string()))
     }
 
     /// 名前からシグナルを解析する
@@ -110,16 +175,47 @@ impl SignalSender {
         // SIG プレフィックスがあれば除去
         let name = s.strip_prefix("SIG").unwrap_or(s);
 
-        match name {
-            "HUP" => Ok(Signal::SIGHUP),
-            "INT" => Ok(Signal::SIGINT),
-            "QUIT" => Ok(Signal::SIGQUIT),
-            "KILL" => Ok(Signal::SIGKILL),
-            "TERM" => Ok(Signal::SIGTERM),
-            "USR1" => Ok(Signal::SIGUSR1),
-            "USR2" => Ok(Signal::SIGUSR2),
-            _ => Err(SafeKillError::InvalidSignal(s.to_string())),
+        if let Some(signal) = NixSignal::iterator().find(|signal| &signal.as_str()[3..] == name) {
+            return Ok(Signal::Standard(signal));
         }
+
+        #[cfg(target_os = "linux")]
+        if let Some(signal) = Self::parse_realtime_name(name) {
+            return Ok(signal);
+        }
+
+        Err(SafeKillError::InvalidSignal(s.to_string()))
+    }
+
+    /// "RTMIN", "RTMIN+n", "RTMAX", "RTMAX-n" 形式のリアルタイムシグナル名を解析する
+    #[cfg(target_os = "linux")]
+    fn parse_realtime_name(name: &str) -> Option<Signal> {
+        let max = i32::from(Signal::realtime_max_offset());
+        let offset = if let Some(rest) = name.strip_prefix("RTMIN") {
+            match rest.strip_prefix('+') {
+                Some(n) => n.parse::<i32>().ok()?,
+                None if rest.is_empty() => 0,
+                None => return None,
+            }
+        } else if let Some(rest) = name.strip_prefix("RTMAX") {
+            match rest.strip_prefix('-') {
+                Some(n) => max - n.parse::<i32>().ok()?,
+                None if rest.is_empty() => max,
+                None => return None,
+            }
+        } else {
+            return None;
+        };
+
+        Self::realtime_offset(offset).map(Signal::Realtime)
+    }
+
+    /// オフセットが有効なリアルタイムシグナルの範囲内なら u8 で返す
+    #[cfg(target_os = "linux")]
+    fn realtime_offset(offset: i32) -> Option<u8> {
+        (0..=i32::from(Signal::realtime_max_offset()))
+            .contains(&offset)
+            .then_some(offset as u8)
     }
 
     /// プロセスにシグナルを送信する
@@ -131,9 +227,18 @@ impl SignalSender {
         }
 
         let nix_pid = Pid::from_raw(pid as i32);
-        let nix_signal = signal.to_nix();
+        let result = match signal {
+            Signal::Standard(nix_signal) => signal::kill(nix_pid, nix_signal),
+            // nix の Signal はリアルタイムシグナルを表現できないため libc を直接呼ぶ
+            #[cfg(target_os = "linux")]
+            Signal::Realtime(_) => {
+                // SAFETY: kill(2) はポインタを受け取らず、PID は上で検証済み
+                let res = unsafe { libc::kill(nix_pid.as_raw(), signal.number()) };
+                nix::errno::Errno::result(res).map(drop)
+            }
+        };
 
-        signal::kill(nix_pid, nix_signal).map_err(|e| match e {
+        result.map_err(|e| match e {
             nix::errno::Errno::ESRCH => SafeKillError::ProcessNotFound(pid),
             nix::errno::Errno::EPERM => SafeKillError::PermissionDenied(pid),
             _ => SafeKillError::SystemError(format!("Failed to send signal: {}", e)),
@@ -207,32 +312,159 @@ mod tests {
 
     #[cfg(target_os = "macos")]
     #[test]
-    fn test_parse_signal_linux_number_rejected_on_macos() {
-        // macOS ではシグナル番号 10 は SIGBUS であり SIGUSR1 ではない
-        assert!(
-            SignalSender::parse_signal("10").is_err(),
-            "macOS ではシグナル番号 10 は拒否されるべき"
+    fn test_parse_signal_linux_number_is_native_signal_on_macos() {
+        // macOS ではシグナル番号 10 は SIGBUS、12 は SIGSYS であり SIGUSR1/SIGUSR2 ではない
+        assert_eq!(
+            SignalSender::parse_signal("10").unwrap(),
+            Signal::Standard(NixSignal::SIGBUS)
         );
-        // macOS ではシグナル番号 12 は SIGSYS であり SIGUSR2 ではない（SIGPIPE は 13）
-        assert!(
-            SignalSender::parse_signal("12").is_err(),
-            "macOS ではシグナル番号 12 は拒否されるべき"
+        assert_eq!(
+            SignalSender::parse_signal("12").unwrap(),
+            Signal::Standard(NixSignal::SIGSYS)
         );
     }
 
     #[cfg(target_os = "linux")]
     #[test]
-    fn test_parse_signal_macos_number_rejected_on_linux() {
-        // Linux ではシグナル番号 30 は SIGPWR であり SIGUSR1 ではない
-        assert!(
-            SignalSender::parse_signal("30").is_err(),
-            "Linux ではシグナル番号 30 は拒否されるべき"
+    fn test_parse_signal_macos_number_is_native_signal_on_linux() {
+        // Linux ではシグナル番号 30 は SIGPWR、31 は SIGSYS であり SIGUSR1/SIGUSR2 ではない
+        assert_eq!(
+            SignalSender::parse_signal("30").unwrap(),
+            Signal::Standard(NixSignal::SIGPWR)
+        );
+        assert_eq!(
+            SignalSender::parse_signal("31").unwrap(),
+            Signal::Standard(NixSignal::SIGSYS)
+        );
+    }
+
+    // 拡張シグナルのテスト
+    #[test]
+    fn test_parse_signal_job_control_and_misc() {
+        assert_eq!(SignalSender::parse_signal("STOP").unwrap(), Signal::SIGSTOP);
+        assert_eq!(
+            SignalSender::parse_signal("SIGCONT").unwrap(),
+            Signal::SIGCONT
+        );
+        assert_eq!(SignalSender::parse_signal("tstp").unwrap(), Signal::SIGTSTP);
+        assert_eq!(
+            SignalSender::parse_signal("WINCH").unwrap(),
+            Signal::SIGWINCH
+        );
+        assert_eq!(
+            SignalSender::parse_signal("SIGALRM").unwrap(),
+            Signal::SIGALRM
         );
-        // Linux ではシグナル番号 31 は SIGSYS であり SIGUSR2 ではない
+        assert_eq!(SignalSender::parse_signal("14").unwrap(), Signal::SIGALRM);
+        assert_eq!(SignalSender::parse_signal("28").unwrap(), Signal::SIGWINCH);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_parse_signal_stop_cont_numbers_linux() {
+        assert_eq!(SignalSender::parse_signal("19").unwrap(), Signal::SIGSTOP);
+        assert_eq!(SignalSender::parse_signal("18").unwrap(), Signal::SIGCONT);
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn test_parse_signal_stop_cont_numbers_macos() {
+        assert_eq!(SignalSender::parse_signal("17").unwrap(), Signal::SIGSTOP);
+        assert_eq!(SignalSender::parse_signal("19").unwrap(), Signal::SIGCONT);
+    }
+
+    #[test]
+    fn test_parse_signal_roundtrip_all() {
+        // 一覧に含まれるすべてのシグナルは名前と番号の両方から解析できる
+        for signal in Signal::all() {
+            assert_eq!(SignalSender::parse_signal(signal.name()).unwrap(), signal);
+            assert_eq!(
+                SignalSender::parse_signal(&signal.number().to_string()).unwrap(),
+                signal
+            );
+        }
+    }
+
+    #[test]
+    fn test_signal_all_sorted_and_contains_common() {
+        let all = Signal::all();
         assert!(
-            SignalSender::parse_signal("31").is_err(),
-            "Linux ではシグナル番号 31 は拒否されるべき"
+            all.windows(2)
+                .all(|pair| pair[0].number() < pair[1].number())
         );
+        for signal in [
+            Signal::SIGKILL,
+            Signal::SIGSTOP,
+            Signal::SIGCONT,
+            Signal::SIGWINCH,
+        ] {
+            assert!(all.contains(&signal));
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_parse_signal_realtime() {
+        let rtmin = libc::SIGRTMIN();
+        let rtmax = libc::SIGRTMAX();
+
+        assert_eq!(
+            SignalSender::parse_signal("SIGRTMIN").unwrap(),
+            Signal::Realtime(0)
+        );
+        assert_eq!(
+            SignalSender::parse_signal("rtmin+3").unwrap(),
+            Signal::Realtime(3)
+        );
+        assert_eq!(
+            SignalSender::parse_signal("SIGRTMAX").unwrap().number(),
+            rtmax
+        );
+        assert_eq!(
+            SignalSender::parse_signal("SIGRTMAX-1").unwrap().number(),
+            rtmax - 1
+        );
+        assert_eq!(
+            SignalSender::parse_signal(&(rtmin + 2).to_string()).unwrap(),
+            Signal::Realtime(2)
+        );
+        assert_eq!(Signal::Realtime(3).name(), "SIGRTMIN+3");
+        assert_eq!(Signal::Realtime(3).number(), rtmin + 3);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_parse_signal_realtime_out_of_range() {
+        let rtmax = libc::SIGRTMAX();
+        assert!(SignalSender::parse_signal(&(rtmax + 1).to_string()).is_err());
+        assert!(SignalSender::parse_signal("SIGRTMIN+99").is_err());
+        assert!(SignalSender::parse_signal("SIGRTMAX-99").is_err());
+        assert!(SignalSender::parse_signal("SIGRTMIN3").is_err());
+        assert!(SignalSender::parse_signal("SIGRTMIN+").is_err());
+    }
+
+    #[test]
+    fn test_send_stop_and_cont_to_child() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("5")
+            .spawn()
+            .expect("sleep プロセスの起動に失敗");
+
+        assert!(SignalSender::send(child.id(), Signal::SIGSTOP).is_ok());
+        assert!(SignalSender::send(child.id(), Signal::SIGCONT).is_ok());
+
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_send_realtime_signal_to_nonexistent_process() {
+        let result = SignalSender::send(999999999, Signal::Realtime(1));
+        assert!(matches!(
+            result,
+            Err(SafeKillError::ProcessNotFound(999999999))
+        ));
     }
 
     // 名前からの解析テスト
@@ -437,11 +669,11 @@ mod tests {
         assert!(Signal::SIGUSR2.number() > 0);
     }
 
-    // サポート外のシグナル番号テスト
+    // プラットフォームのシグナル表にない番号のテスト
     #[test]
-    fn test_parse_signal_unsupported_numbers_in_range() {
-        // 1-31 の範囲内だがサポート対象外のシグナル番号
-        for num in [4, 5, 6, 7, 8, 11, 13, 14, 16, 17, 18, 19, 20, 21] {
+    fn test_parse_signal_unsupported_numbers() {
+        let max = Signal::all().last().unwrap().number();
+        for num in [0, max + 1, 128, 255] {
             let result = SignalSender::parse_signal(&num.to_string());
             assert!(
                 result.is_err(),
@@ -451,6 +683,15 @@ mod tests {
         }
     }
 
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_parse_signal_reserved_numbers_linux() {
+        // 32 から SIGRTMIN 未満はスレッド実装用に予約されていて送信対象にしない
+        for num in 32..libc::SIGRTMIN() {
+            assert!(SignalSender::parse_signal(&num.to_string()).is_err());
+        }
+    }
+
     #[test]
     fn test_signal_usr1_usr2_platform_number() {
         // プラットフォーム固有の番号が正しいことを検証
@@ -0,0 +1,291 @@
+//! safe-kill の Unix ドメインソケット検出モジュール
+//!
+//! Linux では `/proc/net/unix` のソケット inode と `/proc/<pid>/fd` のリンク先を
+//! 突き合わせて、指定パスの AF_UNIX ソケットを保持するプロセスを検出する。
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::error::SafeKillError;
+use crate::process_info::ProcessInfoProvider;
+
+/// Unix ドメインソケットを保持するプロセスの情報
+#[derive(Debug, Clone)]
+pub struct UnixSocketProcess {
+    /// プロセス ID
+    pub pid: u32,
+    /// プロセス名（取得できない場合は "pid:<pid>"）
+    pub name: String,
+    /// ソケットのパス（抽象名前空間は `@` 始まり）
+    pub path: PathBuf,
+}
+
+/// 指定パスの Unix ドメインソケットを保持するプロセスを検出する検出器
+pub struct UnixSocketDetector {
+    provider: ProcessInfoProvider,
+}
+
+impl UnixSocketDetector {
+    /// 新しい UnixSocketDetector を作成
+    pub fn new() -> Self {
+        Self {
+            provider: ProcessInfoProvider::new(),
+        }
+    }
+
+    /// プロセス情報を更新する
+    pub fn refresh(&mut self) {
+        self.provider.refresh();
+    }
+
+    /// 指定パスのソケットを保持するプロセスを PID 順に 1 件ずつ返す
+    ///
+    /// `path` は `resolve_socket_path` で正規化済みであることを想定する。
+    pub fn find_by_path(&self, path: &Path) -> Result<Vec<UnixSocketProcess>, SafeKillError> {
+        let inodes = self.bound_inodes(path)?;
+        Ok(self.find_by_inodes(path, &inodes))
+    }
+
+    /// 指定パスにバインドされたソケットの inode を集める
+    ///
+    /// `/proc/net/unix` はここで 1 回だけ読む。結果は `find_by_inodes` と
+    /// `pid_holds_any` に渡して、保持プロセスの検索と kill 直前の再確認に使い回す。
+    pub fn bound_inodes(&self, path: &Path) -> Result<HashSet<u64>, SafeKillError> {
+        socket_inodes(path).map_err(|e| {
+            SafeKillError::SystemError(format!("Failed to read Unix socket table: {}", e))
+        })
+    }
+
+    /// 指定 inode のソケットを保持するプロセスを PID 順に 1 件ずつ返す
+    pub fn find_by_inodes(&self, path: &Path, inodes: &HashSet<u64>) -> Vec<UnixSocketProcess> {
+        if inodes.is_empty() {
+            return Vec::new();
+        }
+
+        let mut results: Vec<UnixSocketProcess> = all_pids()
+            .into_iter()
+            .filter(|pid| holds_any(*pid, inodes))
+            .map(|pid| UnixSocketProcess {
+                pid,
+                // 表示用の名前。ポリシー判定では呼び出し側で最新の情報を取り直すこと。
+                name: self
+                    .provider
+                    .get(pid)
+                    .map(|p| p.name)
+                    .unwrap_or_else(|| format!("pid:{}", pid)),
+                path: path.to_path_buf(),
+            })
+            .collect();
+
+        results.sort_by_key(|p| p.pid);
+        results
+    }
+
+    /// 指定 PID が指定 inode のソケットをいま保持しているかを再確認する
+    ///
+    /// kill 直前の TOCTOU 緩和用。ソケット表は引き直さず、対象 PID の fd だけを読む。
+    /// 検出後に作り直されたソケットは inode が変わるため一致しない。
+    /// fd の読み取りに失敗した場合は安全側に倒して false を返す。
+    pub fn pid_holds_any(&self, pid: u32, inodes: &HashSet<u64>) -> bool {
+        !inodes.is_empty() && holds_any(pid, inodes)
+    }
+}
+
+impl Default for UnixSocketDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `--socket` に渡されたパスを照合用に正規化する
+///
+/// 抽象名前空間（`@name`）はそのまま返す。ファイルパスは絶対パスにし、
+/// シンボリックリンクを解決する。ソケットファイルが存在しない場合は `NoProcessOnSocket`。
+pub fn resolve_socket_path(path: &Path) -> Result<PathBuf, SafeKillError> {
+    if is_abstract(path) {
+        return Ok(path.to_path_buf());
+    }
+    path.canonicalize()
+        .map_err(|_| SafeKillError::NoProcessOnSocket(path.display().to_string()))
+}
+
+/// 抽象名前空間のソケット名か（`/proc/net/unix` 上では `@` 始まりで表示される）
+fn is_abstract(path: &Path) -> bool {
+    path.as_os_str().as_encoded_bytes().first() == Some(&b'@')
+}
+
+/// 指定パスにバインドされたソケットの inode を `/proc/net/unix` から集める
+#[cfg(target_os = "linux")]
+fn socket_inodes(path: &Path) -> Result<HashSet<u64>, String> {
+    let content = std::fs::read_to_string("/proc/net/unix").map_err(|e| e.to_string())?;
+    Ok(parse_proc_net_unix(&content)
+        .into_iter()
+        .filter(|(_, bound)| bound_path_matches(bound, path))
+        .map(|(inode, _)| inode)
+        .collect())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn socket_inodes(_path: &Path) -> Result<HashSet<u64>, String> {
+    Err("--socket is only supported on Linux".to_string())
+}
+
+/// `/proc/net/unix` に記録されたバインド名が正規化済みのパスと同じソケットを指すか
+///
+/// バインド名は bind(2) に渡された文字列のままなので、相対パスやシンボリックリンクを
+/// 含む場合は解決してから比較する。
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn bound_path_matches(bound: &str, path: &Path) -> bool {
+    let bound = Path::new(bound);
+    if bound == path {
+        return true;
+    }
+    if is_abstract(path) || is_abstract(bound) || bound.file_name() != path.file_name() {
+        return false;
+    }
+    bound.canonicalize().is_ok_and(|resolved| resolved == path)
+}
+
+/// `/proc/net/unix` の内容から `(inode, パス)` の組を取り出す
+///
+/// 形式: `Num RefCount Protocol Flags Type St Inode Path`。パスを持たない
+/// （名前なし）ソケットは除外する。
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_proc_net_unix(content: &str) -> Vec<(u64, String)> {
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let inode = fields.nth(6)?.parse().ok()?;
+            let path: Vec<&str> = fields.collect();
+            (!path.is_empty()).then(|| (inode, path.join(" ")))
+        })
+        .collect()
+}
+
+/// fd のリンク先（`socket:[12345]`）からソケット inode を取り出す
+pub(crate) fn parse_socket_link(link: &Path) -> Option<u64> {
+    link.to_str()?
+        .strip_prefix("socket:[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+/// 指定 PID の fd のいずれかが、指定 inode のソケットを指しているか
+fn holds_any(pid: u32, inodes: &HashSet<u64>) -> bool {
+    let Ok(entries) = std::fs::read_dir(format!("/proc/{}/fd", pid)) else {
+        return false;
+    };
+    entries.flatten().any(|entry| {
+        std::fs::read_link(entry.path())
+            .ok()
+            .and_then(|link| parse_socket_link(&link))
+            .is_some_and(|inode| inodes.contains(&inode))
+    })
+}
+
+/// `/proc` に見えている全 PID
+fn all_pids() -> Vec<u32> {
+    std::fs::read_dir("/proc")
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+
+    const PROC_NET_UNIX: &str = "\
+Num       RefCount Protocol Flags    Type St Inode Path
+0000000000000000: 00000002 00000000 00010000 0001 01 23456 /run/dev/lsp.sock
+0000000000000000: 00000003 00000000 00000000 0001 03 23457
+0000000000000000: 00000002 00000000 00010000 0001 01 23458 @/tmp/.X11-unix/X0
+0000000000000000: 00000002 00000000 00010000 0001 01 23459 /tmp/with space.sock
+";
+
+    #[test]
+    fn test_parse_proc_net_unix_extracts_named_sockets() {
+        assert_eq!(
+            parse_proc_net_unix(PROC_NET_UNIX),
+            vec![
+                (23456, "/run/dev/lsp.sock".to_string()),
+                (23458, "@/tmp/.X11-unix/X0".to_string()),
+                (23459, "/tmp/with space.sock".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_socket_link() {
+        assert_eq!(parse_socket_link(Path::new("socket:[23456]")), Some(23456));
+        assert_eq!(parse_socket_link(Path::new("pipe:[23456]")), None);
+        assert_eq!(parse_socket_link(Path::new("/dev/null")), None);
+    }
+
+    #[test]
+    fn test_resolve_socket_path_keeps_abstract_name() {
+        assert_eq!(
+            resolve_socket_path(Path::new("@safe-kill-test")),
+            Ok(PathBuf::from("@safe-kill-test"))
+        );
+    }
+
+    #[test]
+    fn test_resolve_socket_path_rejects_missing_file() {
+        assert!(matches!(
+            resolve_socket_path(Path::new("/nonexistent/safe-kill.sock")),
+            Err(SafeKillError::NoProcessOnSocket(_))
+        ));
+    }
+
+    #[test]
+    fn test_bound_path_matches_relative_binding() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("dev.sock");
+        let _listener = UnixListener::bind(&socket_path).unwrap();
+        let resolved = resolve_socket_path(&socket_path).unwrap();
+
+        let via_parent = dir.path().join(".").join("dev.sock");
+        assert!(bound_path_matches(via_parent.to_str().unwrap(), &resolved));
+        assert!(!bound_path_matches("/tmp/other/dev.sock", &resolved));
+        assert!(!bound_path_matches("@dev.sock", &resolved));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_find_by_path_detects_current_listener() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("detect.sock");
+        let listener = UnixListener::bind(&socket_path).unwrap();
+        let resolved = resolve_socket_path(&socket_path).unwrap();
+        let detector = UnixSocketDetector::new();
+        let current_pid = ProcessInfoProvider::current_pid();
+
+        let found = detector.find_by_path(&resolved).unwrap();
+        assert_eq!(
+            found.iter().map(|p| p.pid).collect::<Vec<_>>(),
+            vec![current_pid]
+        );
+        let inodes = detector.bound_inodes(&resolved).unwrap();
+        assert!(detector.pid_holds_any(current_pid, &inodes));
+        assert!(!detector.pid_holds_any(1, &inodes));
+
+        drop(listener);
+        assert!(!detector.pid_holds_any(current_pid, &inodes));
+        assert!(detector.find_by_path(&resolved).unwrap().is_empty());
+
+        // 同じパスに作り直したソケットは別の inode なので、以前の検出結果とは一致しない
+        std::fs::remove_file(&socket_path).unwrap();
+        let rebound = UnixListener::bind(&socket_path).unwrap();
+        assert!(!detector.pid_holds_any(current_pid, &inodes));
+        drop(rebound);
+    }
+}
@@ -7,16 +7,55 @@ use assert_cmd::Command;
 use predicates::prelude::*;
 use safe_kill::process_info::ProcessInfoProvider;
 use std::io::Write;
+use std::path::Path;
 use std::process::Stdio;
+use std::sync::OnceLock;
 use tempfile::NamedTempFile;
 
+/// root で実行される環境（CI コンテナなど）向けの HOME
+///
+/// 特権付きの実行では `[privileged]` セクションがないと kill 系の操作が拒否されるため、
+/// 最小限の `[privileged]` だけを置いた設定ディレクトリを HOME として使う。
+fn privileged_home() -> &'static Path {
+    static HOME: OnceLock<tempfile::TempDir> = OnceLock::new();
+    HOME.get_or_init(|| {
+        let home = tempfile::tempdir().expect("一時 HOME を作成できるべき");
+        let config_dir = home.path().join(".config").join("safe-kill");
+        std::fs::create_dir_all(&config_dir).unwrap();
+        std::fs::write(config_dir.join("config.toml"), "[privileged]\n").unwrap();
+        home
+    })
+    .path()
+}
+
+/// root で実行されている場合、設定内容に `[privileged]` セクションを追加する
+fn with_privileged(content: impl Into<String>) -> String {
+    let mut content = content.into();
+    if nix::unistd::geteuid().is_root() {
+        content.push_str("\n[privileged]\n");
+    }
+    content
+}
+
+/// テスト対象の safe-kill コマンドを生成する
+///
+/// root で実行されている場合は `privileged_home` を HOME に設定する。
+/// 個別に HOME を指定するテストでは、その設定ファイルが優先される。
+fn safe_kill_cmd() -> Command {
+    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    if nix::unistd::geteuid().is_root() {
+        cmd.env("HOME", privileged_home());
+    }
+    cmd
+}
+
 // =============================================================================
 // --list オプションの出力確認テスト
 // =============================================================================
 
 #[test]
 fn test_list_command_runs_successfully() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("--list").assert().success().stdout(
         predicate::str::contains("Killable processes").or(predicate::str::contains("No killable")),
     );
@@ -24,7 +63,7 @@ fn test_list_command_runs_successfully() {
 
 #[test]
 fn test_list_command_shows_header_format() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     let assert = cmd.arg("--list").assert();
 
     // ヘッダーか「終了可能なプロセスなし」のどちらかを表示する
@@ -38,14 +77,14 @@ fn test_list_command_shows_header_format() {
 #[test]
 fn test_list_with_dry_run_succeeds_as_list() {
     // `--list` では `--dry-run` が指定されても一覧表示として成功する
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("--list").arg("--dry-run").assert().success();
 }
 
 #[test]
 fn test_list_ignores_invalid_signal() {
     // 一覧表示ではシグナルを使わないため、無効な値でも失敗しない
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("--signal")
         .arg("INVALID")
         .arg("--list")
@@ -55,13 +94,13 @@ fn test_list_ignores_invalid_signal() {
 
 #[test]
 fn test_init_cannot_be_combined_with_list() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("--list").arg("init").assert().failure();
 }
 
 #[test]
 fn test_init_cannot_be_combined_with_pid() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("1234")
         .arg("init")
         .assert()
@@ -75,7 +114,7 @@ fn test_init_cannot_be_combined_with_pid() {
 
 #[test]
 fn test_dry_run_with_pid() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     // 存在しない PID で dry-run 時のエラー経路を確認する
     cmd.arg("999999999")
         .arg("--dry-run")
@@ -88,7 +127,7 @@ fn test_dry_run_with_pid() {
 
 #[test]
 fn test_dry_run_with_name() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("--name")
         .arg("__nonexistent_process_12345__")
         .arg("--dry-run")
@@ -105,7 +144,7 @@ fn test_dry_run_with_name() {
 fn test_dry_run_does_not_kill_self() {
     // dry-run でも自分自身の kill は拒否される
     let current_pid = std::process::id();
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg(current_pid.to_string())
         .arg("--dry-run")
         .assert()
@@ -119,7 +158,7 @@ fn test_dry_run_does_not_kill_self() {
 
 #[test]
 fn test_signal_option_sigterm() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("--signal")
         .arg("SIGTERM")
         .arg("999999999")
@@ -130,7 +169,7 @@ fn test_signal_option_sigterm() {
 
 #[test]
 fn test_signal_option_sigkill() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("--signal")
         .arg("9")
         .arg("999999999")
@@ -141,7 +180,7 @@ fn test_signal_option_sigkill() {
 
 #[test]
 fn test_signal_option_invalid() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("--signal")
         .arg("INVALID_SIGNAL")
         .arg("999999999")
@@ -152,7 +191,7 @@ fn test_signal_option_invalid() {
 
 #[test]
 fn test_signal_option_invalid_number() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("--signal")
         .arg("999")
         .arg("12345")
@@ -161,225 +200,737 @@ fn test_signal_option_invalid_number() {
         .stderr(predicate::str::contains("Invalid signal"));
 }
 
-// =============================================================================
-// 終了コードの確認テスト
-// =============================================================================
-
-#[test]
-fn test_exit_code_success_on_list() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-    cmd.arg("--list").assert().code(0);
-}
-
-#[test]
-fn test_exit_code_process_not_found() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-    cmd.arg("999999999").assert().code(1); // NoTarget exit code (includes ProcessNotFound)
-}
+/// 子プロセスに対して指定シグナルで dry-run を実行する
+fn assert_dry_run_signal_on_child(signal: &str) {
+    let mut child = std::process::Command::new("sleep")
+        .arg("60")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("failed to spawn sleep");
 
-#[test]
-fn test_exit_code_invalid_signal() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-    cmd.arg("--signal")
-        .arg("INVALID")
-        .arg("12345")
+    safe_kill_cmd()
+        .arg("-s")
+        .arg(signal)
+        .arg(child.id().to_string())
+        .arg("--dry-run")
         .assert()
-        .code(255); // GeneralError exit code (InvalidSignal maps to this)
-}
+        .success()
+        .stdout(predicate::str::contains("dry run"));
 
-#[test]
-fn test_exit_code_suicide_prevention() {
-    let current_pid = std::process::id();
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-    cmd.arg(current_pid.to_string()).assert().code(255); // GeneralError (SuicidePrevention)
+    let _ = child.kill();
+    let _ = child.wait();
 }
 
 #[test]
-fn test_exit_code_no_target() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-    cmd.arg("--name")
-        .arg("__nonexistent_process_xyz__")
-        .assert()
-        .code(1); // NoTarget exit code
+fn test_signal_option_job_control() {
+    assert_dry_run_signal_on_child("STOP");
+    assert_dry_run_signal_on_child("SIGCONT");
 }
 
+#[cfg(target_os = "linux")]
 #[test]
-fn test_name_not_found_reports_name() {
-    let missing_name = "__nonexistent_process_xyz__";
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-    cmd.arg("--name").arg(missing_name).assert().code(1).stderr(
-        predicate::str::contains(missing_name)
-            .and(predicate::str::contains("No process found with name:")),
-    );
+fn test_signal_option_realtime() {
+    assert_dry_run_signal_on_child("SIGRTMIN+1");
+    assert_dry_run_signal_on_child("RTMAX");
 }
 
 #[test]
-fn test_name_all_denied_reports_no_killable_target() {
+fn test_signal_forbidden_by_config() {
     use std::fs;
 
-    let pid1_name = ProcessInfoProvider::new()
-        .get(1)
-        .expect("PID 1 should exist")
-        .name;
-    let escaped_name = pid1_name.replace('\\', "\\\\").replace('"', "\\\"");
-
     let temp = tempfile::tempdir().unwrap();
     let config_dir = temp.path().join(".config").join("safe-kill");
     fs::create_dir_all(&config_dir).unwrap();
     fs::write(
         config_dir.join("config.toml"),
-        format!("[denylist]\nprocesses = [\"{}\"]\n", escaped_name),
+        with_privileged("[signals]\nforbid = [\"KILL\"]\n"),
     )
     .unwrap();
 
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-    cmd.env("HOME", temp.path())
-        .arg("--name")
-        .arg(&pid1_name)
+    let mut child = std::process::Command::new("sleep")
+        .arg("60")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("failed to spawn sleep");
+
+    safe_kill_cmd()
+        .env("HOME", temp.path())
+        .arg("-s")
+        .arg("KILL")
+        .arg(child.id().to_string())
+        .arg("--dry-run")
         .assert()
-        .code(1)
+        .code(6)
         .stderr(predicate::str::contains(
-            "No killable process found for name",
+            "Signal SIGKILL is not allowed for descendant targets",
         ));
-}
-
-// =============================================================================
-// CLI引数バリデーションテスト
-// =============================================================================
 
-#[test]
-fn test_no_arguments_shows_error() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-    cmd.assert()
-        .failure()
-        .stderr(predicate::str::contains("No target").or(predicate::str::contains("--help")));
+    let _ = child.kill();
+    let _ = child.wait();
 }
 
 #[test]
-fn test_help_option() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-    cmd.arg("--help").assert().success().stdout(
-        predicate::str::contains("safe-kill")
-            .and(predicate::str::contains("--list"))
-            .and(predicate::str::contains("--signal"))
-            .and(predicate::str::contains("--dry-run")),
-    );
+fn test_signals_subcommand_lists_signals() {
+    safe_kill_cmd()
+        .arg("signals")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("DESCENDANT"))
+        .stdout(predicate::str::contains("SIGKILL"))
+        .stdout(predicate::str::contains("SIGCONT"));
 }
 
 #[test]
-fn test_version_option() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-    cmd.arg("--version")
+fn test_signals_subcommand_shows_forbidden_status() {
+    use std::fs;
+
+    let temp = tempfile::tempdir().unwrap();
+    let config_dir = temp.path().join(".config").join("safe-kill");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(
+        config_dir.join("config.toml"),
+        "[signals.port]\nforbid = [\"KILL\"]\n",
+    )
+    .unwrap();
+
+    safe_kill_cmd()
+        .env("HOME", temp.path())
+        .arg("signals")
         .assert()
         .success()
-        .stdout(predicate::str::contains("safe-kill"));
+        .stdout(
+            predicate::str::is_match(r"(?m)^\s+9\s+SIGKILL\s+allowed\s+allowed\s+forbidden$")
+                .unwrap(),
+        );
 }
 
 #[test]
-fn test_pid_and_name_mutually_exclusive() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-    cmd.arg("12345")
-        .arg("--name")
-        .arg("process_name")
+fn test_signals_subcommand_rejects_signal_option() {
+    safe_kill_cmd()
+        .args(["-s", "KILL", "signals"])
         .assert()
         .failure();
 }
 
 #[test]
-fn test_pid_and_list_mutually_exclusive() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-    cmd.arg("12345").arg("--list").assert().failure();
-}
+fn test_unknown_signal_in_config_is_config_error() {
+    use std::fs;
 
-// =============================================================================
-// 実際のプロセス終了テスト（子プロセスを生成してテスト）
-// =============================================================================
+    let temp = tempfile::tempdir().unwrap();
+    let config_dir = temp.path().join(".config").join("safe-kill");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(
+        config_dir.join("config.toml"),
+        "[signals.allowlisted]\nforbid = [\"SIGNOPE\"]\n",
+    )
+    .unwrap();
+
+    safe_kill_cmd()
+        .env("HOME", temp.path())
+        .arg("--list")
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("unknown signal"));
+}
 
 #[test]
-fn test_kill_child_process_dry_run() {
-    // 待機する子プロセスを起動する
-    let child = std::process::Command::new("sleep")
-        .arg("60")
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn();
+fn test_ports_subcommand_json_reports_socket_on_allowed_port() {
+    use std::fs;
 
-    if let Ok(child) = child {
-        let child_pid = child.id();
+    let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    let port = socket.local_addr().unwrap().port();
+    let temp = tempfile::tempdir().unwrap();
+    let config_dir = temp.path().join(".config").join("safe-kill");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(
+        config_dir.join("config.toml"),
+        format!("[allowed_ports]\nports = [\"udp:{}\"]\n", port),
+    )
+    .unwrap();
 
-        // dry-run では実際に終了せず成功する
-        let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-        let result = cmd.arg(child_pid.to_string()).arg("--dry-run").assert();
+    let output = safe_kill_cmd()
+        .env("HOME", temp.path())
+        .args(["ports", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
 
-        // dry-run 成功として表示される
-        result.success().stdout(predicate::str::contains("dry run"));
+    let entries: Vec<serde_json::Value> =
+        serde_json::from_slice(&output.stdout).expect("JSON 配列を出力するべき");
+    let own = entries
+        .iter()
+        .find(|e| e["port"] == port && e["pid"] == std::process::id())
+        .expect("テストプロセスが保持する UDP ソケットが含まれるべき");
+    assert_eq!(own["protocol"], "udp");
+    assert_eq!(own["address"], "127.0.0.1");
+    // テストプロセスは safe-kill の祖先なので kill できない
+    assert_eq!(own["killable"], false);
+    assert!(own["reason"].is_string());
+    assert!(entries.iter().all(|e| e["port"] == port));
 
-        // プロセスが生き続けていることを確認する
-        let check = std::process::Command::new("kill")
-            .arg("-0")
-            .arg(child_pid.to_string())
-            .status();
+    drop(socket);
+}
 
-        assert!(
-            check.is_ok() && check.unwrap().success(),
-            "Child process should still be running after dry-run"
-        );
+#[test]
+fn test_allow_command_grants_port_until_expiry() {
+    let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    let port = socket.local_addr().unwrap().port();
+    let home = home_with_config("");
 
-        // 後始末として実際に終了する
-        let _ = std::process::Command::new("kill")
-            .arg(child_pid.to_string())
-            .status();
-    }
+    safe_kill_cmd()
+        .env("HOME", home.path())
+        .args(["allow", "--port", &format!("udp:{}", port), "--for", "1h"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "Allowed port udp:{} until",
+            port
+        )));
+    let grants = std::fs::read_to_string(home.path().join(".config/safe-kill/grants.toml"))
+        .expect("grants.toml が作成されるべき");
+    assert!(grants.contains(&format!("port = \"udp:{}\"", port)));
+
+    let output = safe_kill_cmd()
+        .env("HOME", home.path())
+        .args(["ports", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let entries: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(
+        entries
+            .iter()
+            .any(|e| e["port"] == port && e["allowed"] == true),
+        "期限内の許可は [allowed_ports] に合流するべき"
+    );
+
+    drop(socket);
 }
 
 #[test]
-fn test_kill_child_process_actually() {
-    // 待機する子プロセスを起動する
-    let child = std::process::Command::new("sleep")
-        .arg("60")
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn();
+fn test_expired_allowed_port_is_ignored_with_warning() {
+    let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    let port = socket.local_addr().unwrap().port();
+    let home = home_with_config(&format!(
+        "[allowed_ports]\nports = [{{ port = \"udp:{}\", expires = \"2000-01-01T00:00:00Z\" }}]\n",
+        port
+    ));
 
-    if let Ok(mut child) = child {
-        let child_pid = child.id();
+    let output = safe_kill_cmd()
+        .env("HOME", home.path())
+        .args(["ports", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr)
+            .contains("expired at 2000-01-01T00:00:00Z and is ignored")
+    );
+    let entries: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(entries.iter().all(|e| e["port"] != port));
 
-        // 実際に子プロセスを終了する
-        let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-        let result = cmd.arg(child_pid.to_string()).assert();
+    drop(socket);
+}
 
-        // 成功表示になる
-        result.success().stdout(predicate::str::contains("✓"));
+#[test]
+fn test_allow_command_refuses_denylisted_name() {
+    let home = home_with_config("");
 
-        // ゾンビ化を避けるため回収する
-        let _ = child.wait();
+    safe_kill_cmd()
+        .env("HOME", home.path())
+        .args(["allow", "--name", "systemd", "--for", "1h"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("denylist"));
+    assert!(!home.path().join(".config/safe-kill/grants.toml").exists());
+}
 
-        // OS 側の後始末待ち
-        std::thread::sleep(std::time::Duration::from_millis(200));
+#[test]
+fn test_config_validate_reports_every_problem_with_position() {
+    let mut file = NamedTempFile::new().unwrap();
+    writeln!(
+        file,
+        "[allowlist]\nprocesses = \"node\"\n\n[allowed_ports]\nports = [\"3000\", \"0\"]\n\n[signals]\nforbid = [\"SIGFOO\"]"
+    )
+    .unwrap();
+    let path = file.path().display().to_string();
 
-        // すでに終了済みであることを確認する
-        let check = std::process::Command::new("kill")
-            .arg("-0")
-            .arg(child_pid.to_string())
-            .status();
+    safe_kill_cmd()
+        .args(["config", "validate", &path])
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains(format!(
+            "{}:2:13: invalid type",
+            path
+        )))
+        .stderr(predicate::str::contains(format!(
+            "{}:5:18: [allowed_ports]",
+            path
+        )))
+        .stderr(predicate::str::contains(format!(
+            "{}:7:1: [signals] unknown signal",
+            path
+        )))
+        .stderr(predicate::str::contains("3 problem(s) found"));
+}
+
+#[test]
+fn test_config_validate_lint_only_warns() {
+    let mut file = NamedTempFile::new().unwrap();
+    writeln!(
+        file,
+        "[allowed_ports]\nports = [\"3000-3010\", \"3000-30l0\"]"
+    )
+    .unwrap();
+    let path = file.path().display().to_string();
 
-        // `kill -0` が失敗するか非 0 を返せば終了済み
-        let is_terminated = check.is_err() || !check.unwrap().success();
-        assert!(is_terminated, "Child process should be terminated");
-    }
+    safe_kill_cmd()
+        .args(["config", "validate", "--lint", &path])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(format!(
+            "Warning: {}:2:23: [allowed_ports] invalid entry \"3000-30l0\"",
+            path
+        )))
+        .stderr(predicate::str::contains("1 warning(s)"));
+    safe_kill_cmd()
+        .args(["config", "validate", &path])
+        .assert()
+        .code(3);
 }
 
 #[test]
-fn test_kill_child_by_name_dry_run() {
-    // 一意な名前の子プロセスを起動する
-    let mut script = NamedTempFile::new().unwrap();
-    writeln!(script, "#!/bin/bash\nsleep 60").unwrap();
+fn test_config_add_commands_preserve_comments() {
+    let home = home_with_config("# my ports\n[allowed_ports]\nports = [\"3000\"] # dev\n");
+    let path = home.path().join(".config/safe-kill/config.toml");
 
-    let script_path = script.path().to_str().unwrap();
-    let _ = std::process::Command::new("chmod")
-        .arg("+x")
-        .arg(script_path)
-        .status();
+    safe_kill_cmd()
+        .env("HOME", home.path())
+        .args(["config", "add-port", "4000-4010"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Added port 4000-4010 to [allowed_ports]",
+        ));
+    safe_kill_cmd()
+        .env("HOME", home.path())
+        .args(["config", "add-deny", "postgres"])
+        .assert()
+        .success();
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    assert!(
+        content
+            .starts_with("# my ports\n[allowed_ports]\nports = [\"3000\", \"4000-4010\"] # dev\n")
+    );
+    assert!(content.contains("[denylist]\nprocesses = [\"postgres\"]"));
+
+    safe_kill_cmd()
+        .env("HOME", home.path())
+        .args(["config", "validate"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("OK"));
+    safe_kill_cmd()
+        .env("HOME", home.path())
+        .args(["config", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"4000-4010\""))
+        .stdout(predicate::str::contains("\"postgres\""))
+        .stdout(
+            predicate::str::contains("\"systemd\"").or(predicate::str::contains("\"launchd\"")),
+        );
+}
+
+#[test]
+fn test_config_path_from_env_and_option() {
+    let mut from_env = NamedTempFile::new().unwrap();
+    writeln!(from_env, "[allowed_ports]\nports = [\"4100\"]").unwrap();
+    let mut from_option = NamedTempFile::new().unwrap();
+    writeln!(from_option, "[allowed_ports]\nports = [\"4200\"]").unwrap();
+
+    safe_kill_cmd()
+        .env("SAFE_KILL_CONFIG", from_env.path())
+        .args(["config", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"4100\""));
+    // --config は SAFE_KILL_CONFIG より優先する
+    safe_kill_cmd()
+        .env("SAFE_KILL_CONFIG", from_env.path())
+        .args(["config", "show", "--config"])
+        .arg(from_option.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"4200\""))
+        .stdout(predicate::str::contains("\"4100\"").not());
+    // 明示したファイルがなければデフォルトにフォールバックしない
+    safe_kill_cmd()
+        .args(["config", "show", "--config", "/nonexistent/safe-kill.toml"])
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("does not exist"));
+}
+
+#[test]
+fn test_profile_from_option_and_env() {
+    let mut config = NamedTempFile::new().unwrap();
+    writeln!(
+        config,
+        "[allowed_ports]\nports = [\"4500\"]\n\n[profiles.frontend.allowed_ports]\nports = [\"4600-4610\"]\n\n[profiles.data.allowed_ports]\nports = [\"4700\"]"
+    )
+    .unwrap();
+
+    safe_kill_cmd()
+        .args(["config", "show", "--profile", "frontend", "--config"])
+        .arg(config.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "[allowed_ports]\nports = [\"4600-4610\"]",
+        ))
+        .stdout(predicate::str::contains("ports = [\"4500\"]").not());
+    // --profile は SAFE_KILL_PROFILE より優先する
+    safe_kill_cmd()
+        .env("SAFE_KILL_PROFILE", "data")
+        .args(["config", "show", "--config"])
+        .arg(config.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "[allowed_ports]\nports = [\"4700\"]",
+        ));
+    safe_kill_cmd()
+        .env("SAFE_KILL_PROFILE", "data")
+        .args(["config", "show", "--profile", "frontend", "--config"])
+        .arg(config.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "[allowed_ports]\nports = [\"4600-4610\"]",
+        ));
+    safe_kill_cmd()
+        .args(["config", "show", "--profile", "backend", "--config"])
+        .arg(config.path())
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains(
+            "Profile 'backend' is not defined (available: data, frontend)",
+        ));
+}
+
+#[test]
+fn test_env_overrides_only_tighten_the_config() {
+    let mut config = NamedTempFile::new().unwrap();
+    write!(
+        config,
+        "{}",
+        with_privileged(
+            "[allowed_ports]\nports = [\"4300\", \"4400-4500\"]\n\n[ownership]\nenforce = true\n\n[limits]\nsigkills_per_session = 1\n"
+        )
+    )
+    .unwrap();
+
+    safe_kill_cmd()
+        .env("SAFE_KILL_ALLOWED_PORTS", "4300,4400-4410")
+        .env("SAFE_KILL_DENYLIST", "")
+        .env("SAFE_KILL_LIMITS_SIGKILLS_PER_SESSION", "1000")
+        .args(["config", "show", "--set", "confirm.above=2", "--config"])
+        .arg(config.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"4400-4410\""))
+        .stdout(predicate::str::contains("\"4400-4500\"").not())
+        .stdout(predicate::str::contains("above = 2"))
+        .stdout(predicate::str::contains("enforce = true"))
+        .stdout(predicate::str::contains("sigkills_per_session = 1\n"))
+        .stdout(
+            predicate::str::contains("\"systemd\"").or(predicate::str::contains("\"launchd\"")),
+        );
+
+    for (name, value) in [
+        ("SAFE_KILL_OWNERSHIP_ENFORCE", "false"),
+        ("SAFE_KILL_ALLOWED_PORTS", "1-65535"),
+        ("SAFE_KILL_SIGNALS_ALLOW", "SIGKILL"),
+        ("SAFE_KILL_PRIVILEGED_ALLOWLIST_BYPASS", "true"),
+        ("SAFE_KILL_ALLOWED_PORTS", "4410-4400"),
+    ] {
+        safe_kill_cmd()
+            .env(name, value)
+            .args(["config", "show", "--config"])
+            .arg(config.path())
+            .assert()
+            .code(3)
+            .stderr(predicate::str::contains(name));
+    }
+}
+
+#[test]
+fn test_env_override_cannot_allow_unlisted_port() {
+    use std::fs;
+
+    let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    let port = socket.local_addr().unwrap().port();
+
+    let temp = tempfile::tempdir().unwrap();
+    let config_dir = temp.path().join(".config").join("safe-kill");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(config_dir.join("config.toml"), with_privileged("")).unwrap();
+
+    safe_kill_cmd()
+        .env("HOME", temp.path())
+        .args(["--port", &port.to_string(), "--dry-run"])
+        .assert()
+        .code(4);
+    // 設定ファイルにないポートは上書きでも許可できない
+    safe_kill_cmd()
+        .env("HOME", temp.path())
+        .env("SAFE_KILL_ALLOWED_PORTS", "1-65535")
+        .args(["--port", &port.to_string(), "--dry-run"])
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains(
+            "[allowed_ports] is not set in the config file",
+        ));
+
+    drop(socket);
+}
+
+#[test]
+fn test_ports_subcommand_all_succeeds() {
+    safe_kill_cmd().args(["ports", "--all"]).assert().success();
+}
+
+#[test]
+fn test_ports_subcommand_rejects_signal_option() {
+    safe_kill_cmd()
+        .args(["-s", "KILL", "ports"])
+        .assert()
+        .failure();
+}
+
+// =============================================================================
+// 終了コードの確認テスト
+// =============================================================================
+
+#[test]
+fn test_exit_code_success_on_list() {
+    let mut cmd = safe_kill_cmd();
+    cmd.arg("--list").assert().code(0);
+}
+
+#[test]
+fn test_exit_code_process_not_found() {
+    let mut cmd = safe_kill_cmd();
+    cmd.arg("999999999").assert().code(1); // NoTarget exit code (includes ProcessNotFound)
+}
+
+#[test]
+fn test_exit_code_invalid_signal() {
+    let mut cmd = safe_kill_cmd();
+    cmd.arg("--signal")
+        .arg("INVALID")
+        .arg("12345")
+        .assert()
+        .code(255); // GeneralError exit code (InvalidSignal maps to this)
+}
+
+#[test]
+fn test_exit_code_suicide_prevention() {
+    let current_pid = std::process::id();
+    let mut cmd = safe_kill_cmd();
+    cmd.arg(current_pid.to_string()).assert().code(255); // GeneralError (SuicidePrevention)
+}
+
+#[test]
+fn test_exit_code_no_target() {
+    let mut cmd = safe_kill_cmd();
+    cmd.arg("--name")
+        .arg("__nonexistent_process_xyz__")
+        .assert()
+        .code(1); // NoTarget exit code
+}
+
+#[test]
+fn test_name_not_found_reports_name() {
+    let missing_name = "__nonexistent_process_xyz__";
+    let mut cmd = safe_kill_cmd();
+    cmd.arg("--name").arg(missing_name).assert().code(1).stderr(
+        predicate::str::contains(missing_name)
+            .and(predicate::str::contains("No process found with name:")),
+    );
+}
+
+#[test]
+fn test_name_all_denied_reports_no_killable_target() {
+    use std::fs;
+
+    let pid1_name = ProcessInfoProvider::new()
+        .get(1)
+        .expect("PID 1 should exist")
+        .name;
+    let escaped_name = pid1_name.replace('\\', "\\\\").replace('"', "\\\"");
+
+    let temp = tempfile::tempdir().unwrap();
+    let config_dir = temp.path().join(".config").join("safe-kill");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(
+        config_dir.join("config.toml"),
+        with_privileged(format!("[denylist]\nprocesses = [\"{}\"]\n", escaped_name)),
+    )
+    .unwrap();
+
+    let mut cmd = safe_kill_cmd();
+    cmd.env("HOME", temp.path())
+        .arg("--name")
+        .arg(&pid1_name)
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "No killable process found for name",
+        ));
+}
+
+// =============================================================================
+// CLI引数バリデーションテスト
+// =============================================================================
+
+#[test]
+fn test_no_arguments_shows_error() {
+    let mut cmd = safe_kill_cmd();
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("No target").or(predicate::str::contains("--help")));
+}
+
+#[test]
+fn test_help_option() {
+    let mut cmd = safe_kill_cmd();
+    cmd.arg("--help").assert().success().stdout(
+        predicate::str::contains("safe-kill")
+            .and(predicate::str::contains("--list"))
+            .and(predicate::str::contains("--signal"))
+            .and(predicate::str::contains("--dry-run")),
+    );
+}
+
+#[test]
+fn test_version_option() {
+    let mut cmd = safe_kill_cmd();
+    cmd.arg("--version")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("safe-kill"));
+}
+
+#[test]
+fn test_pid_and_name_mutually_exclusive() {
+    let mut cmd = safe_kill_cmd();
+    cmd.arg("12345")
+        .arg("--name")
+        .arg("process_name")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_pid_and_list_mutually_exclusive() {
+    let mut cmd = safe_kill_cmd();
+    cmd.arg("12345").arg("--list").assert().failure();
+}
+
+// =============================================================================
+// 実際のプロセス終了テスト（子プロセスを生成してテスト）
+// =============================================================================
+
+#[test]
+fn test_kill_child_process_dry_run() {
+    // 待機する子プロセスを起動する
+    let child = std::process::Command::new("sleep")
+        .arg("60")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+
+    if let Ok(child) = child {
+        let child_pid = child.id();
+
+        // dry-run では実際に終了せず成功する
+        let mut cmd = safe_kill_cmd();
+        let result = cmd.arg(child_pid.to_string()).arg("--dry-run").assert();
+
+        // dry-run 成功として表示される
+        result.success().stdout(predicate::str::contains("dry run"));
+
+        // プロセスが生き続けていることを確認する
+        let check = std::process::Command::new("kill")
+            .arg("-0")
+            .arg(child_pid.to_string())
+            .status();
+
+        assert!(
+            check.is_ok() && check.unwrap().success(),
+            "Child process should still be running after dry-run"
+        );
+
+        // 後始末として実際に終了する
+        let _ = std::process::Command::new("kill")
+            .arg(child_pid.to_string())
+            .status();
+    }
+}
+
+#[test]
+fn test_kill_child_process_actually() {
+    // 待機する子プロセスを起動する
+    let child = std::process::Command::new("sleep")
+        .arg("60")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+
+    if let Ok(mut child) = child {
+        let child_pid = child.id();
+
+        // 実際に子プロセスを終了する
+        let mut cmd = safe_kill_cmd();
+        let result = cmd.arg(child_pid.to_string()).assert();
+
+        // 成功表示になる
+        result.success().stdout(predicate::str::contains("✓"));
+
+        // ゾンビ化を避けるため回収する
+        let _ = child.wait();
+
+        // OS 側の後始末待ち
+        std::thread::sleep(std::time::Duration::from_millis(200));
+
+        // すでに終了済みであることを確認する
+        let check = std::process::Command::new("kill")
+            .arg("-0")
+            .arg(child_pid.to_string())
+            .status();
+
+        // `kill -0` が失敗するか非 0 を返せば終了済み
+        let is_terminated = check.is_err() || !check.unwrap().success();
+        assert!(is_terminated, "Child process should be terminated");
+    }
+}
+
+#[test]
+fn test_kill_child_by_name_dry_run() {
+    // 一意な名前の子プロセスを起動する
+    let mut script = NamedTempFile::new().unwrap();
+    writeln!(script, "#!/bin/bash\nsleep 60").unwrap();
+
+    let script_path = script.path().to_str().unwrap();
+    let _ = std::process::Command::new("chmod")
+        .arg("+x")
+        .arg(script_path)
+        .status();
 
     let child = std::process::Command::new("bash")
         .arg("-c")
@@ -393,115 +944,375 @@ fn test_kill_child_by_name_dry_run() {
         std::thread::sleep(std::time::Duration::from_millis(100));
 
         // 名前指定の dry-run を試す
-        let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+        let mut cmd = safe_kill_cmd();
         let result = cmd
             .arg("--name")
             .arg("safe_kill_test_target")
             .arg("--dry-run")
             .assert();
 
-        // OS 実装差で見つからない場合があるため、少なくとも異常終了しないことだけ見る
-        result.try_success().ok();
+        // OS 実装差で見つからない場合があるため、少なくとも異常終了しないことだけ見る
+        result.try_success().ok();
+
+        // 後始末
+        let _ = std::process::Command::new("pkill")
+            .arg("-f")
+            .arg("safe_kill_test_target")
+            .status();
+    }
+}
+
+#[test]
+fn test_name_dry_run_summary_uses_would_kill() {
+    let mut child = std::process::Command::new("sleep")
+        .arg("60")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(100));
+
+    let mut cmd = safe_kill_cmd();
+    cmd.arg("--name")
+        .arg("sleep")
+        .arg("--dry-run")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("would kill"));
+
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+// =============================================================================
+// 設定ファイルとの連携テスト
+// =============================================================================
+
+#[test]
+fn test_denylist_prevents_kill() {
+    // launchd (macOS) または systemd (Linux) の kill を試みる - 拒否されるべき
+    #[cfg(target_os = "macos")]
+    {
+        let mut cmd = safe_kill_cmd();
+        cmd.arg("1") // launchd PID
+            .assert()
+            .failure()
+            .stderr(
+                predicate::str::contains("denylist")
+                    .or(predicate::str::contains("not a descendant"))
+                    .or(predicate::str::contains("ancestor of safe-kill"))
+                    .or(predicate::str::contains("denied")),
+            );
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let mut cmd = safe_kill_cmd();
+        cmd.arg("1") // systemd/init PID
+            .assert()
+            .failure()
+            .stderr(
+                predicate::str::contains("denylist")
+                    .or(predicate::str::contains("not a descendant"))
+                    .or(predicate::str::contains("ancestor of safe-kill"))
+                    .or(predicate::str::contains("denied")),
+            );
+    }
+}
+
+#[test]
+fn test_invalid_config_does_not_fall_back_for_kill() {
+    use std::fs;
+
+    let temp = tempfile::tempdir().unwrap();
+    let config_dir = temp.path().join(".config").join("safe-kill");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(
+        config_dir.join("config.toml"),
+        "[denylist]\nprocesses = [\"sleep\"]\n{{invalid}}\n",
+    )
+    .unwrap();
+
+    let mut child = std::process::Command::new("sleep")
+        .arg("60")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("sleep プロセスの起動に失敗");
+
+    let mut cmd = safe_kill_cmd();
+    cmd.env("HOME", temp.path())
+        .arg(child.id().to_string())
+        .arg("--dry-run")
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("Config parse error"));
+
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[test]
+fn test_invalid_config_does_not_fall_back_for_port_kill() {
+    use std::fs;
+
+    let temp = tempfile::tempdir().unwrap();
+    let config_dir = temp.path().join(".config").join("safe-kill");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(
+        config_dir.join("config.toml"),
+        "[allowed_ports]\nports = [\"3000\"]\n{{invalid}}\n",
+    )
+    .unwrap();
+
+    let mut cmd = safe_kill_cmd();
+    cmd.env("HOME", temp.path())
+        .arg("--port")
+        .arg("3000")
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("Config parse error"));
+}
+
+#[test]
+fn test_unknown_config_field_does_not_fall_back_for_list() {
+    use std::fs;
+
+    let temp = tempfile::tempdir().unwrap();
+    let config_dir = temp.path().join(".config").join("safe-kill");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(
+        config_dir.join("config.toml"),
+        "[denylst]\nprocesses = [\"sleep\"]\n",
+    )
+    .unwrap();
+
+    let mut cmd = safe_kill_cmd();
+    cmd.env("HOME", temp.path())
+        .arg("--list")
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("Config parse error"));
+}
+
+#[test]
+fn test_unknown_ownership_user_is_config_error() {
+    use std::fs;
+
+    let temp = tempfile::tempdir().unwrap();
+    let config_dir = temp.path().join(".config").join("safe-kill");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(
+        config_dir.join("config.toml"),
+        "[ownership]\nusers = [\"no_such_user_for_safe_kill\"]\n",
+    )
+    .unwrap();
 
-        // 後始末
-        let _ = std::process::Command::new("pkill")
-            .arg("-f")
-            .arg("safe_kill_test_target")
-            .status();
-    }
+    let mut cmd = safe_kill_cmd();
+    cmd.env("HOME", temp.path())
+        .arg("--list")
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("unknown user"));
 }
 
 #[test]
-fn test_name_dry_run_summary_uses_would_kill() {
+fn test_elevated_without_privileged_section_is_refused() {
+    // 特権付きで実行されている環境でのみ検証できる
+    if !nix::unistd::geteuid().is_root() {
+        return;
+    }
+
+    let temp = tempfile::tempdir().unwrap();
     let mut child = std::process::Command::new("sleep")
         .arg("60")
         .stdout(Stdio::null())
         .stderr(Stdio::null())
         .spawn()
-        .unwrap();
-
-    std::thread::sleep(std::time::Duration::from_millis(100));
+        .expect("sleep プロセスの起動に失敗");
 
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-    cmd.arg("--name")
-        .arg("sleep")
+    let mut cmd = safe_kill_cmd();
+    cmd.env("HOME", temp.path())
+        .arg(child.id().to_string())
         .arg("--dry-run")
         .assert()
-        .success()
-        .stdout(predicate::str::contains("would kill"));
+        .code(5)
+        .stderr(predicate::str::contains("[privileged]"));
+
+    // 読み取り専用の一覧表示は拒否しない
+    let mut cmd = safe_kill_cmd();
+    cmd.env("HOME", temp.path())
+        .arg("--list")
+        .assert()
+        .success();
 
     let _ = child.kill();
     let _ = child.wait();
 }
 
+#[test]
+fn test_custom_config_file_path() {
+    // 一時的な設定ファイルを作成
+    let mut config_file = NamedTempFile::new().unwrap();
+    writeln!(
+        config_file,
+        r#"
+[denylist]
+processes = ["test_denied_process"]
+"#
+    )
+    .unwrap();
+
+    // 設定の読み込みは起動時に行われるため、CLI 経由でカスタムパスを簡単にテストできない。
+    // このテストは設定ファイルが存在してもバイナリが正常に動作することを確認する。
+    let mut cmd = safe_kill_cmd();
+    cmd.arg("--list").assert().success();
+}
+
 // =============================================================================
-// 設定ファイルとの連携テスト
+// エッジケースのテスト
 // =============================================================================
 
 #[test]
-fn test_denylist_prevents_kill() {
-    // launchd (macOS) または systemd (Linux) の kill を試みる - 拒否されるべき
-    #[cfg(target_os = "macos")]
-    {
-        let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-        cmd.arg("1") // launchd PID
-            .assert()
-            .failure()
-            .stderr(
-                predicate::str::contains("denylist")
-                    .or(predicate::str::contains("not a descendant"))
-                    .or(predicate::str::contains("denied")),
-            );
-    }
+fn test_invalid_pid_format() {
+    let mut cmd = safe_kill_cmd();
+    cmd.arg("not_a_number").assert().failure();
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-        cmd.arg("1") // systemd/init PID
-            .assert()
-            .failure()
-            .stderr(
-                predicate::str::contains("denylist")
-                    .or(predicate::str::contains("not a descendant"))
-                    .or(predicate::str::contains("denied")),
-            );
-    }
+#[test]
+fn test_negative_pid() {
+    let mut cmd = safe_kill_cmd();
+    cmd.arg("-1").assert().failure();
 }
 
 #[test]
-fn test_invalid_config_does_not_fall_back_for_kill() {
+fn test_very_large_pid() {
+    let mut cmd = safe_kill_cmd();
+    cmd.arg("99999999999999").assert().failure();
+}
+
+#[test]
+fn test_pid_zero() {
+    // PID 0はUnixで特殊な意味を持つ（プロセスグループ全体にシグナルを送る）
+    // safe-killでは拒否すべき
+    let mut cmd = safe_kill_cmd();
+    cmd.arg("0")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid PID: 0"));
+}
+
+#[test]
+fn test_pid_over_i32_max() {
+    // nix::Pid が扱う i32 の範囲を超える PID は拒否すべき
+    let mut cmd = safe_kill_cmd();
+    cmd.arg("2147483648")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid PID: 2147483648"));
+}
+
+#[test]
+fn test_empty_name() {
+    let mut cmd = safe_kill_cmd();
+    cmd.arg("--name").arg("").assert().failure();
+}
+
+#[test]
+fn test_special_characters_in_name() {
+    let mut cmd = safe_kill_cmd();
+    cmd.arg("--name")
+        .arg("process*with?special[chars]")
+        .assert()
+        .failure()
+        .stderr(
+            predicate::str::contains("No process found with name")
+                .or(predicate::str::contains("not found"))
+                .or(predicate::str::contains("No matching")),
+        );
+}
+
+// =============================================================================
+// --port オプションのテスト (Task 10.1)
+// =============================================================================
+
+#[test]
+fn test_help_shows_port_option() {
+    let mut cmd = safe_kill_cmd();
+    cmd.arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--port").and(predicate::str::contains("-p")));
+}
+
+#[test]
+fn test_help_shows_port_filter_options() {
+    safe_kill_cmd()
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--tcp"))
+        .stdout(predicate::str::contains("--udp"))
+        .stdout(predicate::str::contains("--bind"));
+}
+
+#[test]
+fn test_port_filter_requires_port() {
+    safe_kill_cmd()
+        .args(["--udp", "--list"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("only be used with --port"));
+}
+
+#[test]
+fn test_port_filter_bind_family_mismatch() {
+    safe_kill_cmd()
+        .args(["-p", "3000", "-4", "--bind", "::1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--bind ::1 does not match -4"));
+}
+
+#[test]
+fn test_port_protocol_entry_rejects_other_protocol_socket() {
     use std::fs;
 
+    // テスト自身が 127.0.0.1 に UDP ソケットを保持する
+    let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    let port = socket.local_addr().unwrap().port();
+
     let temp = tempfile::tempdir().unwrap();
     let config_dir = temp.path().join(".config").join("safe-kill");
     fs::create_dir_all(&config_dir).unwrap();
     fs::write(
         config_dir.join("config.toml"),
-        "[denylist]\nprocesses = [\"sleep\"]\n{{invalid}}\n",
+        with_privileged(format!("[allowed_ports]\nports = [\"tcp:{}\"]", port)),
     )
     .unwrap();
 
-    let mut child = std::process::Command::new("sleep")
-        .arg("60")
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()
-        .expect("sleep プロセスの起動に失敗");
+    // TCP のみ許可されているため UDP ソケットは対象にならない
+    safe_kill_cmd()
+        .env("HOME", temp.path())
+        .args(["--port", &port.to_string(), "--dry-run"])
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains("UDP socket bound to 127.0.0.1"));
 
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-    cmd.env("HOME", temp.path())
-        .arg(child.id().to_string())
-        .arg("--dry-run")
+    // --tcp で絞り込むと一致するソケットがない
+    safe_kill_cmd()
+        .env("HOME", temp.path())
+        .args(["--port", &port.to_string(), "--tcp", "--dry-run"])
         .assert()
-        .code(3)
-        .stderr(predicate::str::contains("Config parse error"));
+        .code(1)
+        .stderr(predicate::str::contains("No process"));
 
-    let _ = child.kill();
-    let _ = child.wait();
+    drop(socket);
 }
 
 #[test]
-fn test_invalid_config_does_not_fall_back_for_port_kill() {
+fn test_port_range_must_be_fully_allowed() {
     use std::fs;
 
     let temp = tempfile::tempdir().unwrap();
@@ -509,21 +1320,20 @@ fn test_invalid_config_does_not_fall_back_for_port_kill() {
     fs::create_dir_all(&config_dir).unwrap();
     fs::write(
         config_dir.join("config.toml"),
-        "[allowed_ports]\nports = [\"3000\"]\n{{invalid}}\n",
+        with_privileged("[allowed_ports]\nports = [\"59950-59955\"]"),
     )
     .unwrap();
 
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-    cmd.env("HOME", temp.path())
-        .arg("--port")
-        .arg("3000")
+    safe_kill_cmd()
+        .env("HOME", temp.path())
+        .args(["--port", "59950-59960", "--dry-run"])
         .assert()
-        .code(3)
-        .stderr(predicate::str::contains("Config parse error"));
+        .code(4)
+        .stderr(predicate::str::contains("Port 59956 is not allowed"));
 }
 
 #[test]
-fn test_unknown_config_field_does_not_fall_back_for_list() {
+fn test_port_list_without_processes() {
     use std::fs;
 
     let temp = tempfile::tempdir().unwrap();
@@ -531,117 +1341,384 @@ fn test_unknown_config_field_does_not_fall_back_for_list() {
     fs::create_dir_all(&config_dir).unwrap();
     fs::write(
         config_dir.join("config.toml"),
-        "[denylst]\nprocesses = [\"sleep\"]\n",
+        with_privileged("[allowed_ports]\nports = [\"59950-59955\", \"59958\"]"),
+    )
+    .unwrap();
+
+    safe_kill_cmd()
+        .env("HOME", temp.path())
+        .args(["--port", "59950-59952,59958", "--dry-run"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "No process found on ports 59950-59952,59958",
+        ));
+}
+
+#[test]
+fn test_port_range_invalid_format() {
+    safe_kill_cmd()
+        .args(["--port", "3010-3000"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid port range format"));
+}
+
+/// `[allowed_sockets]` に `glob` を設定した HOME を作る
+fn socket_home(glob: &str) -> tempfile::TempDir {
+    let temp = tempfile::tempdir().unwrap();
+    let config_dir = temp.path().join(".config").join("safe-kill");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(
+        config_dir.join("config.toml"),
+        with_privileged(format!("[allowed_sockets]\npaths = [\"{}\"]", glob)),
+    )
+    .unwrap();
+    temp
+}
+
+#[test]
+fn test_socket_not_allowed_exit_code() {
+    let dir = tempfile::tempdir().unwrap();
+    let socket_path = dir.path().join("dev.sock");
+    let _listener = std::os::unix::net::UnixListener::bind(&socket_path).unwrap();
+    let home = socket_home("/nonexistent/*.sock");
+
+    safe_kill_cmd()
+        .env("HOME", home.path())
+        .arg("--socket")
+        .arg(&socket_path)
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains("[allowed_sockets]"));
+}
+
+#[test]
+fn test_socket_without_holder() {
+    let dir = tempfile::tempdir().unwrap();
+    let home = socket_home(&format!("{}/*.sock", dir.path().display()));
+
+    safe_kill_cmd()
+        .env("HOME", home.path())
+        .arg("--socket")
+        .arg(dir.path().join("missing.sock"))
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("No process found on socket"));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_socket_holder_outside_session_is_not_killed() {
+    let dir = tempfile::tempdir().unwrap();
+    let socket_path = dir.path().join("dev.sock");
+    let _listener = std::os::unix::net::UnixListener::bind(&socket_path).unwrap();
+    let resolved = socket_path.canonicalize().unwrap();
+    let home = socket_home(&format!("{}/*.sock", resolved.parent().unwrap().display()));
+
+    // ソケットを保持しているのは safe-kill の親（テストプロセス）なので拒否される
+    safe_kill_cmd()
+        .env("HOME", home.path())
+        .arg("--socket")
+        .arg(&socket_path)
+        .arg("--dry-run")
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains(
+            "Found 1 process(es), would kill 0",
+        ))
+        .stdout(predicate::str::contains(
+            "Cannot kill self or parent process",
+        ));
+}
+
+#[test]
+fn test_holding_missing_path() {
+    safe_kill_cmd()
+        .args(["--holding", "/nonexistent/safe-kill/app.db"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "No process found holding /nonexistent/safe-kill/app.db",
+        ));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_holding_file_held_by_parent_is_not_killed() {
+    let dir = tempfile::tempdir().unwrap();
+    let file_path = dir.path().join("app.db");
+    let _file = std::fs::File::create(&file_path).unwrap();
+
+    // ファイルを開いているのは safe-kill の親（テストプロセス）なので拒否される
+    safe_kill_cmd()
+        .arg("--holding")
+        .arg(dir.path())
+        .arg("--dry-run")
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("would kill 0"))
+        .stdout(predicate::str::contains(
+            "Cannot kill self or parent process",
+        ));
+}
+
+#[test]
+fn test_holding_rejects_combination_with_name() {
+    safe_kill_cmd()
+        .args(["--holding", "target", "--name", "node"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--holding cannot be combined with PID, --name, or --cwd",
+        ));
+}
+
+/// 一時ディレクトリをカレントディレクトリにした `sleep` を `count` 個起動する
+fn spawn_sleeps_in(dir: &std::path::Path, count: usize) -> Vec<std::process::Child> {
+    (0..count)
+        .map(|_| {
+            std::process::Command::new("sleep")
+                .arg("30")
+                .current_dir(dir)
+                .spawn()
+                .expect("failed to spawn sleep")
+        })
+        .collect()
+}
+
+/// `sleep` を allowlist に入れた HOME を作る（特権実行時もバイパスを有効にする）
+fn sleep_allowlisted_home() -> tempfile::TempDir {
+    let temp = tempfile::tempdir().unwrap();
+    let config_dir = temp.path().join(".config").join("safe-kill");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(
+        config_dir.join("config.toml"),
+        "[allowlist]\nprocesses = [\"sleep\"]\n\n[privileged]\nallowlist_bypass = true\n",
     )
     .unwrap();
-
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-    cmd.env("HOME", temp.path())
-        .arg("--list")
-        .assert()
-        .code(3)
-        .stderr(predicate::str::contains("Config parse error"));
+    temp
 }
 
-#[test]
-fn test_custom_config_file_path() {
-    // 一時的な設定ファイルを作成
-    let mut config_file = NamedTempFile::new().unwrap();
-    writeln!(
-        config_file,
-        r#"
-[denylist]
-processes = ["test_denied_process"]
-"#
+/// `sleep` を別名でコピーし、他のテストの `sleep` と名前で区別できるプロセスを起動する
+fn spawn_renamed_sleep(dir: &std::path::Path, name: &str) -> std::process::Child {
+    let binary = dir.join(name);
+    std::fs::copy(
+        which_sleep().expect("sleep コマンドが見つからない"),
+        &binary,
     )
     .unwrap();
-
-    // 設定の読み込みは起動時に行われるため、CLI 経由でカスタムパスを簡単にテストできない。
-    // このテストは設定ファイルが存在してもバイナリが正常に動作することを確認する。
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-    cmd.arg("--list").assert().success();
+    std::process::Command::new(&binary)
+        .arg("30")
+        .spawn()
+        .expect("failed to spawn renamed sleep")
 }
 
-// =============================================================================
-// エッジケースのテスト
-// =============================================================================
-
-#[test]
-fn test_invalid_pid_format() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-    cmd.arg("not_a_number").assert().failure();
+/// PATH から `sleep` の実体を探す
+fn which_sleep() -> Option<std::path::PathBuf> {
+    std::env::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join("sleep"))
+            .find(|path| path.is_file())
+    })
 }
 
-#[test]
-fn test_negative_pid() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-    cmd.arg("-1").assert().failure();
+/// 指定内容の config.toml を置いた一時 HOME を作る
+fn home_with_config(content: &str) -> tempfile::TempDir {
+    let temp = tempfile::tempdir().unwrap();
+    let config_dir = temp.path().join(".config").join("safe-kill");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(config_dir.join("config.toml"), with_privileged(content)).unwrap();
+    temp
 }
 
+#[cfg(target_os = "linux")]
 #[test]
-fn test_very_large_pid() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-    cmd.arg("99999999999999").assert().failure();
+fn test_interactive_refuses_without_terminal() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut child = spawn_renamed_sleep(dir.path(), "skinteractive");
+
+    safe_kill_cmd()
+        .args(["-i", "-N", "skinteractive"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "--interactive requires a terminal; no signal was sent",
+        ));
+    assert!(child.try_wait().unwrap().is_none());
+
+    let _ = child.kill();
+    let _ = child.wait();
 }
 
+#[cfg(target_os = "linux")]
 #[test]
-fn test_pid_zero() {
-    // PID 0はUnixで特殊な意味を持つ（プロセスグループ全体にシグナルを送る）
-    // safe-killでは拒否すべき
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-    cmd.arg("0")
+fn test_confirm_config_requires_yes_for_sigkill() {
+    let dir = tempfile::tempdir().unwrap();
+    let home = home_with_config("[confirm]\nsigkill = true\n");
+    let mut child = spawn_renamed_sleep(dir.path(), "skconfirmkill");
+
+    // SIGTERM は確認不要
+    safe_kill_cmd()
+        .env("HOME", home.path())
+        .args(["-N", "skconfirmkill", "--dry-run"])
         .assert()
-        .failure()
-        .stderr(predicate::str::contains("Invalid PID: 0"));
+        .success();
+
+    safe_kill_cmd()
+        .env("HOME", home.path())
+        .args(["-N", "skconfirmkill", "-s", "KILL"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("re-run with --yes"));
+    assert!(
+        child.try_wait().unwrap().is_none(),
+        "確認できなければ送らない"
+    );
+
+    safe_kill_cmd()
+        .env("HOME", home.path())
+        .args(["-N", "skconfirmkill", "-s", "KILL", "--yes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("killed 1"));
+
+    let _ = child.kill();
+    let _ = child.wait();
 }
 
+#[cfg(target_os = "linux")]
 #[test]
-fn test_pid_over_i32_max() {
-    // nix::Pid が扱う i32 の範囲を超える PID は拒否すべき
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-    cmd.arg("2147483648")
+fn test_confirm_config_applies_to_holding_kill() {
+    let home = home_with_config("[confirm]\nsigkill = true\n");
+    let dir = tempfile::tempdir().unwrap();
+    let file = std::fs::File::create(dir.path().join("app.db")).unwrap();
+    let mut child = std::process::Command::new("sleep")
+        .arg("30")
+        .stdout(file)
+        .spawn()
+        .expect("failed to spawn sleep");
+
+    safe_kill_cmd()
+        .env("HOME", home.path())
+        .arg("--holding")
+        .arg(dir.path())
+        .args(["-s", "KILL"])
         .assert()
-        .failure()
-        .stderr(predicate::str::contains("Invalid PID: 2147483648"));
+        .code(1)
+        .stderr(predicate::str::contains("re-run with --yes"));
+    assert!(
+        child.try_wait().unwrap().is_none(),
+        "確認できなければ送らない"
+    );
+
+    safe_kill_cmd()
+        .env("HOME", home.path())
+        .arg("--holding")
+        .arg(dir.path())
+        .args(["-s", "KILL", "--yes"])
+        .assert()
+        .success();
+    let _ = child.wait();
 }
 
 #[test]
-fn test_empty_name() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-    cmd.arg("--name").arg("").assert().failure();
+fn test_limits_refuse_sigkill_over_session_quota() {
+    let home = home_with_config("[limits]\nsigkills_per_session = 1\n");
+    let spawn = || {
+        std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .expect("failed to spawn sleep")
+    };
+    let (mut first, mut second) = (spawn(), spawn());
+
+    safe_kill_cmd()
+        .env("HOME", home.path())
+        .args([&first.id().to_string(), "-s", "KILL"])
+        .assert()
+        .success();
+
+    safe_kill_cmd()
+        .env("HOME", home.path())
+        .args([&second.id().to_string(), "-s", "KILL"])
+        .assert()
+        .code(7)
+        .stderr(predicate::str::contains("Rate limit exceeded"));
+    assert!(
+        second.try_wait().unwrap().is_none(),
+        "上限を超えたら送らない"
+    );
+
+    // SIGKILL 以外は数えない
+    safe_kill_cmd()
+        .env("HOME", home.path())
+        .arg(second.id().to_string())
+        .assert()
+        .success();
+    assert!(
+        home.path()
+            .join(".local/state/safe-kill/limits.json")
+            .is_file()
+    );
+
+    for child in [&mut first, &mut second] {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
 }
 
 #[test]
-fn test_special_characters_in_name() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-    cmd.arg("--name")
-        .arg("process*with?special[chars]")
+fn test_cwd_missing_directory() {
+    safe_kill_cmd()
+        .args(["--cwd", "/nonexistent/safe-kill/project"])
         .assert()
-        .failure()
-        .stderr(
-            predicate::str::contains("No process found with name")
-                .or(predicate::str::contains("not found"))
-                .or(predicate::str::contains("No matching")),
-        );
+        .code(1)
+        .stderr(predicate::str::contains(
+            "No process found with working directory in /nonexistent/safe-kill/project",
+        ));
 }
 
-// =============================================================================
-// --port オプションのテスト (Task 10.1)
-// =============================================================================
-
+#[cfg(target_os = "linux")]
 #[test]
-fn test_help_shows_port_option() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
-    cmd.arg("--help")
+fn test_cwd_large_match_requires_yes_without_terminal() {
+    let dir = tempfile::tempdir().unwrap();
+    let home = sleep_allowlisted_home();
+    let mut children = spawn_sleeps_in(dir.path(), 6);
+
+    safe_kill_cmd()
+        .env("HOME", home.path())
+        .arg("--cwd")
+        .arg(dir.path())
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("re-run with --yes"));
+    assert!(
+        children
+            .iter_mut()
+            .all(|child| child.try_wait().unwrap().is_none()),
+        "確認できない場合はシグナルを送らない"
+    );
+
+    safe_kill_cmd()
+        .env("HOME", home.path())
+        .arg("--cwd")
+        .arg(dir.path())
+        .arg("--yes")
         .assert()
         .success()
-        .stdout(predicate::str::contains("--port").and(predicate::str::contains("-p")));
+        .stdout(predicate::str::contains("Found 6 process(es), killed 6"));
+
+    for child in &mut children {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
 }
 
 #[test]
 fn test_port_no_process_on_port() {
     // プロセスが使用していないであろうポート番号を使用
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("--port")
         .arg("59997")
         .assert()
@@ -651,7 +1728,7 @@ fn test_port_no_process_on_port() {
 
 #[test]
 fn test_port_with_dry_run_no_process() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("--port")
         .arg("59998")
         .arg("--dry-run")
@@ -663,7 +1740,7 @@ fn test_port_with_dry_run_no_process() {
 #[test]
 fn test_port_short_option() {
     // -p 短縮形のテスト
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("-p")
         .arg("59996")
         .assert()
@@ -673,7 +1750,7 @@ fn test_port_short_option() {
 
 #[test]
 fn test_port_and_pid_mutually_exclusive() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("--port")
         .arg("8080")
         .arg("12345")
@@ -684,7 +1761,7 @@ fn test_port_and_pid_mutually_exclusive() {
 
 #[test]
 fn test_port_and_name_mutually_exclusive() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("--port")
         .arg("8080")
         .arg("--name")
@@ -696,7 +1773,7 @@ fn test_port_and_name_mutually_exclusive() {
 
 #[test]
 fn test_port_and_list_mutually_exclusive() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("--port")
         .arg("8080")
         .arg("--list")
@@ -707,13 +1784,13 @@ fn test_port_and_list_mutually_exclusive() {
 
 #[test]
 fn test_port_invalid_port_number() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("--port").arg("not_a_number").assert().failure();
 }
 
 #[test]
 fn test_port_out_of_range() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("--port").arg("99999").assert().failure();
 }
 
@@ -723,7 +1800,7 @@ fn test_port_out_of_range() {
 
 #[test]
 fn test_init_help() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("init")
         .arg("--help")
         .assert()
@@ -736,7 +1813,7 @@ fn test_init_force_creates_config() {
     // init --force が正常に実行されることをテスト
     // 実際のユーザー設定に書き込まないよう一時的な HOME を使用
     let temp = tempfile::tempdir().unwrap();
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.env("HOME", temp.path())
         .arg("init")
         .arg("--force")
@@ -745,10 +1822,64 @@ fn test_init_force_creates_config() {
         .stdout(predicate::str::contains("Created").or(predicate::str::contains("config")));
 }
 
+#[test]
+fn test_init_writes_schema_next_to_config() {
+    let temp = tempfile::tempdir().unwrap();
+    safe_kill_cmd()
+        .env("HOME", temp.path())
+        .args(["init", "--force"])
+        .assert()
+        .success();
+
+    let config_dir = temp.path().join(".config/safe-kill");
+    let config = std::fs::read_to_string(config_dir.join("config.toml")).unwrap();
+    assert!(config.starts_with("#:schema ./config.schema.json\n"));
+    let schema = std::fs::read_to_string(config_dir.join("config.schema.json")).unwrap();
+
+    let output = safe_kill_cmd().args(["config", "schema"]).output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), schema);
+    let schema: serde_json::Value = serde_json::from_str(&schema).unwrap();
+    assert!(schema["properties"]["allowed_ports"].is_object());
+}
+
+#[test]
+fn test_init_template_writes_preset_ports() {
+    let temp = tempfile::tempdir().unwrap();
+    let path = temp.path().join("config.toml");
+    safe_kill_cmd()
+        .args(["init", "--force", "--template", "python", "--config"])
+        .arg(&path)
+        .assert()
+        .success();
+
+    let config = std::fs::read_to_string(&path).unwrap();
+    assert!(config.contains("ports = [\"5000\", \"8000\", \"8501\", \"8888\"]"));
+    safe_kill_cmd()
+        .args(["config", "validate"])
+        .arg(&path)
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_init_interactive_requires_terminal() {
+    let temp = tempfile::tempdir().unwrap();
+    let path = temp.path().join("config.toml");
+    safe_kill_cmd()
+        .args(["init", "--interactive", "--config"])
+        .arg(&path)
+        .write_stdin("y\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("requires a terminal"));
+    assert!(!path.exists());
+}
+
 #[test]
 fn test_init_output_shows_hint() {
     let temp = tempfile::tempdir().unwrap();
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.env("HOME", temp.path())
         .arg("init")
         .arg("--force")
@@ -767,7 +1898,7 @@ fn test_init_rejects_signal_option() {
         .join("safe-kill")
         .join("config.toml");
 
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.env("HOME", temp.path())
         .arg("--signal")
         .arg("INVALID")
@@ -786,7 +1917,7 @@ fn test_init_creates_valid_toml() {
     let temp = tempfile::tempdir().unwrap();
 
     // init --force を実行
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.env("HOME", temp.path())
         .arg("init")
         .arg("--force")
@@ -813,9 +1944,15 @@ fn test_init_creates_valid_toml() {
 
 #[test]
 fn test_exit_code_port_not_allowed_without_config() {
-    // 設定ファイルがない場合、ポートは許可されていないのでエラー
+    use std::fs;
+
+    // [allowed_ports] の設定がない場合、ポートは許可されていないのでエラー
+    // （root で実行される環境では [privileged] のみの設定ファイルを置く）
     let temp = tempfile::tempdir().unwrap();
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let config_dir = temp.path().join(".config").join("safe-kill");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(config_dir.join("config.toml"), with_privileged("")).unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.env("HOME", temp.path())
         .arg("--port")
         .arg("3000")
@@ -834,11 +1971,11 @@ fn test_exit_code_port_allowed_but_no_process() {
     fs::create_dir_all(&config_dir).unwrap();
     fs::write(
         config_dir.join("config.toml"),
-        "[allowed_ports]\nports = [\"59997\"]",
+        with_privileged("[allowed_ports]\nports = [\"59997\"]"),
     )
     .unwrap();
 
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.env("HOME", temp.path())
         .arg("--port")
         .arg("59997")
@@ -861,7 +1998,7 @@ fn test_init_cancel_preserves_existing_config() {
     // "n"を入力してキャンセル。
     // キャンセルはユーザーの意図的な操作であり、設定ファイル作成エラーではない。
     // 既存ファイルを変更しない正常な no-op として終了コード 0 を返す。
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.env("HOME", temp.path())
         .arg("init")
         .write_stdin("n\n")
@@ -886,7 +2023,7 @@ fn test_init_overwrite_yes() {
     fs::write(&config_path, "# old config\nold = 1").unwrap();
 
     // "y"を入力して上書き承認
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.env("HOME", temp.path())
         .arg("init")
         .write_stdin("y\n")
@@ -914,7 +2051,7 @@ fn test_init_write_failure_reports_config_error() {
     fs::create_dir_all(&config_path_as_dir).unwrap();
 
     // --force で上書き確認をスキップし、書き込み試行まで進める
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.env("HOME", temp.path())
         .arg("init")
         .arg("--force")
@@ -930,7 +2067,7 @@ fn test_init_write_failure_reports_config_error() {
 #[test]
 fn test_signal_option_zero() {
     // シグナル番号0は無効
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("--signal")
         .arg("0")
         .arg("12345")
@@ -942,7 +2079,7 @@ fn test_signal_option_zero() {
 #[test]
 fn test_signal_option_negative() {
     // 負のシグナル番号は無効
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("--signal")
         .arg("-1")
         .arg("12345")
@@ -953,7 +2090,7 @@ fn test_signal_option_negative() {
 #[test]
 fn test_signal_option_sig_only() {
     // "SIG"のみは無効
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("--signal")
         .arg("SIG")
         .arg("12345")
@@ -965,7 +2102,7 @@ fn test_signal_option_sig_only() {
 #[test]
 fn test_signal_option_whitespace() {
     // 空白のみは無効（clapがトリムする前にエラーになる可能性）
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("--signal")
         .arg("   ")
         .arg("12345")
@@ -980,7 +2117,7 @@ fn test_signal_option_whitespace() {
 #[test]
 fn test_port_boundary_zero() {
     // ポート 0 は OS の自動割り当て用の特殊値なので拒否する
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("--port")
         .arg("0")
         .assert()
@@ -997,11 +2134,11 @@ fn test_port_zero_rejected_even_when_configured() {
     fs::create_dir_all(&config_dir).unwrap();
     fs::write(
         config_dir.join("config.toml"),
-        "[allowed_ports]\nports = [\"0-65535\", \"0\"]",
+        with_privileged("[allowed_ports]\nports = [\"0-65535\", \"0\"]"),
     )
     .unwrap();
 
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.env("HOME", temp.path())
         .arg("--port")
         .arg("0")
@@ -1014,7 +2151,7 @@ fn test_port_zero_rejected_even_when_configured() {
 #[test]
 fn test_port_boundary_max() {
     // ポート65535は有効な最大値
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     // 許可設定されていない場合はPortNotAllowed
     cmd.arg("--port").arg("65535").assert().failure();
 }
@@ -1028,7 +2165,7 @@ fn test_env_var_root_pid_one_is_ignored() {
     // SAFE_KILL_ROOT_PID=1 は信頼ルートとして不適格（PID 1 を許すと全プロセスが
     // 子孫扱いになる fail-open）なため無視され、自動検出ルートにフォールバックする。
     // --list 自体は（自動検出ルート配下を列挙して）常に成功する。
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.env("SAFE_KILL_ROOT_PID", "1")
         .arg("--list")
         .assert()
@@ -1048,7 +2185,7 @@ fn test_env_var_root_pid_itself_is_not_killable() {
     let root_pid = child.id();
 
     // 信頼ルート自体は子孫ではないため、dry-run でも終了対象にしない。
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.env("SAFE_KILL_ROOT_PID", root_pid.to_string())
         .arg(root_pid.to_string())
         .arg("--dry-run")
@@ -1063,7 +2200,7 @@ fn test_env_var_root_pid_itself_is_not_killable() {
 #[test]
 fn test_env_var_root_pid_invalid_ignored() {
     // 無効な値は無視され、デフォルトの挙動になる
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.env("SAFE_KILL_ROOT_PID", "not_a_number")
         .arg("--list")
         .assert()
@@ -1073,13 +2210,101 @@ fn test_env_var_root_pid_invalid_ignored() {
 #[test]
 fn test_env_var_root_pid_zero_ignored() {
     // PID 0 は無効値として無視され、デフォルトの挙動になる
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.env("SAFE_KILL_ROOT_PID", "0")
         .arg("--list")
         .assert()
         .success();
 }
 
+// =============================================================================
+// root サブコマンドテスト
+// =============================================================================
+
+#[test]
+fn test_root_command_shows_pid_and_reason() {
+    let temp = tempfile::tempdir().unwrap();
+    let mut cmd = safe_kill_cmd();
+    cmd.env("HOME", temp.path())
+        .env_remove("SAFE_KILL_ROOT_PID")
+        .arg("root")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Root PID:")
+                .and(predicate::str::contains("Strategy: default"))
+                .and(predicate::str::contains("Reason:")),
+        );
+}
+
+#[test]
+fn test_root_command_reports_env_var_override() {
+    let current_pid = ProcessInfoProvider::current_pid();
+    let mut cmd = safe_kill_cmd();
+    cmd.env("SAFE_KILL_ROOT_PID", current_pid.to_string())
+        .arg("root")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains(format!("Root PID: {}", current_pid))
+                .and(predicate::str::contains("SAFE_KILL_ROOT_PID")),
+        );
+}
+
+#[test]
+fn test_root_command_uses_levels_strategy_from_config() {
+    use std::fs;
+
+    let temp = tempfile::tempdir().unwrap();
+    let config_dir = temp.path().join(".config").join("safe-kill");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(
+        config_dir.join("config.toml"),
+        "[root]\nstrategy = \"levels\"\nlevels = 1\n",
+    )
+    .unwrap();
+
+    // safe-kill の親はこのテストプロセスになる
+    let current_pid = ProcessInfoProvider::current_pid();
+    let mut cmd = safe_kill_cmd();
+    cmd.env("HOME", temp.path())
+        .env_remove("SAFE_KILL_ROOT_PID")
+        .arg("root")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains(format!("Root PID: {}", current_pid))
+                .and(predicate::str::contains("Strategy: levels")),
+        );
+}
+
+#[test]
+fn test_root_command_rejects_invalid_root_config() {
+    use std::fs;
+
+    let temp = tempfile::tempdir().unwrap();
+    let config_dir = temp.path().join(".config").join("safe-kill");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(
+        config_dir.join("config.toml"),
+        "[root]\nstrategy = \"name\"\n",
+    )
+    .unwrap();
+
+    let mut cmd = safe_kill_cmd();
+    cmd.env("HOME", temp.path())
+        .arg("root")
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("[root]"));
+}
+
+#[test]
+fn test_root_command_cannot_be_combined_with_list() {
+    let mut cmd = safe_kill_cmd();
+    cmd.arg("--list").arg("root").assert().failure();
+}
+
 // =============================================================================
 // SIGKILL での子プロセス終了テスト
 // =============================================================================
@@ -1095,7 +2320,7 @@ fn test_kill_child_with_sigkill() {
     if let Ok(mut child) = child {
         let child_pid = child.id();
 
-        let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+        let mut cmd = safe_kill_cmd();
         cmd.arg("--signal")
             .arg("SIGKILL")
             .arg(child_pid.to_string())
@@ -1118,7 +2343,7 @@ fn test_kill_child_with_signal_number() {
     if let Ok(mut child) = child {
         let child_pid = child.id();
 
-        let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+        let mut cmd = safe_kill_cmd();
         cmd.arg("--signal")
             .arg("15") // SIGTERM by number
             .arg(child_pid.to_string())
@@ -1144,7 +2369,7 @@ fn test_init_creates_config_dir() {
     // ディレクトリがまだ存在しないことを確認
     assert!(!config_dir.exists());
 
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.env("HOME", temp.path())
         .arg("init")
         .arg("--force")
@@ -1198,7 +2423,7 @@ fn test_port_kill_with_real_listener() {
         .unwrap();
 
         // dry-run でプロセスが見つかることを確認
-        let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+        let mut cmd = safe_kill_cmd();
         let result = cmd
             .env("HOME", temp.path())
             .arg("--port")
@@ -1226,12 +2451,12 @@ fn test_port_kill_with_config_allowed_port_range() {
     fs::create_dir_all(&config_dir).unwrap();
     fs::write(
         config_dir.join("config.toml"),
-        "[allowed_ports]\nports = [\"59990-59999\"]",
+        with_privileged("[allowed_ports]\nports = [\"59990-59999\"]"),
     )
     .unwrap();
 
     // 範囲内のポートでプロセスがなくても PortNotAllowed ではなく NoProcessOnPort が返る
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.env("HOME", temp.path())
         .arg("--port")
         .arg("59995")
@@ -1250,12 +2475,12 @@ fn test_port_kill_outside_allowed_range() {
     fs::create_dir_all(&config_dir).unwrap();
     fs::write(
         config_dir.join("config.toml"),
-        "[allowed_ports]\nports = [\"3000-3010\"]",
+        with_privileged("[allowed_ports]\nports = [\"3000-3010\"]"),
     )
     .unwrap();
 
     // 範囲外のポートは PortNotAllowed エラー
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.env("HOME", temp.path())
         .arg("--port")
         .arg("4000")
@@ -1274,7 +2499,7 @@ fn test_init_creates_config_file() {
 
     let temp = tempfile::tempdir().unwrap();
 
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.env("HOME", temp.path())
         .arg("init")
         .arg("--force")
@@ -1304,7 +2529,7 @@ fn test_init_twice_with_force() {
     let temp = tempfile::tempdir().unwrap();
 
     // 1回目の init
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.env("HOME", temp.path())
         .arg("init")
         .arg("--force")
@@ -1312,7 +2537,7 @@ fn test_init_twice_with_force() {
         .success();
 
     // 2回目の init（--force で上書き）
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.env("HOME", temp.path())
         .arg("init")
         .arg("--force")
@@ -1327,7 +2552,7 @@ fn test_init_twice_with_force() {
 
 #[test]
 fn test_pid_and_port_conflict() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("12345")
         .arg("--port")
         .arg("3000")
@@ -1338,7 +2563,7 @@ fn test_pid_and_port_conflict() {
 
 #[test]
 fn test_pid_and_name_conflict() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("12345")
         .arg("--name")
         .arg("node")
@@ -1349,7 +2574,7 @@ fn test_pid_and_name_conflict() {
 
 #[test]
 fn test_list_and_pid_conflict() {
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg("12345")
         .arg("--list")
         .assert()
@@ -1375,7 +2600,7 @@ fn test_dry_run_does_not_kill_child_process() {
     let pid = child.id();
 
     // dry-run で kill を試みる
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg(pid.to_string())
         .arg("--dry-run")
         .assert()
@@ -1416,7 +2641,7 @@ fn test_kill_with_signal_number() {
     let pid = child.id();
 
     // シグナル番号 15 (SIGTERM) で kill
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg(pid.to_string())
         .arg("--signal")
         .arg("15")
@@ -1441,7 +2666,7 @@ fn test_kill_with_signal_name_without_prefix() {
     let pid = child.id();
 
     // "KILL" (SIGプレフィックスなし) で kill
-    let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+    let mut cmd = safe_kill_cmd();
     cmd.arg(pid.to_string())
         .arg("--signal")
         .arg("KILL")
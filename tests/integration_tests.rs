@@ -1,9 +1,10 @@
 //! safe-kill の統合テスト
 //!
 //! 実際のプロセスツリー、設定ファイル、シグナル操作を使って公開 API をテストする。
+#![allow(deprecated)] // `with_defaults` / `load` は非推奨だが、公開 API として動作を確認する
 
 use safe_kill::ancestry::AncestryChecker;
-use safe_kill::config::{Config, ProcessList};
+use safe_kill::config::{Config, ConfigLayer, ProcessList};
 use safe_kill::error::SafeKillError;
 use safe_kill::killer::ProcessKiller;
 use safe_kill::policy::{KillPermission, PolicyEngine};
@@ -194,11 +195,14 @@ fn test_config_apply_in_policy_engine() {
     let config = Config {
         allowlist: Some(ProcessList {
             processes: vec!["allowed_test".to_string()],
+            ..Default::default()
         }),
         denylist: Some(ProcessList {
             processes: vec!["denied_test".to_string()],
+            ..Default::default()
         }),
         allowed_ports: None,
+        ..Default::default()
     };
 
     let engine = PolicyEngine::new(config);
@@ -227,7 +231,7 @@ processes = ["custom_process"]
 
     assert!(matches!(
         engine.can_kill(&pid1_info),
-        KillPermission::DeniedByDenylist(ref name) if name == &pid1_info.name
+        KillPermission::DeniedByDenylist(ref name, _) if name == &pid1_info.name
     ));
 }
 
@@ -269,11 +273,14 @@ fn test_config_denylist_precedence_over_allowlist() {
     let config = Config {
         allowlist: Some(ProcessList {
             processes: vec!["conflict".to_string()],
+            ..Default::default()
         }),
         denylist: Some(ProcessList {
             processes: vec!["conflict".to_string()],
+            ..Default::default()
         }),
         allowed_ports: None,
+        ..Default::default()
     };
 
     // denylist が優先される
@@ -408,9 +415,11 @@ fn test_policy_engine_with_dry_run() {
     let config = Config {
         allowlist: Some(ProcessList {
             processes: vec!["safe_kill_test_target".to_string()],
+            ..Default::default()
         }),
         denylist: None,
         allowed_ports: None,
+        ..Default::default()
     };
 
     let engine = PolicyEngine::new(config);
@@ -443,6 +452,71 @@ fn test_policy_engine_suicide_prevention() {
     }
 }
 
+#[test]
+fn test_policy_engine_allowlisted_ancestor_is_protected() {
+    let provider = ProcessInfoProvider::new();
+    let current_pid = ProcessInfoProvider::current_pid();
+    let Some(grandparent) = provider
+        .get(current_pid)
+        .and_then(|p| p.parent_pid)
+        .and_then(|pid| provider.get(pid))
+        .and_then(|p| p.parent_pid)
+        .and_then(|pid| provider.get(pid))
+        .filter(|p| p.pid > 1)
+    else {
+        return;
+    };
+
+    // エージェント本体を想定した祖先プロセスの名前を allowlist に入れても kill できない
+    let config = Config {
+        allowlist: Some(ProcessList {
+            processes: vec![grandparent.name.clone()],
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let engine = PolicyEngine::new(config);
+
+    assert_eq!(
+        engine.can_kill(&grandparent),
+        KillPermission::DeniedAncestor
+    );
+    let result = engine.kill_by_pid(grandparent.pid, Signal::SIGTERM, true);
+    assert!(
+        matches!(result, Err(SafeKillError::AncestorProtected(pid, _)) if pid == grandparent.pid),
+        "祖先プロセスは AncestorProtected で拒否されるべき: {:?}",
+        result
+    );
+}
+
+#[test]
+fn test_policy_engine_ownership_allows_own_child() {
+    let (_dir, mut child, name) = spawn_unique_sleep();
+    let mut file = NamedTempFile::new().unwrap();
+    writeln!(
+        file,
+        r#"
+[allowlist]
+processes = ["{name}"]
+
+[ownership]
+enforce = true
+"#
+    )
+    .unwrap();
+
+    let config = Config::try_load_from_path(Some(file.path().to_path_buf())).unwrap();
+    let engine = PolicyEngine::new(config);
+    let info = ProcessInfoProvider::fetch_fresh(child.id()).unwrap();
+
+    // 自分が起動した子プロセスは起動ユーザーの所有なので許可される
+    assert!(engine.ownership().is_some());
+    assert!(engine.can_kill(&info).is_allowed());
+
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
 #[test]
 fn test_policy_engine_list_killable() {
     let engine = PolicyEngine::with_defaults();
@@ -608,7 +682,9 @@ fn test_config_is_port_allowed_ignores_invalid_specs() {
                 "3000-3001".to_string(), // 有効な範囲
                 "also-bad".to_string(),  // 無効なポート指定
             ],
+            ..Default::default()
         }),
+        ..Default::default()
     };
 
     // 有効な範囲内のポートは許可される
@@ -655,7 +731,9 @@ fn test_port_range_boundary_in_config() {
         denylist: None,
         allowed_ports: Some(AllowedPorts {
             ports: vec!["65535".to_string()],
+            ..Default::default()
         }),
+        ..Default::default()
     };
 
     assert!(config.is_port_allowed(65535));
@@ -667,7 +745,9 @@ fn test_port_range_boundary_in_config() {
         denylist: None,
         allowed_ports: Some(AllowedPorts {
             ports: vec!["0".to_string()],
+            ..Default::default()
         }),
+        ..Default::default()
     };
 
     assert!(!config_min.is_port_allowed(0));
@@ -688,7 +768,9 @@ fn test_port_full_range_config() {
         denylist: None,
         allowed_ports: Some(AllowedPorts {
             ports: vec!["1-65535".to_string()],
+            ..Default::default()
         }),
+        ..Default::default()
     };
 
     // 境界値
@@ -714,6 +796,7 @@ fn test_policy_engine_kill_by_port_not_allowed_default() {
         allowlist: None,
         denylist: None,
         allowed_ports: None,
+        ..Default::default()
     };
     let engine = PolicyEngine::new(config);
 
@@ -730,7 +813,9 @@ fn test_policy_engine_kill_by_port_allowed_but_empty() {
         denylist: None,
         allowed_ports: Some(AllowedPorts {
             ports: vec!["59990".to_string()],
+            ..Default::default()
         }),
+        ..Default::default()
     };
     let engine = PolicyEngine::new(config);
 
@@ -825,8 +910,10 @@ fn test_policy_engine_kill_by_name_denylisted_returns_batch() {
         allowlist: None,
         denylist: Some(ProcessList {
             processes: vec![pid1_info.name.clone()],
+            ..Default::default()
         }),
         allowed_ports: None,
+        ..Default::default()
     };
     let engine = PolicyEngine::new(config);
 
@@ -850,7 +937,9 @@ fn test_config_with_allowed_ports_in_policy_engine() {
         denylist: None,
         allowed_ports: Some(AllowedPorts {
             ports: vec!["59989".to_string()],
+            ..Default::default()
         }),
+        ..Default::default()
     };
     let engine = PolicyEngine::new(config);
 
@@ -924,8 +1013,10 @@ fn test_policy_engine_kill_by_name_mixed_batch() {
         allowlist: None,
         denylist: Some(ProcessList {
             processes: vec![pid1_info.name.clone()],
+            ..Default::default()
         }),
         allowed_ports: None,
+        ..Default::default()
     };
     let engine = PolicyEngine::new(config);
 
@@ -1073,9 +1164,11 @@ fn test_policy_engine_kill_by_name_actual_kill() {
     let config = Config {
         allowlist: Some(ProcessList {
             processes: vec![process_name.clone()],
+            ..Default::default()
         }),
         denylist: None,
         allowed_ports: None,
+        ..Default::default()
     };
     let engine = PolicyEngine::new(config);
 
@@ -1118,6 +1211,7 @@ fn test_port_not_allowed_hint_without_config() {
         allowlist: None,
         denylist: None,
         allowed_ports: None,
+        ..Default::default()
     };
     let hint = config.port_not_allowed_hint(3000);
     assert!(hint.contains("3000"), "ヒントにポート番号が含まれるべき");
@@ -1192,6 +1286,7 @@ fn test_check_port_allowed_error_content() {
         allowlist: None,
         denylist: None,
         allowed_ports: None,
+        ..Default::default()
     };
     let result = config.check_port_allowed(8080);
     assert!(result.is_err());
@@ -1245,9 +1340,16 @@ fn test_config_empty_process_lists() {
     use safe_kill::config::ProcessList;
 
     let config = Config {
-        allowlist: Some(ProcessList { processes: vec![] }),
-        denylist: Some(ProcessList { processes: vec![] }),
+        allowlist: Some(ProcessList {
+            processes: vec![],
+            ..Default::default()
+        }),
+        denylist: Some(ProcessList {
+            processes: vec![],
+            ..Default::default()
+        }),
         allowed_ports: None,
+        ..Default::default()
     };
 
     // 空リストでは何も許可・拒否されない
@@ -1359,7 +1461,9 @@ fn test_policy_engine_kill_by_port_with_real_listener() {
             denylist: None,
             allowed_ports: Some(AllowedPorts {
                 ports: vec![format!("{}", port)],
+                ..Default::default()
             }),
+            ..Default::default()
         };
         let engine = PolicyEngine::new(config);
 
@@ -1395,15 +1499,17 @@ fn test_denylist_overrides_ancestry_for_child() {
         allowlist: None,
         denylist: Some(ProcessList {
             processes: vec!["sleep".to_string()],
+            ..Default::default()
         }),
         allowed_ports: None,
+        ..Default::default()
     };
     let engine = PolicyEngine::new(config);
 
     // 子プロセスでも denylist に含まれていれば拒否
     let result = engine.kill_by_pid(child_pid, Signal::SIGTERM, false);
     assert!(
-        matches!(result, Err(SafeKillError::Denylisted(_))),
+        matches!(result, Err(SafeKillError::Denylisted(..))),
         "denylist のプロセスは子プロセスでも拒否されるべき"
     );
 
@@ -1426,9 +1532,11 @@ fn test_allowlist_cannot_bypass_default_denylist() {
     let config = Config {
         allowlist: Some(ProcessList {
             processes: vec![pid1_info.name.clone()],
+            ..Default::default()
         }),
         denylist: None,
         allowed_ports: None,
+        ..Default::default()
     };
     let engine = PolicyEngine::new(config);
 
@@ -1436,7 +1544,7 @@ fn test_allowlist_cannot_bypass_default_denylist() {
     let permission = engine.can_kill(&pid1_info);
     assert!(matches!(
         permission,
-        KillPermission::DeniedByDenylist(ref name) if name == &pid1_info.name
+        KillPermission::DeniedByDenylist(ref name, _) if name == &pid1_info.name
     ));
 }
 
@@ -1450,7 +1558,9 @@ fn test_port_range_same_start_end_in_policy() {
         denylist: None,
         allowed_ports: Some(AllowedPorts {
             ports: vec!["8080-8080".to_string()],
+            ..Default::default()
         }),
+        ..Default::default()
     };
 
     assert!(config.is_port_allowed(8080));
@@ -1600,7 +1710,9 @@ fn test_policy_engine_kill_by_port_actual_kill() {
             denylist: None,
             allowed_ports: Some(AllowedPorts {
                 ports: vec![format!("{}", port)],
+                ..Default::default()
             }),
+            ..Default::default()
         };
         let engine = PolicyEngine::new(config);
 
@@ -1652,8 +1764,10 @@ fn test_kill_by_name_all_denied_returns_batch_with_failures() {
         allowlist: None,
         denylist: Some(ProcessList {
             processes: vec![current_info.name.clone()],
+            ..Default::default()
         }),
         allowed_ports: None,
+        ..Default::default()
     };
     let engine = PolicyEngine::new(config);
 
@@ -1794,7 +1908,9 @@ fn test_kill_by_port_suicide_prevention() {
         denylist: None,
         allowed_ports: Some(AllowedPorts {
             ports: vec!["59980-59989".to_string()],
+            ..Default::default()
         }),
+        ..Default::default()
     };
     let engine = PolicyEngine::new(config);
 
@@ -1823,7 +1939,7 @@ fn test_exit_code_mapping_for_policy_errors() {
 
     // ポリシー関連のエラーは GeneralError (255) にマッピングされる
     assert_eq!(
-        SafeKillError::Denylisted("test".to_string()).exit_code(),
+        SafeKillError::Denylisted("test".to_string(), ConfigLayer::User).exit_code(),
         SafeKillExitCode::GeneralError,
         "Denylisted は GeneralError であるべき"
     );
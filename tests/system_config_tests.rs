@@ -0,0 +1,98 @@
+//! システム設定（/etc/safe-kill/config.toml）の E2E テスト
+//!
+//! システム設定のパスは固定で、ユーザーが差し替えられないようにしているため、
+//! root で実行され、かつシステム設定がまだない環境でだけ一時的に書き込んで検証する。
+//! 書き込んだ設定は同じテストバイナリで並行する他のテストにも影響するため、
+//! e2e_tests.rs とは別のテストバイナリにし、1 つのテスト関数で順に確認する。
+#![allow(deprecated)] // `cargo_bin` は非推奨だが現状のテストでは実用上問題ない
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+const SYSTEM_CONFIG_PATH: &str = "/etc/safe-kill/config.toml";
+
+/// テスト中だけ置くシステム設定（drop で削除し、作成したディレクトリも片付ける）
+struct SystemConfig {
+    created_dir: Option<PathBuf>,
+}
+
+impl SystemConfig {
+    /// システム設定を書き込めない、または既にある場合は None
+    fn install() -> Option<Self> {
+        let path = Path::new(SYSTEM_CONFIG_PATH);
+        if !nix::unistd::geteuid().is_root() || path.exists() {
+            return None;
+        }
+        let dir = path.parent().unwrap();
+        let created_dir = (!dir.exists()).then(|| dir.to_path_buf());
+        fs::create_dir_all(dir).ok()?;
+        Some(Self { created_dir })
+    }
+
+    fn write(&self, content: &str) {
+        fs::write(SYSTEM_CONFIG_PATH, content).expect("システム設定を書き込めるべき");
+    }
+}
+
+impl Drop for SystemConfig {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(SYSTEM_CONFIG_PATH);
+        if let Some(dir) = &self.created_dir {
+            let _ = fs::remove_dir(dir);
+        }
+    }
+}
+
+#[test]
+fn test_system_config_is_enforced_and_fails_closed() {
+    // root で実行されていて、既存のシステム設定を上書きしない環境でのみ検証できる
+    let Some(system) = SystemConfig::install() else {
+        return;
+    };
+
+    let home = tempfile::tempdir().unwrap();
+    let config_dir = home.path().join(".config").join("safe-kill");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(
+        config_dir.join("config.toml"),
+        "[signals]\nforbid = []\n\n[privileged]\n",
+    )
+    .unwrap();
+
+    let mut child = std::process::Command::new("sleep")
+        .arg("60")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("failed to spawn sleep");
+    let safe_kill = || {
+        let mut cmd = Command::cargo_bin("safe-kill").unwrap();
+        cmd.env("HOME", home.path());
+        cmd
+    };
+
+    // ロックされた [signals] はユーザー設定で空にしても効き、拒否はシステム設定に帰属する
+    system.write("[lock]\nsections = [\"signals\"]\n\n[signals]\nforbid = [\"KILL\"]\n");
+    safe_kill()
+        .args(["-s", "KILL", &child.id().to_string(), "--dry-run"])
+        .assert()
+        .code(6)
+        .stderr(predicate::str::contains(format!(
+            "Signal SIGKILL is not allowed for descendant targets by [signals] (system config {})",
+            SYSTEM_CONFIG_PATH
+        )));
+
+    // 解析できないシステム設定は無視せず設定エラーにする
+    system.write("[lock\n");
+    safe_kill()
+        .args([&child.id().to_string(), "--dry-run"])
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains(SYSTEM_CONFIG_PATH));
+
+    let _ = child.kill();
+    let _ = child.wait();
+}